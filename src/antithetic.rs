@@ -0,0 +1,16 @@
+use crate::{
+    quasi_random::{device, from_uniform},
+    Distribution,
+};
+use tch::{Kind, Tensor};
+
+/// Draws uniforms `u` and maps both `u` and `1 - u` through the icdf of `dist`.
+pub(crate) fn sample_antithetic<D: Distribution + ?Sized>(
+    dist: &D,
+    shape: &[i64],
+) -> (Tensor, Tensor) {
+    let numel = dist.batch_shape().iter().product();
+    let u = Tensor::rand(&[shape, &[numel]].concat(), (Kind::Double, device(dist)));
+    let antithetic: Tensor = 1.0 - &u;
+    (from_uniform(dist, &u), from_uniform(dist, &antithetic))
+}
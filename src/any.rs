@@ -0,0 +1,67 @@
+use crate::{
+    Bernoulli, Beta, Binomial, Categorical, Cauchy, Distribution, Exponential, Gamma, Geometric,
+    MultivariateNormal, NegativeBinomial, Normal, Poisson, RelaxedOneHotCategorical, Uniform,
+};
+
+/// A distribution of any of the families that can be exchanged with other systems, such as
+/// `torch.distributions` or configuration files.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum AnyDistribution {
+    Bernoulli(Bernoulli),
+    Beta(Beta),
+    Binomial(Binomial),
+    Categorical(Categorical),
+    Cauchy(Cauchy),
+    Exponential(Exponential),
+    Gamma(Gamma),
+    Geometric(Geometric),
+    MultivariateNormal(MultivariateNormal),
+    NegativeBinomial(NegativeBinomial),
+    Normal(Normal),
+    Poisson(Poisson),
+    RelaxedOneHotCategorical(RelaxedOneHotCategorical),
+    Uniform(Uniform),
+}
+
+impl AnyDistribution {
+    /// Returns the name of the family of the distribution.
+    pub fn family(&self) -> &'static str {
+        match self {
+            AnyDistribution::Bernoulli(_) => "Bernoulli",
+            AnyDistribution::Beta(_) => "Beta",
+            AnyDistribution::Binomial(_) => "Binomial",
+            AnyDistribution::Categorical(_) => "Categorical",
+            AnyDistribution::Cauchy(_) => "Cauchy",
+            AnyDistribution::Exponential(_) => "Exponential",
+            AnyDistribution::Gamma(_) => "Gamma",
+            AnyDistribution::Geometric(_) => "Geometric",
+            AnyDistribution::MultivariateNormal(_) => "MultivariateNormal",
+            AnyDistribution::NegativeBinomial(_) => "NegativeBinomial",
+            AnyDistribution::Normal(_) => "Normal",
+            AnyDistribution::Poisson(_) => "Poisson",
+            AnyDistribution::RelaxedOneHotCategorical(_) => "RelaxedOneHotCategorical",
+            AnyDistribution::Uniform(_) => "Uniform",
+        }
+    }
+
+    /// Returns the distribution as a trait object.
+    pub fn as_distribution(&self) -> &dyn Distribution {
+        match self {
+            AnyDistribution::Bernoulli(dist) => dist,
+            AnyDistribution::Beta(dist) => dist,
+            AnyDistribution::Binomial(dist) => dist,
+            AnyDistribution::Categorical(dist) => dist,
+            AnyDistribution::Cauchy(dist) => dist,
+            AnyDistribution::Exponential(dist) => dist,
+            AnyDistribution::Gamma(dist) => dist,
+            AnyDistribution::Geometric(dist) => dist,
+            AnyDistribution::MultivariateNormal(dist) => dist,
+            AnyDistribution::NegativeBinomial(dist) => dist,
+            AnyDistribution::Normal(dist) => dist,
+            AnyDistribution::Poisson(dist) => dist,
+            AnyDistribution::RelaxedOneHotCategorical(dist) => dist,
+            AnyDistribution::Uniform(dist) => dist,
+        }
+    }
+}
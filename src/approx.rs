@@ -0,0 +1,66 @@
+//! Approximations of posterior distributions.
+use crate::MultivariateNormal;
+use tch::{
+    nn::{OptimizerConfig, VarStore},
+    Tensor,
+};
+
+/// Returns the Hessian of the scalar `log_prob` at the `[dim]` shaped point `x`.
+fn hessian<F: Fn(&Tensor) -> Tensor>(log_prob: &F, x: &Tensor) -> Tensor {
+    tch::with_grad(|| {
+        let x = x.detach().set_requires_grad(true);
+        let value = log_prob(&x);
+        let grad = Tensor::run_backward(&[&value], &[&x], true, true)
+            .pop()
+            .unwrap();
+        let rows: Vec<Tensor> = (0..x.size()[0])
+            .map(|i| {
+                Tensor::run_backward(&[grad.get(i)], &[&x], true, false)
+                    .pop()
+                    .unwrap()
+            })
+            .collect();
+        Tensor::stack(&rows, 0)
+    })
+}
+
+/// Approximates the posterior with unnormalized log density `log_posterior` by a Gaussian
+/// around its mode, the Laplace approximation.
+///
+/// The mode is found by maximizing `log_posterior` with the optimizer `optimizer` and learning
+/// rate `lr` from the `[dim]` shaped point `init`, for at most `max_steps` steps or until all
+/// gradients are smaller than `1e-6`. The precision matrix of the approximation is the negated
+/// Hessian of `log_posterior` at the mode, which must be positive definite.
+pub fn laplace<F, O>(
+    log_posterior: F,
+    init: &Tensor,
+    optimizer: O,
+    lr: f64,
+    max_steps: i64,
+) -> MultivariateNormal
+where
+    F: Fn(&Tensor) -> Tensor,
+    O: OptimizerConfig,
+{
+    assert_eq!(
+        init.dim(),
+        1,
+        "initial point must be of shape [dim], got {:?}",
+        init.size()
+    );
+    let mut vs = VarStore::new(init.device());
+    let x = vs.root().var_copy("x", init);
+    vs.set_kind(init.kind());
+    let mut opt = optimizer.build(&vs, lr).unwrap();
+    for _ in 0..max_steps {
+        opt.backward_step(&-log_posterior(&x));
+        let converged = tch::no_grad(|| f64::from(x.grad().abs().max()) < 1e-6);
+        if converged {
+            break;
+        }
+    }
+    let mode = x.detach();
+    let hessian = hessian(&log_posterior, &mode);
+    let precision = -(&hessian + hessian.transpose(0, 1)) / 2.0;
+    MultivariateNormal::from_precision(mode, precision)
+}
@@ -0,0 +1,65 @@
+//! Conversions between distributions and `ndarray` arrays.
+//!
+//! Parameters and values are taken as `ArrayD<f64>` and become `Double` tensors on the CPU,
+//! and all results are copied back into `ArrayD<f64>`.
+use crate::{Distribution, Error, Persist};
+use ndarray::ArrayD;
+use std::collections::HashMap;
+use tch::{Device, Kind, Tensor};
+
+/// Returns the tensor holding the values of `array`.
+pub fn to_tensor(array: &ArrayD<f64>) -> Result<Tensor, Error> {
+    Ok(Tensor::try_from(&array.as_standard_layout())?)
+}
+
+/// Returns the array holding the values of `t`, converted to `f64`.
+pub fn to_array(t: &Tensor) -> ArrayD<f64> {
+    let t = t.detach().to_device(Device::Cpu).to_kind(Kind::Double);
+    (&t).try_into()
+        .expect("the number of elements matches the shape")
+}
+
+/// `ndarray` conveniences, implemented for all distributions.
+pub trait NdarrayExt: Distribution {
+    /// Creates a distribution from its parameters, named as by `params`.
+    fn from_ndarray(params: &[(&str, ArrayD<f64>)]) -> Result<Self, Error>
+    where
+        Self: Persist,
+    {
+        let params = params
+            .iter()
+            .map(|(name, array)| Ok((name.to_string(), to_tensor(array)?)))
+            .collect::<Result<HashMap<_, _>, Error>>()?;
+        Self::from_params(&params)
+    }
+
+    /// Returns the parameters of the distribution, named as by `params`.
+    fn to_ndarray(&self) -> Vec<(&'static str, ArrayD<f64>)> {
+        self.params()
+            .into_iter()
+            .map(|(name, t)| (name, to_array(t)))
+            .collect()
+    }
+
+    /// Returns the log-probabilities of `val`, see `try_log_prob`.
+    fn log_prob_ndarray(&self, val: &ArrayD<f64>) -> Result<ArrayD<f64>, Error> {
+        Ok(to_array(&self.try_log_prob(&to_tensor(val)?)?))
+    }
+
+    /// Returns the cumulative distribution function at `val`, see `try_cdf`.
+    fn cdf_ndarray(&self, val: &ArrayD<f64>) -> Result<ArrayD<f64>, Error> {
+        Ok(to_array(&self.try_cdf(&to_tensor(val)?)?))
+    }
+
+    /// Returns the inverse cumulative distribution function at `val`, see `try_icdf`.
+    fn icdf_ndarray(&self, val: &ArrayD<f64>) -> Result<ArrayD<f64>, Error> {
+        Ok(to_array(&self.try_icdf(&to_tensor(val)?)?))
+    }
+
+    /// Draws `shape` shaped samples, see `try_sample`.
+    fn sample_ndarray(&self, shape: &[i64]) -> Result<ArrayD<f64>, Error> {
+        Ok(to_array(&self.try_sample(shape)?))
+    }
+}
+
+impl<D: Distribution + ?Sized> NdarrayExt for D {}
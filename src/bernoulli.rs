@@ -1,81 +1,195 @@
 use crate::{
-    utils::{infinity, logits_to_probs, probs_to_logits},
-    Distribution, KullackLeiberDivergence,
+    constraints::Constraint,
+    error::check_fit_data,
+    numerics,
+    persist::{param, Persist},
+    trace,
+    utils::{copy_cell, float_values, infinity, logits_to_probs, params_match, stack_param},
+    Distribution, Error, KullackLeiberDivergence, Reparameterize, Stack, Trainable,
 };
-use tch::{Kind, Reduction, Tensor};
+use std::borrow::Borrow;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use tch::{nn, Kind, Reduction, Tensor};
 
 /// A Bernoulli distribution.
+///
+/// Only the parameterization given at construction is stored; the other one is computed on
+/// first use and cached.
 #[derive(Debug)]
 pub struct Bernoulli {
-    probs: Tensor,
-    logits: Tensor,
+    probs: OnceCell<Tensor>,
+    logits: OnceCell<Tensor>,
+    batch_shape: Vec<i64>,
 }
 
 impl Clone for Bernoulli {
     fn clone(&self) -> Self {
         Self {
-            probs: self.probs.copy(),
-            logits: self.logits.copy(),
+            probs: copy_cell(&self.probs),
+            logits: copy_cell(&self.logits),
+            batch_shape: self.batch_shape.clone(),
         }
     }
 }
 
 impl Bernoulli {
     /// Creates a Bernoulli distribution from probabilities.
-    pub fn from_probs(probs: Tensor) -> Self {
+    pub fn from_probs(probs: impl Borrow<Tensor>) -> Self {
+        let probs = probs.borrow().shallow_clone();
+        trace::construct("Bernoulli", &[("probs", &probs)]);
         Self {
-            logits: probs_to_logits(&probs, true),
-            probs,
+            batch_shape: probs.size(),
+            probs: OnceCell::from(probs),
+            logits: OnceCell::new(),
         }
     }
 
     /// Creates a Bernoulli distribution from logits.
-    pub fn from_logits(logits: Tensor) -> Self {
+    pub fn from_logits(logits: impl Borrow<Tensor>) -> Self {
+        let logits = logits.borrow().shallow_clone();
+        trace::construct("Bernoulli", &[("logits", &logits)]);
         Self {
-            probs: logits_to_probs(&logits, true),
-            logits,
+            batch_shape: logits.size(),
+            probs: OnceCell::new(),
+            logits: OnceCell::from(logits),
         }
     }
 
+    /// Fits a Bernoulli distribution to `data` by maximum likelihood, the fraction of ones
+    /// along the leading dimension, which indexes the samples.
+    pub fn fit_mle(data: &Tensor) -> Result<Self, Error> {
+        let data = check_fit_data(data, &Constraint::Boolean)?;
+        Ok(Self::from_probs(data.mean_dim(&[0], false, data.kind())))
+    }
+
     /// Returns the probabilities of the distribution.
     pub fn probs(&self) -> &Tensor {
-        &self.probs
+        self.probs
+            .get_or_init(|| logits_to_probs(self.logits.get().unwrap(), true))
+    }
+
+    /// Returns the logits of the distribution.
+    pub fn logits(&self) -> &Tensor {
+        self.logits
+            .get_or_init(|| numerics::logit(self.probs.get().unwrap()))
+    }
+
+    /// Creates a `Bernoulli` distribution of batch `shape` with probabilities of one half
+    /// whose logits are variables under `path`.
+    pub fn trainable(path: &nn::Path, shape: &[i64]) -> Trainable<Self> {
+        let options = (Kind::Float, path.device());
+        let init = Self::from_logits(Tensor::zeros(shape, options));
+        Trainable::new(path, &init, &["logits"], |p| Self::from_logits(&p[0]))
+    }
+}
+
+impl PartialEq for Bernoulli {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
     }
 }
 
 impl Distribution for Bernoulli {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("probs", self.probs()), ("logits", self.logits())]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("probs", Constraint::UnitInterval),
+            ("logits", Constraint::Real),
+        ]
+    }
+
     fn entropy(&self) -> Tensor {
-        self.logits.binary_cross_entropy_with_logits::<Tensor>(
-            &self.probs,
+        self.logits().binary_cross_entropy_with_logits::<Tensor>(
+            self.probs(),
             None,
             None,
             Reduction::None,
         )
     }
 
+    fn mean(&self) -> Tensor {
+        self.probs().shallow_clone()
+    }
+
+    fn variance(&self) -> Tensor {
+        self.probs() * (1.0 - self.probs())
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::Boolean
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (self.probs().zeros_like(), self.probs().ones_like())
+    }
+
     fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        let val = float_values(val, self.logits());
         -self
-            .logits
-            .binary_cross_entropy_with_logits::<Tensor>(val, None, None, Reduction::None)
+            .logits()
+            .binary_cross_entropy_with_logits::<Tensor>(&val, None, None, Reduction::None)
     }
 
     fn sample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        let shape = self.extended_shape(shape);
         tch::no_grad(|| {
-            Tensor::empty(shape, (Kind::Bool, self.probs.device())).bernoulli_(&self.probs)
+            Tensor::empty(&shape, (Kind::Bool, self.probs().device())).bernoulli_(self.probs())
         })
     }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+}
+
+impl Reparameterize for Bernoulli {
+    fn noise(&self, shape: &[i64]) -> Tensor {
+        let shape = self.extended_shape(shape);
+        Tensor::rand(&shape, (self.probs().kind(), self.probs().device()))
+    }
+
+    fn fill_noise(&self, noise: &mut Tensor) {
+        let _ = noise.uniform_(0.0, 1.0);
+    }
+
+    fn reparameterize(&self, noise: &Tensor) -> Tensor {
+        noise.lt_tensor(self.probs()).to_kind(self.probs().kind())
+    }
+}
+
+impl Stack for Bernoulli {
+    fn stack(dists: &[Self]) -> Self {
+        Self::from_probs(stack_param(dists, Self::probs))
+    }
+}
+
+impl Persist for Bernoulli {
+    const FAMILY: &'static str = "bernoulli";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::from_logits(param(params, "logits")?))
+    }
 }
 
 impl KullackLeiberDivergence<Self> for Bernoulli {
     fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
         let t1: Tensor = self.probs() * (self.probs() / other.probs()).log();
-        let t1 = t1.where_self(&other.probs().f_ne(0.0).unwrap(), &infinity(t1.kind()));
-        let t1 = t1.where_self(&self.probs().f_ne(0.0).unwrap(), &0.0.into());
+        let t1 = t1.where_self(&other.probs().f_ne(0.0).unwrap(), &infinity(&t1));
+        let t1 = t1.where_self(&self.probs().f_ne(0.0).unwrap(), &t1.zeros_like());
 
-        let t2: Tensor = (&1.0.into() - self.probs())
-            * ((&1.0.into() - self.probs()) / (&1.0.into() - other.probs())).log();
-        let t2 = t2.where_self(&other.probs().f_ne(1.0).unwrap(), &infinity(t1.kind()));
-        let t2 = t2.where_self(&self.probs().f_ne(1.0).unwrap(), &0.0.into());
+        let self_q: Tensor = 1.0 - self.probs();
+        let other_q: Tensor = 1.0 - other.probs();
+        let t2 = &self_q * (&self_q / other_q).log();
+        let t2 = t2.where_self(&other.probs().f_ne(1.0).unwrap(), &infinity(&t1));
+        let t2 = t2.where_self(&self.probs().f_ne(1.0).unwrap(), &t2.zeros_like());
 
         t1 + t2
     }
@@ -0,0 +1,241 @@
+use crate::{
+    constraints::Constraint,
+    error::{check_fit_data, check_fitted},
+    persist::{param, Persist},
+    trace,
+    utils::{
+        debug_assert_same_device, eps, params_match, sample_moments, stack_param, tiny,
+        NEWTON_STEPS,
+    },
+    Distribution, Error, KullackLeiberDivergence, Stack, Trainable,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use tch::{nn, Kind, Tensor};
+
+/// A Beta distribution.
+#[derive(Debug)]
+pub struct Beta {
+    concentration1: Tensor,
+    concentration0: Tensor,
+    log_beta: Tensor,
+    batch_shape: Vec<i64>,
+}
+
+impl Clone for Beta {
+    fn clone(&self) -> Self {
+        Self {
+            concentration1: self.concentration1.copy(),
+            concentration0: self.concentration0.copy(),
+            log_beta: self.log_beta.copy(),
+            batch_shape: self.batch_shape.clone(),
+        }
+    }
+}
+
+/// Returns the logarithm of the beta function of `a` and `b`.
+fn log_beta(a: &Tensor, b: &Tensor) -> Tensor {
+    a.lgamma() + b.lgamma() - (a + b).lgamma()
+}
+
+impl Beta {
+    /// Creates a new `Beta` distribution with `concentration1` (often referred to as alpha)
+    /// and `concentration0` (often referred to as beta).
+    pub fn new(concentration1: impl Borrow<Tensor>, concentration0: impl Borrow<Tensor>) -> Self {
+        let concentration1 = concentration1.borrow().shallow_clone();
+        let concentration0 = concentration0.borrow().shallow_clone();
+        debug_assert_same_device(&[&concentration1, &concentration0]);
+        trace::construct(
+            "Beta",
+            &[
+                ("concentration1", &concentration1),
+                ("concentration0", &concentration0),
+            ],
+        );
+        let batch_shape = concentration1.size();
+        let log_beta = log_beta(&concentration1, &concentration0);
+        Self {
+            concentration1,
+            concentration0,
+            log_beta,
+            batch_shape,
+        }
+    }
+
+    /// Fits a `Beta` distribution to `data` by the method of moments, matching the sample mean
+    /// and variance along the leading dimension, which indexes the samples.
+    pub fn fit_moments(data: &Tensor) -> Self {
+        let (mean, variance) = sample_moments(data);
+        let total = &mean * (1.0 - &mean) / variance - 1.0;
+        Self::new(&mean * &total, (1.0 - mean) * total)
+    }
+
+    /// Fits a `Beta` distribution to `data` by maximum likelihood along the leading dimension,
+    /// which indexes the samples. Both concentrations are found jointly by Newton's method on
+    /// the log-likelihood, whose gradient and Hessian only involve digamma and trigamma
+    /// functions, starting from the method of moments estimate.
+    pub fn fit_mle(data: &Tensor) -> Result<Self, Error> {
+        let data = check_fit_data(data, &Constraint::UnitInterval)?;
+        let kind = data.kind();
+        let mean_log = data.log().mean_dim(&[0], false, kind);
+        let mean_log1m = (-&data).log1p().mean_dim(&[0], false, kind);
+        let init = Self::fit_moments(&data);
+        let (mut a, mut b) = (init.concentration1, init.concentration0);
+        for _ in 0..NEWTON_STEPS {
+            let total = &a + &b;
+            let digamma_total = total.digamma();
+            let trigamma_total = total.polygamma(1);
+            let grad_a = &digamma_total - a.digamma() + &mean_log;
+            let grad_b = digamma_total - b.digamma() + &mean_log1m;
+            let hessian_aa = &trigamma_total - a.polygamma(1);
+            let hessian_bb = &trigamma_total - b.polygamma(1);
+            let det = &hessian_aa * &hessian_bb - trigamma_total.pow_tensor_scalar(2);
+            let step_a = (&hessian_bb * &grad_a - &trigamma_total * &grad_b) / &det;
+            let step_b = (hessian_aa * grad_b - trigamma_total * grad_a) / det;
+            // Newton steps may at most halve a concentration, which keeps it positive.
+            a = (&a - step_a).maximum(&(&a / 2.0));
+            b = (&b - step_b).maximum(&(&b / 2.0));
+        }
+        check_fitted(&[&a, &b])?;
+        Ok(Self::new(a, b))
+    }
+
+    /// Returns the first concentration parameter, the pseudo-count of ones.
+    pub fn concentration1(&self) -> &Tensor {
+        &self.concentration1
+    }
+
+    /// Returns the second concentration parameter, the pseudo-count of zeros.
+    pub fn concentration0(&self) -> &Tensor {
+        &self.concentration0
+    }
+
+    /// Creates a uniform `Beta` distribution of batch `shape` whose concentrations are
+    /// variables under `path`, stored as their logarithms.
+    pub fn trainable(path: &nn::Path, shape: &[i64]) -> Trainable<Self> {
+        let options = (Kind::Float, path.device());
+        let init = Self::new(Tensor::ones(shape, options), Tensor::ones(shape, options));
+        Trainable::new(path, &init, &["concentration1", "concentration0"], |p| {
+            Self::new(&p[0], &p[1])
+        })
+    }
+}
+
+impl From<(f64, f64)> for Beta {
+    /// Creates a scalar `Beta` distribution from `(concentration1, concentration0)`.
+    fn from((concentration1, concentration0): (f64, f64)) -> Self {
+        Self::new(Tensor::from(concentration1), Tensor::from(concentration0))
+    }
+}
+
+impl PartialEq for Beta {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
+}
+
+impl Distribution for Beta {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![
+            ("concentration1", &self.concentration1),
+            ("concentration0", &self.concentration0),
+        ]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("concentration1", Constraint::Positive),
+            ("concentration0", Constraint::Positive),
+        ]
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        (&self.concentration1 - 1) * val.log() + (&self.concentration0 - 1) * (-val).log1p()
+            - &self.log_beta
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        let shape = self.extended_shape(shape);
+        // Gradients flow back through the implicitly reparameterized Gamma samples.
+        let x = self
+            .concentration1
+            .expand(&shape, false)
+            .internal_standard_gamma();
+        let y = self
+            .concentration0
+            .expand(&shape, false)
+            .internal_standard_gamma();
+        let value = &x / (&x + y);
+        let (tiny, eps) = (tiny(value.kind()).unwrap(), eps(value.kind()).unwrap());
+        let _ = value.detach().clamp_(tiny, 1.0 - eps);
+        value
+    }
+
+    fn entropy(&self) -> Tensor {
+        let total = &self.concentration1 + &self.concentration0;
+        &self.log_beta
+            - (&self.concentration1 - 1) * self.concentration1.digamma()
+            - (&self.concentration0 - 1) * self.concentration0.digamma()
+            + (&total - 2) * total.digamma()
+    }
+
+    fn mean(&self) -> Tensor {
+        &self.concentration1 / (&self.concentration1 + &self.concentration0)
+    }
+
+    fn variance(&self) -> Tensor {
+        let total = &self.concentration1 + &self.concentration0;
+        &self.concentration1 * &self.concentration0 / (total.pow_tensor_scalar(2) * (total + 1))
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::UnitInterval
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (
+            self.concentration1.zeros_like(),
+            self.concentration1.ones_like(),
+        )
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+}
+
+impl Stack for Beta {
+    fn stack(dists: &[Self]) -> Self {
+        Self::new(
+            stack_param(dists, Self::concentration1),
+            stack_param(dists, Self::concentration0),
+        )
+    }
+}
+
+impl Persist for Beta {
+    const FAMILY: &'static str = "beta";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::new(
+            param(params, "concentration1")?,
+            param(params, "concentration0")?,
+        ))
+    }
+}
+
+impl KullackLeiberDivergence<Self> for Beta {
+    fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
+        let total = self.concentration1() + self.concentration0();
+        let other_total = other.concentration1() + other.concentration0();
+        let t1 = &other.log_beta - &self.log_beta;
+        let t2 = (self.concentration1() - other.concentration1()) * self.concentration1().digamma();
+        let t3 = (self.concentration0() - other.concentration0()) * self.concentration0().digamma();
+        let t4 = (other_total - &total) * total.digamma();
+        t1 + t2 + t3 + t4
+    }
+}
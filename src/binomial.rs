@@ -0,0 +1,188 @@
+use crate::{
+    constraints::Constraint,
+    numerics,
+    persist::{param, Persist},
+    trace,
+    utils::{copy_cell, float_values, logits_to_probs, params_match, stack_param},
+    Distribution, Error, Stack,
+};
+use std::borrow::Borrow;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use tch::Tensor;
+
+/// A Binomial distribution counting the successes among `total_count` independent Bernoulli
+/// trials.
+///
+/// Only the parameterization of the success probability given at construction is stored; the
+/// other one is computed on first use and cached.
+#[derive(Debug)]
+pub struct Binomial {
+    total_count: Tensor,
+    probs: OnceCell<Tensor>,
+    logits: OnceCell<Tensor>,
+    batch_shape: Vec<i64>,
+}
+
+impl Clone for Binomial {
+    fn clone(&self) -> Self {
+        Self {
+            total_count: self.total_count.copy(),
+            probs: copy_cell(&self.probs),
+            logits: copy_cell(&self.logits),
+            batch_shape: self.batch_shape.clone(),
+        }
+    }
+}
+
+impl Binomial {
+    /// Creates a Binomial distribution with `total_count` trials from success probabilities.
+    pub fn from_probs(total_count: impl Borrow<Tensor>, probs: impl Borrow<Tensor>) -> Self {
+        let [total_count, probs]: [Tensor; 2] =
+            Tensor::broadcast_tensors(&[total_count.borrow(), probs.borrow()])
+                .try_into()
+                .unwrap();
+        trace::construct(
+            "Binomial",
+            &[("total_count", &total_count), ("probs", &probs)],
+        );
+        Self {
+            batch_shape: probs.size(),
+            total_count,
+            probs: OnceCell::from(probs),
+            logits: OnceCell::new(),
+        }
+    }
+
+    /// Creates a Binomial distribution with `total_count` trials from success logits.
+    pub fn from_logits(total_count: impl Borrow<Tensor>, logits: impl Borrow<Tensor>) -> Self {
+        let [total_count, logits]: [Tensor; 2] =
+            Tensor::broadcast_tensors(&[total_count.borrow(), logits.borrow()])
+                .try_into()
+                .unwrap();
+        trace::construct(
+            "Binomial",
+            &[("total_count", &total_count), ("logits", &logits)],
+        );
+        Self {
+            batch_shape: logits.size(),
+            total_count,
+            probs: OnceCell::new(),
+            logits: OnceCell::from(logits),
+        }
+    }
+
+    /// Returns the number of trials.
+    pub fn total_count(&self) -> &Tensor {
+        &self.total_count
+    }
+
+    /// Returns the success probabilities of the distribution.
+    pub fn probs(&self) -> &Tensor {
+        self.probs
+            .get_or_init(|| logits_to_probs(self.logits.get().unwrap(), true))
+    }
+
+    /// Returns the success logits of the distribution.
+    pub fn logits(&self) -> &Tensor {
+        self.logits
+            .get_or_init(|| numerics::logit(self.probs.get().unwrap()))
+    }
+}
+
+impl PartialEq for Binomial {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
+}
+
+impl Distribution for Binomial {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![
+            ("total_count", &self.total_count),
+            ("probs", self.probs()),
+            ("logits", self.logits()),
+        ]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("total_count", Constraint::nonnegative_integer()),
+            ("probs", Constraint::UnitInterval),
+            ("logits", Constraint::Real),
+        ]
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        let val = &float_values(val, self.logits());
+        let n = &self.total_count;
+        let log_binomial = (n + 1.0).lgamma() - (val + 1.0).lgamma() - (n - val + 1.0).lgamma();
+        log_binomial + val * self.logits() - n * self.logits().softplus()
+    }
+
+    /// Returns `P(X <= val)`, which is `I_{1 - p}(n - k, k + 1)` for `k = floor(val)`.
+    fn cdf(&self, val: &Tensor) -> Tensor {
+        self.validate_sample(val);
+        let k = val.to_kind(self.probs().kind()).floor();
+        let n = &self.total_count;
+        let cdf = numerics::betainc(&(n - &k), &(&k + 1.0), &(1.0 - self.probs()));
+        let cdf = cdf.where_self(&k.lt_tensor(n), &cdf.ones_like());
+        cdf.where_self(&k.ge(0.0), &cdf.zeros_like())
+    }
+
+    fn sample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        let shape = self.extended_shape(shape);
+        tch::no_grad(|| {
+            Tensor::binomial(
+                &self.total_count.expand(&shape, false),
+                &self.probs().expand(&shape, false),
+            )
+        })
+    }
+
+    fn mean(&self) -> Tensor {
+        &self.total_count * self.probs()
+    }
+
+    fn variance(&self) -> Tensor {
+        &self.total_count * self.probs() * (1.0 - self.probs())
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::nonnegative_integer()
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (
+            self.total_count.zeros_like(),
+            self.total_count.shallow_clone(),
+        )
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+}
+
+impl Stack for Binomial {
+    fn stack(dists: &[Self]) -> Self {
+        Self::from_probs(
+            stack_param(dists, Self::total_count),
+            stack_param(dists, Self::probs),
+        )
+    }
+}
+
+impl Persist for Binomial {
+    const FAMILY: &'static str = "binomial";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::from_logits(
+            param(params, "total_count")?,
+            param(params, "logits")?,
+        ))
+    }
+}
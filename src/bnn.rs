@@ -0,0 +1,163 @@
+//! Weight priors and mean-field layers for Bayesian neural networks.
+use crate::{utils::standard_normal, Distribution, KullackLeiberDivergence, Normal};
+use std::fmt;
+use tch::{
+    nn::{self, Init},
+    Kind, Tensor,
+};
+
+type LogProb = Box<dyn Fn(&Tensor) -> Tensor>;
+
+/// Prior distributions over the variables of a `VarStore`.
+///
+/// Priors are attached to variable name prefixes, e.g. `"encoder"` for all variables below the
+/// path `encoder` or `"encoder.weight"` for a single one, and the longest matching prefix
+/// wins. Variables without a matching prefix get the default prior if one is set and a flat
+/// prior otherwise. The batch shape of each prior must broadcast against the variables it
+/// applies to.
+#[derive(Default)]
+pub struct WeightPriors {
+    default: Option<LogProb>,
+    priors: Vec<(String, LogProb)>,
+}
+
+impl WeightPriors {
+    /// Creates priors without any default, so all variables without a prior are unpenalized.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates priors with `prior` applying to every variable without a more specific prior.
+    pub fn with_default<D: Distribution + 'static>(prior: D) -> Self {
+        Self {
+            default: Some(Box::new(move |x| prior.log_prob(x))),
+            priors: Vec::new(),
+        }
+    }
+
+    /// Attaches `prior` to all variables whose name is `prefix` or starts with `prefix.`.
+    pub fn set<D: Distribution + 'static>(&mut self, prefix: &str, prior: D) -> &mut Self {
+        self.priors.retain(|(p, _)| p != prefix);
+        self.priors
+            .push((prefix.to_string(), Box::new(move |x| prior.log_prob(x))));
+        self
+    }
+
+    /// Returns the prior log-density of the variable `name`, if it has a prior.
+    fn find(&self, name: &str) -> Option<&LogProb> {
+        self.priors
+            .iter()
+            .filter(|(prefix, _)| {
+                name == prefix
+                    || (name.starts_with(prefix.as_str()) && name[prefix.len()..].starts_with('.'))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, log_prob)| log_prob)
+            .or(self.default.as_ref())
+    }
+
+    /// Returns the total prior log-probability of all variables of `vs`, differentiable with
+    /// respect to the variables.
+    pub fn log_prob(&self, vs: &nn::VarStore) -> Tensor {
+        let mut total = Tensor::zeros(&[], (Kind::Double, vs.device()));
+        for (name, var) in vs.variables() {
+            if let Some(log_prob) = self.find(&name) {
+                total += log_prob(&var).sum(Kind::Double);
+            }
+        }
+        total
+    }
+}
+
+impl fmt::Debug for WeightPriors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let prefixes: Vec<&str> = self.priors.iter().map(|(p, _)| p.as_str()).collect();
+        f.debug_struct("WeightPriors")
+            .field("default", &self.default.is_some())
+            .field("prefixes", &prefixes)
+            .finish()
+    }
+}
+
+/// A linear layer with a factorized Gaussian posterior over its weights and biases.
+///
+/// Every weight has a mean and an unconstrained scale `rho`, its standard deviation being
+/// `softplus(rho)`. The layer is trained by maximizing the evidence lower bound, the expected
+/// log-likelihood of the outputs minus `kl_divergence` to the prior.
+#[derive(Debug)]
+pub struct MeanFieldLinear {
+    weight_mean: Tensor,
+    weight_rho: Tensor,
+    bias_mean: Tensor,
+    bias_rho: Tensor,
+}
+
+impl MeanFieldLinear {
+    /// Creates a layer with variables in `path` mapping `in_dim` to `out_dim` features, with
+    /// the posterior standard deviations initialized to `init_stddev`.
+    pub fn new(path: &nn::Path, in_dim: i64, out_dim: i64, init_stddev: f64) -> Self {
+        let bound = 1.0 / (in_dim as f64).sqrt();
+        let rho = init_stddev.exp_m1().ln();
+        Self {
+            weight_mean: path.var(
+                "weight_mean",
+                &[out_dim, in_dim],
+                Init::Uniform {
+                    lo: -bound,
+                    up: bound,
+                },
+            ),
+            weight_rho: path.var("weight_rho", &[out_dim, in_dim], Init::Const(rho)),
+            bias_mean: path.var("bias_mean", &[out_dim], Init::Const(0.0)),
+            bias_rho: path.var("bias_rho", &[out_dim], Init::Const(rho)),
+        }
+    }
+
+    /// Returns the posterior over the `[out_dim, in_dim]` shaped weights.
+    pub fn weight_posterior(&self) -> Normal {
+        Normal::new(&self.weight_mean, self.weight_rho.softplus())
+    }
+
+    /// Returns the posterior over the `[out_dim]` shaped biases.
+    pub fn bias_posterior(&self) -> Normal {
+        Normal::new(&self.bias_mean, self.bias_rho.softplus())
+    }
+
+    /// Returns the outputs for the `[batch..., in_dim]` shaped inputs `xs` with the local
+    /// reparameterization trick: instead of sampling the weights, the Gaussian pre-activations
+    /// they induce are sampled independently for every input, which reduces the variance of
+    /// the gradients.
+    pub fn forward(&self, xs: &Tensor) -> Tensor {
+        let mean = xs.linear(&self.weight_mean, Some(&self.bias_mean));
+        let weight_variance = self.weight_rho.softplus().pow_tensor_scalar(2);
+        let bias_variance = self.bias_rho.softplus().pow_tensor_scalar(2);
+        let variance = xs
+            .pow_tensor_scalar(2)
+            .linear(&weight_variance, Some(&bias_variance));
+        let noise = standard_normal(&mean.size(), mean.kind(), mean.device());
+        mean + variance.sqrt() * noise
+    }
+
+    /// Returns the outputs for the inputs `xs` with a single sample of the weights and biases
+    /// shared by all inputs.
+    pub fn forward_sampled(&self, xs: &Tensor) -> Tensor {
+        let weight = self.weight_posterior().rsample(&[]);
+        let bias = self.bias_posterior().rsample(&[]);
+        xs.linear(&weight, Some(&bias))
+    }
+
+    /// Returns the outputs for the inputs `xs` with the posterior means of the weights and
+    /// biases.
+    pub fn forward_mean(&self, xs: &Tensor) -> Tensor {
+        xs.linear(&self.weight_mean, Some(&self.bias_mean))
+    }
+
+    /// Returns the Kullback-Leibler divergence of the posterior over all weights and biases
+    /// from the factorized Gaussian `prior`, which must broadcast against both.
+    pub fn kl_divergence(&self, prior: &Normal) -> Tensor {
+        self.weight_posterior()
+            .kl_divergence(prior)
+            .sum(Kind::Double)
+            + self.bias_posterior().kl_divergence(prior).sum(Kind::Double)
+    }
+}
@@ -0,0 +1,159 @@
+//! Building blocks for Bayesian nonparametric models.
+use crate::{
+    constraints::Constraint, utils::params_match, Categorical, Distribution, MixtureSameFamily,
+    Reparameterize,
+};
+use std::borrow::Borrow;
+use tch::Tensor;
+
+/// Returns the weights of the stick-breaking process which breaks off the fractions
+/// `fractions` of the remaining stick, with the breaks along the last dimension. The last
+/// weight takes the rest of the stick, so `n` fractions give `n + 1` weights summing to one.
+pub fn weights_from_fractions(fractions: &Tensor) -> Tensor {
+    let remaining = (-fractions).log1p().cumsum(-1, fractions.kind()).exp();
+    let mut shape = fractions.size();
+    *shape.last_mut().unwrap() = 1;
+    let ones = Tensor::ones(&shape, (fractions.kind(), fractions.device()));
+    Tensor::cat(&[fractions, &ones], -1) * Tensor::cat(&[&ones, &remaining], -1)
+}
+
+/// Returns a mixture of `components` with the mixing `weights`, e.g. stick-breaking weights
+/// of a truncated Dirichlet process mixture. The rightmost batch dimension of `components`
+/// indexes the components and must match the last dimension of `weights`.
+pub fn dp_mixture<D: Distribution>(weights: &Tensor, components: D) -> MixtureSameFamily<D> {
+    MixtureSameFamily::new(Categorical::from_probs(weights), components)
+}
+
+/// The weights of a Dirichlet process with `concentration`, truncated to `truncation`
+/// components.
+///
+/// The first `truncation - 1` weights are built by breaking off `Beta(1, concentration)`
+/// distributed fractions of the remaining stick, and the last weight takes the rest, so
+/// samples lie on the simplex. Smaller concentrations put more mass on the first components.
+#[derive(Debug)]
+pub struct StickBreaking {
+    concentration: Tensor,
+    batch_shape: Vec<i64>,
+    event_shape: Vec<i64>,
+}
+
+impl Clone for StickBreaking {
+    fn clone(&self) -> Self {
+        Self::new(self.concentration.copy(), self.event_shape[0])
+    }
+}
+
+impl StickBreaking {
+    /// Creates stick-breaking weights with `concentration` and `truncation` components.
+    pub fn new(concentration: impl Borrow<Tensor>, truncation: i64) -> Self {
+        assert!(
+            truncation > 0,
+            "truncation must be positive, got {}",
+            truncation
+        );
+        let concentration = concentration.borrow().shallow_clone();
+        let batch_shape = concentration.size();
+        Self {
+            concentration,
+            batch_shape,
+            event_shape: vec![truncation],
+        }
+    }
+
+    /// Returns the concentration of the Dirichlet process.
+    pub fn concentration(&self) -> &Tensor {
+        &self.concentration
+    }
+
+    /// Returns the number of components.
+    pub fn truncation(&self) -> i64 {
+        self.event_shape[0]
+    }
+
+    /// Draws stick-breaking weights of `shape` and returns the mixture of `components` with
+    /// them, see `dp_mixture`. The batch shape of `components` must be `shape` followed by the
+    /// batch shape of this distribution and the truncation.
+    pub fn sample_mixture<D: Distribution>(
+        &self,
+        shape: &[i64],
+        components: D,
+    ) -> MixtureSameFamily<D> {
+        dp_mixture(&self.sample(shape), components)
+    }
+}
+
+impl PartialEq for StickBreaking {
+    fn eq(&self, other: &Self) -> bool {
+        self.event_shape == other.event_shape
+            && params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
+}
+
+impl Distribution for StickBreaking {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("concentration", &self.concentration)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![("concentration", Constraint::Positive)]
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::Simplex
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        self.validate_sample(val);
+        let n = self.truncation();
+        let kind = val.kind();
+        // The stick remaining before each break, summed from the right for stability.
+        let remaining = val.flip(&[-1]).cumsum(-1, kind).flip(&[-1]);
+        let last = val.select(-1, n - 1);
+        // Each fraction `val_k / remaining_k` has density `a * (1 - fraction)^(a - 1)`, and the
+        // Jacobian of the map to the weights is the product of the remaining sticks.
+        let log_jacobian = remaining
+            .narrow(-1, 1, (n - 2).max(0))
+            .log()
+            .sum_dim_intlist(&[-1], false, kind);
+        self.concentration.log() * (n - 1) as f64 + (&self.concentration - 1.0) * last.log()
+            - log_jacobian
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let noise = self.noise(shape);
+        self.reparameterize(&noise)
+    }
+
+    fn mean(&self) -> Tensor {
+        let n = self.truncation();
+        let concentration = self.concentration.unsqueeze(-1);
+        let kind = concentration.kind();
+        let k = Tensor::arange(n, (kind, concentration.device()));
+        let survival = (&concentration / (&concentration + 1.0)).pow(&k);
+        let is_last = k.eq((n - 1) as f64).to_kind(kind);
+        survival * ((concentration + 1.0).reciprocal() * (1.0 - &is_last) + is_last)
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+
+    fn event_shape(&self) -> &[i64] {
+        &self.event_shape
+    }
+}
+
+impl Reparameterize for StickBreaking {
+    fn noise(&self, shape: &[i64]) -> Tensor {
+        let mut shape = self.extended_shape(shape);
+        *shape.last_mut().unwrap() -= 1;
+        let concentration = &self.concentration;
+        Tensor::rand(&shape, (concentration.kind(), concentration.device()))
+    }
+
+    fn reparameterize(&self, noise: &Tensor) -> Tensor {
+        // `1 - u^(1 / a)` inverts the distribution function of `Beta(1, a)`.
+        let fractions = -(noise.log() / self.concentration.unsqueeze(-1)).expm1();
+        weights_from_fractions(&fractions)
+    }
+}
@@ -0,0 +1,352 @@
+//! A C interface to the distributions over opaque handles.
+//!
+//! Distributions are created by the `tch_distr_<family>_new` functions from arrays of
+//! parameters of length `batch`, one element per distribution of the batch, and must be
+//! released with `tch_distr_free`. All values are exchanged as `double` arrays on the CPU.
+//! Functions that can fail return `0` on success and `-1` on failure, in which case
+//! `tch_distr_last_error` describes the error. The declarations can be generated with
+//! `cbindgen`.
+use crate::{
+    error, AnyDistribution, Bernoulli, Beta, Binomial, Categorical, Cauchy, Error, Exponential,
+    Gamma, Geometric, NegativeBinomial, Normal, Poisson, Uniform,
+};
+use std::{cell::RefCell, ffi::CString, os::raw::c_char, ptr, slice};
+use tch::{Kind, Tensor};
+
+/// An opaque handle to a distribution.
+pub struct TchDistribution(AnyDistribution);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Records `err` as the last error of the thread and returns `-1`.
+fn fail(err: Error) -> i32 {
+    let msg = CString::new(err.to_string().replace('\0', " ")).unwrap();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(msg));
+    -1
+}
+
+/// Returns the tensor of the `len` values at `ptr`, which must not be null.
+unsafe fn tensor_from_raw(ptr: *const f64, len: usize) -> Result<Tensor, Error> {
+    if ptr.is_null() {
+        return Err(Error::Format("got a null pointer".to_string()));
+    }
+    Ok(Tensor::of_slice(slice::from_raw_parts(ptr, len)))
+}
+
+/// Returns the handle to the distribution created by `f` from `params`, or null on failure.
+unsafe fn create<const N: usize>(
+    params: [*const f64; N],
+    batch: usize,
+    f: impl FnOnce([Tensor; N]) -> AnyDistribution,
+) -> *mut TchDistribution {
+    let result = params
+        .iter()
+        .map(|&p| tensor_from_raw(p, batch))
+        .collect::<Result<Vec<_>, _>>()
+        .and_then(|tensors| {
+            let tensors: [Tensor; N] = tensors.try_into().unwrap();
+            error::catch(|| f(tensors))
+        });
+    match result {
+        Ok(dist) => Box::into_raw(Box::new(TchDistribution(dist))),
+        Err(err) => {
+            fail(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Copies the values of `t` to the `len` elements at `out`.
+unsafe fn copy_out(t: &Tensor, out: *mut f64, len: usize) -> Result<(), Error> {
+    if out.is_null() {
+        return Err(Error::Format("got a null pointer".to_string()));
+    }
+    let t = t.to_kind(Kind::Double).contiguous();
+    if t.numel() != len {
+        return Err(Error::Shape(format!(
+            "the output holds {} elements, but {} are needed",
+            len,
+            t.numel()
+        )));
+    }
+    t.f_copy_data(slice::from_raw_parts_mut(out, len), len)?;
+    Ok(())
+}
+
+/// Returns the description of the last error of the calling thread, or null if there was
+/// none. The string is valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn tch_distr_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |msg| msg.as_ptr())
+    })
+}
+
+/// Creates a batch of Bernoulli distributions with success probabilities `probs`.
+///
+/// # Safety
+/// `probs` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_bernoulli_new(
+    probs: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([probs], batch, |[probs]| {
+        AnyDistribution::Bernoulli(Bernoulli::from_probs(probs))
+    })
+}
+
+/// Creates a batch of Beta distributions.
+///
+/// # Safety
+/// `concentration1` and `concentration0` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_beta_new(
+    concentration1: *const f64,
+    concentration0: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([concentration1, concentration0], batch, |[c1, c0]| {
+        AnyDistribution::Beta(Beta::new(c1, c0))
+    })
+}
+
+/// Creates a batch of Binomial distributions of `total_count` trials with success
+/// probabilities `probs`.
+///
+/// # Safety
+/// `total_count` and `probs` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_binomial_new(
+    total_count: *const f64,
+    probs: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([total_count, probs], batch, |[total_count, probs]| {
+        AnyDistribution::Binomial(Binomial::from_probs(total_count, probs))
+    })
+}
+
+/// Creates a batch of Categorical distributions over `categories` categories, with the
+/// probabilities of each distribution stored contiguously in `probs`.
+///
+/// # Safety
+/// `probs` must point to `batch * categories` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_categorical_new(
+    probs: *const f64,
+    batch: usize,
+    categories: usize,
+) -> *mut TchDistribution {
+    create([probs], batch * categories, |[probs]| {
+        let probs = probs.reshape(&[batch as i64, categories as i64]);
+        AnyDistribution::Categorical(Categorical::from_probs(probs))
+    })
+}
+
+/// Creates a batch of Cauchy distributions.
+///
+/// # Safety
+/// `median` and `scale` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_cauchy_new(
+    median: *const f64,
+    scale: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([median, scale], batch, |[median, scale]| {
+        AnyDistribution::Cauchy(Cauchy::new(median, scale))
+    })
+}
+
+/// Creates a batch of Exponential distributions.
+///
+/// # Safety
+/// `rate` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_exponential_new(
+    rate: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([rate], batch, |[rate]| {
+        AnyDistribution::Exponential(Exponential::new(rate))
+    })
+}
+
+/// Creates a batch of Gamma distributions.
+///
+/// # Safety
+/// `concentration` and `rate` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_gamma_new(
+    concentration: *const f64,
+    rate: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([concentration, rate], batch, |[concentration, rate]| {
+        AnyDistribution::Gamma(Gamma::new(concentration, rate))
+    })
+}
+
+/// Creates a batch of Geometric distributions with success probabilities `probs`.
+///
+/// # Safety
+/// `probs` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_geometric_new(
+    probs: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([probs], batch, |[probs]| {
+        AnyDistribution::Geometric(Geometric::from_probs(probs))
+    })
+}
+
+/// Creates a batch of Negative Binomial distributions counting the successes before
+/// `total_count` failures with success probabilities `probs`.
+///
+/// # Safety
+/// `total_count` and `probs` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_negative_binomial_new(
+    total_count: *const f64,
+    probs: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([total_count, probs], batch, |[total_count, probs]| {
+        AnyDistribution::NegativeBinomial(NegativeBinomial::from_probs(total_count, probs))
+    })
+}
+
+/// Creates a batch of Normal distributions.
+///
+/// # Safety
+/// `mean` and `stddev` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_normal_new(
+    mean: *const f64,
+    stddev: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([mean, stddev], batch, |[mean, stddev]| {
+        AnyDistribution::Normal(Normal::new(mean, stddev))
+    })
+}
+
+/// Creates a batch of Poisson distributions.
+///
+/// # Safety
+/// `rate` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_poisson_new(
+    rate: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([rate], batch, |[rate]| {
+        AnyDistribution::Poisson(Poisson::new(rate))
+    })
+}
+
+/// Creates a batch of Uniform distributions.
+///
+/// # Safety
+/// `low` and `high` must point to `batch` values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_uniform_new(
+    low: *const f64,
+    high: *const f64,
+    batch: usize,
+) -> *mut TchDistribution {
+    create([low, high], batch, |[low, high]| {
+        AnyDistribution::Uniform(Uniform::new(low, high))
+    })
+}
+
+/// Releases the distribution `dist`. Null handles are ignored.
+///
+/// # Safety
+/// `dist` must be null or a handle returned by one of the constructors that has not been
+/// released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_free(dist: *mut TchDistribution) {
+    if !dist.is_null() {
+        drop(Box::from_raw(dist));
+    }
+}
+
+/// Returns the number of values of a single sample of `dist`, the product of its batch and
+/// event shapes.
+///
+/// # Safety
+/// `dist` must be a valid handle.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_sample_size(dist: *const TchDistribution) -> usize {
+    let dist = (*dist).0.as_distribution();
+    dist.batch_shape()
+        .iter()
+        .chain(dist.event_shape())
+        .product::<i64>() as usize
+}
+
+/// Draws `n` samples of `dist` into `out`, which receives `n * tch_distr_sample_size(dist)`
+/// values with the samples stored contiguously.
+///
+/// # Safety
+/// `dist` must be a valid handle and `out` must point to `len` writable values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_sample(
+    dist: *const TchDistribution,
+    n: usize,
+    out: *mut f64,
+    len: usize,
+) -> i32 {
+    let dist = (*dist).0.as_distribution();
+    let result = dist
+        .try_sample(&[n as i64])
+        .and_then(|samples| copy_out(&samples, out, len));
+    match result {
+        Ok(()) => 0,
+        Err(err) => fail(err),
+    }
+}
+
+/// Evaluates the log-probabilities of the `len` values at `values` under `dist` into `out`.
+/// The values hold `len / tch_distr_sample_size(dist)` samples stored contiguously, and `out`
+/// receives one log-probability per sample and distribution of the batch.
+///
+/// # Safety
+/// `dist` must be a valid handle, `values` must point to `len` values and `out` must point to
+/// `out_len` writable values.
+#[no_mangle]
+pub unsafe extern "C" fn tch_distr_log_prob(
+    dist: *const TchDistribution,
+    values: *const f64,
+    len: usize,
+    out: *mut f64,
+    out_len: usize,
+) -> i32 {
+    let size = tch_distr_sample_size(dist);
+    let dist = (*dist).0.as_distribution();
+    let result = tensor_from_raw(values, len)
+        .and_then(|values| {
+            if size == 0 || !len.is_multiple_of(size) {
+                return Err(Error::Shape(format!(
+                    "got {} values, which is not a multiple of the sample size {}",
+                    len, size
+                )));
+            }
+            let mut shape = vec![(len / size) as i64];
+            shape.extend_from_slice(dist.batch_shape());
+            shape.extend_from_slice(dist.event_shape());
+            dist.try_log_prob(&values.reshape(&shape))
+        })
+        .and_then(|log_prob| copy_out(&log_prob, out, out_len));
+    match result {
+        Ok(()) => 0,
+        Err(err) => fail(err),
+    }
+}
@@ -1,6 +1,14 @@
 use crate::{
-    utils::{logits_to_probs, min, probs_to_logits}, Distribution};
-use tch::Tensor;
+    constraints::Constraint,
+    numerics,
+    persist::{param, Persist},
+    trace,
+    utils::{logits_to_probs, min, params_match, standard_gumbel},
+    Distribution, Error, KullackLeiberDivergence, Trainable,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use tch::{nn, Kind, Tensor};
 
 /// A Categorical distribution.
 #[derive(Debug)]
@@ -24,7 +32,9 @@ impl Clone for Categorical {
 
 impl Categorical {
     /// Creates a Categorical distribution from probabilities.
-    pub fn from_probs(probs: Tensor) -> Self {
+    pub fn from_probs(probs: impl Borrow<Tensor>) -> Self {
+        let probs = probs.borrow().shallow_clone();
+        trace::construct("Categorical", &[("probs", &probs)]);
         let prob_sum = probs.sum_dim_intlist(&[-1], true, probs.kind());
         let probs = probs / prob_sum;
 
@@ -41,7 +51,7 @@ impl Categorical {
             .clone();
 
         Self {
-            logits: probs_to_logits(&probs, false),
+            logits: numerics::clamp_probs(&probs).log(),
             probs,
             batch_shape,
             num_events,
@@ -49,9 +59,10 @@ impl Categorical {
     }
 
     /// Creates a Categorical distribution from logits.
-    pub fn from_logits(logits: Tensor) -> Self {
-        let logsumexp = logits.logsumexp(&[-1], true);
-        let logits = logits - logsumexp;
+    pub fn from_logits(logits: impl Borrow<Tensor>) -> Self {
+        let logits = logits.borrow().shallow_clone();
+        trace::construct("Categorical", &[("logits", &logits)]);
+        let logits = &logits - numerics::logsumexp(&logits, &[-1], true);
 
         let batch_shape: Vec<i64> = if logits.size().len() > 1 {
             logits.size().split_last().unwrap().1.to_vec()
@@ -85,16 +96,102 @@ impl Categorical {
 
     /// Returns mean of the distribution.
     pub fn mean(&self) -> Tensor {
-        tch::Tensor::full(&[], f64::NAN, (self.probs.kind(), self.probs.device()))
+        Distribution::mean(self)
     }
 
     /// Returns variance of the distribution.
     pub fn variance(&self) -> Tensor {
-        tch::Tensor::full(&[], f64::NAN, (self.probs.kind(), self.probs.device()))
+        Distribution::variance(self)
+    }
+
+    /// Returns the logits perturbed by independent Gumbel noise, shaped
+    /// `[sample_shape, batch_shape, num_events]`.
+    fn perturbed_logits(&self, shape: &[i64]) -> Tensor {
+        let shape = [&self.extended_shape(shape)[..], &[self.num_events]].concat();
+        &self.logits + standard_gumbel(&shape, self.logits.kind(), self.logits.device())
+    }
+
+    /// Draws samples with the Gumbel-max trick: the index of the largest Gumbel-perturbed
+    /// logit is distributed like a sample of the distribution. Unlike `sample`, this does not
+    /// go through `multinomial` and is a plain element-wise kernel followed by an argmax.
+    pub fn sample_gumbel_max(&self, shape: &[i64]) -> Tensor {
+        tch::no_grad(|| self.perturbed_logits(shape).argmax(-1, false))
+    }
+
+    /// Draws `k` distinct categories per sample without replacement with the Gumbel-top-k
+    /// trick, returned by decreasing perturbed logit along a new last dimension of size `k`.
+    pub fn sample_top_k(&self, k: i64, shape: &[i64]) -> Tensor {
+        assert!(
+            (1..=self.num_events).contains(&k),
+            "cannot draw {} of {} categories",
+            k,
+            self.num_events
+        );
+        tch::no_grad(|| self.perturbed_logits(shape).topk(k, -1, true, true).1)
+    }
+
+    /// Draws `k` distinct categories per sample without replacement like `sample_top_k`, and
+    /// returns them together with the log-probabilities of drawing them in that order, computed
+    /// with `log_prob_without_replacement`. Gradients flow back to the logits through the
+    /// log-probabilities, e.g. for score function estimators over subsets.
+    pub fn sample_without_replacement(&self, k: i64, shape: &[i64]) -> (Tensor, Tensor) {
+        let samples = self.sample_top_k(k, shape);
+        let log_prob = self.log_prob_without_replacement(&samples);
+        (samples, log_prob)
+    }
+
+    /// Returns the log-probabilities of drawing the distinct categories along the last
+    /// dimension of `samples` one after another without replacement, i.e. each category is
+    /// drawn with its probability renormalized over the categories not drawn before it.
+    pub fn log_prob_without_replacement(&self, samples: &Tensor) -> Tensor {
+        let size = samples.size();
+        let logits_shape = [&size[..size.len() - 1], &[self.num_events]].concat();
+        let logits = self.logits.expand(&logits_shape, false);
+        let chosen = logits.gather(-1, samples, false);
+        // Logarithm of the mass left before each draw, one minus the mass drawn so far.
+        let drawn = chosen.exp().cumsum(-1, chosen.kind()) - chosen.exp();
+        let remaining = (-drawn).log1p();
+        (chosen - remaining).sum_dim_intlist(&[-1], false, self.logits.kind())
+    }
+
+    /// Draws one-hot encoded samples with straight-through gradients: the forward values are
+    /// exact samples while gradients flow back as if the samples were the probabilities.
+    pub fn rsample_straight_through(&self, shape: &[i64]) -> Tensor {
+        let one_hot = self
+            .sample_gumbel_max(shape)
+            .one_hot(self.num_events)
+            .to_kind(self.probs.kind());
+        one_hot + &self.probs - self.probs.detach()
+    }
+
+    /// Creates a uniform `Categorical` distribution whose logits are variables under `path`.
+    /// The last dimension of `shape` is the number of categories, the leading ones the batch
+    /// shape.
+    pub fn trainable(path: &nn::Path, shape: &[i64]) -> Trainable<Self> {
+        let options = (Kind::Float, path.device());
+        let init = Self::from_logits(Tensor::zeros(shape, options));
+        Trainable::new(path, &init, &["logits"], |p| Self::from_logits(&p[0]))
+    }
+}
+
+impl PartialEq for Categorical {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
     }
 }
 
 impl Distribution for Categorical {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("probs", &self.probs), ("logits", &self.logits)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("probs", Constraint::Simplex),
+            ("logits", Constraint::independent(Constraint::Real, 1)),
+        ]
+    }
+
     fn entropy(&self) -> Tensor {
         let min_real = min(self.logits.kind()).unwrap();
         let logits = self.logits.clamp(min_real, f64::INFINITY);
@@ -102,7 +199,29 @@ impl Distribution for Categorical {
         -p_log_p.sum_dim_intlist(&[-1], false, p_log_p.kind())
     }
 
+    fn mean(&self) -> Tensor {
+        tch::Tensor::full(&[], f64::NAN, (self.probs.kind(), self.probs.device()))
+    }
+
+    fn variance(&self) -> Tensor {
+        tch::Tensor::full(&[], f64::NAN, (self.probs.kind(), self.probs.device()))
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::IntegerInterval(0, self.num_events - 1)
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        let options = (self.probs.kind(), self.probs.device());
+        (
+            Tensor::zeros(&self.batch_shape, options),
+            Tensor::full(&self.batch_shape, (self.num_events - 1) as f64, options),
+        )
+    }
+
     fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
         let value = val.to_kind(tch::Kind::Int64).unsqueeze(-1);
         let value_log_pmf_vec = Tensor::broadcast_tensors(&[value, self.logits.copy()]);
         let value = &value_log_pmf_vec[0];
@@ -113,12 +232,15 @@ impl Distribution for Categorical {
     }
 
     fn sample(&self, sample_shape: &[i64]) -> Tensor {
-        let probs_2d = self.probs.reshape(&[-1, self.num_events]);
-        let numel = sample_shape.iter().product();
-        let x = probs_2d.multinomial(numel, true);
-        let samples_2d = x.transpose(0, 1);
+        let _span = trace::sample(self, sample_shape);
         let ext_shape = self.extended_shape(sample_shape);
-        samples_2d.reshape(&ext_shape)
+        tch::no_grad(|| {
+            let probs_2d = self.probs.reshape(&[-1, self.num_events]);
+            let numel = sample_shape.iter().product();
+            let x = probs_2d.multinomial(numel, true);
+            let samples_2d = x.transpose(0, 1);
+            samples_2d.reshape(&ext_shape)
+        })
     }
 
     fn batch_shape(&self) -> &[i64] {
@@ -126,8 +248,20 @@ impl Distribution for Categorical {
     }
 }
 
-// impl KullackLeiberDivergence<Self> for Categorical {
-//     fn kl_divergence(&self, other: &Self) -> Tensor {
-//         unimplemented!();
-//     }
-// }
+impl KullackLeiberDivergence<Self> for Categorical {
+    fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
+        // Categories without mass under `self` do not contribute, even if `other` excludes them.
+        let t =
+            (&self.probs * (&self.logits - other.logits())).masked_fill(&self.probs.eq(0.0), 0.0);
+        t.sum_dim_intlist(&[-1], false, t.kind())
+    }
+}
+
+impl Persist for Categorical {
+    const FAMILY: &'static str = "categorical";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::from_logits(param(params, "logits")?))
+    }
+}
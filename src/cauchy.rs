@@ -1,12 +1,21 @@
-use crate::Distribution;
+use crate::{
+    constraints::Constraint,
+    persist::{param, Persist},
+    trace,
+    utils::{debug_assert_same_device, params_match},
+    Distribution, Error, KullackLeiberDivergence, Reparameterize, Trainable,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
 use std::f64::consts::PI;
-use tch::Tensor;
+use tch::{nn, Kind, Tensor};
 
 /// A Cauchy distribution.
 #[derive(Debug)]
 pub struct Cauchy {
     median: Tensor,
     scale: Tensor,
+    log_scale: Tensor,
     batch_shape: Vec<i64>,
 }
 
@@ -15,6 +24,7 @@ impl Clone for Cauchy {
         Self {
             median: self.median.copy(),
             scale: self.scale.copy(),
+            log_scale: self.log_scale.copy(),
             batch_shape: self.batch_shape.clone(),
         }
     }
@@ -22,11 +32,17 @@ impl Clone for Cauchy {
 
 impl Cauchy {
     /// Creates a new `Cauchy` distribution `median` and `scale` as half width of the maximum.
-    pub fn new(median: Tensor, scale: Tensor) -> Self {
+    pub fn new(median: impl Borrow<Tensor>, scale: impl Borrow<Tensor>) -> Self {
+        let median = median.borrow().shallow_clone();
+        let scale = scale.borrow().shallow_clone();
+        debug_assert_same_device(&[&median, &scale]);
+        trace::construct("Cauchy", &[("median", &median), ("scale", &scale)]);
         let batch_shape = median.size();
+        let log_scale = scale.log();
         Self {
             median,
             scale,
+            log_scale,
             batch_shape,
         }
     }
@@ -40,26 +56,91 @@ impl Cauchy {
     pub fn scale(&self) -> &Tensor {
         &self.scale
     }
+
+    /// Creates a standard `Cauchy` distribution of batch `shape` whose median and scale are
+    /// variables under `path`, the scale being stored as its logarithm.
+    pub fn trainable(path: &nn::Path, shape: &[i64]) -> Trainable<Self> {
+        let options = (Kind::Float, path.device());
+        let init = Self::new(Tensor::zeros(shape, options), Tensor::ones(shape, options));
+        Trainable::new(path, &init, &["median", "scale"], |p| {
+            Self::new(&p[0], &p[1])
+        })
+    }
+}
+
+impl From<(f64, f64)> for Cauchy {
+    /// Creates a scalar `Cauchy` distribution from `(median, scale)`.
+    fn from((median, scale): (f64, f64)) -> Self {
+        Self::new(Tensor::from(median), Tensor::from(scale))
+    }
+}
+
+impl PartialEq for Cauchy {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
 }
 
 impl Distribution for Cauchy {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("median", &self.median), ("scale", &self.scale)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("median", Constraint::Real),
+            ("scale", Constraint::Positive),
+        ]
+    }
+
     fn log_prob(&self, val: &Tensor) -> Tensor {
-        -PI.ln() - self.scale.log() - (1.0f64 + ((val - &self.median) / &self.scale).pow_tensor_scalar(2)).log()
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        -PI.ln()
+            - &self.log_scale
+            - (1.0f64 + ((val - &self.median) / &self.scale).pow_tensor_scalar(2)).log()
     }
 
     fn sample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
         let shape = self.extended_shape(shape);
-        let eps =
-            Tensor::empty(&shape, (self.median.kind(), self.median.device())).cauchy_(0.0, 1.0);
-        &self.median + eps * &self.scale
+        tch::no_grad(|| {
+            let eps =
+                Tensor::empty(&shape, (self.median.kind(), self.median.device())).cauchy_(0.0, 1.0);
+            &self.median + eps * &self.scale
+        })
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        self.reparameterize(&self.noise(shape))
+    }
+
+    fn sample_antithetic(&self, shape: &[i64]) -> (Tensor, Tensor) {
+        let shape = self.extended_shape(shape);
+        tch::no_grad(|| {
+            let eps = Tensor::empty(&shape, (self.median.kind(), self.median.device()))
+                .cauchy_(0.0, 1.0)
+                * &self.scale;
+            (&self.median + &eps, &self.median - eps)
+        })
     }
 
     fn cdf(&self, val: &Tensor) -> Tensor {
+        self.validate_sample(val);
         ((val - &self.median) / &self.scale).atan() / PI + 0.5
     }
 
     fn entropy(&self) -> Tensor {
-        (4.0 * PI).ln() + self.scale.log()
+        (4.0 * PI).ln() + &self.log_scale
+    }
+
+    fn mean(&self) -> Tensor {
+        self.median.full_like(f64::NAN)
+    }
+
+    fn variance(&self) -> Tensor {
+        self.median.full_like(f64::INFINITY)
     }
 
     fn icdf(&self, val: &Tensor) -> Tensor {
@@ -70,3 +151,41 @@ impl Distribution for Cauchy {
         &self.batch_shape
     }
 }
+
+impl Reparameterize for Cauchy {
+    fn noise(&self, shape: &[i64]) -> Tensor {
+        let shape = self.extended_shape(shape);
+        Tensor::empty(&shape, (self.median.kind(), self.median.device())).cauchy_(0.0, 1.0)
+    }
+
+    fn fill_noise(&self, noise: &mut Tensor) {
+        let _ = noise.cauchy_(0.0, 1.0);
+    }
+
+    fn reparameterize(&self, noise: &Tensor) -> Tensor {
+        &self.median + noise * &self.scale
+    }
+
+    fn reparameterize_out(&self, out: &mut Tensor, noise: &Tensor) {
+        out.copy_(noise);
+        *out *= &self.scale;
+        *out += &self.median;
+    }
+}
+
+impl Persist for Cauchy {
+    const FAMILY: &'static str = "cauchy";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::new(param(params, "median")?, param(params, "scale")?))
+    }
+}
+
+impl KullackLeiberDivergence<Self> for Cauchy {
+    fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
+        let t1 = (self.scale() + other.scale()).pow_tensor_scalar(2.0)
+            + (self.median() - other.median()).pow_tensor_scalar(2.0);
+        t1.log() - (self.scale() * other.scale() * 4.0).log()
+    }
+}
@@ -0,0 +1,317 @@
+//! Construction of distributions from configuration values.
+//!
+//! A distribution is described by an object naming its family and its parameters,
+//!
+//! ```json
+//! {"family": "Normal", "params": {"mean": 0.0, "stddev": [1.0, 2.0]}}
+//! ```
+//!
+//! where each parameter is a number or a nested array of numbers and becomes a `Double`
+//! tensor on the CPU. The parameters are those of the constructors: the discrete families take
+//! either `probs` or `logits`, `Binomial` and `NegativeBinomial` take `total_count` next to
+//! them, and `MultivariateNormal` takes `mean` and one of `cov`, `precision` and `scale_tril`.
+//! YAML and TOML documents can be read into a `serde_json::Value` with their serde crates.
+//! Errors name the offending field.
+use crate::{
+    error, AnyDistribution, Bernoulli, Beta, Binomial, Categorical, Cauchy, Error, Exponential,
+    Gamma, Geometric, MultivariateNormal, NegativeBinomial, Normal, Poisson,
+    RelaxedOneHotCategorical, Uniform,
+};
+use serde_json::{Map, Value};
+use tch::{Device, Kind, Tensor};
+
+/// Returns a format error about the field `field`.
+fn field_error(field: &str, msg: &str) -> Error {
+    Error::Format(format!("{}: {}", field, msg))
+}
+
+/// Appends the numbers of the nested array `value` at depth `depth` to `data`, recording the
+/// length of each level in `shape`.
+fn flatten(
+    value: &Value,
+    field: &str,
+    depth: usize,
+    shape: &mut Vec<i64>,
+    data: &mut Vec<f64>,
+) -> Result<(), Error> {
+    match value {
+        Value::Number(number) => {
+            if depth != shape.len() {
+                return Err(field_error(field, "nested arrays have different lengths"));
+            }
+            let number = number
+                .as_f64()
+                .ok_or_else(|| field_error(field, "number out of range"))?;
+            data.push(number);
+        }
+        Value::Array(items) => {
+            if depth == shape.len() && data.is_empty() {
+                shape.push(items.len() as i64);
+            } else if depth >= shape.len() || shape[depth] != items.len() as i64 {
+                return Err(field_error(field, "nested arrays have different lengths"));
+            }
+            for item in items {
+                flatten(item, field, depth + 1, shape, data)?;
+            }
+        }
+        _ => {
+            return Err(field_error(
+                field,
+                "expected a number or a nested array of numbers",
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Returns the tensor of the number or nested array `value` at `field`.
+fn tensor_from_value(value: &Value, field: &str) -> Result<Tensor, Error> {
+    let mut shape = Vec::new();
+    let mut data = Vec::new();
+    flatten(value, field, 0, &mut shape, &mut data)?;
+    Ok(Tensor::of_slice(&data).reshape(&shape))
+}
+
+/// Returns the number or nested array holding the values of `t`.
+fn tensor_to_value(t: &Tensor) -> Value {
+    if t.dim() == 0 {
+        Value::from(f64::from(t))
+    } else {
+        Value::Array(
+            (0..t.size()[0])
+                .map(|i| tensor_to_value(&t.get(i)))
+                .collect(),
+        )
+    }
+}
+
+/// The parameters of a configuration, remembering which of them were read.
+struct Fields<'a> {
+    params: &'a Map<String, Value>,
+    read: Vec<&'static str>,
+}
+
+impl<'a> Fields<'a> {
+    /// Returns the parameter `name` if it is given.
+    fn optional(&mut self, name: &'static str) -> Result<Option<Tensor>, Error> {
+        self.read.push(name);
+        self.params
+            .get(name)
+            .map(|value| tensor_from_value(value, &format!("params.{}", name)))
+            .transpose()
+    }
+
+    /// Returns the parameter `name`, which must be given.
+    fn required(&mut self, name: &'static str) -> Result<Tensor, Error> {
+        self.optional(name)?
+            .ok_or_else(|| field_error(&format!("params.{}", name), "missing parameter"))
+    }
+
+    /// Returns the index in `names` and the value of the only one of the parameters `names`
+    /// that is given.
+    fn one_of(&mut self, names: &[&'static str]) -> Result<(usize, Tensor), Error> {
+        let mut given = Vec::new();
+        for (i, name) in names.iter().enumerate() {
+            if let Some(t) = self.optional(name)? {
+                given.push((i, t));
+            }
+        }
+        if given.len() != 1 {
+            return Err(field_error(
+                "params",
+                &format!("exactly one of {} must be given", names.join(", ")),
+            ));
+        }
+        Ok(given.remove(0))
+    }
+
+    /// Checks that no parameter was given that has not been read.
+    fn finish(&self, family: &str) -> Result<(), Error> {
+        match self
+            .params
+            .keys()
+            .find(|name| !self.read.contains(&name.as_str()))
+        {
+            Some(name) => Err(field_error(
+                &format!("params.{}", name),
+                &format!("unknown parameter of {}", family),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks that each parameter of `dist` given in `params` satisfies its constraint.
+fn check_constraints(dist: &AnyDistribution, params: &Map<String, Value>) -> Result<(), Error> {
+    let dist = dist.as_distribution();
+    let values = dist.params();
+    for (name, constraint) in dist.arg_constraints() {
+        if !params.contains_key(name) {
+            continue;
+        }
+        if let Some((_, value)) = values.iter().find(|(n, _)| *n == name) {
+            if !constraint.check_all(value) {
+                return Err(Error::Support(format!(
+                    "params.{}: value lies outside of {:?}",
+                    name, constraint
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+impl AnyDistribution {
+    /// Creates a distribution from the configuration `config`, see the module documentation
+    /// for its format.
+    pub fn from_config(config: &Value) -> Result<Self, Error> {
+        let config = config
+            .as_object()
+            .ok_or_else(|| Error::Format("expected a configuration object".to_string()))?;
+        let family = config
+            .get("family")
+            .ok_or_else(|| field_error("family", "missing field"))?
+            .as_str()
+            .ok_or_else(|| field_error("family", "expected a string"))?;
+        let params = match config.get("params") {
+            Some(params) => params
+                .as_object()
+                .ok_or_else(|| field_error("params", "expected an object"))?,
+            None => return Err(field_error("params", "missing field")),
+        };
+        if let Some(name) = config.keys().find(|k| *k != "family" && *k != "params") {
+            return Err(field_error(name, "unknown field"));
+        }
+
+        let mut fields = Fields {
+            params,
+            read: Vec::new(),
+        };
+        let dist = match family {
+            "Bernoulli" => match fields.one_of(&["probs", "logits"])? {
+                (0, probs) => error::catch(|| Bernoulli::from_probs(probs)),
+                (_, logits) => error::catch(|| Bernoulli::from_logits(logits)),
+            }
+            .map(AnyDistribution::Bernoulli),
+            "Beta" => {
+                let concentration1 = fields.required("concentration1")?;
+                let concentration0 = fields.required("concentration0")?;
+                error::catch(|| Beta::new(concentration1, concentration0))
+                    .map(AnyDistribution::Beta)
+            }
+            "Binomial" => {
+                let total_count = fields.required("total_count")?;
+                match fields.one_of(&["probs", "logits"])? {
+                    (0, probs) => error::catch(|| Binomial::from_probs(total_count, probs)),
+                    (_, logits) => error::catch(|| Binomial::from_logits(total_count, logits)),
+                }
+                .map(AnyDistribution::Binomial)
+            }
+            "Categorical" => match fields.one_of(&["probs", "logits"])? {
+                (0, probs) => error::catch(|| Categorical::from_probs(probs)),
+                (_, logits) => error::catch(|| Categorical::from_logits(logits)),
+            }
+            .map(AnyDistribution::Categorical),
+            "Cauchy" => {
+                let median = fields.required("median")?;
+                let scale = fields.required("scale")?;
+                error::catch(|| Cauchy::new(median, scale)).map(AnyDistribution::Cauchy)
+            }
+            "Exponential" => {
+                let rate = fields.required("rate")?;
+                error::catch(|| Exponential::new(rate)).map(AnyDistribution::Exponential)
+            }
+            "Gamma" => {
+                let concentration = fields.required("concentration")?;
+                let rate = fields.required("rate")?;
+                error::catch(|| Gamma::new(concentration, rate)).map(AnyDistribution::Gamma)
+            }
+            "Geometric" => match fields.one_of(&["probs", "logits"])? {
+                (0, probs) => error::catch(|| Geometric::from_probs(probs)),
+                (_, logits) => error::catch(|| Geometric::from_logits(logits)),
+            }
+            .map(AnyDistribution::Geometric),
+            "MultivariateNormal" => {
+                let mean = fields.required("mean")?;
+                match fields.one_of(&["cov", "precision", "scale_tril"])? {
+                    (0, cov) => error::catch(|| MultivariateNormal::from_cov(mean, cov)),
+                    (1, precision) => {
+                        error::catch(|| MultivariateNormal::from_precision(mean, precision))
+                    }
+                    (_, scale_tril) => {
+                        error::catch(|| MultivariateNormal::from_scale_tril(mean, scale_tril))
+                    }
+                }
+                .map(AnyDistribution::MultivariateNormal)
+            }
+            "NegativeBinomial" => {
+                let total_count = fields.required("total_count")?;
+                match fields.one_of(&["probs", "logits"])? {
+                    (0, probs) => error::catch(|| NegativeBinomial::from_probs(total_count, probs)),
+                    (_, logits) => {
+                        error::catch(|| NegativeBinomial::from_logits(total_count, logits))
+                    }
+                }
+                .map(AnyDistribution::NegativeBinomial)
+            }
+            "Normal" => {
+                let mean = fields.required("mean")?;
+                let stddev = fields.required("stddev")?;
+                error::catch(|| Normal::new(mean, stddev)).map(AnyDistribution::Normal)
+            }
+            "Poisson" => {
+                let rate = fields.required("rate")?;
+                error::catch(|| Poisson::new(rate)).map(AnyDistribution::Poisson)
+            }
+            "RelaxedOneHotCategorical" => {
+                let temperature = fields.required("temperature")?;
+                match fields.one_of(&["probs", "logits"])? {
+                    (0, probs) => {
+                        error::catch(|| RelaxedOneHotCategorical::from_probs(temperature, probs))
+                    }
+                    (_, logits) => {
+                        error::catch(|| RelaxedOneHotCategorical::from_logits(temperature, logits))
+                    }
+                }
+                .map(AnyDistribution::RelaxedOneHotCategorical)
+            }
+            "Uniform" => {
+                let low = fields.required("low")?;
+                let high = fields.required("high")?;
+                error::catch(|| Uniform::new(low, high)).map(AnyDistribution::Uniform)
+            }
+            _ => {
+                return Err(field_error(
+                    "family",
+                    &format!("unknown distribution family {}", family),
+                ))
+            }
+        };
+        fields.finish(family)?;
+        let dist = dist.map_err(|err| match err {
+            Error::Evaluation(msg) => field_error("params", &msg),
+            err => err,
+        })?;
+        check_constraints(&dist, params)?;
+        Ok(dist)
+    }
+
+    /// Returns the configuration of the distribution, from which `from_config` creates it
+    /// again. The discrete families are described by their logits.
+    pub fn to_config(&self) -> Value {
+        let params = self.as_distribution().params();
+        let has_logits = params.iter().any(|(name, _)| *name == "logits");
+        let params: Map<String, Value> = params
+            .into_iter()
+            .filter(|(name, _)| !(has_logits && *name == "probs"))
+            .map(|(name, t)| {
+                let t = t.detach().to_device(Device::Cpu).to_kind(Kind::Double);
+                (name.to_string(), tensor_to_value(&t))
+            })
+            .collect();
+        let mut config = Map::new();
+        config.insert("family".to_string(), Value::from(self.family()));
+        config.insert("params".to_string(), Value::Object(params));
+        Value::Object(config)
+    }
+}
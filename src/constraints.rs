@@ -0,0 +1,171 @@
+//! Constraints describing the region a value or parameter lives in.
+use tch::{Kind, Tensor};
+
+/// A region of valid values, used as the support of distributions, the valid range of their
+/// parameters, and the domain and codomain of transforms.
+#[derive(Debug)]
+pub enum Constraint {
+    /// The real line, excluding NaN.
+    Real,
+    /// The positive reals (0, inf).
+    Positive,
+    /// The non-negative reals [0, inf).
+    Nonnegative,
+    /// The open half-line (lower_bound, inf).
+    GreaterThan(Tensor),
+    /// The closed unit interval [0, 1].
+    UnitInterval,
+    /// The closed interval [low, high].
+    Interval(Tensor, Tensor),
+    /// Non-negative vectors over the rightmost dimension that sum to one.
+    Simplex,
+    /// The values 0 and 1.
+    Boolean,
+    /// The integers greater than or equal to the lower bound.
+    IntegerGreaterThan(i64),
+    /// The integers in the closed interval [low, high].
+    IntegerInterval(i64, i64),
+    /// Lower-triangular square matrices with positive diagonal.
+    LowerCholesky,
+    /// Symmetric positive-definite square matrices.
+    PositiveDefinite,
+    /// Reinterprets the given number of rightmost batch dimensions of a constraint as event
+    /// dimensions.
+    Independent(Box<Constraint>, i64),
+}
+
+impl Constraint {
+    /// Creates the open half-line (lower_bound, inf).
+    pub fn greater_than<T: Into<Tensor>>(lower_bound: T) -> Self {
+        Constraint::GreaterThan(lower_bound.into())
+    }
+
+    /// Creates the closed interval [low, high].
+    pub fn interval<T: Into<Tensor>>(low: T, high: T) -> Self {
+        Constraint::Interval(low.into(), high.into())
+    }
+
+    /// Creates the non-negative integers.
+    pub fn nonnegative_integer() -> Self {
+        Constraint::IntegerGreaterThan(0)
+    }
+
+    /// Wraps `constraint` and reinterprets `reinterpreted_batch_ndims` of its rightmost batch
+    /// dimensions as event dimensions.
+    pub fn independent(constraint: Constraint, reinterpreted_batch_ndims: i64) -> Self {
+        Constraint::Independent(Box::new(constraint), reinterpreted_batch_ndims)
+    }
+
+    /// Returns the number of rightmost dimensions that together define a single event.
+    pub fn event_dim(&self) -> i64 {
+        match self {
+            Constraint::Real
+            | Constraint::Positive
+            | Constraint::Nonnegative
+            | Constraint::GreaterThan(_)
+            | Constraint::UnitInterval
+            | Constraint::Interval(..)
+            | Constraint::Boolean
+            | Constraint::IntegerGreaterThan(_)
+            | Constraint::IntegerInterval(..) => 0,
+            Constraint::Simplex => 1,
+            Constraint::LowerCholesky | Constraint::PositiveDefinite => 2,
+            Constraint::Independent(base, ndims) => base.event_dim() + ndims,
+        }
+    }
+
+    /// Returns whether the constraint only contains integers.
+    pub fn is_discrete(&self) -> bool {
+        match self {
+            Constraint::Boolean
+            | Constraint::IntegerGreaterThan(_)
+            | Constraint::IntegerInterval(..) => true,
+            Constraint::Independent(base, _) => base.is_discrete(),
+            _ => false,
+        }
+    }
+
+    /// Checks whether `value` satisfies the constraint. Returns a boolean tensor with the
+    /// `event_dim` rightmost dimensions of `value` removed, which is true for each event that
+    /// lies in the constraint.
+    pub fn check(&self, value: &Tensor) -> Tensor {
+        match self {
+            Constraint::Real => value.eq_tensor(value),
+            Constraint::Positive => value.gt(0.0),
+            Constraint::Nonnegative => value.ge(0.0),
+            Constraint::GreaterThan(lower_bound) => value.gt_tensor(lower_bound),
+            Constraint::UnitInterval => value.ge(0.0).logical_and(&value.le(1.0)),
+            Constraint::Interval(low, high) => {
+                value.ge_tensor(low).logical_and(&value.le_tensor(high))
+            }
+            Constraint::Simplex => {
+                let sum = value.sum_dim_intlist(&[-1], false, value.kind());
+                value
+                    .ge(0.0)
+                    .all_dim(-1, false)
+                    .logical_and(&(sum - 1.0).abs().lt(1e-6))
+            }
+            Constraint::Boolean => value.eq(0).logical_or(&value.eq(1)),
+            Constraint::IntegerGreaterThan(lower_bound) => value
+                .remainder(1)
+                .eq(0)
+                .logical_and(&value.ge(*lower_bound)),
+            Constraint::IntegerInterval(low, high) => value
+                .remainder(1)
+                .eq(0)
+                .logical_and(&value.ge(*low))
+                .logical_and(&value.le(*high)),
+            Constraint::LowerCholesky => {
+                let lower_triangular = value.tril(0).eq_tensor(value).all_dim(-1, false);
+                let positive_diagonal = value.diagonal(0, -2, -1).gt(0.0);
+                lower_triangular
+                    .logical_and(&positive_diagonal)
+                    .all_dim(-1, false)
+            }
+            Constraint::PositiveDefinite => {
+                let symmetric = value
+                    .isclose(&value.transpose(-2, -1), 1e-5, 1e-6, false)
+                    .all_dim(-1, false)
+                    .all_dim(-1, false);
+                let (_, info) = value.linalg_cholesky_ex(false, false);
+                symmetric.logical_and(&info.eq(0))
+            }
+            Constraint::Independent(base, ndims) => {
+                (0..*ndims).fold(base.check(value), |result, _| result.all_dim(-1, false))
+            }
+        }
+    }
+
+    /// Checks whether all elements of `value` satisfy the constraint.
+    pub fn check_all(&self, value: &Tensor) -> bool {
+        bool::from(&self.check(value).to_kind(Kind::Bool).all())
+    }
+}
+
+impl Clone for Constraint {
+    fn clone(&self) -> Self {
+        match self {
+            Constraint::Real => Constraint::Real,
+            Constraint::Positive => Constraint::Positive,
+            Constraint::Nonnegative => Constraint::Nonnegative,
+            Constraint::GreaterThan(lower_bound) => {
+                Constraint::GreaterThan(lower_bound.shallow_clone())
+            }
+            Constraint::UnitInterval => Constraint::UnitInterval,
+            Constraint::Interval(low, high) => {
+                Constraint::Interval(low.shallow_clone(), high.shallow_clone())
+            }
+            Constraint::Simplex => Constraint::Simplex,
+            Constraint::Boolean => Constraint::Boolean,
+            Constraint::IntegerGreaterThan(lower_bound) => {
+                Constraint::IntegerGreaterThan(*lower_bound)
+            }
+            Constraint::IntegerInterval(low, high) => Constraint::IntegerInterval(*low, *high),
+            Constraint::LowerCholesky => Constraint::LowerCholesky,
+            Constraint::PositiveDefinite => Constraint::PositiveDefinite,
+            Constraint::Independent(base, ndims) => {
+                Constraint::Independent(Box::new((**base).clone()), *ndims)
+            }
+        }
+    }
+}
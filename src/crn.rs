@@ -0,0 +1,20 @@
+use crate::Reparameterize;
+use tch::Tensor;
+
+/// Draws sample_shape shaped paired samples of `p` and `q` from a single set of common random
+/// numbers. The draws of the pair are strongly correlated, which keeps the variance of
+/// differences between them low, e.g. in finite-difference estimates or when comparing
+/// policies. Both distributions must have the same batch and event shapes.
+pub fn sample_crn<D: Reparameterize>(p: &D, q: &D, shape: &[i64]) -> (Tensor, Tensor) {
+    assert!(
+        p.batch_shape() == q.batch_shape() && p.event_shape() == q.event_shape(),
+        "distributions with batch shapes {:?} and {:?} and event shapes {:?} and {:?} cannot \
+         share random numbers",
+        p.batch_shape(),
+        q.batch_shape(),
+        p.event_shape(),
+        q.event_shape()
+    );
+    let noise = p.noise(shape);
+    (p.reparameterize(&noise), q.reparameterize(&noise))
+}
@@ -0,0 +1,174 @@
+use crate::{
+    constraints::Constraint,
+    persist::{param, Persist},
+    trace,
+    utils::{eps, params_match, stack_param, tiny},
+    Distribution, Error, KullackLeiberDivergence, Stack,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use tch::Tensor;
+
+/// A Dirichlet distribution over the probability simplex of the rightmost dimension of its
+/// concentration.
+#[derive(Debug)]
+pub struct Dirichlet {
+    concentration: Tensor,
+    batch_shape: Vec<i64>,
+    event_shape: Vec<i64>,
+}
+
+impl Clone for Dirichlet {
+    fn clone(&self) -> Self {
+        Self {
+            concentration: self.concentration.copy(),
+            batch_shape: self.batch_shape.clone(),
+            event_shape: self.event_shape.clone(),
+        }
+    }
+}
+
+impl Dirichlet {
+    /// Creates a new `Dirichlet` distribution with `concentration` (often referred to as
+    /// alpha), whose rightmost dimension indexes the categories.
+    pub fn new(concentration: impl Borrow<Tensor>) -> Self {
+        let concentration = concentration.borrow().shallow_clone();
+        trace::construct("Dirichlet", &[("concentration", &concentration)]);
+        let size = concentration.size();
+        let (&categories, batch_shape) = size
+            .split_last()
+            .expect("concentration must be at least one-dimensional");
+        Self {
+            batch_shape: batch_shape.to_vec(),
+            event_shape: vec![categories],
+            concentration,
+        }
+    }
+
+    /// Returns the concentration of the distribution.
+    pub fn concentration(&self) -> &Tensor {
+        &self.concentration
+    }
+
+    /// Returns the sum of the concentration over the categories.
+    fn total(&self) -> Tensor {
+        self.concentration
+            .sum_dim_intlist(&[-1], false, self.concentration.kind())
+    }
+}
+
+impl PartialEq for Dirichlet {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
+}
+
+impl Distribution for Dirichlet {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("concentration", &self.concentration)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![(
+            "concentration",
+            Constraint::independent(Constraint::Positive, 1),
+        )]
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        let kind = self.concentration.kind();
+        ((&self.concentration - 1.0) * val.log()).sum_dim_intlist(&[-1], false, kind)
+            + self.total().lgamma()
+            - self
+                .concentration
+                .lgamma()
+                .sum_dim_intlist(&[-1], false, kind)
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        let shape = self.extended_shape(shape);
+        // Normalized Gamma samples, whose implicit reparameterization gradients flow back to
+        // the concentration.
+        let gammas = self
+            .concentration
+            .expand(&shape, false)
+            .internal_standard_gamma();
+        let value = &gammas / gammas.sum_dim_intlist(&[-1], true, gammas.kind());
+        let (tiny, eps) = (tiny(value.kind()).unwrap(), eps(value.kind()).unwrap());
+        let _ = value.detach().clamp_(tiny, 1.0 - eps);
+        value
+    }
+
+    fn entropy(&self) -> Tensor {
+        let kind = self.concentration.kind();
+        let categories = self.event_shape[0] as f64;
+        let total = self.total();
+        let log_beta = self
+            .concentration
+            .lgamma()
+            .sum_dim_intlist(&[-1], false, kind)
+            - total.lgamma();
+        let digammas = (&self.concentration - 1.0) * self.concentration.digamma();
+        log_beta + (&total - categories) * total.digamma()
+            - digammas.sum_dim_intlist(&[-1], false, kind)
+    }
+
+    fn mean(&self) -> Tensor {
+        &self.concentration / self.total().unsqueeze(-1)
+    }
+
+    fn variance(&self) -> Tensor {
+        let total = self.total().unsqueeze(-1);
+        &self.concentration * (&total - &self.concentration)
+            / (total.pow_tensor_scalar(2) * (total + 1.0))
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::Simplex
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (
+            self.concentration.zeros_like(),
+            self.concentration.ones_like(),
+        )
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+
+    fn event_shape(&self) -> &[i64] {
+        &self.event_shape
+    }
+}
+
+impl Stack for Dirichlet {
+    fn stack(dists: &[Self]) -> Self {
+        Self::new(stack_param(dists, Self::concentration))
+    }
+}
+
+impl Persist for Dirichlet {
+    const FAMILY: &'static str = "dirichlet";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::new(param(params, "concentration")?))
+    }
+}
+
+impl KullackLeiberDivergence<Self> for Dirichlet {
+    fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
+        let kind = self.concentration.kind();
+        let total = self.total();
+        let t1 = total.lgamma() - other.total().lgamma();
+        let t2 = self.concentration.lgamma() - other.concentration.lgamma();
+        let t3 = &self.concentration - &other.concentration;
+        let t4 = self.concentration.digamma() - total.digamma().unsqueeze(-1);
+        t1 - t2.sum_dim_intlist(&[-1], false, kind) + (t3 * t4).sum_dim_intlist(&[-1], false, kind)
+    }
+}
@@ -0,0 +1,50 @@
+use crate::{utils::float_samples, Distribution};
+use tch::{Kind, Tensor};
+
+/// Binned sample frequencies of a distribution next to the probabilities of the same bins.
+#[derive(Debug)]
+pub struct EmpiricalCheck {
+    /// The increasing bin edges, bin `i` is the half-open interval [edges[i], edges[i + 1]).
+    pub edges: Tensor,
+    /// The fraction of samples that fell into each bin, batched over batch_shape.
+    pub observed: Tensor,
+    /// The probability of each bin computed with `cdf`, batched over batch_shape.
+    pub expected: Tensor,
+}
+
+impl EmpiricalCheck {
+    /// Returns the largest absolute difference between observed and expected bin
+    /// frequencies.
+    pub fn max_abs_error(&self) -> f64 {
+        f64::from(&(&self.observed - &self.expected).abs().max())
+    }
+}
+
+pub(crate) fn empirical_check<D: Distribution + ?Sized>(
+    dist: &D,
+    n_samples: i64,
+    edges: &Tensor,
+) -> EmpiricalCheck {
+    let samples = float_samples(dist, n_samples);
+    let n_bins = edges.size()[0] - 1;
+    let bin_shape = [vec![n_bins], vec![1; samples.dim() - 1]].concat();
+    let edges = edges.to_kind(samples.kind());
+    let lower = edges.narrow(0, 0, n_bins).reshape(&bin_shape);
+    let upper = edges.narrow(0, 1, n_bins).reshape(&bin_shape);
+
+    // Bins are laid out along a new leading dimension, samples along the second one.
+    let samples = samples.unsqueeze(0);
+    let in_bin = samples
+        .ge_tensor(&lower.unsqueeze(1))
+        .logical_and(&samples.lt_tensor(&upper.unsqueeze(1)));
+    let observed = in_bin
+        .to_kind(Kind::Double)
+        .mean_dim(&[1], false, Kind::Double);
+    let expected = dist.cdf(&upper) - dist.cdf(&lower);
+
+    EmpiricalCheck {
+        edges,
+        observed,
+        expected,
+    }
+}
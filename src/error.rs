@@ -0,0 +1,177 @@
+use crate::constraints::Constraint;
+use std::{
+    cell::Cell,
+    fmt,
+    panic::{self, catch_unwind, AssertUnwindSafe},
+    sync::Once,
+};
+use tch::{Kind, TchError, Tensor};
+
+/// Errors returned by the fallible `try_*` methods of a distribution.
+///
+/// # Panic hook
+///
+/// The `try_*` methods, and the other functions returning this error such as the loaders of
+/// configurations, turn panics of tch and of unimplemented methods into errors with
+/// `catch_unwind`. So that these panics are not printed, the first such call replaces the
+/// global panic hook with one that stays silent while such a call runs on the current thread
+/// and forwards every other panic to the hook installed before it. A hook set later with
+/// `std::panic::set_hook` replaces this one, after which caught panics are reported again.
+#[derive(Debug)]
+pub enum Error {
+    /// The shape of a value or sample does not match the distribution.
+    Shape(String),
+    /// A value lies outside of the support or domain of the evaluated method.
+    Support(String),
+    /// An error reported by the underlying tch operation.
+    Tch(TchError),
+    /// The evaluation failed, e.g. because the method is not implemented.
+    Evaluation(String),
+    /// A saved distribution is malformed or of another family than the one being loaded.
+    Format(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Shape(msg) => write!(f, "shape error: {}", msg),
+            Error::Support(msg) => write!(f, "support error: {}", msg),
+            Error::Tch(err) => write!(f, "tch error: {}", err),
+            Error::Evaluation(msg) => write!(f, "evaluation error: {}", msg),
+            Error::Format(msg) => write!(f, "format error: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Tch(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<TchError> for Error {
+    fn from(err: TchError) -> Self {
+        Error::Tch(err)
+    }
+}
+
+thread_local! {
+    /// Whether the current thread is inside `catch`, whose panics are not reported.
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+}
+
+static QUIET_HOOK: Once = Once::new();
+
+/// Runs `f` and turns a panic raised by tch or by an unimplemented method into an error.
+///
+/// The first call installs a global panic hook that is silent while `f` runs, so the panic is
+/// only reported through the returned error. Panics outside of `catch` still reach the
+/// previously installed hook. This side effect is documented on `Error`.
+pub(crate) fn catch<T, F: FnOnce() -> T>(f: F) -> Result<T, Error> {
+    QUIET_HOOK.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING.with(Cell::get) {
+                hook(info)
+            }
+        }));
+    });
+    let catching = CATCHING.with(|c| c.replace(true));
+    let result = catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|c| c.set(catching));
+    result.map_err(|payload| {
+        let msg = if let Some(msg) = payload.downcast_ref::<&str>() {
+            msg.to_string()
+        } else if let Some(msg) = payload.downcast_ref::<String>() {
+            msg.clone()
+        } else {
+            "unknown panic".to_string()
+        };
+        Error::Evaluation(msg)
+    })
+}
+
+/// Checks that a value of shape `shape` can be evaluated by a distribution with the given
+/// batch and event shape.
+pub(crate) fn check_value_shape(
+    shape: &[i64],
+    batch_shape: &[i64],
+    event_shape: &[i64],
+) -> Result<(), Error> {
+    if shape.len() < event_shape.len() || &shape[shape.len() - event_shape.len()..] != event_shape {
+        return Err(Error::Shape(format!(
+            "value of shape {:?} does not end with the event shape {:?}",
+            shape, event_shape
+        )));
+    }
+    let sample_batch = &shape[..shape.len() - event_shape.len()];
+    let broadcasts = sample_batch
+        .iter()
+        .rev()
+        .zip(batch_shape.iter().rev())
+        .all(|(&s, &b)| s == b || s == 1 || b == 1);
+    if !broadcasts {
+        return Err(Error::Shape(format!(
+            "value of shape {:?} does not broadcast against the batch shape {:?}",
+            shape, batch_shape
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that all elements of the probability tensor `val` lie in [0, 1].
+pub(crate) fn check_probability(val: &Tensor) -> Result<(), Error> {
+    let valid = val.f_ge(0.0)?.f_logical_and(&val.f_le(1.0)?)?.f_all()?;
+    if bool::from(&valid) {
+        Ok(())
+    } else {
+        Err(Error::Support(
+            "probabilities passed to icdf must lie in [0, 1]".to_string(),
+        ))
+    }
+}
+
+/// Checks that all events of `val` lie in the support `support`.
+pub(crate) fn check_support(support: &Constraint, val: &Tensor) -> Result<(), Error> {
+    if support.check_all(val) {
+        Ok(())
+    } else {
+        Err(Error::Support(format!(
+            "value lies outside of the support {:?}",
+            support
+        )))
+    }
+}
+
+/// Checks that `data` has a non-empty leading sample dimension and lies in `support`, and
+/// returns it converted to a floating point kind for fitting a distribution to it.
+pub(crate) fn check_fit_data(data: &Tensor, support: &Constraint) -> Result<Tensor, Error> {
+    if data.dim() == 0 || data.size()[0] == 0 {
+        return Err(Error::Shape(format!(
+            "data of shape {:?} has no samples along its leading dimension",
+            data.size()
+        )));
+    }
+    let data = if data.is_floating_point() {
+        data.shallow_clone()
+    } else {
+        data.f_to_kind(Kind::Double)?
+    };
+    check_support(support, &data)?;
+    Ok(data)
+}
+
+/// Checks that all fitted parameters `params` are finite.
+pub(crate) fn check_fitted(params: &[&Tensor]) -> Result<(), Error> {
+    for param in params {
+        if !bool::from(&param.f_isfinite()?.f_all()?) {
+            return Err(Error::Evaluation(
+                "fitting produced non-finite parameters, the data may be degenerate".to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
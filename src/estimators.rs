@@ -0,0 +1,170 @@
+//! Monte Carlo gradient and importance sampling estimators.
+use crate::{numerics, Distribution};
+use tch::Tensor;
+
+/// Exponential moving average of the values of `f`, used as a baseline by `score_function`
+/// across optimization steps.
+#[derive(Debug)]
+pub struct MovingAverage {
+    decay: f64,
+    value: Option<Tensor>,
+}
+
+impl MovingAverage {
+    /// Creates an empty moving average which keeps `decay` of its value at every update.
+    pub fn new(decay: f64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&decay),
+            "decay must be in [0, 1), got {}",
+            decay
+        );
+        Self { decay, value: None }
+    }
+
+    /// Returns the current value of the average, if it has been updated.
+    pub fn value(&self) -> Option<&Tensor> {
+        self.value.as_ref()
+    }
+
+    fn update(&mut self, mean: Tensor) {
+        self.value = Some(match self.value.take() {
+            Some(value) => value * self.decay + mean * (1.0 - self.decay),
+            None => mean,
+        });
+    }
+}
+
+/// A control variate subtracted from the values of `f` in `score_function`.
+#[derive(Debug)]
+pub enum Baseline<'a> {
+    /// No baseline.
+    None,
+    /// A moving average of `f` over previous calls, updated with the mean of the current
+    /// samples after computing the estimate.
+    MovingAverage(&'a mut MovingAverage),
+    /// The mean of `f` over all other samples of the same call. Requires at least two samples.
+    LeaveOneOut,
+}
+
+/// Returns a surrogate of `E[f(x)]` for `n_samples` samples `x` of `dist` whose backward gives
+/// the score function (REINFORCE) estimate of the gradient with respect to the parameters of
+/// `dist`. This also works for distributions without reparameterized samples, such as
+/// `Categorical` or `Poisson`.
+///
+/// `f` is called with the `[n_samples, batch..., event...]` shaped samples and returns
+/// `[n_samples, batch...]` shaped values. The surrogate evaluates to their mean over the sample
+/// dimension. Gradients of `f` with respect to any of its own parameters are kept.
+pub fn score_function<D, F>(dist: &D, f: F, n_samples: i64, baseline: Baseline) -> Tensor
+where
+    D: Distribution,
+    F: Fn(&Tensor) -> Tensor,
+{
+    let samples = dist.sample(&[n_samples]);
+    let values = f(&samples);
+    let kind = values.kind();
+    let detached = values.detach();
+    let advantage = match baseline {
+        Baseline::None => detached,
+        Baseline::MovingAverage(average) => {
+            let advantage = match average.value() {
+                Some(value) => &detached - value,
+                None => detached.shallow_clone(),
+            };
+            average.update(detached.mean_dim(&[0], false, kind));
+            advantage
+        }
+        Baseline::LeaveOneOut => {
+            assert!(
+                n_samples > 1,
+                "a leave-one-out baseline requires at least two samples"
+            );
+            let others =
+                (detached.sum_dim_intlist(&[0], true, kind) - &detached) / (n_samples - 1) as f64;
+            detached - others
+        }
+    };
+    let log_prob = dist.log_prob(&samples);
+    let score = &log_prob - log_prob.detach();
+    (values + advantage * score).mean_dim(&[0], false, kind)
+}
+
+/// Result of importance sampling with `importance_sampling` or
+/// `self_normalized_importance_sampling`.
+#[derive(Debug)]
+pub struct ImportanceEstimate {
+    /// The estimated quantity, of the batch shape of the proposal.
+    pub estimate: Tensor,
+    /// The log importance weights `log p(x) - log q(x)` of the samples, of shape
+    /// `[n_samples, batch...]`.
+    pub log_weights: Tensor,
+    /// The effective sample size `(sum w)^2 / sum w^2` of the weights.
+    pub effective_sample_size: Tensor,
+}
+
+fn log_weights<D, P>(target_log_prob: P, proposal: &D, n_samples: i64) -> (Tensor, Tensor)
+where
+    D: Distribution,
+    P: Fn(&Tensor) -> Tensor,
+{
+    assert!(n_samples > 0, "need at least one sample, got {}", n_samples);
+    let samples = proposal.sample(&[n_samples]);
+    let log_weights = target_log_prob(&samples) - proposal.log_prob(&samples);
+    (samples, log_weights)
+}
+
+fn effective_sample_size(log_weights: &Tensor) -> Tensor {
+    let log_sum = numerics::logsumexp(log_weights, &[0], false);
+    let log_sum_sq = numerics::logsumexp(&(log_weights * 2.0), &[0], false);
+    (log_sum * 2.0 - log_sum_sq).exp()
+}
+
+/// Estimates the log normalizing constant `log Z = log E_q[p(x) / q(x)]` of the unnormalized
+/// density `target_log_prob` from `n_samples` samples of `proposal`, e.g. the log marginal
+/// likelihood of a model.
+///
+/// `target_log_prob` is called with the `[n_samples, batch..., event...]` shaped samples and
+/// returns `[n_samples, batch...]` shaped log densities.
+pub fn importance_sampling<D, P>(
+    target_log_prob: P,
+    proposal: &D,
+    n_samples: i64,
+) -> ImportanceEstimate
+where
+    D: Distribution,
+    P: Fn(&Tensor) -> Tensor,
+{
+    let (_, log_weights) = log_weights(target_log_prob, proposal, n_samples);
+    ImportanceEstimate {
+        estimate: numerics::logsumexp(&log_weights, &[0], false) - (n_samples as f64).ln(),
+        effective_sample_size: effective_sample_size(&log_weights),
+        log_weights,
+    }
+}
+
+/// Estimates the expectation `E_p[f(x)]` under the possibly unnormalized density
+/// `target_log_prob` from `n_samples` samples of `proposal`, with weights normalized to sum to
+/// one. The estimate is biased but consistent, e.g. for off-policy evaluation.
+///
+/// `target_log_prob` and `f` are called with the `[n_samples, batch..., event...]` shaped
+/// samples and return `[n_samples, batch...]` shaped values.
+pub fn self_normalized_importance_sampling<D, P, F>(
+    target_log_prob: P,
+    proposal: &D,
+    f: F,
+    n_samples: i64,
+) -> ImportanceEstimate
+where
+    D: Distribution,
+    P: Fn(&Tensor) -> Tensor,
+    F: Fn(&Tensor) -> Tensor,
+{
+    let (samples, log_weights) = log_weights(target_log_prob, proposal, n_samples);
+    let values = f(&samples);
+    let kind = values.kind();
+    let weights = log_weights.softmax(0, kind);
+    ImportanceEstimate {
+        estimate: (weights * values).sum_dim_intlist(&[0], false, kind),
+        effective_sample_size: effective_sample_size(&log_weights),
+        log_weights,
+    }
+}
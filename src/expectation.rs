@@ -0,0 +1,63 @@
+use crate::{utils::float_samples, Distribution};
+use tch::{Kind, Tensor};
+
+/// Monte Carlo estimate of the expectation of a function of the samples of a distribution.
+#[derive(Debug)]
+pub struct Expectation {
+    /// The sample mean of the function values.
+    pub mean: Tensor,
+    /// The unbiased sample variance of the function values.
+    pub variance: Tensor,
+    /// The number of samples the estimate is based on.
+    pub n_samples: i64,
+}
+
+impl Expectation {
+    /// Returns the standard error of the estimated mean.
+    pub fn std_error(&self) -> Tensor {
+        (&self.variance / self.n_samples as f64).sqrt()
+    }
+}
+
+pub(crate) fn mc_expectation<D, F>(dist: &D, f: F, n_samples: i64, chunk_size: i64) -> Expectation
+where
+    D: Distribution + ?Sized,
+    F: Fn(&Tensor) -> Tensor,
+{
+    assert!(
+        n_samples > 1,
+        "need at least two samples, got {}",
+        n_samples
+    );
+    assert!(
+        chunk_size > 0,
+        "chunk size must be positive, got {}",
+        chunk_size
+    );
+
+    // Per-chunk means and sums of squared deviations are merged with the pairwise update of
+    // Chan et al., so only a single chunk of samples is alive at any time.
+    let mut n = 0;
+    let mut mean = Tensor::from(0.0);
+    let mut m2 = Tensor::from(0.0);
+    while n < n_samples {
+        let n_chunk = chunk_size.min(n_samples - n);
+        let values = f(&float_samples(dist, n_chunk)).to_kind(Kind::Double);
+        let chunk_mean = values.mean_dim(&[0], false, Kind::Double);
+        let chunk_m2 = (&values - &chunk_mean)
+            .pow_tensor_scalar(2)
+            .sum_dim_intlist(&[0], false, Kind::Double);
+
+        let total = (n + n_chunk) as f64;
+        let delta = &chunk_mean - &mean;
+        mean = &mean + &delta * (n_chunk as f64 / total);
+        m2 = &m2 + chunk_m2 + delta.pow_tensor_scalar(2) * (n as f64 * n_chunk as f64 / total);
+        n += n_chunk;
+    }
+
+    Expectation {
+        mean,
+        variance: m2 / (n - 1) as f64,
+        n_samples: n,
+    }
+}
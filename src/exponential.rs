@@ -1,10 +1,22 @@
-use crate::{Distribution, KullackLeiberDivergence};
-use tch::Tensor;
+use crate::{
+    constraints::Constraint,
+    error::check_fit_data,
+    numerics,
+    persist::{param, Persist},
+    trace,
+    utils::{params_match, stack_param, EULER_GAMMA},
+    Distribution, Error, Gamma, KullackLeiberDivergence, Normal, Reparameterize, Stack, Trainable,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use tch::{nn, Kind, Tensor};
 
 /// An Exponential distribution.
 #[derive(Debug)]
 pub struct Exponential {
     rate: Tensor,
+    log_rate: Tensor,
     batch_shape: Vec<i64>,
 }
 
@@ -12,6 +24,7 @@ impl Clone for Exponential {
     fn clone(&self) -> Self {
         Self {
             rate: self.rate.copy(),
+            log_rate: self.log_rate.copy(),
             batch_shape: self.batch_shape.clone(),
         }
     }
@@ -19,37 +32,131 @@ impl Clone for Exponential {
 
 impl Exponential {
     /// Creates a new `Exponential` distribution with `rate`.
-    pub fn new(rate: Tensor) -> Self {
+    pub fn new(rate: impl Borrow<Tensor>) -> Self {
+        let rate = rate.borrow().shallow_clone();
+        trace::construct("Exponential", &[("rate", &rate)]);
         let batch_shape = rate.size();
-        Self { rate, batch_shape }
+        let log_rate = rate.log();
+        Self {
+            rate,
+            log_rate,
+            batch_shape,
+        }
+    }
+
+    /// Creates a standard `Exponential` distribution with unit rate.
+    pub fn standard() -> Self {
+        Self::new(Tensor::from(1.0))
+    }
+
+    /// Fits an `Exponential` distribution to `data` by maximum likelihood, the inverse of the
+    /// sample mean along the leading dimension, which indexes the samples.
+    pub fn fit_mle(data: &Tensor) -> Result<Self, Error> {
+        let data = check_fit_data(data, &Constraint::Nonnegative)?;
+        Ok(Self::new(
+            data.mean_dim(&[0], false, data.kind()).reciprocal(),
+        ))
     }
 
     /// Returns the rate of the distribution.
     pub fn rate(&self) -> &Tensor {
         &self.rate
     }
+
+    /// Creates an `Exponential` distribution of batch `shape` with unit rate, which is a
+    /// variable under `path` stored as its logarithm.
+    pub fn trainable(path: &nn::Path, shape: &[i64]) -> Trainable<Self> {
+        let options = (Kind::Float, path.device());
+        let init = Self::new(Tensor::ones(shape, options));
+        Trainable::new(path, &init, &["rate"], |p| Self::new(&p[0]))
+    }
+}
+
+impl From<f64> for Exponential {
+    /// Creates a scalar `Exponential` distribution from `rate`.
+    fn from(rate: f64) -> Self {
+        Self::new(Tensor::from(rate))
+    }
+}
+
+impl PartialEq for Exponential {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
 }
 
 impl Distribution for Exponential {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("rate", &self.rate)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![("rate", Constraint::Positive)]
+    }
+
     fn log_prob(&self, val: &Tensor) -> Tensor {
-        self.rate.log() - &self.rate * val
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        &self.log_rate - &self.rate * val
     }
 
     fn sample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
         let shape = self.extended_shape(shape);
-        Tensor::empty(&shape, (self.rate.kind(), self.rate.device())).exponential_(1.0) / &self.rate
+        tch::no_grad(|| {
+            Tensor::empty(&shape, (self.rate.kind(), self.rate.device())).exponential_(1.0)
+                / &self.rate
+        })
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        self.reparameterize(&self.noise(shape))
     }
 
     fn cdf(&self, val: &Tensor) -> Tensor {
-        1.0f64 - (-&self.rate * val).exp()
+        self.validate_sample(val);
+        // Clamped like the Uniform cdf, as the closed form is negative below the support.
+        (-(-&self.rate * val).expm1()).clamp_min(0.0)
+    }
+
+    fn log_prob_out(&self, out: &mut Tensor, val: &Tensor) {
+        self.validate_sample(val);
+        out.copy_(val);
+        *out *= &self.rate;
+        let _ = out.neg_();
+        *out += &self.log_rate;
+    }
+
+    fn cdf_out(&self, out: &mut Tensor, val: &Tensor) {
+        self.validate_sample(val);
+        out.copy_(val);
+        *out *= &self.rate;
+        let _ = out.neg_().expm1_().neg_().clamp_min_(0.0);
     }
 
     fn entropy(&self) -> Tensor {
-        1.0f64 - self.rate.log()
+        1.0f64 - &self.log_rate
+    }
+
+    fn mean(&self) -> Tensor {
+        self.rate.reciprocal()
+    }
+
+    fn variance(&self) -> Tensor {
+        self.rate.pow_tensor_scalar(-2)
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::Nonnegative
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (self.rate.zeros_like(), self.rate.full_like(f64::INFINITY))
     }
 
     fn icdf(&self, val: &Tensor) -> Tensor {
-        -(1.0f64 - val).log() / &self.rate
+        -(-numerics::clamp_open_unit(val)).log1p() / &self.rate
     }
 
     fn batch_shape(&self) -> &[i64] {
@@ -57,10 +164,67 @@ impl Distribution for Exponential {
     }
 }
 
+impl Reparameterize for Exponential {
+    fn noise(&self, shape: &[i64]) -> Tensor {
+        let shape = self.extended_shape(shape);
+        Tensor::empty(&shape, (self.rate.kind(), self.rate.device())).exponential_(1.0)
+    }
+
+    fn fill_noise(&self, noise: &mut Tensor) {
+        let _ = noise.exponential_(1.0);
+    }
+
+    fn reparameterize(&self, noise: &Tensor) -> Tensor {
+        noise / &self.rate
+    }
+
+    fn reparameterize_out(&self, out: &mut Tensor, noise: &Tensor) {
+        out.copy_(noise);
+        *out /= &self.rate;
+    }
+}
+
+impl Stack for Exponential {
+    fn stack(dists: &[Self]) -> Self {
+        Self::new(stack_param(dists, Self::rate))
+    }
+}
+
+impl Persist for Exponential {
+    const FAMILY: &'static str = "exponential";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::new(param(params, "rate")?))
+    }
+}
+
 impl KullackLeiberDivergence<Self> for Exponential {
     fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
         let rate_ratio = other.rate() / self.rate();
         let t1 = -rate_ratio.log();
-        t1 + rate_ratio - &1.0.into()
+        t1 + rate_ratio - 1.0
+    }
+}
+
+impl KullackLeiberDivergence<Gamma> for Exponential {
+    fn kl_divergence(&self, other: &Gamma) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
+        let rate_ratio = other.rate() / self.rate();
+        let t1 = -other.concentration() * rate_ratio.log();
+        let t2 = other.concentration().lgamma() + other.concentration() * EULER_GAMMA;
+        t1 + rate_ratio + t2 - (1.0 + EULER_GAMMA)
+    }
+}
+
+impl KullackLeiberDivergence<Normal> for Exponential {
+    fn kl_divergence(&self, other: &Normal) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
+        let variance = other.stddev().pow_tensor_scalar(2.0);
+        let rate_sqr = self.rate().pow_tensor_scalar(2.0);
+        let t1 = (&rate_sqr * &variance * (2.0 * PI)).log() * 0.5;
+        let t2 = rate_sqr.reciprocal() - other.mean() / self.rate()
+            + other.mean().pow_tensor_scalar(2.0) * 0.5;
+        t1 - 1.0 + t2 / variance
     }
 }
@@ -0,0 +1,141 @@
+//! Export of samples and evaluation grids to `.npy` and `.npz` files, and of sampled datasets
+//! to Arrow record batches with the `arrow` feature.
+//!
+//! The files hold plain arrays readable with `numpy.load` in Python or `NPZ.jl` in Julia, for
+//! plotting and analysing distributions outside of Rust.
+use crate::{error, Distribution, Error};
+use std::path::Path;
+use tch::{Device, Kind, Tensor};
+
+/// Draws a `shape` shaped sample of `dist` and writes it to the `.npy` file `path`.
+pub fn samples_to_npy<D, P>(dist: &D, shape: &[i64], path: P) -> Result<(), Error>
+where
+    D: Distribution + ?Sized,
+    P: AsRef<Path>,
+{
+    Ok(dist.try_sample(shape)?.write_npy(path)?)
+}
+
+/// Draws one sample of `dist` for each named shape of `shapes` and writes them to the `.npz`
+/// file `path`, each under its name.
+pub fn samples_to_npz<D, P>(dist: &D, shapes: &[(&str, &[i64])], path: P) -> Result<(), Error>
+where
+    D: Distribution + ?Sized,
+    P: AsRef<Path>,
+{
+    let samples = shapes
+        .iter()
+        .map(|(name, shape)| Ok((*name, dist.try_sample(shape)?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+    Ok(Tensor::write_npz(&samples, path)?)
+}
+
+/// Evaluates the univariate `dist` on the one-dimensional grid `values` and writes the
+/// results to the `.npz` file `path`.
+///
+/// The file holds `values`, `log_prob` and, when the distribution implements it, `cdf`. For a
+/// batched distribution the results have the shape of the grid followed by the batch shape.
+/// Unlike `try_log_prob`, points outside the support are evaluated rather than rejected, so
+/// the grid may extend past the support.
+pub fn grid_to_npz<D, P>(dist: &D, values: &Tensor, path: P) -> Result<(), Error>
+where
+    D: Distribution + ?Sized,
+    P: AsRef<Path>,
+{
+    if !dist.event_shape().is_empty() || values.dim() != 1 {
+        return Err(Error::Shape(format!(
+            "evaluation grids need a univariate distribution and a one-dimensional grid, got \
+             event shape {:?} and grid shape {:?}",
+            dist.event_shape(),
+            values.size()
+        )));
+    }
+    let mut shape = values.size();
+    shape.extend(dist.batch_shape().iter().map(|_| 1));
+    let val = values.reshape(&shape);
+    let mut named = vec![
+        ("values", values.shallow_clone()),
+        ("log_prob", error::catch(|| dist.log_prob(&val))?),
+    ];
+    if let Ok(cdf) = error::catch(|| dist.cdf(&val)) {
+        named.push(("cdf", cdf));
+    }
+    Ok(Tensor::write_npz(&named, path)?)
+}
+
+/// Evaluates the univariate `dist` on `steps` evenly spaced points from `low` to `high`, see
+/// `grid_to_npz`.
+pub fn linspace_to_npz<D, P>(
+    dist: &D,
+    low: f64,
+    high: f64,
+    steps: i64,
+    path: P,
+) -> Result<(), Error>
+where
+    D: Distribution + ?Sized,
+    P: AsRef<Path>,
+{
+    let (kind, device) = dist
+        .params()
+        .first()
+        .map_or((Kind::Double, Device::Cpu), |(_, p)| (p.kind(), p.device()));
+    let values = Tensor::linspace(low, high, steps, (kind, device));
+    grid_to_npz(dist, &values, path)
+}
+
+/// Draws `n` samples of `dist` into an Arrow record batch with one row per sample.
+///
+/// The batch and event dimensions of each sample are flattened into columns in row-major
+/// order, named by `column_names`, or `x0`, `x1`, ... when `column_names` is empty. Samples of
+/// integer kinds become `Int64` columns and all others `Float64` columns.
+#[cfg(feature = "arrow")]
+pub fn to_arrow<D>(
+    dist: &D,
+    n: i64,
+    column_names: &[&str],
+) -> Result<arrow::record_batch::RecordBatch, Error>
+where
+    D: Distribution + ?Sized,
+{
+    use arrow::{
+        array::{ArrayRef, Float64Array, Int64Array},
+        datatypes::{DataType, Field, Schema},
+        record_batch::RecordBatch,
+    };
+    use std::sync::Arc;
+
+    let samples = dist.try_sample(&[n])?.to_device(Device::Cpu);
+    // Inferring the number of columns with -1 fails when there are no samples.
+    let columns = samples.size()[1..].iter().product::<i64>();
+    let samples = samples.reshape(&[n, columns]);
+    let columns = columns as usize;
+    let names: Vec<String> = if column_names.is_empty() {
+        (0..columns).map(|i| format!("x{}", i)).collect()
+    } else if column_names.len() == columns {
+        column_names.iter().map(|name| name.to_string()).collect()
+    } else {
+        return Err(Error::Shape(format!(
+            "got {} column names for samples with {} columns",
+            column_names.len(),
+            columns
+        )));
+    };
+    let integer = !samples.is_floating_point();
+    let mut fields = Vec::with_capacity(columns);
+    let mut arrays: Vec<ArrayRef> = Vec::with_capacity(columns);
+    for (i, name) in names.into_iter().enumerate() {
+        let column = samples.select(1, i as i64).contiguous();
+        if integer {
+            fields.push(Field::new(&name, DataType::Int64, false));
+            let values = Vec::<i64>::from(&column.to_kind(Kind::Int64));
+            arrays.push(Arc::new(Int64Array::from(values)));
+        } else {
+            fields.push(Field::new(&name, DataType::Float64, false));
+            let values = Vec::<f64>::from(&column.to_kind(Kind::Double));
+            arrays.push(Arc::new(Float64Array::from(values)));
+        }
+    }
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)
+        .map_err(|err| Error::Format(err.to_string()))
+}
@@ -0,0 +1,346 @@
+//! A Python extension module exposing the distributions with an API modelled on
+//! `torch.distributions`.
+//!
+//! Parameters and values are `torch.Tensor`s or anything `torch.as_tensor` accepts, and all
+//! results are `torch.Tensor`s. The distributions live on the CPU and do not record gradients.
+//!
+//! The module is compiled with the `python` feature, so that it can be tested and embedded
+//! with `pyo3::wrap_pymodule!(tch_distr::extension::tch_distr)`. Only the `extension-module`
+//! feature builds a library that Python can import.
+
+// The pyo3 0.16 macros define impls inside of constants.
+#![allow(non_local_definitions)]
+
+use crate::{
+    error::catch,
+    python::{tensor_from_py, tensor_to_py, AnyDistribution, ToPy},
+    Bernoulli, Beta, Categorical, Cauchy, Distribution, Exponential, Gamma, Geometric,
+    MultivariateNormal, Normal, Poisson, RelaxedOneHotCategorical, Uniform,
+};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use tch::Tensor;
+
+/// Converts `obj` to a tensor with `torch.as_tensor`.
+fn as_tensor(obj: &PyAny) -> PyResult<Tensor> {
+    let py = obj.py();
+    tensor_from_py(py.import("torch")?.call_method1("as_tensor", (obj,))?)
+}
+
+/// The parameters of the discrete families, given either as probabilities or as logits.
+enum ProbsOrLogits {
+    Probs(Tensor),
+    Logits(Tensor),
+}
+
+/// Returns the one of `probs` and `logits` that is given, as `torch.distributions` requires
+/// exactly one of them.
+fn probs_or_logits(probs: Option<&PyAny>, logits: Option<&PyAny>) -> PyResult<ProbsOrLogits> {
+    match (probs, logits) {
+        (Some(probs), None) => Ok(ProbsOrLogits::Probs(as_tensor(probs)?)),
+        (None, Some(logits)) => Ok(ProbsOrLogits::Logits(as_tensor(logits)?)),
+        _ => Err(PyValueError::new_err(
+            "either probs or logits must be specified, but not both",
+        )),
+    }
+}
+
+/// The base class of all distributions of the module.
+#[pyclass(name = "Distribution", module = "tch_distr", subclass)]
+struct PyDistribution {
+    inner: AnyDistribution,
+}
+
+impl PyDistribution {
+    fn new(inner: AnyDistribution) -> Self {
+        Self { inner }
+    }
+
+    fn dist(&self) -> &dyn Distribution {
+        self.inner.as_distribution()
+    }
+}
+
+#[pymethods]
+impl PyDistribution {
+    fn log_prob(&self, py: Python<'_>, value: &PyAny) -> PyResult<PyObject> {
+        tensor_to_py(py, &self.dist().try_log_prob(&as_tensor(value)?)?)
+    }
+
+    fn cdf(&self, py: Python<'_>, value: &PyAny) -> PyResult<PyObject> {
+        tensor_to_py(py, &self.dist().try_cdf(&as_tensor(value)?)?)
+    }
+
+    fn icdf(&self, py: Python<'_>, value: &PyAny) -> PyResult<PyObject> {
+        tensor_to_py(py, &self.dist().try_icdf(&as_tensor(value)?)?)
+    }
+
+    fn entropy(&self, py: Python<'_>) -> PyResult<PyObject> {
+        tensor_to_py(py, &catch(|| self.dist().entropy())?)
+    }
+
+    #[args(sample_shape = "Vec::new()")]
+    fn sample(&self, py: Python<'_>, sample_shape: Vec<i64>) -> PyResult<PyObject> {
+        tensor_to_py(py, &self.dist().try_sample(&sample_shape)?)
+    }
+
+    #[getter]
+    fn mean(&self, py: Python<'_>) -> PyResult<PyObject> {
+        tensor_to_py(py, &catch(|| self.dist().mean())?)
+    }
+
+    #[getter]
+    fn variance(&self, py: Python<'_>) -> PyResult<PyObject> {
+        tensor_to_py(py, &catch(|| self.dist().variance())?)
+    }
+
+    #[getter]
+    fn stddev(&self, py: Python<'_>) -> PyResult<PyObject> {
+        tensor_to_py(py, &catch(|| self.dist().variance().sqrt())?)
+    }
+
+    #[getter]
+    fn batch_shape(&self) -> Vec<i64> {
+        self.dist().batch_shape().to_vec()
+    }
+
+    #[getter]
+    fn event_shape(&self) -> Vec<i64> {
+        self.dist().event_shape().to_vec()
+    }
+
+    /// Returns the equivalent `torch.distributions` object.
+    fn to_torch(&self, py: Python<'_>) -> PyResult<PyObject> {
+        self.inner.to_py(py)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.inner)
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Bernoulli", module = "tch_distr")]
+struct PyBernoulli;
+
+#[pymethods]
+impl PyBernoulli {
+    #[new]
+    #[args(probs = "None", logits = "None")]
+    fn new(probs: Option<&PyAny>, logits: Option<&PyAny>) -> PyResult<(Self, PyDistribution)> {
+        let dist = match probs_or_logits(probs, logits)? {
+            ProbsOrLogits::Probs(probs) => Bernoulli::from_probs(probs),
+            ProbsOrLogits::Logits(logits) => Bernoulli::from_logits(logits),
+        };
+        Ok((Self, PyDistribution::new(AnyDistribution::Bernoulli(dist))))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Beta", module = "tch_distr")]
+struct PyBeta;
+
+#[pymethods]
+impl PyBeta {
+    #[new]
+    fn new(concentration1: &PyAny, concentration0: &PyAny) -> PyResult<(Self, PyDistribution)> {
+        let dist = Beta::new(as_tensor(concentration1)?, as_tensor(concentration0)?);
+        Ok((Self, PyDistribution::new(AnyDistribution::Beta(dist))))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Categorical", module = "tch_distr")]
+struct PyCategorical;
+
+#[pymethods]
+impl PyCategorical {
+    #[new]
+    #[args(probs = "None", logits = "None")]
+    fn new(probs: Option<&PyAny>, logits: Option<&PyAny>) -> PyResult<(Self, PyDistribution)> {
+        let dist = match probs_or_logits(probs, logits)? {
+            ProbsOrLogits::Probs(probs) => Categorical::from_probs(probs),
+            ProbsOrLogits::Logits(logits) => Categorical::from_logits(logits),
+        };
+        Ok((
+            Self,
+            PyDistribution::new(AnyDistribution::Categorical(dist)),
+        ))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Cauchy", module = "tch_distr")]
+struct PyCauchy;
+
+#[pymethods]
+impl PyCauchy {
+    #[new]
+    fn new(loc: &PyAny, scale: &PyAny) -> PyResult<(Self, PyDistribution)> {
+        let dist = Cauchy::new(as_tensor(loc)?, as_tensor(scale)?);
+        Ok((Self, PyDistribution::new(AnyDistribution::Cauchy(dist))))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Exponential", module = "tch_distr")]
+struct PyExponential;
+
+#[pymethods]
+impl PyExponential {
+    #[new]
+    fn new(rate: &PyAny) -> PyResult<(Self, PyDistribution)> {
+        let dist = Exponential::new(as_tensor(rate)?);
+        Ok((
+            Self,
+            PyDistribution::new(AnyDistribution::Exponential(dist)),
+        ))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Gamma", module = "tch_distr")]
+struct PyGamma;
+
+#[pymethods]
+impl PyGamma {
+    #[new]
+    fn new(concentration: &PyAny, rate: &PyAny) -> PyResult<(Self, PyDistribution)> {
+        let dist = Gamma::new(as_tensor(concentration)?, as_tensor(rate)?);
+        Ok((Self, PyDistribution::new(AnyDistribution::Gamma(dist))))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Geometric", module = "tch_distr")]
+struct PyGeometric;
+
+#[pymethods]
+impl PyGeometric {
+    #[new]
+    #[args(probs = "None", logits = "None")]
+    fn new(probs: Option<&PyAny>, logits: Option<&PyAny>) -> PyResult<(Self, PyDistribution)> {
+        let dist = match probs_or_logits(probs, logits)? {
+            ProbsOrLogits::Probs(probs) => Geometric::from_probs(probs),
+            ProbsOrLogits::Logits(logits) => Geometric::from_logits(logits),
+        };
+        Ok((Self, PyDistribution::new(AnyDistribution::Geometric(dist))))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "MultivariateNormal", module = "tch_distr")]
+struct PyMultivariateNormal;
+
+#[pymethods]
+impl PyMultivariateNormal {
+    #[new]
+    #[args(
+        covariance_matrix = "None",
+        precision_matrix = "None",
+        scale_tril = "None"
+    )]
+    fn new(
+        loc: &PyAny,
+        covariance_matrix: Option<&PyAny>,
+        precision_matrix: Option<&PyAny>,
+        scale_tril: Option<&PyAny>,
+    ) -> PyResult<(Self, PyDistribution)> {
+        let loc = as_tensor(loc)?;
+        let dist = match (covariance_matrix, precision_matrix, scale_tril) {
+            (Some(cov), None, None) => MultivariateNormal::from_cov(loc, as_tensor(cov)?),
+            (None, Some(precision), None) => {
+                MultivariateNormal::from_precision(loc, as_tensor(precision)?)
+            }
+            (None, None, Some(scale_tril)) => {
+                MultivariateNormal::from_scale_tril(loc, as_tensor(scale_tril)?)
+            }
+            _ => {
+                return Err(PyValueError::new_err(
+                    "exactly one of covariance_matrix, precision_matrix or scale_tril must be \
+                     specified",
+                ))
+            }
+        };
+        Ok((
+            Self,
+            PyDistribution::new(AnyDistribution::MultivariateNormal(dist)),
+        ))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Normal", module = "tch_distr")]
+struct PyNormal;
+
+#[pymethods]
+impl PyNormal {
+    #[new]
+    fn new(loc: &PyAny, scale: &PyAny) -> PyResult<(Self, PyDistribution)> {
+        let dist = Normal::new(as_tensor(loc)?, as_tensor(scale)?);
+        Ok((Self, PyDistribution::new(AnyDistribution::Normal(dist))))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Poisson", module = "tch_distr")]
+struct PyPoisson;
+
+#[pymethods]
+impl PyPoisson {
+    #[new]
+    fn new(rate: &PyAny) -> PyResult<(Self, PyDistribution)> {
+        let dist = Poisson::new(as_tensor(rate)?);
+        Ok((Self, PyDistribution::new(AnyDistribution::Poisson(dist))))
+    }
+}
+
+#[pyclass(
+    extends = PyDistribution,
+    name = "RelaxedOneHotCategorical",
+    module = "tch_distr"
+)]
+struct PyRelaxedOneHotCategorical;
+
+#[pymethods]
+impl PyRelaxedOneHotCategorical {
+    #[new]
+    #[args(probs = "None", logits = "None")]
+    fn new(
+        temperature: &PyAny,
+        probs: Option<&PyAny>,
+        logits: Option<&PyAny>,
+    ) -> PyResult<(Self, PyDistribution)> {
+        let temperature = as_tensor(temperature)?;
+        let dist = match probs_or_logits(probs, logits)? {
+            ProbsOrLogits::Probs(probs) => RelaxedOneHotCategorical::from_probs(temperature, probs),
+            ProbsOrLogits::Logits(logits) => {
+                RelaxedOneHotCategorical::from_logits(temperature, logits)
+            }
+        };
+        Ok((
+            Self,
+            PyDistribution::new(AnyDistribution::RelaxedOneHotCategorical(dist)),
+        ))
+    }
+}
+
+#[pyclass(extends = PyDistribution, name = "Uniform", module = "tch_distr")]
+struct PyUniform;
+
+#[pymethods]
+impl PyUniform {
+    #[new]
+    fn new(low: &PyAny, high: &PyAny) -> PyResult<(Self, PyDistribution)> {
+        let dist = Uniform::new(as_tensor(low)?, as_tensor(high)?);
+        Ok((Self, PyDistribution::new(AnyDistribution::Uniform(dist))))
+    }
+}
+
+/// Initializes the `tch_distr` module with a class for each distribution.
+#[pymodule]
+pub fn tch_distr(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyDistribution>()?;
+    m.add_class::<PyBernoulli>()?;
+    m.add_class::<PyBeta>()?;
+    m.add_class::<PyCategorical>()?;
+    m.add_class::<PyCauchy>()?;
+    m.add_class::<PyExponential>()?;
+    m.add_class::<PyGamma>()?;
+    m.add_class::<PyGeometric>()?;
+    m.add_class::<PyMultivariateNormal>()?;
+    m.add_class::<PyNormal>()?;
+    m.add_class::<PyPoisson>()?;
+    m.add_class::<PyRelaxedOneHotCategorical>()?;
+    m.add_class::<PyUniform>()?;
+    Ok(())
+}
@@ -0,0 +1,276 @@
+//! Fitting distributions and models to data.
+use crate::{
+    constraints::Constraint,
+    numerics,
+    transforms::{transform_to, Transform},
+    Bernoulli, Categorical, Distribution, MixtureSameFamily, Normal, Parametrized, Poisson,
+};
+use std::fmt;
+use tch::{
+    nn::{OptimizerConfig, VarStore},
+    Device, Kind, Tensor,
+};
+
+/// Named parameters of a model, stored in unconstrained space in a `VarStore`.
+///
+/// Each parameter is kept as an unconstrained variable and mapped through `transform_to` of
+/// its constraint whenever it is read with `get`, so any `tch::nn` optimizer can update the
+/// variables without ever leaving the support of the parameters.
+pub struct Params {
+    vs: VarStore,
+    names: Vec<&'static str>,
+    unconstrained: Vec<Tensor>,
+    transforms: Vec<Box<dyn Transform>>,
+}
+
+impl Params {
+    /// Creates an empty set of parameters whose variables live on `device`.
+    pub fn new(device: Device) -> Self {
+        Self {
+            vs: VarStore::new(device),
+            names: Vec::new(),
+            unconstrained: Vec::new(),
+            transforms: Vec::new(),
+        }
+    }
+
+    /// Adds the parameter `name` with the initial value `init`, which must satisfy
+    /// `constraint`. The variable has the kind of `init`.
+    pub fn add(&mut self, name: &'static str, init: &Tensor, constraint: &Constraint) -> &mut Self {
+        assert!(
+            !self.names.contains(&name),
+            "parameter {} is already defined",
+            name
+        );
+        let transform = transform_to(constraint).unwrap_or_else(|| {
+            panic!(
+                "no transform to the constraint {:?} of {}",
+                constraint, name
+            )
+        });
+        let value = tch::no_grad(|| transform.inverse(init));
+        let mut var = self.vs.root().var_copy(name, &value);
+        if var.kind() != value.kind() {
+            var.set_data(&var.to_kind(value.kind()));
+        }
+        self.names.push(name);
+        self.unconstrained.push(var);
+        self.transforms.push(transform);
+        self
+    }
+
+    /// Returns the constrained value of the parameter `name`. Gradients flow back to its
+    /// unconstrained variable.
+    pub fn get(&self, name: &str) -> Tensor {
+        let index = self
+            .names
+            .iter()
+            .position(|&param| param == name)
+            .unwrap_or_else(|| panic!("no parameter named {}", name));
+        self.transforms[index].forward(&self.unconstrained[index])
+    }
+
+    /// Returns the names of the parameters.
+    pub fn names(&self) -> &[&'static str] {
+        &self.names
+    }
+
+    /// Returns the unconstrained variables, in the order of `names`.
+    pub fn unconstrained(&self) -> &[Tensor] {
+        &self.unconstrained
+    }
+
+    /// Returns the var store holding the unconstrained variables.
+    pub fn var_store(&self) -> &VarStore {
+        &self.vs
+    }
+}
+
+impl fmt::Debug for Params {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Params")
+            .field("names", &self.names)
+            .field("unconstrained", &self.unconstrained)
+            .field("transforms", &self.transforms)
+            .finish()
+    }
+}
+
+/// Finds the maximum a posteriori estimate of `params` by maximizing `log_joint`, the sum of
+/// the log prior and the log likelihood of the data given the parameters, with the optimizer
+/// `optimizer` and learning rate `lr`. Stops after `max_steps` steps or once the log joint
+/// changes by less than `tol` between steps, and returns its final value.
+pub fn map<F, O>(
+    log_joint: F,
+    params: &Params,
+    optimizer: O,
+    lr: f64,
+    max_steps: i64,
+    tol: f64,
+) -> f64
+where
+    F: Fn(&Params) -> Tensor,
+    O: OptimizerConfig,
+{
+    let mut opt = optimizer.build(params.var_store(), lr).unwrap();
+    let mut previous = f64::NEG_INFINITY;
+    for _ in 0..max_steps {
+        let value = log_joint(params);
+        opt.backward_step(&-&value);
+        let value = f64::from(&value);
+        if (value - previous).abs() < tol {
+            break;
+        }
+        previous = value;
+    }
+    tch::no_grad(|| f64::from(log_joint(params)))
+}
+
+/// Component families with a closed form M-step for `em`.
+pub trait MStep: Distribution + Sized {
+    /// Returns the `[k]` batched components maximizing the log-likelihood of the `[n, event...]`
+    /// shaped `data`, with the samples weighted by the `[n, k]` shaped `responsibilities` of
+    /// each component.
+    fn m_step(&self, data: &Tensor, responsibilities: &Tensor) -> Self;
+}
+
+/// Returns the `[k]` shaped total responsibilities and responsibility weighted means of the
+/// `[n]` shaped `data`.
+fn weighted_means(data: &Tensor, responsibilities: &Tensor) -> (Tensor, Tensor) {
+    let kind = responsibilities.kind();
+    let totals = responsibilities.sum_dim_intlist(&[0], false, kind);
+    let means =
+        (responsibilities * data.unsqueeze(-1)).sum_dim_intlist(&[0], false, kind) / &totals;
+    (totals, means)
+}
+
+impl MStep for Normal {
+    fn m_step(&self, data: &Tensor, responsibilities: &Tensor) -> Self {
+        let (totals, means) = weighted_means(data, responsibilities);
+        let squares = (data.unsqueeze(-1) - &means).pow_tensor_scalar(2);
+        let variances =
+            (responsibilities * squares).sum_dim_intlist(&[0], false, totals.kind()) / totals;
+        Normal::new(means, variances.sqrt())
+    }
+}
+
+impl MStep for Bernoulli {
+    fn m_step(&self, data: &Tensor, responsibilities: &Tensor) -> Self {
+        let (_, means) = weighted_means(data, responsibilities);
+        Bernoulli::from_probs(means.clamp(1e-6, 1.0 - 1e-6))
+    }
+}
+
+impl MStep for Poisson {
+    fn m_step(&self, data: &Tensor, responsibilities: &Tensor) -> Self {
+        let (_, means) = weighted_means(data, responsibilities);
+        Poisson::new(means)
+    }
+}
+
+/// Returns an M-step for `em_with` which fits the parameters `names` of the components by
+/// maximizing their responsibility weighted log-likelihood with `n_steps` steps of the
+/// optimizer `optimizer` with learning rate `lr`, for families without a closed form M-step.
+/// The parameters are optimized in unconstrained space like `Parametrized` does, and `build`
+/// creates the components from the constrained parameters, given in the order of `names`.
+pub fn gradient_m_step<D, F, O>(
+    names: &[&'static str],
+    build: F,
+    optimizer: O,
+    lr: f64,
+    n_steps: i64,
+) -> impl Fn(&D, &Tensor, &Tensor) -> D
+where
+    D: Distribution,
+    F: Fn(&[Tensor]) -> D,
+    O: OptimizerConfig + Copy,
+{
+    let names = names.to_vec();
+    move |components, data, responsibilities| {
+        let vs = VarStore::new(data.device());
+        let params = Parametrized::new(&vs.root(), components, &names, |p: &[Tensor]| build(p));
+        let mut opt = optimizer.build(&vs, lr).unwrap();
+        let dim = -1 - components.event_shape().len() as i64;
+        let data = data.unsqueeze(dim);
+        for _ in 0..n_steps {
+            let log_prob = params.distribution().log_prob(&data);
+            let loss = -(responsibilities * log_prob).mean(responsibilities.kind());
+            opt.backward_step(&loss);
+        }
+        tch::no_grad(|| params.distribution())
+    }
+}
+
+/// Outcome of fitting a mixture with `em`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EmResult {
+    /// The mean log-likelihood of the data under the fitted mixture.
+    pub log_likelihood: f64,
+    /// The number of EM iterations taken.
+    pub n_iters: i64,
+    /// Whether the mean log-likelihood changed by less than the tolerance in the last
+    /// iteration.
+    pub converged: bool,
+}
+
+/// Fits `mixture` to the `[n, event...]` shaped `data` by expectation maximization, starting
+/// from its current parameters. Stops after `max_iters` iterations or once the mean
+/// log-likelihood of the data changes by less than `tol`. The mixture must have an empty
+/// batch shape.
+pub fn em<D: MStep>(
+    mixture: &mut MixtureSameFamily<D>,
+    data: &Tensor,
+    max_iters: i64,
+    tol: f64,
+) -> EmResult {
+    em_with(mixture, data, max_iters, tol, D::m_step)
+}
+
+/// Fits `mixture` like `em`, computing the components of each M-step with
+/// `m_step(components, data, responsibilities)`, e.g. one created by `gradient_m_step`.
+pub fn em_with<D, M>(
+    mixture: &mut MixtureSameFamily<D>,
+    data: &Tensor,
+    max_iters: i64,
+    tol: f64,
+    m_step: M,
+) -> EmResult
+where
+    D: Distribution,
+    M: Fn(&D, &Tensor, &Tensor) -> D,
+{
+    assert!(
+        mixture.batch_shape().is_empty(),
+        "em requires a mixture with an empty batch shape, got {:?}",
+        mixture.batch_shape()
+    );
+    let e_step = |mixture: &MixtureSameFamily<D>| {
+        tch::no_grad(|| {
+            let joint = mixture.component_log_prob(data) + mixture.mixture().logits();
+            let log_likelihood =
+                f64::from(numerics::logsumexp(&joint, &[-1], false).mean(Kind::Double));
+            (joint.softmax(-1, joint.kind()), log_likelihood)
+        })
+    };
+    let (mut responsibilities, mut log_likelihood) = e_step(mixture);
+    for n_iters in 1..=max_iters {
+        let weights =
+            tch::no_grad(|| responsibilities.mean_dim(&[0], false, responsibilities.kind()));
+        let components = m_step(mixture.components(), data, &responsibilities);
+        *mixture = MixtureSameFamily::new(Categorical::from_probs(weights), components);
+        let previous = log_likelihood;
+        (responsibilities, log_likelihood) = e_step(mixture);
+        if (log_likelihood - previous).abs() < tol {
+            return EmResult {
+                log_likelihood,
+                n_iters,
+                converged: true,
+            };
+        }
+    }
+    EmResult {
+        log_likelihood,
+        n_iters: max_iters,
+        converged: false,
+    }
+}
@@ -1,11 +1,24 @@
-use crate::{Distribution, KullackLeiberDivergence};
-use tch::Tensor;
+use crate::{
+    constraints::Constraint,
+    error::{check_fit_data, check_fitted},
+    persist::{param, Persist},
+    trace,
+    utils::{
+        debug_assert_same_device, params_match, sample_moments, stack_param, tiny, NEWTON_STEPS,
+    },
+    Distribution, Error, Exponential, KullackLeiberDivergence, Stack, Trainable,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use tch::{nn, Kind, Tensor};
 
 /// A Gamma distribution.
 #[derive(Debug)]
 pub struct Gamma {
     concentration: Tensor,
     rate: Tensor,
+    log_rate: Tensor,
+    lgamma_concentration: Tensor,
     batch_shape: Vec<i64>,
 }
 
@@ -14,6 +27,8 @@ impl Clone for Gamma {
         Self {
             concentration: self.concentration.copy(),
             rate: self.rate.copy(),
+            log_rate: self.log_rate.copy(),
+            lgamma_concentration: self.lgamma_concentration.copy(),
             batch_shape: self.batch_shape.clone(),
         }
     }
@@ -21,15 +36,54 @@ impl Clone for Gamma {
 
 impl Gamma {
     // Creates a gamma distribution with`concentration` and `rate`.
-    pub fn new(concentration: Tensor, rate: Tensor) -> Self {
+    pub fn new(concentration: impl Borrow<Tensor>, rate: impl Borrow<Tensor>) -> Self {
+        let concentration = concentration.borrow().shallow_clone();
+        let rate = rate.borrow().shallow_clone();
+        debug_assert_same_device(&[&concentration, &rate]);
+        trace::construct(
+            "Gamma",
+            &[("concentration", &concentration), ("rate", &rate)],
+        );
         let batch_shape = concentration.size();
+        let log_rate = rate.log();
+        let lgamma_concentration = concentration.lgamma();
         Self {
             concentration,
             rate,
+            log_rate,
+            lgamma_concentration,
             batch_shape,
         }
     }
 
+    /// Fits a `Gamma` distribution to `data` by the method of moments, matching the sample mean
+    /// and variance along the leading dimension, which indexes the samples.
+    pub fn fit_moments(data: &Tensor) -> Self {
+        let (mean, variance) = sample_moments(data);
+        Self::new(mean.pow_tensor_scalar(2) / &variance, mean / variance)
+    }
+
+    /// Fits a `Gamma` distribution to `data` by maximum likelihood along the leading dimension,
+    /// which indexes the samples. The concentration `a` solves
+    /// `log(a) - digamma(a) = log(mean(x)) - mean(log(x))` by Newton's method, starting from a
+    /// closed form approximation, and the rate is `a / mean(x)`.
+    pub fn fit_mle(data: &Tensor) -> Result<Self, Error> {
+        let data = check_fit_data(data, &Constraint::Positive)?;
+        let mean = data.mean_dim(&[0], false, data.kind());
+        let s = mean.log() - data.log().mean_dim(&[0], false, data.kind());
+        let mut concentration: Tensor =
+            (3.0 - &s + ((&s - 3.0).pow_tensor_scalar(2) + &s * 24.0).sqrt()) / (&s * 12.0);
+        for _ in 0..NEWTON_STEPS {
+            let value = concentration.log() - concentration.digamma() - &s;
+            let derivative = concentration.reciprocal() - concentration.polygamma(1);
+            // Newton steps may at most halve the concentration, which keeps it positive.
+            concentration = (&concentration - value / derivative).maximum(&(&concentration / 2.0));
+        }
+        let rate = &concentration / mean;
+        check_fitted(&[&concentration, &rate])?;
+        Ok(Self::new(concentration, rate))
+    }
+
     /// Returns shape parameter of the distribution (often referred to as alpha).
     pub fn concentration(&self) -> &Tensor {
         &self.concentration
@@ -39,33 +93,126 @@ impl Gamma {
     pub fn rate(&self) -> &Tensor {
         &self.rate
     }
+
+    /// Creates a `Gamma` distribution of batch `shape` with unit concentration and rate,
+    /// which are variables under `path` stored as their logarithms.
+    pub fn trainable(path: &nn::Path, shape: &[i64]) -> Trainable<Self> {
+        let options = (Kind::Float, path.device());
+        let init = Self::new(Tensor::ones(shape, options), Tensor::ones(shape, options));
+        Trainable::new(path, &init, &["concentration", "rate"], |p| {
+            Self::new(&p[0], &p[1])
+        })
+    }
+}
+
+impl From<(f64, f64)> for Gamma {
+    /// Creates a scalar `Gamma` distribution from `(concentration, rate)`.
+    fn from((concentration, rate): (f64, f64)) -> Self {
+        Self::new(Tensor::from(concentration), Tensor::from(rate))
+    }
+}
+
+impl PartialEq for Gamma {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
 }
 
 impl Distribution for Gamma {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("concentration", &self.concentration), ("rate", &self.rate)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("concentration", Constraint::Positive),
+            ("rate", Constraint::Positive),
+        ]
+    }
+
     fn log_prob(&self, val: &Tensor) -> Tensor {
-        &self.concentration * self.rate.log() + (&self.concentration - 1) * val.log()
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        &self.concentration * &self.log_rate + (&self.concentration - 1) * val.log()
+            - &self.rate * val
+            - &self.lgamma_concentration
+    }
+
+    fn log_prob_and_entropy(&self, val: &Tensor) -> (Tensor, Tensor) {
+        self.validate_sample(val);
+        let concentration_m1 = &self.concentration - 1;
+        let log_prob = &self.concentration * &self.log_rate + &concentration_m1 * val.log()
             - &self.rate * val
-            - self.concentration.lgamma()
+            - &self.lgamma_concentration;
+        let entropy = &self.concentration - &self.log_rate + &self.lgamma_concentration
+            - concentration_m1 * self.concentration.digamma();
+        (log_prob, entropy)
     }
 
-    fn sample(&self, shape: &[i64]) -> Tensor {
-        let _shape = self.extended_shape(shape);
-        todo!("seems like some bindings are missing")
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        let shape = self.extended_shape(shape);
+        // The backward of `_standard_gamma` is the implicit reparameterization gradient
+        // `_standard_gamma_grad`, so gradients flow back to the concentration.
+        let value = self
+            .concentration
+            .expand(&shape, false)
+            .internal_standard_gamma()
+            / &self.rate;
+        let _ = value.detach().clamp_min_(tiny(value.kind()).unwrap());
+        value
     }
 
     fn entropy(&self) -> Tensor {
-        &self.concentration - self.rate.log()
-            + self.concentration.lgamma()
+        &self.concentration - &self.log_rate
+            + &self.lgamma_concentration
             + (1.0 - &self.concentration) * self.concentration.digamma()
     }
 
+    fn mean(&self) -> Tensor {
+        &self.concentration / &self.rate
+    }
+
+    fn variance(&self) -> Tensor {
+        &self.concentration / self.rate.pow_tensor_scalar(2)
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::Nonnegative
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (self.rate.zeros_like(), self.rate.full_like(f64::INFINITY))
+    }
+
     fn batch_shape(&self) -> &[i64] {
         &self.batch_shape
     }
 }
 
+impl Stack for Gamma {
+    fn stack(dists: &[Self]) -> Self {
+        Self::new(
+            stack_param(dists, Self::concentration),
+            stack_param(dists, Self::rate),
+        )
+    }
+}
+
+impl Persist for Gamma {
+    const FAMILY: &'static str = "gamma";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::new(
+            param(params, "concentration")?,
+            param(params, "rate")?,
+        ))
+    }
+}
+
 impl KullackLeiberDivergence<Self> for Gamma {
     fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
         let t1 = other.concentration() * (self.rate() / other.rate()).log();
         let t2 = other.concentration().lgamma() - self.concentration().lgamma();
         let t3 = (self.concentration() - other.concentration()) * self.concentration().digamma();
@@ -73,3 +220,10 @@ impl KullackLeiberDivergence<Self> for Gamma {
         t1 + t2 + t3 + t4
     }
 }
+
+impl KullackLeiberDivergence<Exponential> for Gamma {
+    fn kl_divergence(&self, other: &Exponential) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
+        -self.entropy() - other.rate().log() + other.rate() * self.mean()
+    }
+}
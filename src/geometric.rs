@@ -1,22 +1,35 @@
 use crate::{
-    utils::{logits_to_probs, probs_to_logits, tiny},
-    Distribution, KullackLeiberDivergence,
+    constraints::Constraint,
+    error::check_fit_data,
+    numerics,
+    persist::{param, Persist},
+    trace,
+    utils::{copy_cell, float_values, logits_to_probs, params_match, stack_param, tiny},
+    Distribution, Error, KullackLeiberDivergence, Reparameterize, Stack, Trainable,
 };
-use tch::{Reduction, Tensor};
+use std::borrow::Borrow;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use tch::{nn, Kind, Reduction, Tensor};
 
 /// A Geometric distribution.
+///
+/// Only the parameterization given at construction is stored; the other one is computed on
+/// first use and cached.
 #[derive(Debug)]
 pub struct Geometric {
-    probs: Tensor,
-    logits: Tensor,
+    probs: OnceCell<Tensor>,
+    logits: OnceCell<Tensor>,
+    stores_logits: bool,
     batch_shape: Vec<i64>,
 }
 
 impl Clone for Geometric {
     fn clone(&self) -> Self {
         Self {
-            probs: self.probs.copy(),
-            logits: self.logits.copy(),
+            probs: copy_cell(&self.probs),
+            logits: copy_cell(&self.logits),
+            stores_logits: self.stores_logits,
             batch_shape: self.batch_shape.clone(),
         }
     }
@@ -24,64 +37,140 @@ impl Clone for Geometric {
 
 impl Geometric {
     /// Creates a Geometric distribution from probabilities.
-    pub fn from_probs(probs: Tensor) -> Self {
+    pub fn from_probs(probs: impl Borrow<Tensor>) -> Self {
+        let probs = probs.borrow().shallow_clone();
+        trace::construct("Geometric", &[("probs", &probs)]);
         let batch_shape = probs.size();
         Self {
-            logits: probs_to_logits(&probs, true),
-            probs,
+            probs: OnceCell::from(probs),
+            logits: OnceCell::new(),
+            stores_logits: false,
             batch_shape,
         }
     }
 
     /// Creates a Geometric distribution from logits.
-    pub fn from_logits(logits: Tensor) -> Self {
+    pub fn from_logits(logits: impl Borrow<Tensor>) -> Self {
+        let logits = logits.borrow().shallow_clone();
+        trace::construct("Geometric", &[("logits", &logits)]);
         let batch_shape = logits.size();
         Self {
-            probs: logits_to_probs(&logits, true),
-            logits,
+            probs: OnceCell::new(),
+            logits: OnceCell::from(logits),
+            stores_logits: true,
             batch_shape,
         }
     }
 
+    /// Fits a Geometric distribution to `data` by maximum likelihood, the inverse of one plus
+    /// the mean number of failures along the leading dimension, which indexes the samples.
+    pub fn fit_mle(data: &Tensor) -> Result<Self, Error> {
+        let data = check_fit_data(data, &Constraint::nonnegative_integer())?;
+        Ok(Self::from_probs(
+            (data.mean_dim(&[0], false, data.kind()) + 1.0).reciprocal(),
+        ))
+    }
+
     /// Returns the logits of the distribution.
     pub fn logits(&self) -> &Tensor {
-        &self.logits
+        self.logits
+            .get_or_init(|| numerics::logit(self.probs.get().unwrap()))
     }
 
     /// Returns the probabilities of the distribution.
     pub fn probs(&self) -> &Tensor {
-        &self.probs
+        self.probs
+            .get_or_init(|| logits_to_probs(self.logits.get().unwrap(), true))
+    }
+
+    /// Returns the logits of the distribution without clamping the probabilities, which are
+    /// infinite for probabilities of zero or one.
+    fn exact_logits(&self) -> Tensor {
+        if self.stores_logits {
+            self.logits().shallow_clone()
+        } else {
+            numerics::log_odds(self.probs())
+        }
+    }
+
+    /// Creates a `Geometric` distribution of batch `shape` with probabilities of one half
+    /// whose logits are variables under `path`.
+    pub fn trainable(path: &nn::Path, shape: &[i64]) -> Trainable<Self> {
+        let options = (Kind::Float, path.device());
+        let init = Self::from_logits(Tensor::zeros(shape, options));
+        Trainable::new(path, &init, &["logits"], |p| Self::from_logits(&p[0]))
+    }
+}
+
+impl PartialEq for Geometric {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
     }
 }
 
 impl Distribution for Geometric {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("probs", self.probs()), ("logits", self.logits())]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("probs", Constraint::UnitInterval),
+            ("logits", Constraint::Real),
+        ]
+    }
+
     fn log_prob(&self, val: &Tensor) -> Tensor {
-        let cond = &self
-            .probs
-            .f_eq(1)
-            .unwrap()
-            .logical_and(&val.f_eq(0).unwrap());
-        let probs = self.probs.where_self(&cond.logical_not(), &0.0.into());
-        val * (-probs).log1p() + self.probs.log()
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        let val = &float_values(val, self.probs());
+        // log(p) = -softplus(-logits) and log(1 - p) = -softplus(logits), which stay finite
+        // when the probabilities round to zero or one.
+        let logits = self.exact_logits();
+        let failures = -val * logits.softplus();
+        let failures = failures.where_self(&val.f_ne(0).unwrap(), &failures.zeros_like());
+        failures - (-logits).softplus()
     }
 
     fn sample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
         let shape = self.extended_shape(shape);
-        let tiny = tiny(self.probs.kind()).unwrap();
+        let tiny = tiny(self.probs().kind()).unwrap();
         tch::no_grad(|| {
-            let u =
-                Tensor::empty(&shape, (self.probs.kind(), self.probs.device())).uniform_(tiny, 1.0);
-            (u.log() / (-&self.probs).log1p()).floor()
+            let u = Tensor::empty(&shape, (self.probs().kind(), self.probs().device()))
+                .uniform_(tiny, 1.0);
+            (u.log() / (-self.probs()).log1p()).floor()
         })
     }
 
+    fn mean(&self) -> Tensor {
+        // (1 - p) / p = exp(-logits), which is exactly zero for a probability of one and
+        // stays accurate when the probabilities computed from logits round to one.
+        (-self.exact_logits()).exp()
+    }
+
+    fn variance(&self) -> Tensor {
+        self.mean() / self.probs()
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::nonnegative_integer()
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (
+            self.probs().zeros_like(),
+            self.probs().full_like(f64::INFINITY),
+        )
+    }
+
     fn entropy(&self) -> Tensor {
-        self.logits.binary_cross_entropy_with_logits::<Tensor>(
-            &self.probs,
+        self.logits().binary_cross_entropy_with_logits::<Tensor>(
+            self.probs(),
             None,
             None,
             Reduction::None,
-        ) / &self.probs
+        ) / self.probs()
     }
 
     fn batch_shape(&self) -> &[i64] {
@@ -89,8 +178,39 @@ impl Distribution for Geometric {
     }
 }
 
+impl Reparameterize for Geometric {
+    fn noise(&self, shape: &[i64]) -> Tensor {
+        let shape = self.extended_shape(shape);
+        let tiny = tiny(self.probs().kind()).unwrap();
+        Tensor::empty(&shape, (self.probs().kind(), self.probs().device())).uniform_(tiny, 1.0)
+    }
+
+    fn fill_noise(&self, noise: &mut Tensor) {
+        let _ = noise.uniform_(tiny(noise.kind()).unwrap(), 1.0);
+    }
+
+    fn reparameterize(&self, noise: &Tensor) -> Tensor {
+        (noise.log() / (-self.probs()).log1p()).floor()
+    }
+}
+
+impl Stack for Geometric {
+    fn stack(dists: &[Self]) -> Self {
+        Self::from_probs(stack_param(dists, Self::probs))
+    }
+}
+
+impl Persist for Geometric {
+    const FAMILY: &'static str = "geometric";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::from_logits(param(params, "logits")?))
+    }
+}
+
 impl KullackLeiberDivergence<Self> for Geometric {
     fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
         -self.entropy() - (-other.probs()).log1p() / self.probs() - other.logits()
     }
 }
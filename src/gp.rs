@@ -0,0 +1,187 @@
+//! Gaussian process regression.
+use crate::{Distribution, MultivariateNormal};
+use std::borrow::Borrow;
+use tch::Tensor;
+
+/// Jitter added to the diagonal of covariance matrices of function values to keep their
+/// Cholesky factorization stable.
+const JITTER: f64 = 1e-6;
+
+/// A covariance function of a Gaussian process.
+pub trait Kernel {
+    /// Returns the `[n, m]` shaped covariances between the `[n, dim]` shaped inputs `x1` and
+    /// the `[m, dim]` shaped inputs `x2`.
+    fn matrix(&self, x1: &Tensor, x2: &Tensor) -> Tensor;
+}
+
+/// Returns the `[n, m]` shaped squared distances between the rows of `x1` and `x2`, scaled by
+/// `lengthscale`.
+fn scaled_sq_dist(x1: &Tensor, x2: &Tensor, lengthscale: &Tensor) -> Tensor {
+    let x1 = x1 / lengthscale;
+    let x2 = x2 / lengthscale;
+    let kind = x1.kind();
+    let sq1 = x1.pow_tensor_scalar(2).sum_dim_intlist(&[-1], true, kind);
+    let sq2 = x2.pow_tensor_scalar(2).sum_dim_intlist(&[-1], true, kind);
+    (sq1 - x1.matmul(&x2.transpose(-2, -1)) * 2.0 + sq2.transpose(-2, -1)).clamp_min(0.0)
+}
+
+/// The squared exponential kernel `variance * exp(-r^2 / 2)`, with `r` the distance between
+/// the inputs divided by the lengthscale.
+#[derive(Debug)]
+pub struct Rbf {
+    lengthscale: Tensor,
+    variance: Tensor,
+}
+
+impl Rbf {
+    /// Creates a kernel with `lengthscale`, either scalar or `[dim]` shaped, and signal
+    /// `variance`.
+    pub fn new(lengthscale: impl Borrow<Tensor>, variance: impl Borrow<Tensor>) -> Self {
+        Self {
+            lengthscale: lengthscale.borrow().shallow_clone(),
+            variance: variance.borrow().shallow_clone(),
+        }
+    }
+}
+
+impl Kernel for Rbf {
+    fn matrix(&self, x1: &Tensor, x2: &Tensor) -> Tensor {
+        (scaled_sq_dist(x1, x2, &self.lengthscale) * -0.5).exp() * &self.variance
+    }
+}
+
+/// The Matérn kernel with smoothness `nu` of 1/2, 3/2 or 5/2, e.g.
+/// `variance * (1 + sqrt(3) r) * exp(-sqrt(3) r)` for 3/2, with `r` the distance between the
+/// inputs divided by the lengthscale.
+#[derive(Debug)]
+pub struct Matern {
+    nu: f64,
+    lengthscale: Tensor,
+    variance: Tensor,
+}
+
+impl Matern {
+    /// Creates a kernel with smoothness `nu`, which must be 0.5, 1.5 or 2.5, `lengthscale`,
+    /// either scalar or `[dim]` shaped, and signal `variance`.
+    pub fn new(nu: f64, lengthscale: impl Borrow<Tensor>, variance: impl Borrow<Tensor>) -> Self {
+        assert!(
+            [0.5, 1.5, 2.5].contains(&nu),
+            "nu must be 0.5, 1.5 or 2.5, got {}",
+            nu
+        );
+        Self {
+            nu,
+            lengthscale: lengthscale.borrow().shallow_clone(),
+            variance: variance.borrow().shallow_clone(),
+        }
+    }
+}
+
+impl Kernel for Matern {
+    fn matrix(&self, x1: &Tensor, x2: &Tensor) -> Tensor {
+        // Clamping keeps the gradient of the square root finite at zero distance.
+        let r = scaled_sq_dist(x1, x2, &self.lengthscale)
+            .clamp_min(1e-12)
+            .sqrt();
+        let scaled = &r * (2.0 * self.nu).sqrt();
+        let polynomial = if self.nu == 0.5 {
+            r.ones_like()
+        } else if self.nu == 1.5 {
+            &scaled + 1.0
+        } else {
+            &scaled + 1.0 + scaled.pow_tensor_scalar(2) / 3.0
+        };
+        polynomial * (-scaled).exp() * &self.variance
+    }
+}
+
+/// A zero mean Gaussian process prior with observations corrupted by Gaussian noise.
+#[derive(Debug)]
+pub struct GaussianProcess<K: Kernel> {
+    kernel: K,
+    noise: Tensor,
+}
+
+impl<K: Kernel> GaussianProcess<K> {
+    /// Creates a process with covariance function `kernel` and observation noise variance
+    /// `noise`.
+    pub fn new(kernel: K, noise: impl Borrow<Tensor>) -> Self {
+        Self {
+            kernel,
+            noise: noise.borrow().shallow_clone(),
+        }
+    }
+
+    /// Returns the covariance function.
+    pub fn kernel(&self) -> &K {
+        &self.kernel
+    }
+
+    /// Returns the observation noise variance.
+    pub fn noise(&self) -> &Tensor {
+        &self.noise
+    }
+
+    /// Returns the distribution of the function values at the `[n, dim]` shaped inputs `x`.
+    pub fn prior(&self, x: &Tensor) -> MultivariateNormal {
+        let cov = self.kernel.matrix(x, x);
+        let n = cov.size()[0];
+        let cov = &cov + Tensor::eye(n, (cov.kind(), cov.device())) * JITTER;
+        MultivariateNormal::from_cov(cov.zeros_like().select(-1, 0), cov)
+    }
+
+    /// Returns the Cholesky factor of the covariance of noisy observations at the inputs `x`.
+    fn observation_scale_tril(&self, x: &Tensor) -> Tensor {
+        let cov = self.kernel.matrix(x, x);
+        let n = cov.size()[0];
+        let cov = &cov + Tensor::eye(n, (cov.kind(), cov.device())) * (&self.noise + JITTER);
+        cov.linalg_cholesky(false)
+    }
+
+    /// Returns the log marginal likelihood of the `[n]` shaped observations `y` at the inputs
+    /// `x`, with the function values integrated out. Maximizing it with respect to the
+    /// parameters of the kernel and the noise trains the hyperparameters.
+    pub fn marginal_log_likelihood(&self, x: &Tensor, y: &Tensor) -> Tensor {
+        let scale_tril = self.observation_scale_tril(x);
+        MultivariateNormal::from_scale_tril(y.zeros_like(), scale_tril).log_prob(y)
+    }
+
+    /// Conditions the process on the `[n]` shaped observations `y` at the `[n, dim]` shaped
+    /// inputs `x`.
+    pub fn posterior(&self, x: &Tensor, y: &Tensor) -> GpPosterior<'_, K> {
+        let scale_tril = self.observation_scale_tril(x);
+        let alpha = y.unsqueeze(-1).cholesky_solve(&scale_tril, false);
+        GpPosterior {
+            gp: self,
+            x: x.shallow_clone(),
+            scale_tril,
+            alpha,
+        }
+    }
+}
+
+/// A Gaussian process conditioned on noisy observations.
+#[derive(Debug)]
+pub struct GpPosterior<'a, K: Kernel> {
+    gp: &'a GaussianProcess<K>,
+    x: Tensor,
+    scale_tril: Tensor,
+    alpha: Tensor,
+}
+
+impl<K: Kernel> GpPosterior<'_, K> {
+    /// Returns the posterior distribution of the function values at the `[m, dim]` shaped
+    /// inputs `x`. Adding the noise variance of the process to the diagonal of its covariance
+    /// gives the predictive distribution of new observations.
+    pub fn predict(&self, x: &Tensor) -> MultivariateNormal {
+        let cross = self.gp.kernel.matrix(&self.x, x);
+        let mean = cross.transpose(-2, -1).matmul(&self.alpha).squeeze_dim(-1);
+        let v = self
+            .scale_tril
+            .linalg_solve_triangular(&cross, false, true, false);
+        let cov = self.gp.kernel.matrix(x, x) - v.transpose(-2, -1).matmul(&v);
+        let m = cov.size()[0];
+        let cov = &cov + Tensor::eye(m, (cov.kind(), cov.device())) * JITTER;
+        MultivariateNormal::from_cov(mean, cov)
+    }
+}
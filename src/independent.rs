@@ -0,0 +1,132 @@
+use crate::{constraints::Constraint, utils::sum_rightmost, Distribution, KullackLeiberDivergence};
+use tch::Tensor;
+
+/// A distribution that reinterprets some of the rightmost batch dimensions of a base
+/// distribution as event dimensions, so that the log-probabilities of the base distribution
+/// are summed over them. This turns e.g. a batch of univariate Normals into a single
+/// multivariate distribution with diagonal covariance.
+#[derive(Debug)]
+pub struct Independent<D: Distribution> {
+    base: D,
+    reinterpreted_batch_ndims: i64,
+    batch_shape: Vec<i64>,
+    event_shape: Vec<i64>,
+}
+
+impl<D: Distribution> Independent<D> {
+    /// Creates a distribution treating the `reinterpreted_batch_ndims` rightmost batch
+    /// dimensions of `base` as event dimensions.
+    pub fn new(base: D, reinterpreted_batch_ndims: i64) -> Self {
+        let base_batch_shape = base.batch_shape();
+        assert!(
+            (0..=base_batch_shape.len() as i64).contains(&reinterpreted_batch_ndims),
+            "cannot reinterpret {} dimensions of batch shape {:?}",
+            reinterpreted_batch_ndims,
+            base_batch_shape
+        );
+        let shape = [base_batch_shape, base.event_shape()].concat();
+        let event_dim = reinterpreted_batch_ndims as usize + base.event_shape().len();
+        let (batch_shape, event_shape) = shape.split_at(shape.len() - event_dim);
+        let (batch_shape, event_shape) = (batch_shape.to_vec(), event_shape.to_vec());
+        Self {
+            base,
+            reinterpreted_batch_ndims,
+            batch_shape,
+            event_shape,
+        }
+    }
+
+    /// Returns the base distribution.
+    pub fn base(&self) -> &D {
+        &self.base
+    }
+
+    /// Returns the number of batch dimensions of the base distribution that are reinterpreted
+    /// as event dimensions.
+    pub fn reinterpreted_batch_ndims(&self) -> i64 {
+        self.reinterpreted_batch_ndims
+    }
+}
+
+impl<D: Distribution> Distribution for Independent<D> {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        self.base.params()
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        self.base.arg_constraints()
+    }
+
+    fn support(&self) -> Constraint {
+        match self.reinterpreted_batch_ndims {
+            0 => self.base.support(),
+            n => Constraint::independent(self.base.support(), n),
+        }
+    }
+
+    fn entropy(&self) -> Tensor {
+        sum_rightmost(&self.base.entropy(), self.reinterpreted_batch_ndims)
+    }
+
+    fn mean(&self) -> Tensor {
+        self.base.mean()
+    }
+
+    fn variance(&self) -> Tensor {
+        self.base.variance()
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        sum_rightmost(&self.base.log_prob(val), self.reinterpreted_batch_ndims)
+    }
+
+    fn sample(&self, shape: &[i64]) -> Tensor {
+        self.base.sample(shape)
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        self.base.rsample(shape)
+    }
+
+    fn sample_qmc(&self, n: i64) -> Tensor {
+        self.base.sample_qmc(n)
+    }
+
+    fn sample_antithetic(&self, shape: &[i64]) -> (Tensor, Tensor) {
+        self.base.sample_antithetic(shape)
+    }
+
+    fn sample_stratified(&self, n: i64, strata: i64) -> Tensor {
+        self.base.sample_stratified(n, strata)
+    }
+
+    fn sample_latin_hypercube(&self, n: i64) -> Tensor {
+        self.base.sample_latin_hypercube(n)
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+
+    fn event_shape(&self) -> &[i64] {
+        &self.event_shape
+    }
+}
+
+impl<P, Q> KullackLeiberDivergence<Independent<Q>> for Independent<P>
+where
+    P: Distribution + KullackLeiberDivergence<Q>,
+    Q: Distribution,
+{
+    fn kl_divergence(&self, other: &Independent<Q>) -> Tensor {
+        assert_eq!(
+            self.reinterpreted_batch_ndims, other.reinterpreted_batch_ndims,
+            "KL divergence between Independent distributions needs the same number of \
+             reinterpreted dimensions"
+        );
+        sum_rightmost(
+            &self.base.kl_divergence(&other.base),
+            self.reinterpreted_batch_ndims,
+        )
+    }
+}
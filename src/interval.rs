@@ -0,0 +1,39 @@
+use crate::{utils::float_samples, Distribution};
+use tch::Tensor;
+
+fn check_mass(mass: f64) {
+    assert!(
+        mass > 0.0 && mass <= 1.0,
+        "mass must lie in (0, 1], got {}",
+        mass
+    );
+}
+
+pub(crate) fn equal_tailed<D: Distribution + ?Sized>(dist: &D, mass: f64) -> (Tensor, Tensor) {
+    check_mass(mass);
+    let tail = (1.0 - mass) / 2.0;
+    (
+        dist.icdf(&Tensor::from(tail)),
+        dist.icdf(&Tensor::from(1.0 - tail)),
+    )
+}
+
+/// Estimates the highest density interval by finding the narrowest window of sorted samples
+/// that contains a `mass` fraction of them.
+pub(crate) fn highest_density<D: Distribution + ?Sized>(
+    dist: &D,
+    mass: f64,
+    n_samples: i64,
+) -> (Tensor, Tensor) {
+    check_mass(mass);
+    let (sorted, _) = float_samples(dist, n_samples).sort(0, false);
+    let k = ((mass * n_samples as f64).ceil() as i64).clamp(1, n_samples);
+    let n_windows = n_samples - k + 1;
+    let lower = sorted.narrow(0, 0, n_windows);
+    let upper = sorted.narrow(0, k - 1, n_windows);
+    let start = (&upper - &lower).argmin(0, true);
+    (
+        lower.gather(0, &start, false).squeeze_dim(0),
+        upper.gather(0, &start, false).squeeze_dim(0),
+    )
+}
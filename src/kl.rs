@@ -0,0 +1,31 @@
+use crate::{Distribution, KullackLeiberDivergence, Stack};
+use tch::Tensor;
+
+/// Computes the KL divergences between each pair `(p[i], q[i])` in a single batched
+/// evaluation. The result has a leading dimension of size `p.len()` followed by the batch shape
+/// of the distributions.
+pub fn kl_divergence_batched<P, Q>(p: &[P], q: &[Q]) -> Tensor
+where
+    P: Stack + KullackLeiberDivergence<Q>,
+    Q: Stack + Distribution,
+{
+    assert_eq!(
+        p.len(),
+        q.len(),
+        "got {} and {} distributions to compare",
+        p.len(),
+        q.len()
+    );
+    P::stack(p).kl_divergence(&Q::stack(q))
+}
+
+/// Computes the KL divergences between each of `p` and the distribution `q` in a single
+/// batched evaluation. The parameters of `q` are broadcast against the stacked parameters of
+/// `p`, so `q` may itself be batched over the batch shape of the distributions in `p`.
+pub fn kl_divergence_to<P, Q>(p: &[P], q: &Q) -> Tensor
+where
+    P: Stack + KullackLeiberDivergence<Q>,
+    Q: Distribution,
+{
+    P::stack(p).kl_divergence(q)
+}
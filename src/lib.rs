@@ -1,16 +1,78 @@
-use tch::Tensor;
+use tch::{Kind, Reduction, Tensor};
 
+mod antithetic;
+mod any;
+pub mod approx;
+#[cfg(feature = "ndarray")]
+pub mod array;
 mod bernoulli;
+mod beta;
+mod binomial;
+pub mod bnn;
+pub mod bnp;
+#[cfg(feature = "capi")]
+pub mod capi;
+mod categorical;
 mod cauchy;
+#[cfg(feature = "config")]
+mod config;
+pub mod constraints;
+mod crn;
+mod dirichlet;
+mod empirical;
+mod error;
+pub mod estimators;
+mod expectation;
 mod exponential;
+pub mod export;
+#[cfg(feature = "python")]
+pub mod extension;
+pub mod fit;
 mod gamma;
 mod geometric;
+pub mod gp;
+mod independent;
+mod interval;
+mod kl;
+pub mod losses;
+pub mod mcmc;
+mod mixture_same_family;
 mod multivariate_normal;
+mod negative_binomial;
+pub mod nn;
 mod normal;
+pub mod numerics;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+mod parametrized;
+mod persist;
 mod poisson;
+pub mod predictive;
+#[cfg(feature = "python")]
+pub mod python;
+mod quasi_random;
+mod relaxed_categorical;
+pub mod rl;
+#[cfg(feature = "rand")]
+mod rng;
+mod sampler;
+pub mod sampling;
+#[cfg(feature = "scalar")]
+pub mod scalar;
+pub mod smc;
+#[cfg(feature = "scalar")]
+mod special;
+mod summary;
+mod tanh_normal;
+pub mod tensorboard;
+pub mod testing;
+mod trace;
+mod transformed_distribution;
+pub mod transforms;
 mod uniform;
 mod utils;
-mod categorical;
+mod validation;
+pub mod vi;
 
 pub trait Distribution {
     /// Returns the cumulative density/mass function evaluated at `val`.
@@ -23,6 +85,28 @@ pub trait Distribution {
         unimplemented!()
     }
 
+    /// Returns the mean of the distribution, batched over batch_shape. Estimated by Monte Carlo
+    /// unless a closed form is implemented.
+    fn mean(&self) -> Tensor {
+        utils::float_samples(self, utils::MONTE_CARLO_SAMPLES).mean_dim(&[0], false, Kind::Double)
+    }
+
+    /// Returns the variance of the distribution, batched over batch_shape. Estimated by Monte
+    /// Carlo unless a closed form is implemented.
+    fn variance(&self) -> Tensor {
+        utils::float_samples(self, utils::MONTE_CARLO_SAMPLES).var_dim(&[0], true, false)
+    }
+
+    /// Returns the constraint describing the support of the distribution.
+    fn support(&self) -> constraints::Constraint {
+        constraints::Constraint::Real
+    }
+
+    /// Returns the lower and upper bounds of the support of the distribution.
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (f64::NEG_INFINITY.into(), f64::INFINITY.into())
+    }
+
     /// Returns the inverse cumulative density/mass function evaluated at `val`.
     fn icdf(&self, _val: &Tensor) -> Tensor {
         unimplemented!()
@@ -32,11 +116,251 @@ pub trait Distribution {
     fn log_prob(&self, val: &Tensor) -> Tensor;
 
     /// Generates a sample_shape shaped sample or sample_shape shaped batch of
-    /// samples if the distribution parameters are batched.
-    fn sample(&self, _shape: &[i64]) -> Tensor {
+    /// samples if the distribution parameters are batched. Samples are drawn under
+    /// `tch::no_grad`, so no autograd graph is recorded; use `rsample` to keep gradients.
+    /// Defaults to `rsample` without gradients.
+    fn sample(&self, shape: &[i64]) -> Tensor {
+        tch::no_grad(|| self.rsample(shape))
+    }
+
+    /// Generates a sample_shape shaped reparameterized sample or sample_shape shaped batch of
+    /// reparameterized samples if the distribution parameters are batched. Unlike `sample`,
+    /// gradients flow back to the parameters.
+    fn rsample(&self, _shape: &[i64]) -> Tensor {
         unimplemented!()
     }
 
+    /// Returns the log-probabilities of `val` together with the entropy of the distribution.
+    /// Distributions override this to compute the terms both share only once.
+    fn log_prob_and_entropy(&self, val: &Tensor) -> (Tensor, Tensor) {
+        (self.log_prob(val), self.entropy())
+    }
+
+    /// Returns the log-probabilities of `S` stacked values shaped `[S, ...]` in a single
+    /// broadcast evaluation. Unlike `log_prob`, the leading dimension is always treated as
+    /// the sample dimension, even when the remaining dimensions have a lower rank than the
+    /// batch and event shapes. The result is shaped `[S, batch_shape]`.
+    fn log_prob_n(&self, values: &Tensor) -> Tensor {
+        let shape = values.size();
+        assert!(!shape.is_empty(), "values need a leading sample dimension");
+        let rank = self.batch_shape().len() + self.event_shape().len();
+        let missing = rank.saturating_sub(shape.len() - 1);
+        let values = values.reshape(&[&shape[..1], &vec![1; missing], &shape[1..]].concat()[..]);
+        let log_prob = self.log_prob(&values);
+        log_prob.expand(&[&shape[..1], self.batch_shape()].concat(), false)
+    }
+
+    /// Writes the log-probabilities of `val` into `out`, which must already have the broadcast
+    /// shape of `val` and the parameters. Distributions with a closed form evaluate it in-place
+    /// without allocating; the others copy the result of `log_prob`.
+    fn log_prob_out(&self, out: &mut Tensor, val: &Tensor) {
+        out.copy_(&self.log_prob(val));
+    }
+
+    /// Writes the cumulative density/mass function evaluated at `val` into `out`, like
+    /// `log_prob_out`.
+    fn cdf_out(&self, out: &mut Tensor, val: &Tensor) {
+        out.copy_(&self.cdf(val));
+    }
+
+    /// Draws `n` quasi-random samples by pushing the points of a Sobol sequence through
+    /// `icdf`, with one Sobol dimension per element of the batch. Quasi-random samples cover
+    /// the support more evenly than pseudo-random ones, which reduces the variance of Monte
+    /// Carlo integrals in low dimensions. Only available for univariate distributions
+    /// implementing `icdf`.
+    fn sample_qmc(&self, n: i64) -> Tensor {
+        quasi_random::sample_qmc(self, n)
+    }
+
+    /// Draws `n` samples by stratified sampling: the unit interval is split into `strata`
+    /// equally likely strata, each receiving an equal share of the samples, and the uniforms
+    /// are pushed through `icdf`. Only available for univariate distributions implementing
+    /// `icdf`.
+    fn sample_stratified(&self, n: i64, strata: i64) -> Tensor {
+        quasi_random::sample_stratified(self, n, strata)
+    }
+
+    /// Draws `n` Latin hypercube samples: every batch element is split into `n` equally likely
+    /// strata, each containing exactly one sample, with the strata of different batch elements
+    /// randomly paired. Use `Independent` to treat the batch as a product distribution.
+    /// Only available for univariate distributions implementing `icdf`.
+    fn sample_latin_hypercube(&self, n: i64) -> Tensor {
+        quasi_random::sample_latin_hypercube(self, n)
+    }
+
+    /// Draws a sample_shape shaped sample together with its antithetic counterpart, which has
+    /// the same distribution but is negatively correlated with the first sample. Averaging
+    /// over both halves reduces the variance of Monte Carlo estimates of monotone functions.
+    /// Symmetric families reflect the draws around their center, the others map `u` and
+    /// `1 - u` through `icdf`.
+    fn sample_antithetic(&self, shape: &[i64]) -> (Tensor, Tensor) {
+        antithetic::sample_antithetic(self, shape)
+    }
+
+    /// Fallible version of `log_prob` that checks the shape of `val` and returns an error
+    /// instead of panicking.
+    ///
+    /// The first call replaces the global panic hook with one that stays silent for the
+    /// panics caught here, see [`Error`].
+    fn try_log_prob(&self, val: &Tensor) -> Result<Tensor, Error> {
+        error::check_value_shape(&val.size(), self.batch_shape(), self.event_shape())?;
+        if validate_args() {
+            error::check_support(&self.support(), val)?;
+        }
+        error::catch(|| self.log_prob(val))
+    }
+
+    /// Fallible version of `cdf` that checks the shape of `val` and returns an error instead
+    /// of panicking.
+    ///
+    /// The first call replaces the global panic hook with one that stays silent for the
+    /// panics caught here, see [`Error`].
+    fn try_cdf(&self, val: &Tensor) -> Result<Tensor, Error> {
+        error::check_value_shape(&val.size(), self.batch_shape(), self.event_shape())?;
+        if validate_args() {
+            error::check_support(&self.support(), val)?;
+        }
+        error::catch(|| self.cdf(val))
+    }
+
+    /// Fallible version of `icdf` that checks that `val` holds probabilities in [0, 1] and
+    /// returns an error instead of panicking.
+    ///
+    /// The first call replaces the global panic hook with one that stays silent for the
+    /// panics caught here, see [`Error`].
+    fn try_icdf(&self, val: &Tensor) -> Result<Tensor, Error> {
+        error::check_value_shape(&val.size(), self.batch_shape(), self.event_shape())?;
+        error::check_probability(val)?;
+        error::catch(|| self.icdf(val))
+    }
+
+    /// Fallible version of `sample` that returns an error instead of panicking.
+    ///
+    /// The first call replaces the global panic hook with one that stays silent for the
+    /// panics caught here, see [`Error`].
+    fn try_sample(&self, shape: &[i64]) -> Result<Tensor, Error> {
+        if shape.iter().any(|&s| s < 0) {
+            return Err(Error::Shape(format!(
+                "sample shape {:?} contains negative dimensions",
+                shape
+            )));
+        }
+        error::catch(|| self.sample(shape))
+    }
+
+    /// Returns the negative log-likelihood of `val`, reduced over all dimensions.
+    fn nll(&self, val: &Tensor, reduction: Reduction) -> Tensor {
+        utils::reduce(-self.log_prob(val), None, reduction)
+    }
+
+    /// Returns the negative log-likelihood of `val`, reduced over the dimensions `dims` of the
+    /// log-probabilities.
+    fn nll_dims(&self, val: &Tensor, dims: &[i64], reduction: Reduction) -> Tensor {
+        utils::reduce(-self.log_prob(val), Some(dims), reduction)
+    }
+
+    /// Returns the log-probabilities of `val` scaled by `weights`, which are broadcast against
+    /// the shape of the log-probabilities.
+    fn weighted_log_prob(&self, val: &Tensor, weights: &Tensor) -> Tensor {
+        self.log_prob(val) * weights
+    }
+
+    /// Returns the weighted negative log-likelihood of `val`. The `Mean` reduction divides by
+    /// the sum of the broadcast weights rather than by the number of elements.
+    fn weighted_nll(&self, val: &Tensor, weights: &Tensor, reduction: Reduction) -> Tensor {
+        let weighted = -self.weighted_log_prob(val, weights);
+        match reduction {
+            Reduction::Mean => {
+                let total = weights.expand(&weighted.size(), false).sum(weighted.kind());
+                weighted.sum(weighted.kind()) / total
+            }
+            reduction => utils::reduce(weighted, None, reduction),
+        }
+    }
+
+    /// Returns the equal-tailed interval containing `mass` of the probability, computed with
+    /// `icdf`.
+    fn interval(&self, mass: f64) -> (Tensor, Tensor) {
+        interval::equal_tailed(self, mass)
+    }
+
+    /// Returns the highest density interval containing `mass` of the probability, estimated
+    /// from samples. Unlike `interval` this is the narrowest such interval for unimodal but
+    /// asymmetric distributions.
+    fn hdi(&self, mass: f64) -> (Tensor, Tensor) {
+        interval::highest_density(self, mass, utils::MONTE_CARLO_SAMPLES)
+    }
+
+    /// Returns the mean, standard deviation, a few quantiles and the support bounds of the
+    /// distribution. Quantiles are estimated from samples.
+    fn summary(&self) -> Summary {
+        summary::summarize(self)
+    }
+
+    /// Estimates the expectation of `f` applied to the samples of the distribution from
+    /// `n_samples` samples. Samples are drawn `chunk_size` at a time and the mean and variance
+    /// of the values of `f` are accumulated online, so memory use is bounded by the chunk size.
+    /// `f` receives a chunk of samples along the leading dimension and must keep that
+    /// dimension in its output.
+    fn mc_expectation<F>(&self, f: F, n_samples: i64, chunk_size: i64) -> Expectation
+    where
+        Self: Sized,
+        F: Fn(&Tensor) -> Tensor,
+    {
+        expectation::mc_expectation(self, f, n_samples, chunk_size)
+    }
+
+    /// Returns the named parameter tensors of the distribution.
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        Vec::new()
+    }
+
+    /// Returns the constraint each named parameter of `params` must satisfy.
+    fn arg_constraints(&self) -> Vec<(&'static str, constraints::Constraint)> {
+        Vec::new()
+    }
+
+    /// Returns true if `other` has the same parameter shapes and all its parameters are
+    /// element-wise equal to this distribution's within `atol + rtol * |other|`.
+    fn approx_eq(&self, other: &Self, rtol: f64, atol: f64) -> bool
+    where
+        Self: Sized,
+    {
+        utils::params_match(&self.params(), &other.params(), |a, b| {
+            a.allclose(b, rtol, atol, false)
+        })
+    }
+
+    /// Draws `n_samples` samples and returns the fraction of them falling into each of the
+    /// bins delimited by the increasing edges `bins`, next to the bin probabilities computed
+    /// with `cdf` (only meaningful for continuous distributions).
+    fn empirical_check(&self, n_samples: i64, bins: &Tensor) -> EmpiricalCheck {
+        empirical::empirical_check(self, n_samples, bins)
+    }
+
+    /// Draws a single sample from a distribution with scalar parameters and returns it as
+    /// `f64`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the distribution has a non-empty batch or event shape, as its sample then has
+    /// more than one element.
+    fn sample_scalar(&self) -> f64 {
+        f64::from(&self.sample(&[]))
+    }
+
+    /// Draws a single sample from a discrete distribution with scalar parameters and returns
+    /// it as `i64`. The samples of continuous distributions are truncated toward zero, so use
+    /// `sample_scalar` for these.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the distribution has a non-empty batch or event shape, as its sample then has
+    /// more than one element.
+    fn sample_scalar_i64(&self) -> i64 {
+        i64::from(&self.sample(&[]))
+    }
+
     #[doc(hidden)]
     fn batch_shape(&self) -> &[i64] {
         &[]
@@ -47,6 +371,25 @@ pub trait Distribution {
         &[]
     }
 
+    /// Panics if validation is enabled and `val` lies outside of the support. In debug builds
+    /// also asserts that `val` lives on the same device as the parameters.
+    #[doc(hidden)]
+    fn validate_sample(&self, val: &Tensor) {
+        for (name, param) in self.params() {
+            debug_assert_eq!(
+                param.device(),
+                val.device(),
+                "value and parameter {} are on different devices",
+                name
+            );
+        }
+        if validate_args() {
+            if let Err(err) = error::check_support(&self.support(), val) {
+                panic!("{}", err);
+            }
+        }
+    }
+
     #[doc(hidden)]
     fn extended_shape(&self, shape: &[i64]) -> Vec<i64> {
         [shape, self.batch_shape(), self.event_shape()].concat()
@@ -59,13 +402,62 @@ pub trait KullackLeiberDivergence<D: Distribution> {
     fn kl_divergence(&self, other: &D) -> Tensor;
 }
 
+pub trait Reparameterize: Distribution {
+    /// Draws sample_shape shaped parameter-free noise from which `reparameterize` computes
+    /// samples.
+    fn noise(&self, shape: &[i64]) -> Tensor;
+
+    /// Maps `noise` drawn with `noise` to samples of this distribution. Gradients flow back
+    /// to the parameters of continuous distributions.
+    fn reparameterize(&self, noise: &Tensor) -> Tensor;
+
+    /// Refills `noise`, previously drawn with `noise`, with fresh noise in-place.
+    fn fill_noise(&self, noise: &mut Tensor) {
+        let size = noise.size();
+        let n_dims = self.batch_shape().len() + self.event_shape().len();
+        noise.copy_(&self.noise(&size[..size.len() - n_dims]));
+    }
+
+    /// Writes the samples computed from `noise` into `out`, like `reparameterize`. Affine
+    /// families evaluate them in-place without allocating.
+    fn reparameterize_out(&self, out: &mut Tensor, noise: &Tensor) {
+        out.copy_(&self.reparameterize(noise));
+    }
+}
+
+pub trait Stack: Sized {
+    /// Creates a single distribution whose parameters are the parameters of `dists` stacked
+    /// along a new leading batch dimension. All distributions must have the same batch shape.
+    fn stack(dists: &[Self]) -> Self;
+}
+
+pub use any::AnyDistribution;
 pub use bernoulli::Bernoulli;
+pub use beta::Beta;
+pub use binomial::Binomial;
+pub use categorical::Categorical;
 pub use cauchy::Cauchy;
+pub use crn::sample_crn;
+pub use dirichlet::Dirichlet;
+pub use empirical::EmpiricalCheck;
+pub use error::Error;
+pub use expectation::Expectation;
 pub use exponential::Exponential;
 pub use gamma::Gamma;
 pub use geometric::Geometric;
+pub use independent::Independent;
+pub use kl::{kl_divergence_batched, kl_divergence_to};
+pub use mixture_same_family::MixtureSameFamily;
 pub use multivariate_normal::MultivariateNormal;
+pub use negative_binomial::NegativeBinomial;
 pub use normal::Normal;
+pub use parametrized::{Parametrized, Trainable};
+pub use persist::Persist;
 pub use poisson::Poisson;
+pub use relaxed_categorical::RelaxedOneHotCategorical;
+pub use sampler::Sampler;
+pub use summary::{Summary, SUMMARY_QUANTILES};
+pub use tanh_normal::TanhNormal;
+pub use transformed_distribution::TransformedDistribution;
 pub use uniform::Uniform;
-pub use categorical::Categorical;
+pub use validation::{set_validate_args, validate_args};
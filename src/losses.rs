@@ -0,0 +1,65 @@
+//! Loss functions derived from the densities of the distributions.
+use crate::{utils::reduce, Categorical, Distribution, KullackLeiberDivergence, Normal, Poisson};
+use tch::{Reduction, Tensor};
+
+/// Returns the negative log-likelihood of `target` under Normals with `mean` and standard
+/// deviation `stddev`.
+pub fn gaussian_nll(
+    mean: &Tensor,
+    stddev: &Tensor,
+    target: &Tensor,
+    reduction: Reduction,
+) -> Tensor {
+    Normal::new(mean, stddev).nll(target, reduction)
+}
+
+/// Returns the negative log-likelihood of the counts `target` under Poissons with `rate`.
+pub fn poisson_nll(rate: &Tensor, target: &Tensor, reduction: Reduction) -> Tensor {
+    Poisson::new(rate).nll(target, reduction)
+}
+
+/// Returns the cross entropy between the class indices `labels` smoothed with `smoothing`
+/// and Categoricals with `logits`, whose last dimension indexes the classes. Each label is
+/// replaced by a mixture putting `1 - smoothing` of the mass on the label and spreading
+/// `smoothing` uniformly over all classes. A `smoothing` of zero is the plain negative
+/// log-likelihood.
+pub fn categorical_nll_with_label_smoothing(
+    logits: &Tensor,
+    labels: &Tensor,
+    smoothing: f64,
+    reduction: Reduction,
+) -> Tensor {
+    assert!(
+        (0.0..=1.0).contains(&smoothing),
+        "smoothing must lie in [0, 1], got {}",
+        smoothing
+    );
+    let categorical = Categorical::from_logits(logits);
+    let nll = -categorical.log_prob(labels);
+    let logits = categorical.logits();
+    let uniform_nll = -logits.mean_dim(&[-1], false, logits.kind());
+    reduce(
+        nll * (1.0 - smoothing) + uniform_nll * smoothing,
+        None,
+        reduction,
+    )
+}
+
+/// Returns the negative log-likelihood of `target` under `dist` plus `beta` times the
+/// Kullback-Leibler divergence of `dist` from `prior`, the negative of a (beta-weighted)
+/// evidence lower bound. The divergence is broadcast against the negative log-likelihoods
+/// before the reduction.
+pub fn kl_regularized_nll<D, P>(
+    dist: &D,
+    target: &Tensor,
+    prior: &P,
+    beta: f64,
+    reduction: Reduction,
+) -> Tensor
+where
+    D: Distribution + KullackLeiberDivergence<P>,
+    P: Distribution,
+{
+    let loss = -dist.log_prob(target) + dist.kl_divergence(prior) * beta;
+    reduce(loss, None, reduction)
+}
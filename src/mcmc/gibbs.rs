@@ -0,0 +1,144 @@
+use crate::Distribution;
+use std::fmt;
+use tch::Tensor;
+
+/// The current values of the blocks of a `Gibbs` sampler.
+#[derive(Debug, Default)]
+pub struct GibbsState {
+    names: Vec<&'static str>,
+    values: Vec<Tensor>,
+}
+
+impl GibbsState {
+    /// Returns the current value of the block `name`.
+    pub fn get(&self, name: &str) -> &Tensor {
+        let index = self
+            .names
+            .iter()
+            .position(|&block| block == name)
+            .unwrap_or_else(|| panic!("no block named {}", name));
+        &self.values[index]
+    }
+
+    /// Returns the names and current values of all blocks, in the order they were registered.
+    pub fn blocks(&self) -> Vec<(&'static str, &Tensor)> {
+        self.names.iter().copied().zip(self.values.iter()).collect()
+    }
+}
+
+type Conditional<'a> = Box<dyn Fn(&GibbsState) -> Tensor + 'a>;
+
+/// Gibbs sampling from user-provided full conditional distributions.
+///
+/// Every block of variables is registered with a closure returning its distribution given the
+/// current values of all other blocks, which is usually available in closed form for
+/// conjugate models. Each sweep draws the blocks one after another in the order they were
+/// registered, so later blocks see the new values of earlier ones.
+pub struct Gibbs<'a> {
+    state: GibbsState,
+    conditionals: Vec<Conditional<'a>>,
+    thinning: i64,
+}
+
+impl<'a> Gibbs<'a> {
+    /// Creates a sampler without any blocks, keeping every sweep.
+    pub fn new() -> Self {
+        Self {
+            state: GibbsState::default(),
+            conditionals: Vec::new(),
+            thinning: 1,
+        }
+    }
+
+    /// Registers the block `name`, starting at `init`, whose full conditional distribution given
+    /// the current state is returned by `conditional`. Its samples must have the shape of
+    /// `init`.
+    pub fn block<D, F>(&mut self, name: &'static str, init: Tensor, conditional: F) -> &mut Self
+    where
+        D: Distribution,
+        F: Fn(&GibbsState) -> D + 'a,
+    {
+        assert!(
+            !self.state.names.contains(&name),
+            "block {} is already registered",
+            name
+        );
+        self.state.names.push(name);
+        self.state.values.push(init.detach());
+        self.conditionals
+            .push(Box::new(move |state| conditional(state).sample(&[])));
+        self
+    }
+
+    /// Keeps only every `thinning`-th sweep in `sample`.
+    pub fn set_thinning(&mut self, thinning: i64) -> &mut Self {
+        assert!(thinning > 0, "thinning must be positive, got {}", thinning);
+        self.thinning = thinning;
+        self
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> &GibbsState {
+        &self.state
+    }
+
+    /// Draws every block once from its full conditional.
+    pub fn step(&mut self) -> &GibbsState {
+        tch::no_grad(|| {
+            for (index, conditional) in self.conditionals.iter().enumerate() {
+                let value = conditional(&self.state);
+                assert_eq!(
+                    value.size(),
+                    self.state.values[index].size(),
+                    "conditional of block {} has samples of the wrong shape",
+                    self.state.names[index]
+                );
+                self.state.values[index] = value;
+            }
+        });
+        &self.state
+    }
+
+    /// Takes `n_steps` sweeps without storing them, to let the chain forget its initial state.
+    pub fn warmup(&mut self, n_steps: i64) {
+        for _ in 0..n_steps {
+            self.step();
+        }
+    }
+
+    /// Keeps `n_samples` sweeps, skipping sweeps according to the thinning, and returns the
+    /// kept values of every block stacked along a new leading dimension.
+    pub fn sample(&mut self, n_samples: i64) -> Vec<(&'static str, Tensor)> {
+        let mut samples: Vec<Vec<Tensor>> = self.state.names.iter().map(|_| Vec::new()).collect();
+        for _ in 0..n_samples {
+            for _ in 0..self.thinning {
+                self.step();
+            }
+            for (samples, value) in samples.iter_mut().zip(self.state.values.iter()) {
+                samples.push(value.shallow_clone());
+            }
+        }
+        self.state
+            .names
+            .iter()
+            .copied()
+            .zip(samples)
+            .map(|(name, samples)| (name, Tensor::stack(&samples, 0)))
+            .collect()
+    }
+}
+
+impl Default for Gibbs<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Gibbs<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Gibbs")
+            .field("state", &self.state)
+            .field("thinning", &self.thinning)
+            .finish()
+    }
+}
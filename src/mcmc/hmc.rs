@@ -0,0 +1,134 @@
+use super::{kinetic_energy, leapfrog, momentum, value_and_grad, VarianceEstimator};
+use std::fmt;
+use tch::Tensor;
+
+/// Hamiltonian Monte Carlo with a fixed number of leapfrog steps per transition.
+///
+/// Gradients of the log density are computed with autograd. The diagonal mass matrix is the
+/// identity until it is adapted to the variance of the states during `warmup`.
+pub struct Hmc<F> {
+    log_prob: F,
+    step_size: f64,
+    n_leapfrog: i64,
+    inv_mass: Tensor,
+    position: Tensor,
+    value: Tensor,
+    grad: Tensor,
+    n_accepted: Tensor,
+    n_steps: i64,
+}
+
+impl<F: Fn(&Tensor) -> Tensor> Hmc<F> {
+    /// Creates a sampler for `log_prob` whose chains start at the `[n_chains, dim]` shaped
+    /// states `init`, taking `n_leapfrog` steps of size `step_size` per transition.
+    pub fn new(log_prob: F, init: &Tensor, step_size: f64, n_leapfrog: i64) -> Self {
+        assert_eq!(
+            init.dim(),
+            2,
+            "initial states must be of shape [n_chains, dim], got {:?}",
+            init.size()
+        );
+        assert!(
+            n_leapfrog > 0,
+            "need at least one leapfrog step, got {}",
+            n_leapfrog
+        );
+        let position = init.detach();
+        let (value, grad) = value_and_grad(&log_prob, &position);
+        Self {
+            step_size,
+            n_leapfrog,
+            inv_mass: position.get(0).ones_like(),
+            n_accepted: value.zeros_like(),
+            n_steps: 0,
+            log_prob,
+            position,
+            value,
+            grad,
+        }
+    }
+
+    /// Returns the current states of the chains.
+    pub fn position(&self) -> &Tensor {
+        &self.position
+    }
+
+    /// Returns the diagonal of the inverse mass matrix.
+    pub fn inverse_mass(&self) -> &Tensor {
+        &self.inv_mass
+    }
+
+    /// Returns the fraction of accepted transitions of each chain since the last warmup.
+    pub fn acceptance_rate(&self) -> Tensor {
+        &self.n_accepted / (self.n_steps.max(1) as f64)
+    }
+
+    /// Takes a transition of every chain and returns the new states.
+    pub fn step(&mut self) -> &Tensor {
+        tch::no_grad(|| {
+            let p0 = momentum(&self.position, &self.inv_mass);
+            let (mut x, mut p, mut value, mut grad) = (
+                self.position.shallow_clone(),
+                p0.shallow_clone(),
+                self.value.shallow_clone(),
+                self.grad.shallow_clone(),
+            );
+            for _ in 0..self.n_leapfrog {
+                (x, p, value, grad) = leapfrog(
+                    &self.log_prob,
+                    &x,
+                    &p,
+                    &grad,
+                    self.step_size,
+                    &self.inv_mass,
+                );
+            }
+            let log_accept = &value - kinetic_energy(&p, &self.inv_mass) - &self.value
+                + kinetic_energy(&p0, &self.inv_mass);
+            // Divergent trajectories produce NaNs, which compare false and are rejected.
+            let accept = value.rand_like().log().lt_tensor(&log_accept);
+            let accept_state = accept.unsqueeze(-1);
+            self.position = x.where_self(&accept_state, &self.position);
+            self.grad = grad.where_self(&accept_state, &self.grad);
+            self.value = value.where_self(&accept, &self.value);
+            self.n_accepted += accept.to_kind(self.n_accepted.kind());
+            self.n_steps += 1;
+        });
+        &self.position
+    }
+
+    /// Takes `n_steps` transitions, adapting the inverse mass matrix to the variance of the
+    /// states visited during the second half, and resets the acceptance statistics.
+    pub fn warmup(&mut self, n_steps: i64) {
+        let mut estimator = VarianceEstimator::new(&self.position);
+        for i in 0..n_steps {
+            self.step();
+            if 2 * i >= n_steps {
+                tch::no_grad(|| estimator.update(&self.position));
+            }
+        }
+        if estimator.n > 1 {
+            self.inv_mass = tch::no_grad(|| estimator.variance());
+        }
+        self.n_accepted = self.n_accepted.zeros_like();
+        self.n_steps = 0;
+    }
+
+    /// Takes `n_samples` transitions and returns the visited states, stacked into a
+    /// `[n_samples, n_chains, dim]` shaped tensor.
+    pub fn sample(&mut self, n_samples: i64) -> Tensor {
+        let samples: Vec<Tensor> = (0..n_samples).map(|_| self.step().copy()).collect();
+        Tensor::stack(&samples, 0)
+    }
+}
+
+impl<F> fmt::Debug for Hmc<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Hmc")
+            .field("step_size", &self.step_size)
+            .field("n_leapfrog", &self.n_leapfrog)
+            .field("inv_mass", &self.inv_mass)
+            .field("position", &self.position)
+            .finish()
+    }
+}
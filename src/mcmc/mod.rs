@@ -0,0 +1,92 @@
+//! Markov chain Monte Carlo samplers.
+//!
+//! `Hmc`, `Nuts` and `SliceSampler` sample user-provided log densities and run several chains
+//! at once. Their states are `[n_chains, dim]` shaped tensors and log densities are called with
+//! such a batch of states, returning `[n_chains]` shaped values. `Gibbs` instead draws from
+//! user-provided full conditional distributions.
+use tch::Tensor;
+
+mod gibbs;
+mod hmc;
+mod nuts;
+mod slice;
+
+pub use gibbs::{Gibbs, GibbsState};
+pub use hmc::Hmc;
+pub use nuts::Nuts;
+pub use slice::SliceSampler;
+
+/// Evaluates `log_prob` and its gradient at `x`, detached from any graph.
+fn value_and_grad<F: Fn(&Tensor) -> Tensor>(log_prob: &F, x: &Tensor) -> (Tensor, Tensor) {
+    tch::with_grad(|| {
+        let x = x.detach().set_requires_grad(true);
+        let value = log_prob(&x);
+        let mut grad = Tensor::run_backward(&[value.sum(value.kind())], &[&x], false, false);
+        (value.detach(), grad.pop().unwrap())
+    })
+}
+
+/// Takes a single leapfrog step of size `step_size` from position `x` with momentum `p` and
+/// log density gradient `grad` at `x`, for a diagonal mass matrix with inverse `inv_mass`.
+/// Returns the new position and momentum with the log density and its gradient at the new
+/// position.
+fn leapfrog<F: Fn(&Tensor) -> Tensor>(
+    log_prob: &F,
+    x: &Tensor,
+    p: &Tensor,
+    grad: &Tensor,
+    step_size: f64,
+    inv_mass: &Tensor,
+) -> (Tensor, Tensor, Tensor, Tensor) {
+    let p = p + grad * (step_size * 0.5);
+    let x = x + &p * inv_mass * step_size;
+    let (value, grad) = value_and_grad(log_prob, &x);
+    let p = p + &grad * (step_size * 0.5);
+    (x, p, value, grad)
+}
+
+/// Returns the kinetic energy of the momenta `p` for a diagonal mass matrix with inverse
+/// `inv_mass`.
+fn kinetic_energy(p: &Tensor, inv_mass: &Tensor) -> Tensor {
+    (p.pow_tensor_scalar(2) * inv_mass).sum_dim_intlist(&[-1], false, p.kind()) * 0.5
+}
+
+/// Draws momenta for the states `x` for a diagonal mass matrix with inverse `inv_mass`.
+fn momentum(x: &Tensor, inv_mass: &Tensor) -> Tensor {
+    x.randn_like() / inv_mass.sqrt()
+}
+
+/// Running estimate of the elementwise variance of the states of all chains, used to adapt
+/// the diagonal mass matrix during warmup.
+struct VarianceEstimator {
+    n: i64,
+    mean: Tensor,
+    m2: Tensor,
+}
+
+impl VarianceEstimator {
+    fn new(x: &Tensor) -> Self {
+        Self {
+            n: 0,
+            mean: x.get(0).zeros_like(),
+            m2: x.get(0).zeros_like(),
+        }
+    }
+
+    /// Adds the states `x` of all chains, one observation per chain.
+    fn update(&mut self, x: &Tensor) {
+        for x in x.unbind(0) {
+            self.n += 1;
+            let delta = &x - &self.mean;
+            self.mean += &delta / self.n as f64;
+            self.m2 += delta * (x - &self.mean);
+        }
+    }
+
+    /// Returns the variance, shrunk towards `1e-3` for few observations like Stan does.
+    fn variance(&self) -> Tensor {
+        let n = self.n as f64;
+        let variance = &self.m2 / (n - 1.0).max(1.0);
+        variance * (n / (n + 5.0)) + 1e-3 * (5.0 / (n + 5.0))
+    }
+}
@@ -0,0 +1,373 @@
+use super::{kinetic_energy, leapfrog, momentum, value_and_grad, VarianceEstimator};
+use std::fmt;
+use tch::Tensor;
+
+/// Energy error beyond which a trajectory is considered divergent.
+const MAX_ENERGY_ERROR: f64 = 1000.0;
+
+/// A point of a trajectory of a single chain, kept with a leading batch dimension of one.
+struct State {
+    x: Tensor,
+    p: Tensor,
+    value: Tensor,
+    grad: Tensor,
+}
+
+impl State {
+    fn shallow_clone(&self) -> Self {
+        Self {
+            x: self.x.shallow_clone(),
+            p: self.p.shallow_clone(),
+            value: self.value.shallow_clone(),
+            grad: self.grad.shallow_clone(),
+        }
+    }
+}
+
+/// A balanced binary tree of leapfrog steps, represented by its leftmost and rightmost
+/// states and a state drawn uniformly among its states inside the slice.
+struct Tree {
+    minus: State,
+    plus: State,
+    proposal: State,
+    n_valid: f64,
+    keep_going: bool,
+    sum_accept: f64,
+    n_leapfrog: i64,
+    divergent: bool,
+}
+
+/// Dual averaging of the log step size by Hoffman & Gelman (2014).
+#[derive(Debug)]
+struct DualAveraging {
+    mu: f64,
+    h_bar: f64,
+    log_step_size_bar: f64,
+    m: f64,
+}
+
+impl DualAveraging {
+    const GAMMA: f64 = 0.05;
+    const T0: f64 = 10.0;
+    const KAPPA: f64 = 0.75;
+
+    fn new(step_size: f64) -> Self {
+        Self {
+            mu: (10.0 * step_size).ln(),
+            h_bar: 0.0,
+            log_step_size_bar: 0.0,
+            m: 0.0,
+        }
+    }
+
+    /// Updates the averages with the acceptance statistic `accept` and returns the next step
+    /// size.
+    fn update(&mut self, accept: f64, target_accept: f64) -> f64 {
+        self.m += 1.0;
+        let w = 1.0 / (self.m + Self::T0);
+        self.h_bar = (1.0 - w) * self.h_bar + w * (target_accept - accept);
+        let log_step_size = self.mu - self.m.sqrt() / Self::GAMMA * self.h_bar;
+        let eta = self.m.powf(-Self::KAPPA);
+        self.log_step_size_bar = eta * log_step_size + (1.0 - eta) * self.log_step_size_bar;
+        log_step_size.exp()
+    }
+
+    /// Returns the averaged step size to use after adaptation.
+    fn step_size(&self) -> f64 {
+        self.log_step_size_bar.exp()
+    }
+}
+
+/// The No-U-Turn Sampler of Hoffman & Gelman (2014).
+///
+/// Trajectories are extended in both directions until they make a U-turn or reach
+/// `2^max_tree_depth` leapfrog steps, so no trajectory length has to be tuned. During
+/// `warmup` the step size is adapted by dual averaging towards `target_accept` and the
+/// diagonal mass matrix to the variance of the states. Like `Hmc`, several chains are run at
+/// once, but each one builds its own trajectory.
+pub struct Nuts<F> {
+    log_prob: F,
+    step_size: f64,
+    target_accept: f64,
+    max_tree_depth: usize,
+    inv_mass: Tensor,
+    position: Tensor,
+    value: Tensor,
+    grad: Tensor,
+    sum_accept: Vec<f64>,
+    n_divergent: Vec<i64>,
+    n_steps: i64,
+}
+
+impl<F: Fn(&Tensor) -> Tensor> Nuts<F> {
+    /// Creates a sampler for `log_prob` whose chains start at the `[n_chains, dim]` shaped
+    /// states `init`, with an initial step size of `step_size`. The step size is adapted to an
+    /// acceptance statistic of 0.8 during warmup and trees are at most 10 levels deep.
+    pub fn new(log_prob: F, init: &Tensor, step_size: f64) -> Self {
+        assert_eq!(
+            init.dim(),
+            2,
+            "initial states must be of shape [n_chains, dim], got {:?}",
+            init.size()
+        );
+        let position = init.detach();
+        let (value, grad) = value_and_grad(&log_prob, &position);
+        let n_chains = position.size()[0] as usize;
+        Self {
+            step_size,
+            target_accept: 0.8,
+            max_tree_depth: 10,
+            inv_mass: position.get(0).ones_like(),
+            sum_accept: vec![0.0; n_chains],
+            n_divergent: vec![0; n_chains],
+            n_steps: 0,
+            log_prob,
+            position,
+            value,
+            grad,
+        }
+    }
+
+    /// Sets the acceptance statistic the step size is adapted to during `warmup`.
+    pub fn set_target_accept(&mut self, target_accept: f64) {
+        assert!(
+            target_accept > 0.0 && target_accept < 1.0,
+            "target acceptance must be in (0, 1), got {}",
+            target_accept
+        );
+        self.target_accept = target_accept;
+    }
+
+    /// Sets the maximum depth of the trees, limiting trajectories to `2^max_tree_depth`
+    /// leapfrog steps.
+    pub fn set_max_tree_depth(&mut self, max_tree_depth: usize) {
+        self.max_tree_depth = max_tree_depth;
+    }
+
+    /// Returns the current states of the chains.
+    pub fn position(&self) -> &Tensor {
+        &self.position
+    }
+
+    /// Returns the current step size.
+    pub fn step_size(&self) -> f64 {
+        self.step_size
+    }
+
+    /// Returns the diagonal of the inverse mass matrix.
+    pub fn inverse_mass(&self) -> &Tensor {
+        &self.inv_mass
+    }
+
+    /// Returns the mean acceptance statistic of each chain since the last warmup.
+    pub fn acceptance_rate(&self) -> Vec<f64> {
+        let n_steps = self.n_steps.max(1) as f64;
+        self.sum_accept.iter().map(|a| a / n_steps).collect()
+    }
+
+    /// Returns the number of divergent transitions of each chain since the last warmup. Many
+    /// divergences indicate a step size too large for the curvature of the target, and biased
+    /// samples.
+    pub fn divergences(&self) -> &[i64] {
+        &self.n_divergent
+    }
+
+    /// Takes a transition of every chain and returns the new states.
+    pub fn step(&mut self) -> &Tensor {
+        self.transition();
+        &self.position
+    }
+
+    /// Takes a transition of every chain and returns the mean acceptance statistic.
+    fn transition(&mut self) -> f64 {
+        let n_chains = self.sum_accept.len();
+        let mut states = Vec::with_capacity(n_chains);
+        let mut total_accept = 0.0;
+        tch::no_grad(|| {
+            for chain in 0..n_chains {
+                let state = State {
+                    x: self.position.narrow(0, chain as i64, 1),
+                    p: momentum(&self.position.narrow(0, chain as i64, 1), &self.inv_mass),
+                    value: self.value.narrow(0, chain as i64, 1),
+                    grad: self.grad.narrow(0, chain as i64, 1),
+                };
+                let (state, accept, divergent) = self.trajectory(state);
+                self.sum_accept[chain] += accept;
+                self.n_divergent[chain] += divergent as i64;
+                total_accept += accept;
+                states.push(state);
+            }
+            let cat = |f: fn(&State) -> &Tensor| {
+                Tensor::cat(&states.iter().map(f).collect::<Vec<_>>(), 0)
+            };
+            self.position = cat(|s| &s.x);
+            self.value = cat(|s| &s.value);
+            self.grad = cat(|s| &s.grad);
+        });
+        self.n_steps += 1;
+        total_accept / n_chains as f64
+    }
+
+    fn joint(&self, state: &State) -> f64 {
+        (&state.value - kinetic_energy(&state.p, &self.inv_mass)).double_value(&[0])
+    }
+
+    /// Returns false if the trajectory from `minus` to `plus` makes a U-turn.
+    fn no_u_turn(&self, minus: &State, plus: &State) -> bool {
+        let dx = &plus.x - &minus.x;
+        let forward = (&dx * &plus.p * &self.inv_mass).sum(dx.kind());
+        let backward = (&dx * &minus.p * &self.inv_mass).sum(dx.kind());
+        f64::from(forward) >= 0.0 && f64::from(backward) >= 0.0
+    }
+
+    /// Builds a trajectory from `state` and returns the next state of the chain, the
+    /// acceptance statistic and whether the trajectory diverged.
+    fn trajectory(&self, state: State) -> (State, f64, bool) {
+        let joint0 = self.joint(&state);
+        let log_u = uniform().ln() + joint0;
+        let mut minus = state.shallow_clone();
+        let mut plus = state.shallow_clone();
+        let mut proposal = state;
+        let mut n_valid = 1.0;
+        let mut sum_accept = 0.0;
+        let mut n_leapfrog = 0;
+        let mut divergent = false;
+        for depth in 0..self.max_tree_depth {
+            let direction = if uniform() < 0.5 { -1.0 } else { 1.0 };
+            let tree = if direction < 0.0 {
+                self.build_tree(&minus, log_u, direction, depth, joint0)
+            } else {
+                self.build_tree(&plus, log_u, direction, depth, joint0)
+            };
+            sum_accept += tree.sum_accept;
+            n_leapfrog += tree.n_leapfrog;
+            divergent |= tree.divergent;
+            if !tree.keep_going {
+                break;
+            }
+            if uniform() < tree.n_valid / n_valid {
+                proposal = tree.proposal;
+            }
+            n_valid += tree.n_valid;
+            if direction < 0.0 {
+                minus = tree.minus;
+            } else {
+                plus = tree.plus;
+            }
+            if !self.no_u_turn(&minus, &plus) {
+                break;
+            }
+        }
+        (proposal, sum_accept / n_leapfrog.max(1) as f64, divergent)
+    }
+
+    fn build_tree(
+        &self,
+        state: &State,
+        log_u: f64,
+        direction: f64,
+        depth: usize,
+        joint0: f64,
+    ) -> Tree {
+        if depth == 0 {
+            let (x, p, value, grad) = leapfrog(
+                &self.log_prob,
+                &state.x,
+                &state.p,
+                &state.grad,
+                direction * self.step_size,
+                &self.inv_mass,
+            );
+            let state = State { x, p, value, grad };
+            let joint = self.joint(&state);
+            let divergent = joint.is_nan() || log_u >= MAX_ENERGY_ERROR + joint;
+            let accept = (joint - joint0).exp().min(1.0);
+            return Tree {
+                minus: state.shallow_clone(),
+                plus: state.shallow_clone(),
+                proposal: state,
+                n_valid: if log_u <= joint { 1.0 } else { 0.0 },
+                keep_going: !divergent,
+                sum_accept: if accept.is_nan() { 0.0 } else { accept },
+                n_leapfrog: 1,
+                divergent,
+            };
+        }
+        let mut tree = self.build_tree(state, log_u, direction, depth - 1, joint0);
+        if !tree.keep_going {
+            return tree;
+        }
+        let other = if direction < 0.0 {
+            self.build_tree(&tree.minus, log_u, direction, depth - 1, joint0)
+        } else {
+            self.build_tree(&tree.plus, log_u, direction, depth - 1, joint0)
+        };
+        let n_valid = tree.n_valid + other.n_valid;
+        if n_valid > 0.0 && uniform() < other.n_valid / n_valid {
+            tree.proposal = other.proposal;
+        }
+        if direction < 0.0 {
+            tree.minus = other.minus;
+        } else {
+            tree.plus = other.plus;
+        }
+        tree.n_valid = n_valid;
+        tree.sum_accept += other.sum_accept;
+        tree.n_leapfrog += other.n_leapfrog;
+        tree.divergent |= other.divergent;
+        tree.keep_going = other.keep_going && self.no_u_turn(&tree.minus, &tree.plus);
+        tree
+    }
+
+    /// Takes `n_steps` transitions, adapting the step size by dual averaging throughout and the
+    /// inverse mass matrix to the variance of the states visited during the middle half, and
+    /// resets the acceptance and divergence statistics.
+    pub fn warmup(&mut self, n_steps: i64) {
+        let mut averaging = DualAveraging::new(self.step_size);
+        let mut estimator = VarianceEstimator::new(&self.position);
+        let (start, end) = (n_steps / 4, n_steps - n_steps / 4);
+        for i in 0..n_steps {
+            let accept = self.transition();
+            self.step_size = averaging.update(accept, self.target_accept);
+            if i >= start && i < end {
+                tch::no_grad(|| estimator.update(&self.position));
+            }
+            if i + 1 == end && estimator.n > 1 {
+                // The step size has to be adapted anew to the adapted mass matrix.
+                self.inv_mass = tch::no_grad(|| estimator.variance());
+                averaging = DualAveraging::new(self.step_size);
+            }
+        }
+        if n_steps > 0 {
+            self.step_size = averaging.step_size();
+        }
+        self.sum_accept.iter_mut().for_each(|a| *a = 0.0);
+        self.n_divergent.iter_mut().for_each(|n| *n = 0);
+        self.n_steps = 0;
+    }
+
+    /// Takes `n_samples` transitions and returns the visited states, stacked into a
+    /// `[n_samples, n_chains, dim]` shaped tensor.
+    pub fn sample(&mut self, n_samples: i64) -> Tensor {
+        let samples: Vec<Tensor> = (0..n_samples).map(|_| self.step().copy()).collect();
+        Tensor::stack(&samples, 0)
+    }
+}
+
+/// Draws a uniform number in [0, 1) from the torch generator, so seeding with
+/// `tch::manual_seed` makes the chains reproducible.
+fn uniform() -> f64 {
+    f64::from(Tensor::rand(&[], tch::kind::DOUBLE_CPU))
+}
+
+impl<F> fmt::Debug for Nuts<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Nuts")
+            .field("step_size", &self.step_size)
+            .field("target_accept", &self.target_accept)
+            .field("max_tree_depth", &self.max_tree_depth)
+            .field("inv_mass", &self.inv_mass)
+            .field("position", &self.position)
+            .finish()
+    }
+}
@@ -0,0 +1,149 @@
+use std::fmt;
+use tch::{Kind, Tensor};
+
+/// Univariate slice sampling with stepping out and shrinkage by Neal (2003), applied to one
+/// coordinate of the states after another.
+///
+/// Only evaluations of the log density are needed, so the target does not have to be
+/// differentiable. All chains are updated at once, each with its own slice.
+pub struct SliceSampler<F> {
+    log_prob: F,
+    width: f64,
+    max_steps_out: i64,
+    position: Tensor,
+    value: Tensor,
+}
+
+impl<F: Fn(&Tensor) -> Tensor> SliceSampler<F> {
+    /// Creates a sampler for `log_prob` whose chains start at the `[n_chains, dim]` shaped
+    /// states `init`. Slices are found by stepping out intervals of size `width` at most 100
+    /// times.
+    pub fn new(log_prob: F, init: &Tensor, width: f64) -> Self {
+        assert_eq!(
+            init.dim(),
+            2,
+            "initial states must be of shape [n_chains, dim], got {:?}",
+            init.size()
+        );
+        assert!(width > 0.0, "width must be positive, got {}", width);
+        let position = init.detach();
+        let value = tch::no_grad(|| log_prob(&position));
+        Self {
+            log_prob,
+            width,
+            max_steps_out: 100,
+            position,
+            value,
+        }
+    }
+
+    /// Sets the maximum number of times the interval is stepped out, split randomly between
+    /// both of its ends.
+    pub fn set_max_steps_out(&mut self, max_steps_out: i64) {
+        assert!(
+            max_steps_out > 0,
+            "need at least one step, got {}",
+            max_steps_out
+        );
+        self.max_steps_out = max_steps_out;
+    }
+
+    /// Returns the current states of the chains.
+    pub fn position(&self) -> &Tensor {
+        &self.position
+    }
+
+    /// Updates every coordinate of every chain once and returns the new states.
+    pub fn step(&mut self) -> &Tensor {
+        tch::no_grad(|| {
+            for dim in 0..self.position.size()[1] {
+                self.update(dim);
+            }
+        });
+        &self.position
+    }
+
+    /// Returns the log density of the states with coordinate `dim` replaced by `coord`.
+    fn log_prob_at(&self, dim: i64, coord: &Tensor) -> Tensor {
+        let index = Tensor::of_slice(&[dim]).to_device(coord.device());
+        let x = self.position.index_copy(1, &index, &coord.unsqueeze(1));
+        (self.log_prob)(&x)
+    }
+
+    /// Moves `bound` by `step` until it leaves the slice above `level` or `max_steps` steps were
+    /// taken.
+    fn step_out(
+        &self,
+        dim: i64,
+        mut bound: Tensor,
+        level: &Tensor,
+        mut max_steps: Tensor,
+        step: f64,
+    ) -> Tensor {
+        loop {
+            let active = max_steps
+                .gt(0.0)
+                .logical_and(&self.log_prob_at(dim, &bound).gt_tensor(level));
+            if !bool::from(active.any()) {
+                return bound;
+            }
+            let active = active.to_kind(bound.kind());
+            bound += &active * step;
+            max_steps -= active;
+        }
+    }
+
+    fn update(&mut self, dim: i64) {
+        let x = self.position.select(1, dim);
+        let level = &self.value + self.value.rand_like().log();
+        let low = &x - x.rand_like() * self.width;
+        let high = &low + self.width;
+
+        // Steps out while the ends are inside the slice, splitting the maximum number of steps
+        // randomly between both ends to keep the chains reversible.
+        let steps_low = (x.rand_like() * self.max_steps_out as f64).floor();
+        let steps_high = (self.max_steps_out - 1) as f64 - &steps_low;
+        let mut low = self.step_out(dim, low, &level, steps_low, -self.width);
+        let mut high = self.step_out(dim, high, &level, steps_high, self.width);
+
+        // Shrinks the interval towards the current state until a point inside the slice is hit.
+        let mut accepted = x.zeros_like().to_kind(Kind::Bool);
+        let mut next = x.shallow_clone();
+        let mut value = self.value.shallow_clone();
+        while !bool::from(accepted.all()) {
+            let proposal = &low + (&high - &low) * x.rand_like();
+            let proposal_value = self.log_prob_at(dim, &proposal);
+            let hit = proposal_value
+                .gt_tensor(&level)
+                .logical_and(&accepted.logical_not());
+            next = proposal.where_self(&hit, &next);
+            value = proposal_value.where_self(&hit, &value);
+            accepted = accepted.logical_or(&hit);
+            let missed = hit.logical_not();
+            let below = proposal.lt_tensor(&x);
+            low = proposal.where_self(&below.logical_and(&missed), &low);
+            high = proposal.where_self(&below.logical_not().logical_and(&missed), &high);
+        }
+
+        let index = Tensor::of_slice(&[dim]).to_device(x.device());
+        self.position = self.position.index_copy(1, &index, &next.unsqueeze(1));
+        self.value = value;
+    }
+
+    /// Takes `n_samples` transitions and returns the visited states, stacked into a
+    /// `[n_samples, n_chains, dim]` shaped tensor.
+    pub fn sample(&mut self, n_samples: i64) -> Tensor {
+        let samples: Vec<Tensor> = (0..n_samples).map(|_| self.step().copy()).collect();
+        Tensor::stack(&samples, 0)
+    }
+}
+
+impl<F> fmt::Debug for SliceSampler<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SliceSampler")
+            .field("width", &self.width)
+            .field("max_steps_out", &self.max_steps_out)
+            .field("position", &self.position)
+            .finish()
+    }
+}
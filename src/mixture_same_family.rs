@@ -0,0 +1,138 @@
+use crate::{constraints::Constraint, numerics, Categorical, Distribution};
+use tch::Tensor;
+
+/// A mixture of distributions of the same family.
+///
+/// The rightmost batch dimension of `components` indexes the components, which are selected
+/// with the probabilities of `mixture`. A batch of univariate Normals of batch shape `[k]`
+/// e.g. becomes a Gaussian mixture with `k` components.
+#[derive(Debug)]
+pub struct MixtureSameFamily<D: Distribution> {
+    mixture: Categorical,
+    components: D,
+    batch_shape: Vec<i64>,
+}
+
+impl<D: Distribution> MixtureSameFamily<D> {
+    /// Creates a mixture of `components` with the mixing distribution `mixture`. The batch
+    /// shape of `components` must be the batch shape of `mixture` followed by its number of
+    /// categories.
+    pub fn new(mixture: Categorical, components: D) -> Self {
+        let num_events = *mixture.probs().size().last().unwrap();
+        let expected = [mixture.batch_shape(), &[num_events]].concat();
+        assert_eq!(
+            components.batch_shape(),
+            expected.as_slice(),
+            "components of batch shape {:?} do not match a mixture of {} categories and batch \
+             shape {:?}",
+            components.batch_shape(),
+            num_events,
+            mixture.batch_shape()
+        );
+        let batch_shape = mixture.batch_shape().to_vec();
+        Self {
+            mixture,
+            components,
+            batch_shape,
+        }
+    }
+
+    /// Returns the mixing distribution.
+    pub fn mixture(&self) -> &Categorical {
+        &self.mixture
+    }
+
+    /// Returns the components.
+    pub fn components(&self) -> &D {
+        &self.components
+    }
+
+    /// Returns the dimension indexing the components in tensors shaped like the batch and
+    /// event shapes of the components.
+    fn component_dim(&self) -> i64 {
+        -1 - self.components.event_shape().len() as i64
+    }
+
+    /// Returns the mixture probabilities, broadcastable to the batch and event shapes of the
+    /// components.
+    fn padded_probs(&self) -> Tensor {
+        let mut probs = self.mixture.probs().shallow_clone();
+        for _ in self.components.event_shape() {
+            probs = probs.unsqueeze(-1);
+        }
+        probs
+    }
+
+    /// Returns the log-probabilities of `val` under each of the components, with the
+    /// components along the last dimension.
+    pub fn component_log_prob(&self, val: &Tensor) -> Tensor {
+        self.components
+            .log_prob(&val.unsqueeze(self.component_dim()))
+    }
+}
+
+impl<D: Distribution> Distribution for MixtureSameFamily<D> {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        let mut params = vec![("mixture_logits", self.mixture.logits())];
+        params.extend(self.components.params());
+        params
+    }
+
+    fn support(&self) -> Constraint {
+        self.components.support()
+    }
+
+    fn mean(&self) -> Tensor {
+        let means = self.components.mean();
+        (self.padded_probs() * means).sum_dim_intlist(
+            &[self.component_dim()],
+            false,
+            self.mixture.probs().kind(),
+        )
+    }
+
+    fn variance(&self) -> Tensor {
+        let dim = self.component_dim();
+        let kind = self.mixture.probs().kind();
+        let probs = self.padded_probs();
+        let means = self.components.mean();
+        let mean = (&probs * &means).sum_dim_intlist(&[dim], true, kind);
+        let mean_variance =
+            (&probs * self.components.variance()).sum_dim_intlist(&[dim], false, kind);
+        let variance_mean =
+            (probs * (means - mean).pow_tensor_scalar(2)).sum_dim_intlist(&[dim], false, kind);
+        mean_variance + variance_mean
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        numerics::logsumexp(
+            &(self.component_log_prob(val) + self.mixture.logits()),
+            &[-1],
+            false,
+        )
+    }
+
+    fn sample(&self, shape: &[i64]) -> Tensor {
+        tch::no_grad(|| {
+            let dim = self.component_dim();
+            let samples = self.components.sample(shape);
+            let mut index = self.mixture.sample(shape).unsqueeze(-1);
+            for _ in self.components.event_shape() {
+                index = index.unsqueeze(-1);
+            }
+            let mut index_shape = samples.size();
+            index_shape[(samples.dim() as i64 + dim) as usize] = 1;
+            samples
+                .gather(dim, &index.expand(&index_shape, false), false)
+                .squeeze_dim(dim)
+        })
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+
+    fn event_shape(&self) -> &[i64] {
+        self.components.event_shape()
+    }
+}
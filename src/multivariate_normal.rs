@@ -1,5 +1,14 @@
-use crate::{utils::standard_normal, Distribution};
+use crate::{
+    constraints::Constraint,
+    persist::{param, Persist},
+    trace,
+    utils::{broadcast_shapes, debug_assert_same_device, params_match, standard_normal},
+    Distribution, Error, KullackLeiberDivergence, Reparameterize,
+};
 
+use std::borrow::Borrow;
+use std::cell::OnceCell;
+use std::collections::HashMap;
 use std::f64::consts::PI;
 use tch::{
     Kind::{Double, Float},
@@ -7,114 +16,291 @@ use tch::{
 };
 
 /// A Multivariate Normal distribution.
+///
+/// Whichever parameterization is given, the Cholesky factor of the covariance matrix is
+/// computed once at construction and all methods work off it. The covariance and precision
+/// matrices are derived from the factor on first use and cached.
+///
+/// A distribution created with [`MultivariateNormal::from_diag`] additionally keeps the
+/// diagonal of the factor and evaluates densities, samples and divergences elementwise,
+/// without triangular solves.
 #[derive(Debug)]
 pub struct MultivariateNormal {
     mean: Tensor,
     scale_tril: Tensor,
-    cov: Tensor,
+    scale_diag: Option<Tensor>,
+    cov: OnceCell<Tensor>,
+    precision: OnceCell<Tensor>,
+    half_log_det: Tensor,
     batch_shape: Vec<i64>,
     event_shape: Vec<i64>,
 }
 
-fn split_shapes(shape: &[i64]) -> (Vec<i64>, Vec<i64>) {
-    match shape.split_last() {
-        Some((last, before)) => (vec![*last], before.to_vec()),
-        None => (vec![1], vec![0]),
+/// Returns half the log-determinant of the covariance matrix `scale_tril * scale_tril^T`.
+fn half_log_det(scale_tril: &Tensor) -> Tensor {
+    scale_tril
+        .diagonal(0, -2, -1)
+        .log()
+        .sum_dim_intlist(&[-1], false, Double)
+}
+
+/// Broadcasts the batch dimensions of `mean` against those of the parameter `name`, whose
+/// `param_dims` rightmost dimensions must have the size of the event like in PyTorch. Returns
+/// both expanded to the common batch shape, followed by the batch and event shapes.
+fn broadcast_params(
+    mean: &Tensor,
+    param: &Tensor,
+    name: &str,
+    param_dims: usize,
+) -> Result<(Tensor, Tensor, Vec<i64>, Vec<i64>), Error> {
+    let mean_size = mean.size();
+    let param_size = param.size();
+    let (&n, mean_batch) = mean_size
+        .split_last()
+        .ok_or_else(|| Error::Shape("mean must be at least one-dimensional".to_string()))?;
+    if param_size.len() < param_dims {
+        return Err(Error::Shape(format!(
+            "{} must be at least {}-dimensional, got shape {:?}",
+            name, param_dims, param_size
+        )));
     }
+    let (param_batch, param_event) = param_size.split_at(param_size.len() - param_dims);
+    if param_event.iter().any(|&s| s != n) {
+        return Err(Error::Shape(format!(
+            "{} of shape {:?} does not match the event shape [{}] of mean",
+            name, param_size, n
+        )));
+    }
+    let batch_shape = broadcast_shapes(mean_batch, param_batch).ok_or_else(|| {
+        Error::Shape(format!(
+            "the batch shapes {:?} of mean and {:?} of {} cannot be broadcast",
+            mean_batch, param_batch, name
+        ))
+    })?;
+    let mean = mean.expand(&[&batch_shape[..], &[n]].concat(), false);
+    let param = param.expand(&[&batch_shape[..], param_event].concat(), false);
+    Ok((mean, param, batch_shape, vec![n]))
 }
 
 impl MultivariateNormal {
     /// Creates a Multivariate Normal distribution with `mean` and covariance matrix `cov`.
-    pub fn from_cov(mean: Tensor, cov: Tensor) -> Self {
-        let mean_ = mean.unsqueeze(-1);
-        let cov_mean = Tensor::broadcast_tensors(&[cov.copy(), mean_]);
-        let mean_size = cov_mean[1]
-            .size()
-            .split_last()
-            .map(|(_, before)| before.to_vec())
-            .unwrap_or_else(Vec::new);
-        let (event_shape, batch_shape) = split_shapes(&mean_size);
-        Self {
-            mean: cov_mean[1].mean_dim(&[-1], false, cov_mean[1].kind()),
-            scale_tril: cov.cholesky(false),
-            cov: cov_mean[0].copy(),
+    pub fn from_cov(mean: impl Borrow<Tensor>, cov: impl Borrow<Tensor>) -> Self {
+        Self::try_from_cov(mean, cov).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a Multivariate Normal distribution like `from_cov`, returning an error instead
+    /// of panicking when the shapes of the parameters do not match or the Cholesky
+    /// decomposition fails.
+    pub fn try_from_cov(
+        mean: impl Borrow<Tensor>,
+        cov: impl Borrow<Tensor>,
+    ) -> Result<Self, Error> {
+        let mean = mean.borrow().shallow_clone();
+        let cov = cov.borrow().shallow_clone();
+        debug_assert_same_device(&[&mean, &cov]);
+        trace::construct("MultivariateNormal", &[("mean", &mean), ("cov", &cov)]);
+        let (mean, cov, batch_shape, event_shape) = broadcast_params(&mean, &cov, "cov", 2)?;
+        let scale_tril = cov.f_cholesky(false)?;
+        Ok(Self {
+            mean,
+            half_log_det: half_log_det(&scale_tril),
+            scale_tril,
+            scale_diag: None,
+            cov: OnceCell::from(cov),
+            precision: OnceCell::new(),
             batch_shape,
             event_shape,
-        }
+        })
     }
 
     /// Creates a Multivariate Normal distribution with `mean` and precision matrix `precision`.
-    pub fn from_precision(mean: Tensor, precision: Tensor) -> Self {
-        let mean_ = mean.unsqueeze(-1);
-        let precision_mean = Tensor::broadcast_tensors(&[precision.copy(), mean_]);
-        let mean_size = precision_mean[1]
-            .size()
-            .split_last()
-            .map(|(_, before)| before.to_vec())
-            .unwrap_or_else(Vec::new);
-        let scale_tril = precision_to_scale_tril(&precision);
-        let (event_shape, batch_shape) = split_shapes(&mean_size);
-        let cov = scale_tril.matmul(&scale_tril.transpose(-1, -2)).expand(
-            &[
-                batch_shape.as_slice(),
-                event_shape.as_slice(),
-                event_shape.as_slice(),
-            ]
-            .concat(),
-            true,
+    pub fn from_precision(mean: impl Borrow<Tensor>, precision: impl Borrow<Tensor>) -> Self {
+        Self::try_from_precision(mean, precision).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a Multivariate Normal distribution like `from_precision`, returning an error
+    /// instead of panicking when the shapes of the parameters do not match or the Cholesky
+    /// decomposition fails.
+    pub fn try_from_precision(
+        mean: impl Borrow<Tensor>,
+        precision: impl Borrow<Tensor>,
+    ) -> Result<Self, Error> {
+        let mean = mean.borrow().shallow_clone();
+        let precision = precision.borrow().shallow_clone();
+        debug_assert_same_device(&[&mean, &precision]);
+        trace::construct(
+            "MultivariateNormal",
+            &[("mean", &mean), ("precision", &precision)],
         );
-        Self {
-            mean: precision_mean[1].mean_dim(&[-1], false, precision_mean[1].kind()),
+        let (mean, precision, batch_shape, event_shape) =
+            broadcast_params(&mean, &precision, "precision", 2)?;
+        let scale_tril = precision_to_scale_tril(&precision)?;
+        Ok(Self {
+            mean,
+            half_log_det: half_log_det(&scale_tril),
             scale_tril,
-            cov,
+            scale_diag: None,
+            cov: OnceCell::new(),
+            precision: OnceCell::from(precision),
             batch_shape,
             event_shape,
-        }
+        })
     }
 
     /// Creates a Multivariate Normal distribution with `mean` and scale tril matrix `scale_tril`.
-    pub fn from_scale_tril(mean: Tensor, scale_tril: Tensor) -> Self {
-        let mean_ = mean.unsqueeze(-1);
-        let scale_tril_mean = Tensor::broadcast_tensors(&[scale_tril.copy(), mean_]);
-        let mean_size = scale_tril_mean[1]
-            .size()
-            .split_last()
-            .map(|(_, before)| before.to_vec())
-            .unwrap_or_else(Vec::new);
-        let (event_shape, batch_shape) = split_shapes(&mean_size);
-        let cov = scale_tril.matmul(&scale_tril.transpose(-1, -2)).expand(
-            &[
-                batch_shape.as_slice(),
-                event_shape.as_slice(),
-                &event_shape.as_slice(),
-            ]
-            .concat(),
-            true,
+    pub fn from_scale_tril(mean: impl Borrow<Tensor>, scale_tril: impl Borrow<Tensor>) -> Self {
+        Self::try_from_scale_tril(mean, scale_tril).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a Multivariate Normal distribution like `from_scale_tril`, returning an error
+    /// instead of panicking when the shapes of the parameters do not match.
+    pub fn try_from_scale_tril(
+        mean: impl Borrow<Tensor>,
+        scale_tril: impl Borrow<Tensor>,
+    ) -> Result<Self, Error> {
+        let mean = mean.borrow().shallow_clone();
+        let scale_tril = scale_tril.borrow().shallow_clone();
+        debug_assert_same_device(&[&mean, &scale_tril]);
+        trace::construct(
+            "MultivariateNormal",
+            &[("mean", &mean), ("scale_tril", &scale_tril)],
+        );
+        let (mean, scale_tril, batch_shape, event_shape) =
+            broadcast_params(&mean, &scale_tril, "scale_tril", 2)?;
+        Ok(Self {
+            mean,
+            half_log_det: half_log_det(&scale_tril),
+            scale_tril,
+            scale_diag: None,
+            cov: OnceCell::new(),
+            precision: OnceCell::new(),
+            batch_shape,
+            event_shape,
+        })
+    }
+
+    /// Creates a Multivariate Normal distribution with `mean` and a diagonal covariance matrix
+    /// whose diagonal is `variance`.
+    pub fn from_diag(mean: impl Borrow<Tensor>, variance: impl Borrow<Tensor>) -> Self {
+        Self::try_from_diag(mean, variance).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Creates a Multivariate Normal distribution like `from_diag`, returning an error instead
+    /// of panicking when the shapes of the parameters do not match.
+    pub fn try_from_diag(
+        mean: impl Borrow<Tensor>,
+        variance: impl Borrow<Tensor>,
+    ) -> Result<Self, Error> {
+        let mean = mean.borrow().shallow_clone();
+        let variance = variance.borrow().shallow_clone();
+        debug_assert_same_device(&[&mean, &variance]);
+        trace::construct(
+            "MultivariateNormal",
+            &[("mean", &mean), ("variance", &variance)],
         );
-        Self {
-            mean: scale_tril_mean[1].mean_dim(&[-1], false, scale_tril_mean[1].kind()),
-            scale_tril: scale_tril_mean[0].copy(),
-            cov,
+        let (mean, variance, batch_shape, event_shape) =
+            broadcast_params(&mean, &variance, "variance", 1)?;
+        let scale_diag = variance.sqrt();
+        Ok(Self {
+            mean,
+            half_log_det: scale_diag.log().sum_dim_intlist(&[-1], false, Double),
+            scale_tril: scale_diag.diag_embed(0, -2, -1),
+            scale_diag: Some(scale_diag),
+            cov: OnceCell::new(),
+            precision: OnceCell::new(),
             batch_shape,
             event_shape,
+        })
+    }
+
+    /// Returns the lower triangular Cholesky factor of the covariance matrix.
+    pub fn scale_tril(&self) -> &Tensor {
+        &self.scale_tril
+    }
+
+    /// Returns the covariance matrix of the distribution.
+    pub fn covariance_matrix(&self) -> &Tensor {
+        self.cov.get_or_init(|| {
+            if let Some(scale_diag) = &self.scale_diag {
+                return scale_diag.pow_tensor_scalar(2).diag_embed(0, -2, -1);
+            }
+            self.scale_tril
+                .matmul(&self.scale_tril.transpose(-1, -2))
+                .expand(&self.matrix_shape(), true)
+        })
+    }
+
+    /// Returns the precision matrix of the distribution.
+    pub fn precision_matrix(&self) -> &Tensor {
+        self.precision.get_or_init(|| {
+            if let Some(scale_diag) = &self.scale_diag {
+                return scale_diag.pow_tensor_scalar(-2).diag_embed(0, -2, -1);
+            }
+            self.scale_tril
+                .cholesky_inverse(false)
+                .expand(&self.matrix_shape(), true)
+        })
+    }
+
+    /// Returns true if the distribution was created with a diagonal covariance matrix.
+    pub fn is_diagonal(&self) -> bool {
+        self.scale_diag.is_some()
+    }
+
+    /// Multiplies the trailing event dimension of `eps` with the Cholesky factor.
+    fn scale(&self, eps: &Tensor) -> Tensor {
+        match &self.scale_diag {
+            Some(scale_diag) => eps * scale_diag,
+            None => self.scale_tril.matmul(&eps.unsqueeze(-1)).squeeze_dim(-1),
         }
     }
 
-    pub fn rsample(&self, shape: &[i64]) -> Tensor {
-        let shape = self.extended_shape(shape);
-        let eps = standard_normal(&shape, self.mean.kind(), self.mean.device());
-        &self.mean + &self.scale_tril.matmul(&eps.unsqueeze(-1)).squeeze_dim(-1)
+    /// Returns the squared Mahalanobis distance of `diff` under the covariance matrix.
+    fn mahalanobis(&self, diff: &Tensor) -> Tensor {
+        match &self.scale_diag {
+            Some(scale_diag) => {
+                (diff / scale_diag)
+                    .pow_tensor_scalar(2)
+                    .sum_dim_intlist(&[-1], false, Double)
+            }
+            None => batch_mahalanobis(&self.scale_tril, diff).totype(Double),
+        }
+    }
+
+    fn matrix_shape(&self) -> Vec<i64> {
+        [
+            self.batch_shape.as_slice(),
+            self.event_shape.as_slice(),
+            self.event_shape.as_slice(),
+        ]
+        .concat()
+    }
+}
+
+impl PartialEq for MultivariateNormal {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
     }
 }
 
 impl Distribution for MultivariateNormal {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("mean", &self.mean), ("scale_tril", &self.scale_tril)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("mean", Constraint::independent(Constraint::Real, 1)),
+            ("scale_tril", Constraint::LowerCholesky),
+        ]
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::independent(Constraint::Real, 1)
+    }
+
     fn entropy(&self) -> Tensor {
-        let half_log_det =
-            self.scale_tril
-                .diagonal(0, -2, -1)
-                .log()
-                .sum_dim_intlist(&[-1], true, Double);
-        let h = (0.5 * self.event_shape[0] as f64) * (1.0 + (2.0 * PI).ln()) + half_log_det;
+        let h = (0.5 * self.event_shape[0] as f64) * (1.0 + (2.0 * PI).ln()) + &self.half_log_det;
         if self.batch_shape.is_empty() {
             h
         } else {
@@ -122,15 +308,42 @@ impl Distribution for MultivariateNormal {
         }
     }
 
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        let shape = self.extended_shape(shape);
+        let eps = standard_normal(&shape, self.mean.kind(), self.mean.device());
+        &self.mean + self.scale(&eps)
+    }
+
+    fn sample_antithetic(&self, shape: &[i64]) -> (Tensor, Tensor) {
+        let shape = self.extended_shape(shape);
+        tch::no_grad(|| {
+            let eps = standard_normal(&shape, self.mean.kind(), self.mean.device());
+            let eps = self.scale(&eps);
+            (&self.mean + &eps, &self.mean - eps)
+        })
+    }
+
+    fn mean(&self) -> Tensor {
+        self.mean.shallow_clone()
+    }
+
+    fn variance(&self) -> Tensor {
+        if let Some(scale_diag) = &self.scale_diag {
+            return scale_diag.pow_tensor_scalar(2);
+        }
+        self.scale_tril
+            .pow_tensor_scalar(2)
+            .sum_dim_intlist(&[-1], false, self.scale_tril.kind())
+            .expand(&self.extended_shape(&[]), true)
+    }
+
     fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
         let diff = val - &self.mean;
-        let m = batch_mahalanobis(&self.scale_tril, &diff).totype(Double);
-        let half_log_det =
-            self.scale_tril
-                .diagonal(0, -2, -1)
-                .log()
-                .sum_dim_intlist(&[-1], true, Double);
-        -0.5 * (self.event_shape[0] as f64 * (2.0 * PI).ln() + m) - half_log_det
+        let m = self.mahalanobis(&diff);
+        -0.5 * (self.event_shape[0] as f64 * (2.0 * PI).ln() + m) - &self.half_log_det
     }
 
     fn batch_shape(&self) -> &[i64] {
@@ -142,15 +355,57 @@ impl Distribution for MultivariateNormal {
     }
 }
 
-fn precision_to_scale_tril(precision_matrix: &Tensor) -> Tensor {
-    let l_f = precision_matrix.flip(&[-2, -1]).cholesky(false);
+impl Reparameterize for MultivariateNormal {
+    fn noise(&self, shape: &[i64]) -> Tensor {
+        standard_normal(
+            &self.extended_shape(shape),
+            self.mean.kind(),
+            self.mean.device(),
+        )
+    }
+
+    fn fill_noise(&self, noise: &mut Tensor) {
+        let _ = noise.normal_(0.0, 1.0);
+    }
+
+    fn reparameterize(&self, noise: &Tensor) -> Tensor {
+        &self.mean + self.scale(noise)
+    }
+}
+
+impl KullackLeiberDivergence<Self> for MultivariateNormal {
+    fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
+        let n = self.event_shape[0];
+        let half_term1 = &other.half_log_det - &self.half_log_det;
+        let term3 = other.mahalanobis(&(&other.mean - &self.mean));
+        if let (Some(self_diag), Some(other_diag)) = (&self.scale_diag, &other.scale_diag) {
+            let term2 = (self_diag / other_diag)
+                .pow_tensor_scalar(2)
+                .sum_dim_intlist(&[-1], false, Double);
+            return half_term1 + (term2 + term3 - n as f64) * 0.5;
+        }
+        let scale_trils = Tensor::broadcast_tensors(&[
+            other.scale_tril.shallow_clone(),
+            self.scale_tril.shallow_clone(),
+        ]);
+        let (solved, _) = scale_trils[1].triangular_solve(&scale_trils[0], false, false, false);
+        let term2 = solved
+            .pow_tensor_scalar(2)
+            .sum_dim_intlist(&[-2, -1], false, Double);
+        half_term1 + (term2 + term3 - n as f64) * 0.5
+    }
+}
+
+fn precision_to_scale_tril(precision_matrix: &Tensor) -> Result<Tensor, Error> {
+    let l_f = precision_matrix.flip(&[-2, -1]).f_cholesky(false)?;
     let l_inv = l_f.flip(&[-2, -1]).transpose(-2, -1);
     let (l, _) = Tensor::eye(
         *precision_matrix.size().last().unwrap(),
         (precision_matrix.kind(), precision_matrix.device()),
     )
     .triangular_solve(&l_inv, false, false, false);
-    l
+    Ok(l)
 }
 
 fn batch_mahalanobis(b_l: &Tensor, b_x: &Tensor) -> Tensor {
@@ -210,3 +465,14 @@ fn batch_mahalanobis(b_l: &Tensor, b_x: &Tensor) -> Tensor {
     let reshaped_m = permuted_m.permute(&permute_inv_dims);
     reshaped_m.reshape(&b_x_batch_shape)
 }
+
+impl Persist for MultivariateNormal {
+    const FAMILY: &'static str = "multivariate_normal";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::from_scale_tril(
+            param(params, "mean")?,
+            param(params, "scale_tril")?,
+        ))
+    }
+}
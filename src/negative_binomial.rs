@@ -0,0 +1,198 @@
+use crate::{
+    constraints::Constraint,
+    numerics,
+    persist::{param, Persist},
+    trace,
+    utils::{copy_cell, float_values, logits_to_probs, params_match, stack_param},
+    Distribution, Error, Stack,
+};
+use std::borrow::Borrow;
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use tch::Tensor;
+
+/// A Negative Binomial distribution counting the successes of independent Bernoulli trials
+/// before `total_count` failures.
+///
+/// Only the parameterization of the success probability given at construction is stored; the
+/// other one is computed on first use and cached.
+#[derive(Debug)]
+pub struct NegativeBinomial {
+    total_count: Tensor,
+    probs: OnceCell<Tensor>,
+    logits: OnceCell<Tensor>,
+    batch_shape: Vec<i64>,
+}
+
+impl Clone for NegativeBinomial {
+    fn clone(&self) -> Self {
+        Self {
+            total_count: self.total_count.copy(),
+            probs: copy_cell(&self.probs),
+            logits: copy_cell(&self.logits),
+            batch_shape: self.batch_shape.clone(),
+        }
+    }
+}
+
+impl NegativeBinomial {
+    /// Creates a Negative Binomial distribution with `total_count` failures from success
+    /// probabilities.
+    pub fn from_probs(total_count: impl Borrow<Tensor>, probs: impl Borrow<Tensor>) -> Self {
+        let [total_count, probs]: [Tensor; 2] =
+            Tensor::broadcast_tensors(&[total_count.borrow(), probs.borrow()])
+                .try_into()
+                .unwrap();
+        trace::construct(
+            "NegativeBinomial",
+            &[("total_count", &total_count), ("probs", &probs)],
+        );
+        Self {
+            batch_shape: probs.size(),
+            total_count,
+            probs: OnceCell::from(probs),
+            logits: OnceCell::new(),
+        }
+    }
+
+    /// Creates a Negative Binomial distribution with `total_count` failures from success
+    /// logits.
+    pub fn from_logits(total_count: impl Borrow<Tensor>, logits: impl Borrow<Tensor>) -> Self {
+        let [total_count, logits]: [Tensor; 2] =
+            Tensor::broadcast_tensors(&[total_count.borrow(), logits.borrow()])
+                .try_into()
+                .unwrap();
+        trace::construct(
+            "NegativeBinomial",
+            &[("total_count", &total_count), ("logits", &logits)],
+        );
+        Self {
+            batch_shape: logits.size(),
+            total_count,
+            probs: OnceCell::new(),
+            logits: OnceCell::from(logits),
+        }
+    }
+
+    /// Returns the number of failures.
+    pub fn total_count(&self) -> &Tensor {
+        &self.total_count
+    }
+
+    /// Returns the success probabilities of the distribution.
+    pub fn probs(&self) -> &Tensor {
+        self.probs
+            .get_or_init(|| logits_to_probs(self.logits.get().unwrap(), true))
+    }
+
+    /// Returns the success logits of the distribution.
+    pub fn logits(&self) -> &Tensor {
+        self.logits
+            .get_or_init(|| numerics::logit(self.probs.get().unwrap()))
+    }
+}
+
+impl PartialEq for NegativeBinomial {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
+}
+
+impl Distribution for NegativeBinomial {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![
+            ("total_count", &self.total_count),
+            ("probs", self.probs()),
+            ("logits", self.logits()),
+        ]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("total_count", Constraint::Nonnegative),
+            ("probs", Constraint::UnitInterval),
+            ("logits", Constraint::Real),
+        ]
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        let val = &float_values(val, self.logits());
+        let r = &self.total_count;
+        let log_unnormalized =
+            r * (-self.logits()).log_sigmoid() + val * self.logits().log_sigmoid();
+        let log_normalization = (val + 1.0).lgamma() + r.lgamma() - (r + val).lgamma();
+        // The normalization vanishes for the point mass at zero of zero failures.
+        let log_normalization = log_normalization.where_self(
+            &(r + val).f_ne(0.0).unwrap(),
+            &log_normalization.zeros_like(),
+        );
+        log_unnormalized - log_normalization
+    }
+
+    /// Returns `P(X <= val)`, which is `I_{1 - p}(r, k + 1)` for `k = floor(val)`.
+    fn cdf(&self, val: &Tensor) -> Tensor {
+        self.validate_sample(val);
+        let k = val.to_kind(self.probs().kind()).floor();
+        let cdf = numerics::betainc(&self.total_count, &(&k + 1.0), &(1.0 - self.probs()));
+        cdf.where_self(&k.ge(0.0), &cdf.zeros_like())
+    }
+
+    fn sample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        let shape = self.extended_shape(shape);
+        // A Poisson mixture over Gamma distributed rates.
+        tch::no_grad(|| {
+            let rate = self
+                .total_count
+                .expand(&shape, false)
+                .internal_standard_gamma()
+                * self.logits().exp();
+            rate.poisson()
+        })
+    }
+
+    fn mean(&self) -> Tensor {
+        &self.total_count * self.logits().exp()
+    }
+
+    fn variance(&self) -> Tensor {
+        self.mean() / (-self.logits()).sigmoid()
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::nonnegative_integer()
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (
+            self.total_count.zeros_like(),
+            self.total_count.full_like(f64::INFINITY),
+        )
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+}
+
+impl Stack for NegativeBinomial {
+    fn stack(dists: &[Self]) -> Self {
+        Self::from_probs(
+            stack_param(dists, Self::total_count),
+            stack_param(dists, Self::probs),
+        )
+    }
+}
+
+impl Persist for NegativeBinomial {
+    const FAMILY: &'static str = "negative_binomial";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::from_logits(
+            param(params, "total_count")?,
+            param(params, "logits")?,
+        ))
+    }
+}
@@ -0,0 +1,163 @@
+//! Neural network heads whose outputs are distributions.
+//!
+//! Each head wraps `tch::nn` layers mapping a batch of features to the raw parameters of a
+//! distribution, and maps the raw outputs into the constrained parameter space.
+use crate::{utils::eps, Categorical, Distribution, Independent, MixtureSameFamily, Normal};
+use tch::{nn, Kind, Tensor};
+
+/// A layer mapping features to a distribution.
+pub trait Head {
+    /// The distribution returned by the head.
+    type Output: Distribution;
+
+    /// Returns the distribution for the `[batch..., in_dim]` shaped features `xs`.
+    fn forward(&self, xs: &Tensor) -> Self::Output;
+}
+
+/// A head returning `Normal` distributions of batch shape `[batch..., out_dim]`.
+///
+/// The means are the outputs of a linear layer, the standard deviations the softplus of the
+/// outputs of another one.
+#[derive(Debug)]
+pub struct NormalHead {
+    mean: nn::Linear,
+    stddev: nn::Linear,
+}
+
+impl NormalHead {
+    /// Creates a head with variables in `path` mapping `in_dim` features to `out_dim`
+    /// independent Normals.
+    pub fn new(path: &nn::Path, in_dim: i64, out_dim: i64) -> Self {
+        Self {
+            mean: nn::linear(path / "mean", in_dim, out_dim, Default::default()),
+            stddev: nn::linear(path / "stddev", in_dim, out_dim, Default::default()),
+        }
+    }
+}
+
+impl Head for NormalHead {
+    type Output = Normal;
+
+    fn forward(&self, xs: &Tensor) -> Normal {
+        let stddev = xs.apply(&self.stddev).softplus();
+        let stddev = &stddev + eps(stddev.kind()).unwrap();
+        Normal::new(xs.apply(&self.mean), stddev)
+    }
+}
+
+/// A head returning `Categorical` distributions over `n_classes` classes of batch shape
+/// `[batch...]`, with the outputs of a linear layer as logits.
+#[derive(Debug)]
+pub struct CategoricalHead {
+    logits: nn::Linear,
+}
+
+impl CategoricalHead {
+    /// Creates a head with variables in `path` mapping `in_dim` features to the logits of
+    /// `n_classes` classes.
+    pub fn new(path: &nn::Path, in_dim: i64, n_classes: i64) -> Self {
+        Self {
+            logits: nn::linear(path / "logits", in_dim, n_classes, Default::default()),
+        }
+    }
+}
+
+impl Head for CategoricalHead {
+    type Output = Categorical;
+
+    fn forward(&self, xs: &Tensor) -> Categorical {
+        Categorical::from_logits(xs.apply(&self.logits))
+    }
+}
+
+/// A head returning mixtures of the distributions of another head.
+///
+/// The component head must return distributions of batch shape `[batch..., n_components]`,
+/// e.g. a `NormalHead` with `n_components` outputs for a univariate Gaussian mixture. The
+/// mixture weights are the softmax of the outputs of a linear layer.
+#[derive(Debug)]
+pub struct MixtureHead<H: Head> {
+    logits: nn::Linear,
+    components: H,
+}
+
+impl<H: Head> MixtureHead<H> {
+    /// Creates a head with variables in `path` mixing the `n_components` components returned
+    /// by `components` with weights computed from `in_dim` features.
+    pub fn new(path: &nn::Path, in_dim: i64, n_components: i64, components: H) -> Self {
+        Self {
+            logits: nn::linear(path / "logits", in_dim, n_components, Default::default()),
+            components,
+        }
+    }
+
+    /// Returns the component head.
+    pub fn components(&self) -> &H {
+        &self.components
+    }
+}
+
+impl<H: Head> Head for MixtureHead<H> {
+    type Output = MixtureSameFamily<H::Output>;
+
+    fn forward(&self, xs: &Tensor) -> Self::Output {
+        MixtureSameFamily::new(
+            Categorical::from_logits(xs.apply(&self.logits)),
+            self.components.forward(xs),
+        )
+    }
+}
+
+/// A mixture density network head returning mixtures of `n_components` Normals with
+/// diagonal covariance over `out_dim` outputs, of batch shape `[batch...]` and event shape
+/// `[out_dim]`.
+///
+/// The mixture weights are the softmax of the outputs of a linear layer, and every component
+/// has its own means and softplus standard deviations.
+#[derive(Debug)]
+pub struct MdnHead {
+    logits: nn::Linear,
+    mean: nn::Linear,
+    stddev: nn::Linear,
+    n_components: i64,
+    out_dim: i64,
+}
+
+impl MdnHead {
+    /// Creates a head with variables in `path` mapping `in_dim` features to mixtures of
+    /// `n_components` components over `out_dim` outputs.
+    pub fn new(path: &nn::Path, in_dim: i64, n_components: i64, out_dim: i64) -> Self {
+        let params = n_components * out_dim;
+        Self {
+            logits: nn::linear(path / "logits", in_dim, n_components, Default::default()),
+            mean: nn::linear(path / "mean", in_dim, params, Default::default()),
+            stddev: nn::linear(path / "stddev", in_dim, params, Default::default()),
+            n_components,
+            out_dim,
+        }
+    }
+
+    /// Returns the mean negative log-likelihood of the `[batch..., out_dim]` shaped targets
+    /// `ys` given the features `xs`. The mixture weights are normalized in log space and the
+    /// components are combined with `logsumexp`, so the loss stays finite even when all
+    /// components assign vanishing densities to a target.
+    pub fn nll(&self, xs: &Tensor, ys: &Tensor) -> Tensor {
+        -self.forward(xs).log_prob(ys).mean(Kind::Float)
+    }
+}
+
+impl Head for MdnHead {
+    type Output = MixtureSameFamily<Independent<Normal>>;
+
+    fn forward(&self, xs: &Tensor) -> Self::Output {
+        let size = xs.size();
+        let shape = [&size[..size.len() - 1], &[self.n_components, self.out_dim]].concat();
+        let mean = xs.apply(&self.mean).reshape(&shape);
+        let stddev = xs.apply(&self.stddev).reshape(&shape).softplus();
+        let stddev = &stddev + eps(stddev.kind()).unwrap();
+        MixtureSameFamily::new(
+            Categorical::from_logits(xs.apply(&self.logits)),
+            Independent::new(Normal::new(mean, stddev), 1),
+        )
+    }
+}
@@ -1,12 +1,22 @@
-use crate::{utils::standard_normal, Distribution, KullackLeiberDivergence};
+use crate::{
+    constraints::Constraint,
+    error::check_fit_data,
+    persist::{param, Persist},
+    trace,
+    utils::{debug_assert_same_device, params_match, sample_moments, stack_param, standard_normal},
+    Distribution, Error, KullackLeiberDivergence, Reparameterize, Stack, Trainable,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
 use std::f64::consts::PI;
-use tch::Tensor;
+use tch::{nn, Kind, Tensor};
 
 /// A Guassian distribution.
 #[derive(Debug)]
 pub struct Normal {
     mean: Tensor,
     stddev: Tensor,
+    log_stddev: Tensor,
     batch_shape: Vec<i64>,
 }
 
@@ -15,6 +25,7 @@ impl Clone for Normal {
         Self {
             mean: self.mean.copy(),
             stddev: self.stddev.copy(),
+            log_stddev: self.log_stddev.copy(),
             batch_shape: self.batch_shape.clone(),
         }
     }
@@ -22,15 +33,41 @@ impl Clone for Normal {
 
 impl Normal {
     /// Creates a new `Normal` distribution with a standard deviation `stddev` around `mean`.
-    pub fn new(mean: Tensor, stddev: Tensor) -> Self {
+    pub fn new(mean: impl Borrow<Tensor>, stddev: impl Borrow<Tensor>) -> Self {
+        let mean = mean.borrow().shallow_clone();
+        let stddev = stddev.borrow().shallow_clone();
+        debug_assert_same_device(&[&mean, &stddev]);
+        trace::construct("Normal", &[("mean", &mean), ("stddev", &stddev)]);
         let batch_shape = mean.size();
+        let log_stddev = stddev.log();
         Self {
             mean,
             stddev,
+            log_stddev,
             batch_shape,
         }
     }
 
+    /// Creates a standard `Normal` distribution with zero mean and unit standard deviation.
+    pub fn standard() -> Self {
+        Self::new(Tensor::from(0.0), Tensor::from(1.0))
+    }
+
+    /// Fits a `Normal` distribution to `data` by the method of moments, matching the sample mean
+    /// and variance along the leading dimension, which indexes the samples.
+    pub fn fit_moments(data: &Tensor) -> Self {
+        let (mean, variance) = sample_moments(data);
+        Self::new(mean, variance.sqrt())
+    }
+
+    /// Fits a `Normal` distribution to `data` by maximum likelihood, the sample mean and the
+    /// biased sample standard deviation along the leading dimension, which indexes the
+    /// samples.
+    pub fn fit_mle(data: &Tensor) -> Result<Self, Error> {
+        let data = check_fit_data(data, &Constraint::Real)?;
+        Ok(Self::fit_moments(&data))
+    }
+
     /// Returns the mean of the distribution.
     pub fn mean(&self) -> &Tensor {
         &self.mean
@@ -41,43 +78,111 @@ impl Normal {
         &self.stddev
     }
 
-    /// Returns sample(s) by using reparameterization trick
-    pub fn rsample(&self, shape: &[i64]) -> Tensor {
-        let shape = self.extended_shape(shape);
-        let eps = standard_normal(&shape, self.mean.kind(), self.mean.device());
-        &self.mean + eps * &self.stddev
+    /// Creates a standard `Normal` distribution of batch `shape` whose mean and standard
+    /// deviation are variables under `path`, the standard deviation being stored as its
+    /// logarithm.
+    pub fn trainable(path: &nn::Path, shape: &[i64]) -> Trainable<Self> {
+        let options = (Kind::Float, path.device());
+        let init = Self::new(Tensor::zeros(shape, options), Tensor::ones(shape, options));
+        Trainable::new(path, &init, &["mean", "stddev"], |p| {
+            Self::new(&p[0], &p[1])
+        })
+    }
+}
+
+impl From<(f64, f64)> for Normal {
+    /// Creates a scalar `Normal` distribution from `(mean, stddev)`.
+    fn from((mean, stddev): (f64, f64)) -> Self {
+        Self::new(Tensor::from(mean), Tensor::from(stddev))
+    }
+}
+
+impl PartialEq for Normal {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
     }
 }
 
 impl Distribution for Normal {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("mean", &self.mean), ("stddev", &self.stddev)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![("mean", Constraint::Real), ("stddev", Constraint::Positive)]
+    }
+
     fn entropy(&self) -> Tensor {
-        0.5 + 0.5 * (2.0 * PI).ln() + self.stddev.log()
+        0.5 + 0.5 * (2.0 * PI).ln() + &self.log_stddev
+    }
+
+    fn mean(&self) -> Tensor {
+        self.mean.shallow_clone()
     }
 
-    fn sample(&self, shape: &[i64]) -> Tensor {
+    fn variance(&self) -> Tensor {
+        self.stddev.pow_tensor_scalar(2)
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
         let shape = self.extended_shape(shape);
-        // tch 0.8.0 removed this ->
-        // Tensor::normal_tensor_tensor_out(
-        //     &Tensor::empty(&shape, (self.mean.kind(), self.mean.device())),
-        //     &self.mean.expand(&shape, false),
-        //     &self.stddev.expand(&shape, false),
-        // )
-        Tensor::normal_(
-            &mut Tensor::empty(&shape, (self.mean.kind(), self.mean.device())),
-            f64::from(&self.mean),
-            f64::from(&self.stddev),
-        )
+        let eps = standard_normal(&shape, self.mean.kind(), self.mean.device());
+        &self.mean + eps * &self.stddev
+    }
+
+    fn sample_antithetic(&self, shape: &[i64]) -> (Tensor, Tensor) {
+        let shape = self.extended_shape(shape);
+        tch::no_grad(|| {
+            let eps = standard_normal(&shape, self.mean.kind(), self.mean.device()) * &self.stddev;
+            (&self.mean + &eps, &self.mean - eps)
+        })
     }
 
     fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
         let var = self.stddev.pow_tensor_scalar(2);
-        -(val - &self.mean).pow_tensor_scalar(2) / (2.0 * var) - self.stddev().log() - (2.0 * PI).sqrt().ln()
+        -(val - &self.mean).pow_tensor_scalar(2) / (2.0 * var)
+            - &self.log_stddev
+            - (2.0 * PI).sqrt().ln()
+    }
+
+    fn log_prob_and_entropy(&self, val: &Tensor) -> (Tensor, Tensor) {
+        self.validate_sample(val);
+        let log_normalizer = &self.log_stddev + (2.0 * PI).sqrt().ln();
+        let z = (val - &self.mean) / &self.stddev;
+        let log_prob = -z.pow_tensor_scalar(2) / 2.0 - &log_normalizer;
+        (log_prob, log_normalizer + 0.5)
     }
 
     fn cdf(&self, val: &Tensor) -> Tensor {
+        self.validate_sample(val);
         0.5 * (1.0 + ((val - &self.mean) * self.stddev.reciprocal() / 2.0f64.sqrt()).erf())
     }
 
+    fn log_prob_out(&self, out: &mut Tensor, val: &Tensor) {
+        self.validate_sample(val);
+        out.copy_(val);
+        *out -= &self.mean;
+        *out /= &self.stddev;
+        let _ = out.pow_(2);
+        *out *= -0.5;
+        *out -= &self.log_stddev;
+        *out -= (2.0 * PI).sqrt().ln();
+    }
+
+    fn cdf_out(&self, out: &mut Tensor, val: &Tensor) {
+        self.validate_sample(val);
+        out.copy_(val);
+        *out -= &self.mean;
+        *out /= &self.stddev;
+        *out /= 2.0f64.sqrt();
+        let _ = out.erf_();
+        *out += 1.0;
+        *out *= 0.5;
+    }
+
     fn icdf(&self, val: &Tensor) -> Tensor {
         &self.mean + &self.stddev * (2.0f64 * val - 1.0f64).erfinv() * 2.0f64.sqrt()
     }
@@ -87,10 +192,52 @@ impl Distribution for Normal {
     }
 }
 
+impl Reparameterize for Normal {
+    fn noise(&self, shape: &[i64]) -> Tensor {
+        standard_normal(
+            &self.extended_shape(shape),
+            self.mean.kind(),
+            self.mean.device(),
+        )
+    }
+
+    fn fill_noise(&self, noise: &mut Tensor) {
+        let _ = noise.normal_(0.0, 1.0);
+    }
+
+    fn reparameterize(&self, noise: &Tensor) -> Tensor {
+        &self.mean + noise * &self.stddev
+    }
+
+    fn reparameterize_out(&self, out: &mut Tensor, noise: &Tensor) {
+        out.copy_(noise);
+        *out *= &self.stddev;
+        *out += &self.mean;
+    }
+}
+
+impl Stack for Normal {
+    fn stack(dists: &[Self]) -> Self {
+        Self::new(
+            stack_param(dists, Self::mean),
+            stack_param(dists, Self::stddev),
+        )
+    }
+}
+
+impl Persist for Normal {
+    const FAMILY: &'static str = "normal";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::new(param(params, "mean")?, param(params, "stddev")?))
+    }
+}
+
 impl KullackLeiberDivergence<Self> for Normal {
     fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
         let var_ratio = (self.stddev() / other.stddev()).pow_tensor_scalar(2.0);
         let t1 = ((self.mean() - other.mean()) / other.stddev()).pow_tensor_scalar(2.0);
-        &0.5.into() * (&var_ratio + &t1 - &1.into() - var_ratio.log())
+        (&var_ratio + &t1 - 1.0 - var_ratio.log()) * 0.5
     }
 }
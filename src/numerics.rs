@@ -0,0 +1,111 @@
+//! Numerically stable primitives shared by the distributions.
+//!
+//! The distributions convert between probabilities and logits and reduce in log space through
+//! these functions. `log1mexp` and `logaddexp` are not needed by the distributions themselves
+//! and are provided for complements of distribution functions and combinations of
+//! log-probabilities computed outside of the crate.
+use crate::utils::{eps, tiny};
+use std::f64::consts::LN_2;
+use tch::{Kind::Double, Tensor};
+
+/// Returns `log(1 - exp(x))` for `x <= 0`, switching between `log(-expm1(x))` near zero and
+/// `log1p(-exp(x))` far from it.
+pub fn log1mexp(x: &Tensor) -> Tensor {
+    let near_zero = (-(x.expm1())).log();
+    let far = (-(x.exp())).log1p();
+    near_zero.where_self(&x.f_gt(-LN_2).unwrap(), &far)
+}
+
+/// Returns `log(exp(a) + exp(b))`, which is `-inf` when both are `-inf`.
+pub fn logaddexp(a: &Tensor, b: &Tensor) -> Tensor {
+    a.logaddexp(b)
+}
+
+/// Returns `log(sum(exp(x)))` over the dimensions `dims`, which is `-inf` when all summed
+/// elements are `-inf`.
+pub fn logsumexp(x: &Tensor, dims: &[i64], keepdim: bool) -> Tensor {
+    x.logsumexp(dims, keepdim)
+}
+
+/// Returns the inverse of the softplus, `log(exp(y) - 1)`, in a form that is stable for
+/// large `y`.
+pub fn softplus_inverse(y: &Tensor) -> Tensor {
+    y + (-(-y).expm1()).log()
+}
+
+/// Clamps `probs` to `[eps, 1 - eps]`, so that their logarithms and log-odds are finite.
+pub fn clamp_probs(probs: &Tensor) -> Tensor {
+    let eps = eps(probs.kind()).unwrap();
+    probs.clamp(eps, 1.0 - eps)
+}
+
+/// Clamps the uniforms `u` to `[tiny, 1 - eps]` inside the open unit interval, so that inverse
+/// CDFs evaluated at them, and their logarithms and `log1p(-u)`, are finite.
+pub fn clamp_open_unit(u: &Tensor) -> Tensor {
+    let (tiny, eps) = (tiny(u.kind()).unwrap(), eps(u.kind()).unwrap());
+    u.clamp(tiny, 1.0 - eps)
+}
+
+/// Returns the log-odds `log(p) - log(1 - p)` of `probs`, which are infinite for
+/// probabilities of zero or one.
+pub fn log_odds(probs: &Tensor) -> Tensor {
+    probs.log() - (-probs).log1p()
+}
+
+/// Returns the log-odds `log(p) - log(1 - p)` of the clamped probabilities `probs`.
+pub fn logit(probs: &Tensor) -> Tensor {
+    log_odds(&clamp_probs(probs))
+}
+
+/// Returns the regularized incomplete beta function `I_x(a, b)`, evaluated in double
+/// precision by its continued fraction on whichever side of the mean it converges quickly
+/// and returned in the kind of `x`.
+pub fn betainc(a: &Tensor, b: &Tensor, x: &Tensor) -> Tensor {
+    const MAX_TERMS: i64 = 1000;
+    const FPMIN: f64 = 1e-300;
+    let kind = x.kind();
+    let (a, b, x) = (a.to_kind(Double), b.to_kind(Double), x.to_kind(Double));
+    let swap = x.gt_tensor(&((&a + 1.0) / (&a + &b + 2.0)));
+    let (p, q) = (
+        a.where_self(&swap.logical_not(), &b),
+        b.where_self(&swap.logical_not(), &a),
+    );
+    let y = x
+        .where_self(&swap.logical_not(), &(1.0 - &x))
+        .clamp(0.0, 1.0);
+
+    // Lentz's method, with the magnitudes of the intermediate terms kept above `FPMIN`.
+    let clamp = |v: Tensor| {
+        let tiny = v.full_like(FPMIN);
+        tiny.where_self(&v.abs().lt(FPMIN), &v)
+    };
+    let mut c = y.ones_like();
+    let mut d = clamp(1.0 - (&p + &q) * &y / (&p + 1.0)).reciprocal();
+    let mut h = d.shallow_clone();
+    for m in 1..MAX_TERMS {
+        let m = m as f64;
+        let even = m * (&q - m) * &y / ((&p + (2.0 * m - 1.0)) * (&p + 2.0 * m));
+        d = clamp(1.0 + &even * &d).reciprocal();
+        c = clamp(1.0 + &even / &c);
+        h = &h * &d * &c;
+        let odd = -(&p + m) * (&p + &q + m) * &y / ((&p + 2.0 * m) * (&p + (2.0 * m + 1.0)));
+        d = clamp(1.0 + &odd * &d).reciprocal();
+        c = clamp(1.0 + &odd / &c);
+        let delta = &d * &c;
+        h = &h * &delta;
+        let converged = (&delta - 1.0).abs().lt(1e-15).logical_or(&delta.isnan());
+        if bool::from(converged.all()) {
+            break;
+        }
+    }
+
+    let log_front =
+        &p * y.log() + &q * (-&y).log1p() + (&p + &q).lgamma() - p.lgamma() - q.lgamma();
+    let result = log_front.exp() * h / p;
+    let complement: Tensor = 1.0 - &result;
+    let result = complement.where_self(&swap, &result);
+    // The continued fraction is undefined at the endpoints.
+    let result = result.where_self(&x.gt(0.0), &x.zeros_like());
+    let result = result.where_self(&x.lt(1.0), &x.ones_like());
+    result.to_kind(kind)
+}
@@ -0,0 +1,69 @@
+//! Sampling and evaluation of many independent distributions on the rayon thread pool.
+//!
+//! Tensors can be sent to but not shared between threads, which is why the helpers borrow
+//! the distributions mutably. libtorch has a single global generator, which `sample` draws
+//! from in whatever order the threads run, so its samples are not reproducible with
+//! `tch::manual_seed` alone. `sample_seeded` gives each distribution its own seed instead.
+use crate::Distribution;
+use rayon::prelude::*;
+use std::sync::{Mutex, PoisonError};
+use tch::Tensor;
+
+/// Held while the global generator is seeded for and sampled by a single distribution.
+static GENERATOR: Mutex<()> = Mutex::new(());
+
+fn stack(results: Vec<Tensor>) -> Tensor {
+    assert!(
+        !results.is_empty(),
+        "cannot stack results of no distributions"
+    );
+    Tensor::stack(&results, 0)
+}
+
+/// Draws a sample_shape shaped sample from each of `dists` in parallel and stacks them along
+/// a new leading dimension.
+pub fn sample<D: Distribution + Send>(dists: &mut [D], shape: &[i64]) -> Tensor {
+    stack(
+        dists
+            .par_iter_mut()
+            .map(|dist| dist.sample(shape))
+            .collect(),
+    )
+}
+
+/// Draws a sample_shape shaped sample from each of `dists` like `sample`, seeding the
+/// generator with `seed + i` before drawing from `dists[i]`.
+///
+/// The samples are the same for any number of threads. Since the generator is global, the
+/// draws themselves are serialized, and the generator is left seeded by the last of them.
+pub fn sample_seeded<D: Distribution + Send>(dists: &mut [D], shape: &[i64], seed: i64) -> Tensor {
+    stack(
+        dists
+            .par_iter_mut()
+            .enumerate()
+            .map(|(i, dist)| {
+                let _generator = GENERATOR.lock().unwrap_or_else(PoisonError::into_inner);
+                tch::manual_seed(seed + i as i64);
+                dist.sample(shape)
+            })
+            .collect(),
+    )
+}
+
+/// Evaluates the log-probabilities of `values[i]` under `dists[i]` in parallel and stacks
+/// them along a new leading dimension.
+pub fn log_prob<D: Distribution + Send>(dists: &mut [D], values: &Tensor) -> Tensor {
+    assert_eq!(
+        values.size().first().copied(),
+        Some(dists.len() as i64),
+        "need one value per distribution"
+    );
+    let values: Vec<Tensor> = (0..dists.len() as i64).map(|i| values.get(i)).collect();
+    stack(
+        dists
+            .par_iter_mut()
+            .zip(values)
+            .map(|(dist, val)| dist.log_prob(&val))
+            .collect(),
+    )
+}
@@ -0,0 +1,102 @@
+use crate::{
+    transforms::{biject_to, Transform},
+    Distribution,
+};
+use std::{fmt, marker::PhantomData};
+use tch::{nn::Path, Tensor};
+
+/// A distribution with trainable parameters stored in unconstrained space.
+///
+/// Each selected parameter is kept as an unconstrained variable of a `VarStore` and mapped
+/// through `biject_to` of its constraint in `arg_constraints` whenever the distribution is
+/// rebuilt with `distribution`. The variables can then be optimized by any `tch::nn` optimizer
+/// without ever leaving the support of the parameters. Constraints depending on other
+/// parameters, such as `high > low` for `Uniform`, are taken from the initial distribution.
+pub struct Parametrized<D, F> {
+    names: Vec<&'static str>,
+    unconstrained: Vec<Tensor>,
+    transforms: Vec<Box<dyn Transform>>,
+    build: F,
+    marker: PhantomData<D>,
+}
+
+/// A `Parametrized` distribution rebuilt by a plain function, as returned by the `trainable`
+/// constructors of the distributions.
+pub type Trainable<D> = Parametrized<D, fn(&[Tensor]) -> D>;
+
+impl<D, F> Parametrized<D, F>
+where
+    D: Distribution,
+    F: Fn(&[Tensor]) -> D,
+{
+    /// Creates variables under `path` for the parameters `names` of `init`, initialized to
+    /// its values. `build` creates a distribution from the constrained parameters, given in
+    /// the order of `names`.
+    pub fn new(path: &Path, init: &D, names: &[&'static str], build: F) -> Self {
+        let params = init.params();
+        let constraints = init.arg_constraints();
+        let (unconstrained, transforms) = names
+            .iter()
+            .map(|&name| {
+                let (_, value) = params
+                    .iter()
+                    .find(|(param, _)| *param == name)
+                    .unwrap_or_else(|| panic!("distribution has no parameter {}", name));
+                let (_, constraint) = constraints
+                    .iter()
+                    .find(|(param, _)| *param == name)
+                    .unwrap_or_else(|| panic!("parameter {} has no constraint", name));
+                let transform = biject_to(constraint).unwrap_or_else(|| {
+                    panic!(
+                        "no bijection to the constraint {:?} of {}",
+                        constraint, name
+                    )
+                });
+                let init = tch::no_grad(|| transform.inverse(value));
+                (path.var_copy(name, &init), transform)
+            })
+            .unzip();
+        Self {
+            names: names.to_vec(),
+            unconstrained,
+            transforms,
+            build,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the names of the trainable parameters.
+    pub fn names(&self) -> &[&'static str] {
+        &self.names
+    }
+
+    /// Returns the unconstrained variables, in the order of `names`.
+    pub fn unconstrained(&self) -> &[Tensor] {
+        &self.unconstrained
+    }
+
+    /// Returns the constrained values of the parameters, in the order of `names`.
+    pub fn constrained(&self) -> Vec<Tensor> {
+        self.unconstrained
+            .iter()
+            .zip(self.transforms.iter())
+            .map(|(x, transform)| transform.forward(x))
+            .collect()
+    }
+
+    /// Builds the distribution from the current values of the variables. Gradients flow back
+    /// to the unconstrained variables.
+    pub fn distribution(&self) -> D {
+        (self.build)(&self.constrained())
+    }
+}
+
+impl<D, F> fmt::Debug for Parametrized<D, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Parametrized")
+            .field("names", &self.names)
+            .field("unconstrained", &self.unconstrained)
+            .field("transforms", &self.transforms)
+            .finish()
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{Distribution, Error};
+use std::{collections::HashMap, path::Path};
+use tch::{Device, Kind, Tensor};
+
+/// Name of the tensor holding the UTF-8 encoded family of a saved distribution.
+const FAMILY_KEY: &str = "__family__";
+/// Name of the tensor holding the device of a saved distribution, `-1` for the CPU and the
+/// index of the device for CUDA.
+const DEVICE_KEY: &str = "__device__";
+
+/// Distributions whose parameters can be saved to and loaded from a file.
+///
+/// Each parameter is stored as a named tensor with `Tensor::save_multi`, so the parameters
+/// keep their dtype, next to the family of the distribution and the device of its parameters.
+/// Loading checks the family and moves the parameters back to the device they were saved
+/// from.
+pub trait Persist: Distribution + Sized {
+    /// The name of the family, stored with the parameters and checked on loading.
+    const FAMILY: &'static str;
+
+    /// Creates a distribution from its parameters, keyed by the names returned by `params`.
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error>;
+
+    /// Saves the parameters of the distribution to `path`.
+    fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let params = self.params();
+        let device = params.first().map_or(Device::Cpu, |(_, p)| p.device());
+        let device = match device {
+            Device::Cpu => -1,
+            Device::Cuda(index) => index as i64,
+        };
+        let mut named = vec![
+            (FAMILY_KEY, Tensor::of_slice(Self::FAMILY.as_bytes())),
+            (DEVICE_KEY, Tensor::from(device)),
+        ];
+        named.extend(params.into_iter().map(|(name, p)| (name, p.detach())));
+        Ok(Tensor::save_multi(&named, path)?)
+    }
+
+    /// Loads a distribution saved by `save` from `path`.
+    fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let mut params: HashMap<String, Tensor> = Tensor::load_multi(path)?.into_iter().collect();
+        let family = params
+            .remove(FAMILY_KEY)
+            .ok_or_else(|| Error::Format("the saved distribution has no family".to_string()))?;
+        let family = String::from_utf8(Vec::<u8>::from(&family.to_kind(Kind::Uint8)))
+            .map_err(|err| Error::Format(format!("invalid family: {}", err)))?;
+        if family != Self::FAMILY {
+            return Err(Error::Format(format!(
+                "expected a saved {} distribution, found {}",
+                Self::FAMILY,
+                family
+            )));
+        }
+        let device = match params.remove(DEVICE_KEY).map(|d| i64::from(&d)) {
+            Some(-1) | None => Device::Cpu,
+            Some(index) => Device::Cuda(index as usize),
+        };
+        let params = params
+            .into_iter()
+            .map(|(name, p)| (name, p.to_device(device)))
+            .collect();
+        Self::from_params(&params)
+    }
+}
+
+/// Returns the parameter `name` of a loaded distribution.
+pub(crate) fn param<'a>(
+    params: &'a HashMap<String, Tensor>,
+    name: &str,
+) -> Result<&'a Tensor, Error> {
+    params
+        .get(name)
+        .ok_or_else(|| Error::Format(format!("the saved distribution has no parameter {}", name)))
+}
@@ -1,5 +1,14 @@
-use crate::{Distribution, KullackLeiberDivergence};
-use tch::Tensor;
+use crate::{
+    constraints::Constraint,
+    error::check_fit_data,
+    persist::{param, Persist},
+    trace,
+    utils::{float_values, params_match, stack_param},
+    Distribution, Error, KullackLeiberDivergence, Stack, Trainable,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use tch::{nn, Kind, Tensor};
 
 /// A Poisson distribution.
 #[derive(Debug)]
@@ -19,25 +28,83 @@ impl Clone for Poisson {
 
 impl Poisson {
     /// Creates a new `Poisson` distribution with `rate`.
-    pub fn new(rate: Tensor) -> Self {
+    pub fn new(rate: impl Borrow<Tensor>) -> Self {
+        let rate = rate.borrow().shallow_clone();
+        trace::construct("Poisson", &[("rate", &rate)]);
         let batch_shape = rate.size();
         Self { rate, batch_shape }
     }
 
+    /// Fits a `Poisson` distribution to `data` by maximum likelihood, the sample mean along the
+    /// leading dimension, which indexes the samples.
+    pub fn fit_mle(data: &Tensor) -> Result<Self, Error> {
+        let data = check_fit_data(data, &Constraint::nonnegative_integer())?;
+        Ok(Self::new(data.mean_dim(&[0], false, data.kind())))
+    }
+
     /// Returns the rate of the distribution.
     pub fn rate(&self) -> &Tensor {
         &self.rate
     }
+
+    /// Creates a `Poisson` distribution of batch `shape` with unit rate, which is a variable
+    /// under `path` stored as its logarithm.
+    pub fn trainable(path: &nn::Path, shape: &[i64]) -> Trainable<Self> {
+        let options = (Kind::Float, path.device());
+        let init = Self::new(Tensor::ones(shape, options));
+        Trainable::new(path, &init, &["rate"], |p| Self::new(&p[0]))
+    }
+}
+
+impl From<f64> for Poisson {
+    /// Creates a scalar `Poisson` distribution from `rate`.
+    fn from(rate: f64) -> Self {
+        Self::new(Tensor::from(rate))
+    }
+}
+
+impl PartialEq for Poisson {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
 }
 
 impl Distribution for Poisson {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("rate", &self.rate)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![("rate", Constraint::Nonnegative)]
+    }
+
     fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        let val = &float_values(val, &self.rate);
         (self.rate.log() * val) - &self.rate - (val + 1).lgamma()
     }
 
     fn sample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
         let shape = self.extended_shape(shape);
-        Tensor::empty(&shape, (self.rate.kind(), self.rate.device())).poisson()
+        tch::no_grad(|| self.rate.expand(&shape, false).poisson())
+    }
+
+    fn mean(&self) -> Tensor {
+        self.rate.shallow_clone()
+    }
+
+    fn variance(&self) -> Tensor {
+        self.rate.shallow_clone()
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::nonnegative_integer()
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (self.rate.zeros_like(), self.rate.full_like(f64::INFINITY))
     }
 
     fn batch_shape(&self) -> &[i64] {
@@ -45,8 +112,23 @@ impl Distribution for Poisson {
     }
 }
 
+impl Stack for Poisson {
+    fn stack(dists: &[Self]) -> Self {
+        Self::new(stack_param(dists, Self::rate))
+    }
+}
+
+impl Persist for Poisson {
+    const FAMILY: &'static str = "poisson";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::new(param(params, "rate")?))
+    }
+}
+
 impl KullackLeiberDivergence<Self> for Poisson {
     fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
         self.rate() * (self.rate().log() - other.rate().log()) - (self.rate() - other.rate())
     }
 }
@@ -0,0 +1,144 @@
+//! Posterior predictive distributions.
+use crate::{constraints::Constraint, numerics, utils::float_samples, Distribution, Stack};
+use tch::{Kind, Tensor};
+
+/// The posterior predictive distribution of a model, an equally weighted mixture of the model
+/// evaluated at each of a set of posterior draws.
+///
+/// The model distributions are stacked along a new leading batch dimension indexing the
+/// draws, which is mixed over by all methods.
+#[derive(Debug)]
+pub struct PosteriorPredictive<D: Distribution> {
+    draws: D,
+    n_draws: i64,
+    batch_shape: Vec<i64>,
+}
+
+/// Returns the posterior predictive distribution of `model` for the `[n_draws, ...]` shaped
+/// `posterior_samples`, e.g. the output of an MCMC sampler. `model` maps a single draw of the
+/// parameters to the distribution of the data given them, which must have the same batch
+/// shape for all draws.
+pub fn mixture_of_draws<D, F>(posterior_samples: &Tensor, model: F) -> PosteriorPredictive<D>
+where
+    D: Distribution + Stack,
+    F: Fn(&Tensor) -> D,
+{
+    assert!(
+        posterior_samples.dim() >= 1,
+        "posterior samples need a leading draw dimension, got shape {:?}",
+        posterior_samples.size()
+    );
+    let n_draws = posterior_samples.size()[0];
+    let draws: Vec<D> = (0..n_draws)
+        .map(|i| model(&posterior_samples.get(i)))
+        .collect();
+    let draws = D::stack(&draws);
+    let batch_shape = draws.batch_shape()[1..].to_vec();
+    PosteriorPredictive {
+        draws,
+        n_draws,
+        batch_shape,
+    }
+}
+
+impl<D: Distribution> PosteriorPredictive<D> {
+    /// Returns the model distributions of all draws, stacked along the leading batch
+    /// dimension.
+    pub fn draws(&self) -> &D {
+        &self.draws
+    }
+
+    /// Returns the number of posterior draws.
+    pub fn n_draws(&self) -> i64 {
+        self.n_draws
+    }
+
+    /// Returns the dimension indexing the draws in tensors shaped like the batch and event
+    /// shapes of the stacked draws.
+    fn draw_dim(&self) -> i64 {
+        -1 - (self.batch_shape.len() + self.draws.event_shape().len()) as i64
+    }
+
+    /// Returns the dimension of the draws in log-probabilities of the stacked draws.
+    fn log_prob_draw_dim(&self) -> i64 {
+        -1 - self.batch_shape.len() as i64
+    }
+
+    /// Returns the equal-tailed predictive interval containing `mass` of the probability,
+    /// estimated from the quantiles of `n_samples` predictive samples.
+    pub fn sample_interval(&self, mass: f64, n_samples: i64) -> (Tensor, Tensor) {
+        assert!(
+            mass > 0.0 && mass <= 1.0,
+            "mass must lie in (0, 1], got {}",
+            mass
+        );
+        let samples = float_samples(self, n_samples);
+        let tail = (1.0 - mass) / 2.0;
+        (
+            samples.quantile_scalar(tail, 0, false, "linear"),
+            samples.quantile_scalar(1.0 - tail, 0, false, "linear"),
+        )
+    }
+
+    /// Returns the log pointwise predictive density of the held-out observations `data`,
+    /// whose leading dimension indexes the observations: the sum over observations of the
+    /// log of their predictive density averaged over the draws.
+    pub fn log_predictive_density(&self, data: &Tensor) -> Tensor {
+        self.log_prob(data)
+            .sum_dim_intlist(&[0], false, Kind::Double)
+    }
+}
+
+impl<D: Distribution> Distribution for PosteriorPredictive<D> {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        self.draws.params()
+    }
+
+    fn support(&self) -> Constraint {
+        self.draws.support()
+    }
+
+    fn mean(&self) -> Tensor {
+        let means = self.draws.mean();
+        means.mean_dim(&[self.draw_dim()], false, means.kind())
+    }
+
+    fn variance(&self) -> Tensor {
+        let dim = self.draw_dim();
+        let means = self.draws.mean();
+        let mean_variance = self.draws.variance().mean_dim(&[dim], false, means.kind());
+        let variance_mean = means.var_dim(&[dim], false, false);
+        mean_variance + variance_mean
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        let dim = self.log_prob_draw_dim();
+        let log_prob = self.draws.log_prob(&val.unsqueeze(self.draw_dim()));
+        numerics::logsumexp(&log_prob, &[dim], false) - (self.n_draws as f64).ln()
+    }
+
+    fn sample(&self, shape: &[i64]) -> Tensor {
+        tch::no_grad(|| {
+            let dim = self.draw_dim();
+            let samples = self.draws.sample(shape);
+            let mut index_shape = samples.size();
+            index_shape[(samples.dim() as i64 + dim) as usize] = 1;
+            // The same draw is picked for all elements of an event.
+            let event_dims = self.draws.event_shape().len();
+            let mut draw_shape = index_shape.clone();
+            let n_dims = draw_shape.len();
+            draw_shape[n_dims - event_dims..].fill(1);
+            let index = Tensor::randint(self.n_draws, &draw_shape, (Kind::Int64, samples.device()))
+                .expand(&index_shape, false);
+            samples.gather(dim, &index, false).squeeze_dim(dim)
+        })
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+
+    fn event_shape(&self) -> &[i64] {
+        self.draws.event_shape()
+    }
+}
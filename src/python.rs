@@ -0,0 +1,269 @@
+//! Conversions between the distributions of this crate and `torch.distributions`.
+//!
+//! Parameters cross the boundary as NumPy arrays, so they are copied to the CPU and keep
+//! their dtype if it is `float32` or `float64`. Distributions converted from Python are
+//! created on the CPU and, like their Python counterparts, are not attached to any autograd
+//! graph.
+use crate::{
+    Bernoulli, Beta, Binomial, Categorical, Cauchy, Distribution, Error, Exponential, Gamma,
+    Geometric, MultivariateNormal, NegativeBinomial, Normal, Poisson, RelaxedOneHotCategorical,
+    Uniform,
+};
+use numpy::{PyReadonlyArrayDyn, ToPyArray};
+use pyo3::{exceptions::PyValueError, prelude::*, types::IntoPyDict};
+use std::convert::{TryFrom, TryInto};
+use tch::{Device, Kind, Tensor};
+
+pub use crate::AnyDistribution;
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// Returns the `torch.Tensor` holding the values of `t`.
+pub(crate) fn tensor_to_py(py: Python<'_>, t: &Tensor) -> PyResult<PyObject> {
+    let array: ndarray::ArrayD<f64> = (&t.detach().to_device(Device::Cpu).to_kind(Kind::Double))
+        .try_into()
+        .map_err(|err: ndarray::ShapeError| PyValueError::new_err(err.to_string()))?;
+    let torch = py.import("torch")?;
+    let mut tensor = torch
+        .getattr("from_numpy")?
+        .call1((array.to_pyarray(py),))?;
+    if t.kind() == Kind::Float {
+        tensor = tensor.call_method1("to", (torch.getattr("float32")?,))?;
+    }
+    Ok(tensor.into())
+}
+
+/// Returns the CPU tensor holding the values of the `torch.Tensor` `obj`.
+pub(crate) fn tensor_from_py(obj: &PyAny) -> PyResult<Tensor> {
+    let is_float = obj.getattr("dtype")?.str()?.to_str()? == "torch.float32";
+    let array: PyReadonlyArrayDyn<'_, f64> = obj
+        .call_method0("detach")?
+        .call_method0("cpu")?
+        .call_method0("double")?
+        .call_method0("contiguous")?
+        .call_method0("numpy")?
+        .extract()?;
+    let t =
+        Tensor::try_from(array.as_array()).map_err(|err| PyValueError::new_err(err.to_string()))?;
+    Ok(if is_float { t.to_kind(Kind::Float) } else { t })
+}
+
+/// Creates an instance of the `torch.distributions` class `name` from the keyword arguments
+/// `params`.
+fn construct(py: Python<'_>, name: &str, params: &[(&str, &Tensor)]) -> PyResult<PyObject> {
+    let kwargs = params
+        .iter()
+        .map(|&(key, t)| Ok((key, tensor_to_py(py, t)?)))
+        .collect::<PyResult<Vec<_>>>()?
+        .into_py_dict(py);
+    let class = py.import("torch.distributions")?.getattr(name)?;
+    Ok(class.call((), Some(kwargs))?.into())
+}
+
+/// Distributions with an equivalent in `torch.distributions`.
+pub trait ToPy {
+    /// Returns the equivalent `torch.distributions` object.
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject>;
+}
+
+impl ToPy for Bernoulli {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(py, "Bernoulli", &[("logits", self.logits())])
+    }
+}
+
+impl ToPy for Beta {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(
+            py,
+            "Beta",
+            &[
+                ("concentration1", self.concentration1()),
+                ("concentration0", self.concentration0()),
+            ],
+        )
+    }
+}
+
+impl ToPy for Binomial {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(
+            py,
+            "Binomial",
+            &[
+                ("total_count", self.total_count()),
+                ("logits", self.logits()),
+            ],
+        )
+    }
+}
+
+impl ToPy for Categorical {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(py, "Categorical", &[("logits", self.logits())])
+    }
+}
+
+impl ToPy for Cauchy {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(
+            py,
+            "Cauchy",
+            &[("loc", self.median()), ("scale", self.scale())],
+        )
+    }
+}
+
+impl ToPy for Exponential {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(py, "Exponential", &[("rate", self.rate())])
+    }
+}
+
+impl ToPy for Gamma {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(
+            py,
+            "Gamma",
+            &[
+                ("concentration", self.concentration()),
+                ("rate", self.rate()),
+            ],
+        )
+    }
+}
+
+impl ToPy for Geometric {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(py, "Geometric", &[("logits", self.logits())])
+    }
+}
+
+impl ToPy for MultivariateNormal {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(
+            py,
+            "MultivariateNormal",
+            &[
+                ("loc", &Distribution::mean(self)),
+                ("scale_tril", self.scale_tril()),
+            ],
+        )
+    }
+}
+
+impl ToPy for NegativeBinomial {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(
+            py,
+            "NegativeBinomial",
+            &[
+                ("total_count", self.total_count()),
+                ("logits", self.logits()),
+            ],
+        )
+    }
+}
+
+impl ToPy for Normal {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(
+            py,
+            "Normal",
+            &[("loc", self.mean()), ("scale", self.stddev())],
+        )
+    }
+}
+
+impl ToPy for Poisson {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(py, "Poisson", &[("rate", self.rate())])
+    }
+}
+
+impl ToPy for RelaxedOneHotCategorical {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(
+            py,
+            "RelaxedOneHotCategorical",
+            &[
+                ("temperature", self.temperature()),
+                ("logits", self.logits()),
+            ],
+        )
+    }
+}
+
+impl ToPy for Uniform {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        construct(py, "Uniform", &[("low", self.low()), ("high", self.high())])
+    }
+}
+
+impl ToPy for AnyDistribution {
+    fn to_py(&self, py: Python<'_>) -> PyResult<PyObject> {
+        match self {
+            AnyDistribution::Bernoulli(dist) => dist.to_py(py),
+            AnyDistribution::Beta(dist) => dist.to_py(py),
+            AnyDistribution::Binomial(dist) => dist.to_py(py),
+            AnyDistribution::Categorical(dist) => dist.to_py(py),
+            AnyDistribution::Cauchy(dist) => dist.to_py(py),
+            AnyDistribution::Exponential(dist) => dist.to_py(py),
+            AnyDistribution::Gamma(dist) => dist.to_py(py),
+            AnyDistribution::Geometric(dist) => dist.to_py(py),
+            AnyDistribution::MultivariateNormal(dist) => dist.to_py(py),
+            AnyDistribution::NegativeBinomial(dist) => dist.to_py(py),
+            AnyDistribution::Normal(dist) => dist.to_py(py),
+            AnyDistribution::Poisson(dist) => dist.to_py(py),
+            AnyDistribution::RelaxedOneHotCategorical(dist) => dist.to_py(py),
+            AnyDistribution::Uniform(dist) => dist.to_py(py),
+        }
+    }
+}
+
+/// Converts the `torch.distributions` object `obj` to the equivalent distribution of this
+/// crate. Fails with a `ValueError` if its family is not supported.
+pub fn from_py(obj: &PyAny) -> PyResult<AnyDistribution> {
+    let param = |name: &str| tensor_from_py(obj.getattr(name)?);
+    let family = obj.get_type().name()?;
+    let dist = match family {
+        "Bernoulli" => AnyDistribution::Bernoulli(Bernoulli::from_logits(param("logits")?)),
+        "Beta" => AnyDistribution::Beta(Beta::new(
+            param("concentration1")?,
+            param("concentration0")?,
+        )),
+        "Binomial" => AnyDistribution::Binomial(Binomial::from_logits(
+            param("total_count")?,
+            param("logits")?,
+        )),
+        "Categorical" => AnyDistribution::Categorical(Categorical::from_logits(param("logits")?)),
+        "Cauchy" => AnyDistribution::Cauchy(Cauchy::new(param("loc")?, param("scale")?)),
+        "Exponential" => AnyDistribution::Exponential(Exponential::new(param("rate")?)),
+        "Gamma" => AnyDistribution::Gamma(Gamma::new(param("concentration")?, param("rate")?)),
+        "Geometric" => AnyDistribution::Geometric(Geometric::from_logits(param("logits")?)),
+        "MultivariateNormal" => AnyDistribution::MultivariateNormal(
+            MultivariateNormal::from_scale_tril(param("loc")?, param("scale_tril")?),
+        ),
+        "NegativeBinomial" => AnyDistribution::NegativeBinomial(NegativeBinomial::from_logits(
+            param("total_count")?,
+            param("logits")?,
+        )),
+        "Normal" => AnyDistribution::Normal(Normal::new(param("loc")?, param("scale")?)),
+        "Poisson" => AnyDistribution::Poisson(Poisson::new(param("rate")?)),
+        "RelaxedOneHotCategorical" => AnyDistribution::RelaxedOneHotCategorical(
+            RelaxedOneHotCategorical::from_logits(param("temperature")?, param("logits")?),
+        ),
+        "Uniform" => AnyDistribution::Uniform(Uniform::new(param("low")?, param("high")?)),
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "unsupported distribution {}",
+                family
+            )))
+        }
+    };
+    Ok(dist)
+}
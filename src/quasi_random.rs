@@ -0,0 +1,81 @@
+use crate::{numerics, Distribution};
+use tch::{Device, Kind, Tensor};
+
+/// Number of bits of the Sobol direction numbers, as in `torch.quasirandom.SobolEngine`.
+const SOBOL_MAX_BIT: i64 = 30;
+/// Largest dimension the built-in Sobol direction numbers support.
+const SOBOL_MAX_DIM: i64 = 21201;
+
+/// Returns the device of the first parameter of `dist`, or the CPU for distributions without
+/// parameters.
+pub(crate) fn device<D: Distribution + ?Sized>(dist: &D) -> Device {
+    dist.params()
+        .first()
+        .map_or(Device::Cpu, |(_, param)| param.device())
+}
+
+/// Returns the first `n` points after the origin of the unscrambled `dimension` dimensional
+/// Sobol sequence, shaped `[n, dimension]`. Skipping the origin keeps every point inside the
+/// open unit cube.
+pub(crate) fn sobol(n: i64, dimension: i64) -> Tensor {
+    assert!(
+        dimension <= SOBOL_MAX_DIM,
+        "Sobol sequences support at most {} dimensions, got {}",
+        SOBOL_MAX_DIM,
+        dimension
+    );
+    let mut state = Tensor::zeros(&[dimension, SOBOL_MAX_BIT], (Kind::Int64, Device::Cpu));
+    let _ = state.internal_sobol_engine_initialize_state_(dimension);
+    let quasi = Tensor::zeros(&[dimension], (Kind::Int64, Device::Cpu));
+    let (points, _) =
+        Tensor::internal_sobol_engine_draw(&quasi, n, &state, dimension, 0, Kind::Double);
+    points
+}
+
+/// Maps uniforms `u` shaped `[sample_shape, numel(batch_shape)]` to samples shaped
+/// `[sample_shape, batch_shape]` through the icdf of `dist`. The uniforms are clamped to the
+/// open unit interval first, so that unbounded supports map to finite samples.
+pub(crate) fn from_uniform<D: Distribution + ?Sized>(dist: &D, u: &Tensor) -> Tensor {
+    assert!(
+        dist.event_shape().is_empty(),
+        "sampling through the icdf needs a univariate distribution"
+    );
+    let u_shape = u.size();
+    let shape = [&u_shape[..u_shape.len() - 1], dist.batch_shape()].concat();
+    let u = numerics::clamp_open_unit(&u.reshape(&shape).to_device(device(dist)));
+    tch::no_grad(|| dist.icdf(&u))
+}
+
+/// Draws `n` samples with stratum `floor(i * strata / n)` for sample `i`, drawing the
+/// uniform of each sample uniformly within its stratum.
+pub(crate) fn sample_stratified<D: Distribution + ?Sized>(dist: &D, n: i64, strata: i64) -> Tensor {
+    assert!(
+        strata > 0 && strata <= n,
+        "need between 1 and {} strata, got {}",
+        n,
+        strata
+    );
+    let numel = dist.batch_shape().iter().product();
+    let options = (Kind::Double, device(dist));
+    let stratum = (Tensor::arange(n, options) * strata as f64 / n as f64)
+        .floor()
+        .unsqueeze(-1);
+    let u = (stratum + Tensor::rand(&[n, numel], options)) / strata as f64;
+    from_uniform(dist, &u)
+}
+
+/// Draws `n` samples such that, in every batch element, exactly one sample falls into each
+/// of `n` equally likely strata. The strata are paired across batch elements by independent
+/// random permutations.
+pub(crate) fn sample_latin_hypercube<D: Distribution + ?Sized>(dist: &D, n: i64) -> Tensor {
+    let numel = dist.batch_shape().iter().product();
+    let options = (Kind::Double, device(dist));
+    let ranks = Tensor::rand(&[n, numel], options).argsort(0, false);
+    let u = (ranks.to_kind(Kind::Double) + Tensor::rand(&[n, numel], options)) / n as f64;
+    from_uniform(dist, &u)
+}
+
+pub(crate) fn sample_qmc<D: Distribution + ?Sized>(dist: &D, n: i64) -> Tensor {
+    let dimension = dist.batch_shape().iter().product();
+    from_uniform(dist, &sobol(n, dimension))
+}
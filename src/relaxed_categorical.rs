@@ -0,0 +1,155 @@
+use crate::{
+    constraints::Constraint,
+    numerics, trace,
+    utils::{params_match, standard_gumbel},
+    Categorical, Distribution, Reparameterize,
+};
+use std::borrow::Borrow;
+use tch::Tensor;
+
+/// A relaxed one-hot Categorical distribution, also known as the Concrete or Gumbel-softmax
+/// distribution.
+///
+/// Samples are points of the probability simplex that approach one-hot samples of the
+/// underlying `Categorical` as the temperature goes to zero, and are differentiable with
+/// respect to the logits.
+#[derive(Debug)]
+pub struct RelaxedOneHotCategorical {
+    temperature: Tensor,
+    categorical: Categorical,
+    batch_shape: Vec<i64>,
+    event_shape: Vec<i64>,
+}
+
+impl Clone for RelaxedOneHotCategorical {
+    fn clone(&self) -> Self {
+        Self::new(self.temperature.copy(), self.categorical.clone())
+    }
+}
+
+impl RelaxedOneHotCategorical {
+    /// Creates a relaxation of `categorical` with `temperature`, which must broadcast against
+    /// the batch shape of `categorical`.
+    pub fn new(temperature: impl Borrow<Tensor>, categorical: Categorical) -> Self {
+        let temperature = temperature.borrow().shallow_clone();
+        trace::construct("RelaxedOneHotCategorical", &[("temperature", &temperature)]);
+        let batch_shape = categorical.batch_shape().to_vec();
+        let event_shape = vec![*categorical.logits().size().last().unwrap()];
+        Self {
+            temperature,
+            categorical,
+            batch_shape,
+            event_shape,
+        }
+    }
+
+    /// Creates a relaxed one-hot Categorical distribution with `temperature` from
+    /// probabilities.
+    pub fn from_probs(temperature: impl Borrow<Tensor>, probs: impl Borrow<Tensor>) -> Self {
+        Self::new(temperature, Categorical::from_probs(probs))
+    }
+
+    /// Creates a relaxed one-hot Categorical distribution with `temperature` from logits.
+    pub fn from_logits(temperature: impl Borrow<Tensor>, logits: impl Borrow<Tensor>) -> Self {
+        Self::new(temperature, Categorical::from_logits(logits))
+    }
+
+    /// Returns the temperature of the relaxation.
+    pub fn temperature(&self) -> &Tensor {
+        &self.temperature
+    }
+
+    /// Returns the relaxed categorical distribution.
+    pub fn categorical(&self) -> &Categorical {
+        &self.categorical
+    }
+
+    /// Returns the probabilities of the distribution.
+    pub fn probs(&self) -> &Tensor {
+        self.categorical.probs()
+    }
+
+    /// Returns the logits of the distribution.
+    pub fn logits(&self) -> &Tensor {
+        self.categorical.logits()
+    }
+
+    /// Draws hard one-hot samples with straight-through gradients: the forward values are the
+    /// one-hot encodings of the largest entries of relaxed samples, while gradients flow back
+    /// through the relaxed samples as if they had been returned.
+    pub fn rsample_straight_through(&self, shape: &[i64]) -> Tensor {
+        let soft = self.rsample(shape);
+        let hard = soft
+            .argmax(-1, false)
+            .one_hot(self.event_shape[0])
+            .to_kind(soft.kind());
+        hard - soft.detach() + soft
+    }
+}
+
+impl PartialEq for RelaxedOneHotCategorical {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
+}
+
+impl Distribution for RelaxedOneHotCategorical {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![
+            ("temperature", &self.temperature),
+            ("logits", self.categorical.logits()),
+        ]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("temperature", Constraint::Positive),
+            ("logits", Constraint::Real),
+        ]
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::Simplex
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        let n = self.event_shape[0] as f64;
+        let log_val = val.log();
+        let temperature = self.temperature.unsqueeze(-1);
+        let score = self.logits() - &log_val * &temperature;
+        let score = (&score - numerics::logsumexp(&score, &[-1], true)).sum_dim_intlist(
+            &[-1],
+            false,
+            score.kind(),
+        );
+        let log_scale = self.temperature.full_like(n).lgamma() + self.temperature.log() * (n - 1.0);
+        score + log_scale - log_val.sum_dim_intlist(&[-1], false, log_val.kind())
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        let noise = self.noise(shape);
+        self.reparameterize(&noise)
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+
+    fn event_shape(&self) -> &[i64] {
+        &self.event_shape
+    }
+}
+
+impl Reparameterize for RelaxedOneHotCategorical {
+    fn noise(&self, shape: &[i64]) -> Tensor {
+        let logits = self.logits();
+        standard_gumbel(&self.extended_shape(shape), logits.kind(), logits.device())
+    }
+
+    fn reparameterize(&self, noise: &Tensor) -> Tensor {
+        ((self.logits() + noise) / self.temperature.unsqueeze(-1)).softmax(-1, noise.kind())
+    }
+}
@@ -0,0 +1,72 @@
+//! Helpers for policy-gradient reinforcement learning.
+use crate::{utils::sum_rightmost, Distribution, KullackLeiberDivergence};
+use std::cell::OnceCell;
+use tch::Tensor;
+
+/// The action distribution of a policy for a batch of states.
+///
+/// The leading batch dimension of the distribution indexes the states, and all further batch
+/// dimensions are action dimensions, e.g. a Normal of batch shape `[n_states, action_dim]`
+/// for continuous actions or a Categorical of batch shape `[n_states]` for discrete ones.
+/// All per-state quantities are summed over the action dimensions, so they are `[n_states]`
+/// shaped as PPO and A2C losses expect.
+#[derive(Debug)]
+pub struct PolicyOutput<D: Distribution> {
+    dist: D,
+    action_dims: i64,
+    entropy: OnceCell<Tensor>,
+}
+
+impl<D: Distribution> PolicyOutput<D> {
+    /// Wraps the action distribution `dist` for a batch of states.
+    pub fn new(dist: D) -> Self {
+        assert!(
+            !dist.batch_shape().is_empty(),
+            "policy distributions need a leading state dimension"
+        );
+        let action_dims = dist.batch_shape().len() as i64 - 1;
+        Self {
+            dist,
+            action_dims,
+            entropy: OnceCell::new(),
+        }
+    }
+
+    /// Returns the wrapped action distribution.
+    pub fn distribution(&self) -> &D {
+        &self.dist
+    }
+
+    /// Draws one action per state, without gradients.
+    pub fn sample(&self) -> Tensor {
+        self.dist.sample(&[])
+    }
+
+    /// Returns the `[n_states]` shaped log-probabilities of the `actions`.
+    pub fn log_prob(&self, actions: &Tensor) -> Tensor {
+        sum_rightmost(&self.dist.log_prob(actions), self.action_dims)
+    }
+
+    /// Returns the `[n_states]` shaped entropies of the policy. The entropies are computed on
+    /// first use and cached.
+    pub fn entropy(&self) -> &Tensor {
+        self.entropy
+            .get_or_init(|| sum_rightmost(&self.dist.entropy(), self.action_dims))
+    }
+
+    /// Returns the `[n_states]` shaped probability ratios `pi(actions) / pi_old(actions)` of
+    /// the clipped PPO objective, given the log-probabilities `old_log_prob` of the actions
+    /// under the policy which collected them.
+    pub fn ratio(&self, actions: &Tensor, old_log_prob: &Tensor) -> Tensor {
+        (self.log_prob(actions) - old_log_prob).exp()
+    }
+
+    /// Returns the `[n_states]` shaped Kullback-Leibler divergences `KL(self || other)` of this
+    /// policy to `other`, e.g. the policy before an update.
+    pub fn kl_to(&self, other: &Self) -> Tensor
+    where
+        D: KullackLeiberDivergence<D>,
+    {
+        sum_rightmost(&self.dist.kl_divergence(&other.dist), self.action_dims)
+    }
+}
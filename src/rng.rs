@@ -0,0 +1,177 @@
+//! Implementations of `rand::distributions::Distribution` for the families with scalar
+//! parameters.
+//!
+//! The draws are computed in plain Rust from uniforms of the given generator, without
+//! allocating tensors or launching kernels, which makes them cheap enough for simulations
+//! drawing one value at a time.
+//!
+//! `ScalarDistribution` holds its parameters as `f64` and is the one to draw streams of values
+//! from. The tensor families convert themselves into it at every draw, which reads their
+//! parameters back from libtorch each time, and panic unless the parameters hold a single
+//! element on the CPU.
+use crate::scalar::ScalarDistribution;
+use crate::special::ln_gamma;
+use crate::{Bernoulli, Beta, Cauchy, Exponential, Gamma, Geometric, Normal, Poisson, Uniform};
+use rand::{distributions::Distribution, Rng};
+use std::f64::consts::PI;
+
+/// Returns `dist` with its parameters read from their tensors.
+fn scalar<'a, D>(dist: &'a D) -> ScalarDistribution
+where
+    ScalarDistribution: TryFrom<&'a D, Error = crate::Error>,
+{
+    ScalarDistribution::try_from(dist).unwrap_or_else(|err| panic!("{}", err))
+}
+
+/// Returns a uniform draw from `(0, 1]`, whose logarithm is finite.
+fn open_uniform<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    1.0 - rng.gen::<f64>()
+}
+
+/// Returns a standard normal draw by the Box-Muller transform.
+fn standard_normal<R: Rng + ?Sized>(rng: &mut R) -> f64 {
+    (-2.0 * open_uniform(rng).ln()).sqrt() * (2.0 * PI * rng.gen::<f64>()).cos()
+}
+
+/// Returns a draw of a unit rate Gamma distribution with `concentration` by the method of
+/// Marsaglia and Tsang, boosting concentrations below one.
+fn standard_gamma<R: Rng + ?Sized>(rng: &mut R, concentration: f64) -> f64 {
+    if concentration < 1.0 {
+        let boost = open_uniform(rng).powf(1.0 / concentration);
+        return standard_gamma(rng, concentration + 1.0) * boost;
+    }
+    let d = concentration - 1.0 / 3.0;
+    let c = 1.0 / (9.0 * d).sqrt();
+    loop {
+        let x = standard_normal(rng);
+        let v = (1.0 + c * x).powi(3);
+        if v <= 0.0 {
+            continue;
+        }
+        let u = open_uniform(rng);
+        if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v;
+        }
+    }
+}
+
+/// Returns a Poisson draw with `rate`, by inversion for small rates and by the transformed
+/// rejection method of Hörmann for large ones.
+fn poisson<R: Rng + ?Sized>(rng: &mut R, rate: f64) -> u64 {
+    if rate < 10.0 {
+        let threshold = (-rate).exp();
+        let mut k = 0;
+        let mut product = rng.gen::<f64>();
+        while product > threshold {
+            k += 1;
+            product *= rng.gen::<f64>();
+        }
+        return k;
+    }
+    let sqrt_rate = rate.sqrt();
+    let b = 0.931 + 2.53 * sqrt_rate;
+    let a = -0.059 + 0.02483 * b;
+    let inv_alpha = 1.1239 + 1.1328 / (b - 3.4);
+    let v_r = 0.9277 - 3.6224 / (b - 2.0);
+    loop {
+        let u = rng.gen::<f64>() - 0.5;
+        let v = open_uniform(rng);
+        let us = 0.5 - u.abs();
+        let k = ((2.0 * a / us + b) * u + rate + 0.43).floor();
+        if us >= 0.07 && v <= v_r {
+            return k as u64;
+        }
+        if k < 0.0 || (us < 0.013 && v > us) {
+            continue;
+        }
+        if v.ln() + inv_alpha.ln() - (a / (us * us) + b).ln()
+            <= -rate + k * rate.ln() - ln_gamma(k + 1.0)
+        {
+            return k as u64;
+        }
+    }
+}
+
+impl Distribution<f64> for ScalarDistribution {
+    /// Draws a value of the distribution, which is a whole number for the discrete families.
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        match *self {
+            Self::Bernoulli { probs } => (rng.gen::<f64>() < probs) as u64 as f64,
+            Self::Beta {
+                concentration1,
+                concentration0,
+            } => {
+                let x = standard_gamma(rng, concentration1);
+                let y = standard_gamma(rng, concentration0);
+                x / (x + y)
+            }
+            Self::Cauchy { median, scale } => {
+                median + scale * (PI * (rng.gen::<f64>() - 0.5)).tan()
+            }
+            Self::Exponential { rate } => -open_uniform(rng).ln() / rate,
+            Self::Gamma {
+                concentration,
+                rate,
+            } => standard_gamma(rng, concentration) / rate,
+            // The number of failures before the first success, like `Geometric::sample`.
+            Self::Geometric { probs } => (open_uniform(rng).ln() / (-probs).ln_1p()).floor(),
+            Self::Normal { mean, stddev } => mean + stddev * standard_normal(rng),
+            Self::Poisson { rate } => poisson(rng, rate) as f64,
+            Self::Uniform { low, high } => low + (high - low) * rng.gen::<f64>(),
+        }
+    }
+}
+
+impl Distribution<f64> for Normal {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        scalar(self).sample(rng)
+    }
+}
+
+impl Distribution<f64> for Uniform {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        scalar(self).sample(rng)
+    }
+}
+
+impl Distribution<f64> for Exponential {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        scalar(self).sample(rng)
+    }
+}
+
+impl Distribution<f64> for Cauchy {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        scalar(self).sample(rng)
+    }
+}
+
+impl Distribution<f64> for Gamma {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        scalar(self).sample(rng)
+    }
+}
+
+impl Distribution<f64> for Beta {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        scalar(self).sample(rng)
+    }
+}
+
+impl Distribution<u64> for Bernoulli {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        scalar(self).sample(rng) as u64
+    }
+}
+
+impl Distribution<u64> for Geometric {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        scalar(self).sample(rng) as u64
+    }
+}
+
+impl Distribution<u64> for Poisson {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        scalar(self).sample(rng) as u64
+    }
+}
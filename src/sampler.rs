@@ -0,0 +1,48 @@
+use crate::Reparameterize;
+use tch::Tensor;
+
+/// Draws samples of a fixed shape from a distribution into reused buffers.
+///
+/// The noise and sample buffers are allocated once when the sampler is created and each call
+/// to `next` refills them in-place, so that sampling in a tight loop does not allocate for the
+/// families whose noise and reparameterization are evaluated in-place. Samples are drawn
+/// without gradients; use `rsample` when gradients are needed.
+#[derive(Debug)]
+pub struct Sampler<'a, D: Reparameterize> {
+    dist: &'a D,
+    noise: Tensor,
+    samples: Tensor,
+}
+
+impl<'a, D: Reparameterize> Sampler<'a, D> {
+    /// Creates a sampler drawing sample_shape shaped samples from `dist`.
+    pub fn new(dist: &'a D, shape: &[i64]) -> Self {
+        let (noise, samples) = tch::no_grad(|| {
+            let noise = dist.noise(shape);
+            let samples = dist.reparameterize(&noise);
+            (noise, samples)
+        });
+        Self {
+            dist,
+            noise,
+            samples,
+        }
+    }
+
+    /// Draws a new batch of samples into the sample buffer and returns it. The returned tensor
+    /// is overwritten by the next call, `copy` it to keep it around.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> &Tensor {
+        tch::no_grad(|| {
+            self.dist.fill_noise(&mut self.noise);
+            self.dist.reparameterize_out(&mut self.samples, &self.noise);
+        });
+        &self.samples
+    }
+
+    /// Returns the samples drawn by the last call to `next`, or by the construction of the
+    /// sampler.
+    pub fn samples(&self) -> &Tensor {
+        &self.samples
+    }
+}
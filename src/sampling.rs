@@ -0,0 +1,46 @@
+//! Generic samplers for densities that are only known up to a constant.
+use crate::Distribution;
+use tch::Tensor;
+
+/// Draws sample_shape shaped samples of the possibly unnormalized density `target_log_prob`
+/// by rejection sampling from `proposal`.
+///
+/// Proposals are drawn in batches of the requested size and a proposal `x` is accepted with
+/// probability `exp(target_log_prob(x) - log_m - proposal.log_prob(x))`, so `log_m` must
+/// bound `target_log_prob(x) - proposal.log_prob(x)` from above everywhere. Batches are drawn
+/// until enough samples have been accepted, which takes `exp(log_m) / Z` batches on average
+/// for a target with normalizing constant `Z`.
+///
+/// `target_log_prob` is called with `[n, event...]` shaped proposals and returns `[n]` shaped
+/// log densities. The proposal must have an empty batch shape.
+pub fn rejection<D, P>(target_log_prob: P, proposal: &D, log_m: f64, shape: &[i64]) -> Tensor
+where
+    D: Distribution,
+    P: Fn(&Tensor) -> Tensor,
+{
+    assert!(
+        proposal.batch_shape().is_empty(),
+        "rejection sampling requires a proposal with an empty batch shape, got {:?}",
+        proposal.batch_shape()
+    );
+    let n: i64 = shape.iter().product();
+    tch::no_grad(|| {
+        let mut accepted = Vec::new();
+        let mut count = 0;
+        while count < n {
+            let proposals = proposal.sample(&[n]);
+            let log_ratio = target_log_prob(&proposals) - proposal.log_prob(&proposals) - log_m;
+            let accept = log_ratio
+                .rand_like()
+                .log()
+                .lt_tensor(&log_ratio)
+                .nonzero()
+                .squeeze_dim(-1);
+            count += accept.size()[0];
+            accepted.push(proposals.index_select(0, &accept));
+        }
+        Tensor::cat(&accepted, 0)
+            .narrow(0, 0, n)
+            .reshape(&[shape, proposal.event_shape()].concat())
+    })
+}
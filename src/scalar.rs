@@ -0,0 +1,307 @@
+//! Evaluation of distributions with scalar parameters in plain Rust.
+//!
+//! A `ScalarDistribution` is created once from a distribution whose parameters hold a single
+//! element on the CPU, after which `log_prob`, `pdf`, `cdf` and `icdf` are computed from closed
+//! forms on `f64` without going through libtorch, which avoids the dispatch overhead that
+//! dominates when scoring one value at a time.
+//!
+//! The special functions behind the closed forms are implemented in the crate rather than taken
+//! from statrs, so the feature adds no dependency. The tests check them against the tensor
+//! implementations to a relative tolerance of `1e-9`.
+use crate::special::{beta_inc, erfc, gamma_p, gamma_q, ln_gamma, normal_icdf};
+use crate::{
+    Bernoulli, Beta, Cauchy, Error, Exponential, Gamma, Geometric, Normal, Poisson, Uniform,
+};
+use std::f64::consts::PI;
+use tch::{Device, Tensor};
+
+/// A distribution with scalar parameters.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScalarDistribution {
+    Bernoulli {
+        probs: f64,
+    },
+    Beta {
+        concentration1: f64,
+        concentration0: f64,
+    },
+    Cauchy {
+        median: f64,
+        scale: f64,
+    },
+    Exponential {
+        rate: f64,
+    },
+    Gamma {
+        concentration: f64,
+        rate: f64,
+    },
+    Geometric {
+        probs: f64,
+    },
+    Normal {
+        mean: f64,
+        stddev: f64,
+    },
+    Poisson {
+        rate: f64,
+    },
+    Uniform {
+        low: f64,
+        high: f64,
+    },
+}
+
+impl ScalarDistribution {
+    /// Returns the log of the probability density or mass function at `x`, which is negative
+    /// infinity outside the support.
+    pub fn log_prob(&self, x: f64) -> f64 {
+        match *self {
+            Self::Bernoulli { probs } => {
+                if x == 1.0 {
+                    probs.ln()
+                } else if x == 0.0 {
+                    (-probs).ln_1p()
+                } else {
+                    f64::NEG_INFINITY
+                }
+            }
+            Self::Beta {
+                concentration1: a,
+                concentration0: b,
+            } => {
+                if x <= 0.0 || x >= 1.0 {
+                    return f64::NEG_INFINITY;
+                }
+                (a - 1.0) * x.ln() + (b - 1.0) * (-x).ln_1p() + ln_gamma(a + b)
+                    - ln_gamma(a)
+                    - ln_gamma(b)
+            }
+            Self::Cauchy { median, scale } => {
+                let z = (x - median) / scale;
+                -(PI * scale).ln() - z.powi(2).ln_1p()
+            }
+            Self::Exponential { rate } => {
+                if x < 0.0 {
+                    return f64::NEG_INFINITY;
+                }
+                rate.ln() - rate * x
+            }
+            Self::Gamma {
+                concentration,
+                rate,
+            } => {
+                if x <= 0.0 {
+                    return f64::NEG_INFINITY;
+                }
+                concentration * rate.ln() + (concentration - 1.0) * x.ln()
+                    - rate * x
+                    - ln_gamma(concentration)
+            }
+            Self::Geometric { probs } => {
+                if x < 0.0 || x.fract() != 0.0 {
+                    return f64::NEG_INFINITY;
+                }
+                x * (-probs).ln_1p() + probs.ln()
+            }
+            Self::Normal { mean, stddev } => {
+                let z = (x - mean) / stddev;
+                -0.5 * z * z - stddev.ln() - 0.5 * (2.0 * PI).ln()
+            }
+            Self::Poisson { rate } => {
+                if x < 0.0 || x.fract() != 0.0 {
+                    return f64::NEG_INFINITY;
+                }
+                x * rate.ln() - rate - ln_gamma(x + 1.0)
+            }
+            Self::Uniform { low, high } => {
+                if x < low || x >= high {
+                    return f64::NEG_INFINITY;
+                }
+                -(high - low).ln()
+            }
+        }
+    }
+
+    /// Returns the probability density or mass function at `x`.
+    pub fn pdf(&self, x: f64) -> f64 {
+        self.log_prob(x).exp()
+    }
+
+    /// Returns the cumulative distribution function at `x`.
+    pub fn cdf(&self, x: f64) -> f64 {
+        match *self {
+            Self::Bernoulli { probs } => {
+                if x < 0.0 {
+                    0.0
+                } else if x < 1.0 {
+                    1.0 - probs
+                } else {
+                    1.0
+                }
+            }
+            Self::Beta {
+                concentration1,
+                concentration0,
+            } => beta_inc(concentration1, concentration0, x),
+            Self::Cauchy { median, scale } => 0.5 + ((x - median) / scale).atan() / PI,
+            Self::Exponential { rate } => {
+                if x < 0.0 {
+                    return 0.0;
+                }
+                -(-rate * x).exp_m1()
+            }
+            Self::Gamma {
+                concentration,
+                rate,
+            } => gamma_p(concentration, rate * x),
+            Self::Geometric { probs } => {
+                if x < 0.0 {
+                    return 0.0;
+                }
+                -((x.floor() + 1.0) * (-probs).ln_1p()).exp_m1()
+            }
+            Self::Normal { mean, stddev } => 0.5 * erfc(-(x - mean) / (stddev * 2f64.sqrt())),
+            Self::Poisson { rate } => {
+                if x < 0.0 {
+                    return 0.0;
+                }
+                gamma_q(x.floor() + 1.0, rate)
+            }
+            Self::Uniform { low, high } => ((x - low) / (high - low)).clamp(0.0, 1.0),
+        }
+    }
+
+    /// Returns the inverse cumulative distribution function at `p`.
+    ///
+    /// Fails with `Error::Evaluation` for the families whose quantile function has no closed
+    /// form.
+    pub fn icdf(&self, p: f64) -> Result<f64, Error> {
+        Ok(match *self {
+            Self::Cauchy { median, scale } => median + scale * (PI * (p - 0.5)).tan(),
+            Self::Exponential { rate } => -(-p).ln_1p() / rate,
+            Self::Normal { mean, stddev } => mean + stddev * normal_icdf(p),
+            Self::Uniform { low, high } => low + p * (high - low),
+            _ => {
+                return Err(Error::Evaluation(format!(
+                    "icdf of {:?} has no closed form",
+                    self
+                )))
+            }
+        })
+    }
+}
+
+/// Returns the value of the parameter `t`, which must hold a single element on the CPU.
+fn scalar(t: &Tensor, name: &str) -> Result<f64, Error> {
+    if t.numel() != 1 {
+        return Err(Error::Shape(format!(
+            "scalar evaluation needs a scalar {}, got shape {:?}",
+            name,
+            t.size()
+        )));
+    }
+    if t.device() != Device::Cpu {
+        return Err(Error::Shape(format!(
+            "scalar evaluation needs {} on the CPU, got {:?}",
+            name,
+            t.device()
+        )));
+    }
+    Ok(t.double_value(&[]))
+}
+
+impl TryFrom<&Bernoulli> for ScalarDistribution {
+    type Error = Error;
+
+    fn try_from(dist: &Bernoulli) -> Result<Self, Error> {
+        Ok(Self::Bernoulli {
+            probs: scalar(dist.probs(), "probs")?,
+        })
+    }
+}
+
+impl TryFrom<&Beta> for ScalarDistribution {
+    type Error = Error;
+
+    fn try_from(dist: &Beta) -> Result<Self, Error> {
+        Ok(Self::Beta {
+            concentration1: scalar(dist.concentration1(), "concentration1")?,
+            concentration0: scalar(dist.concentration0(), "concentration0")?,
+        })
+    }
+}
+
+impl TryFrom<&Cauchy> for ScalarDistribution {
+    type Error = Error;
+
+    fn try_from(dist: &Cauchy) -> Result<Self, Error> {
+        Ok(Self::Cauchy {
+            median: scalar(dist.median(), "median")?,
+            scale: scalar(dist.scale(), "scale")?,
+        })
+    }
+}
+
+impl TryFrom<&Exponential> for ScalarDistribution {
+    type Error = Error;
+
+    fn try_from(dist: &Exponential) -> Result<Self, Error> {
+        Ok(Self::Exponential {
+            rate: scalar(dist.rate(), "rate")?,
+        })
+    }
+}
+
+impl TryFrom<&Gamma> for ScalarDistribution {
+    type Error = Error;
+
+    fn try_from(dist: &Gamma) -> Result<Self, Error> {
+        Ok(Self::Gamma {
+            concentration: scalar(dist.concentration(), "concentration")?,
+            rate: scalar(dist.rate(), "rate")?,
+        })
+    }
+}
+
+impl TryFrom<&Geometric> for ScalarDistribution {
+    type Error = Error;
+
+    fn try_from(dist: &Geometric) -> Result<Self, Error> {
+        Ok(Self::Geometric {
+            probs: scalar(dist.probs(), "probs")?,
+        })
+    }
+}
+
+impl TryFrom<&Normal> for ScalarDistribution {
+    type Error = Error;
+
+    fn try_from(dist: &Normal) -> Result<Self, Error> {
+        Ok(Self::Normal {
+            mean: scalar(dist.mean(), "mean")?,
+            stddev: scalar(dist.stddev(), "stddev")?,
+        })
+    }
+}
+
+impl TryFrom<&Poisson> for ScalarDistribution {
+    type Error = Error;
+
+    fn try_from(dist: &Poisson) -> Result<Self, Error> {
+        Ok(Self::Poisson {
+            rate: scalar(dist.rate(), "rate")?,
+        })
+    }
+}
+
+impl TryFrom<&Uniform> for ScalarDistribution {
+    type Error = Error;
+
+    fn try_from(dist: &Uniform) -> Result<Self, Error> {
+        Ok(Self::Uniform {
+            low: scalar(dist.low(), "low")?,
+            high: scalar(dist.high(), "high")?,
+        })
+    }
+}
@@ -0,0 +1,165 @@
+//! Sequential Monte Carlo for state-space models.
+use crate::{numerics, utils::sum_rightmost, Distribution};
+use tch::{Kind, Tensor};
+
+/// Scheme drawing the ancestors of the particles when resampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resampling {
+    /// Independent draws from the weights.
+    Multinomial,
+    /// A single uniform offset shared by all `n` evenly spaced strata.
+    Systematic,
+    /// An independent uniform draw within each of `n` evenly spaced strata.
+    Stratified,
+}
+
+/// Returns `[n]` shaped indices of ancestors drawn with `scheme` for the `[n]` shaped
+/// normalized log weights `log_weights`.
+pub fn resample(log_weights: &Tensor, scheme: Resampling) -> Tensor {
+    let n = log_weights.size()[0];
+    let weights = log_weights.softmax(0, Kind::Double);
+    let offsets = match scheme {
+        Resampling::Multinomial => return weights.multinomial(n, true),
+        Resampling::Systematic => Tensor::rand(&[1], (Kind::Double, weights.device())),
+        Resampling::Stratified => Tensor::rand(&[n], (Kind::Double, weights.device())),
+    };
+    let positions = (Tensor::arange(n, (Kind::Double, weights.device())) + offsets) / n as f64;
+    let cdf = weights.cumsum(0, Kind::Double);
+    positions
+        .searchsorted::<Tensor>(&cdf, false, true, "right", None)
+        .clamp_max(n - 1)
+}
+
+/// A particle filter with adaptive resampling.
+///
+/// The particles are a `[n_particles, dim...]` shaped tensor. In every step they are moved
+/// with a proposal distribution batched over the particles and reweighted, then resampled if
+/// their effective sample size falls below a fraction of the number of particles.
+#[derive(Debug)]
+pub struct ParticleFilter {
+    particles: Tensor,
+    log_weights: Tensor,
+    resampling: Resampling,
+    ess_threshold: f64,
+    log_evidence: Tensor,
+    ess_history: Vec<f64>,
+}
+
+impl ParticleFilter {
+    /// Creates a filter with equally weighted initial `particles` which resamples with
+    /// `resampling` whenever the effective sample size falls below `ess_threshold` times the
+    /// number of particles.
+    pub fn new(particles: Tensor, resampling: Resampling, ess_threshold: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&ess_threshold),
+            "ess threshold must be in [0, 1], got {}",
+            ess_threshold
+        );
+        let n = particles.size()[0];
+        let log_weights = Tensor::full(&[n], -(n as f64).ln(), (Kind::Double, particles.device()));
+        Self {
+            log_evidence: Tensor::zeros(&[], (Kind::Double, particles.device())),
+            particles,
+            log_weights,
+            resampling,
+            ess_threshold,
+            ess_history: Vec::new(),
+        }
+    }
+
+    /// Returns the current particles.
+    pub fn particles(&self) -> &Tensor {
+        &self.particles
+    }
+
+    /// Returns the `[n_particles]` shaped normalized log weights of the particles.
+    pub fn log_weights(&self) -> &Tensor {
+        &self.log_weights
+    }
+
+    /// Returns the effective sample size `1 / sum w^2` of the current weights.
+    pub fn ess(&self) -> f64 {
+        f64::from((-numerics::logsumexp(&(&self.log_weights * 2.0), &[0], false)).exp())
+    }
+
+    /// Returns the effective sample size after reweighting in each step so far, before any
+    /// resampling.
+    pub fn ess_history(&self) -> &[f64] {
+        &self.ess_history
+    }
+
+    /// Returns the estimate of the log marginal likelihood of all observations so far.
+    pub fn log_evidence(&self) -> &Tensor {
+        &self.log_evidence
+    }
+
+    /// Returns the weighted mean of the particles, the filtering estimate of the state.
+    pub fn mean(&self) -> Tensor {
+        let weights = self.log_weights.exp().to_kind(self.particles.kind());
+        let shape: Vec<i64> = std::iter::once(-1)
+            .chain((1..self.particles.dim()).map(|_| 1))
+            .collect();
+        (&self.particles * weights.reshape(&shape)).sum_dim_intlist(
+            &[0],
+            false,
+            self.particles.kind(),
+        )
+    }
+
+    /// Moves every particle with a sample of the distribution returned by `proposal` for the
+    /// current particles, and reweights it with `log_weight(previous, particles)` minus the log
+    /// density of the proposal. `log_weight` returns `[n_particles]` shaped values; for a
+    /// state-space model it is the sum of the log transition density and the log likelihood of
+    /// the observation. The log densities of the proposal are summed over all but the first
+    /// dimension.
+    pub fn step<D, F, W>(&mut self, proposal: F, log_weight: W)
+    where
+        D: Distribution,
+        F: FnOnce(&Tensor) -> D,
+        W: FnOnce(&Tensor, &Tensor) -> Tensor,
+    {
+        tch::no_grad(|| {
+            let proposal = proposal(&self.particles);
+            let particles = proposal.sample(&[]);
+            let log_q = proposal.log_prob(&particles);
+            let log_q = sum_rightmost(&log_q, log_q.dim() as i64 - 1);
+            let increment = log_weight(&self.particles, &particles) - log_q;
+            self.particles = particles;
+            self.reweight(increment);
+        })
+    }
+
+    /// Moves every particle with a sample of the distribution returned by `transition` for the
+    /// current particles, and reweights it with the log likelihood `log_likelihood(particles)`
+    /// of the observation. This is the bootstrap filter, using the transition density of the
+    /// model as proposal.
+    pub fn step_bootstrap<D, F, L>(&mut self, transition: F, log_likelihood: L)
+    where
+        D: Distribution,
+        F: FnOnce(&Tensor) -> D,
+        L: FnOnce(&Tensor) -> Tensor,
+    {
+        tch::no_grad(|| {
+            let particles = transition(&self.particles).sample(&[]);
+            let increment = log_likelihood(&particles);
+            self.particles = particles;
+            self.reweight(increment);
+        })
+    }
+
+    fn reweight(&mut self, increment: Tensor) {
+        let log_weights = &self.log_weights + increment.to_kind(Kind::Double);
+        let log_normalizer = numerics::logsumexp(&log_weights, &[0], false);
+        self.log_evidence += &log_normalizer;
+        self.log_weights = log_weights - log_normalizer;
+
+        let ess = self.ess();
+        self.ess_history.push(ess);
+        let n = self.log_weights.size()[0];
+        if ess < self.ess_threshold * n as f64 {
+            let ancestors = resample(&self.log_weights, self.resampling);
+            self.particles = self.particles.index_select(0, &ancestors);
+            self.log_weights = self.log_weights.full_like(-(n as f64).ln());
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{
+    utils::{float_samples, MONTE_CARLO_SAMPLES},
+    Distribution,
+};
+use tch::Tensor;
+
+/// Quantiles reported by `Distribution::summary`.
+pub const SUMMARY_QUANTILES: [f64; 5] = [0.05, 0.25, 0.5, 0.75, 0.95];
+
+/// Summary statistics of a distribution, batched over batch_shape.
+#[derive(Debug)]
+pub struct Summary {
+    /// The mean of the distribution.
+    pub mean: Tensor,
+    /// The standard deviation of the distribution.
+    pub stddev: Tensor,
+    /// Pairs of probability levels and the corresponding quantiles.
+    pub quantiles: Vec<(f64, Tensor)>,
+    /// The lower and upper bounds of the support.
+    pub support: (Tensor, Tensor),
+}
+
+pub(crate) fn summarize<D: Distribution + ?Sized>(dist: &D) -> Summary {
+    // Quantiles are estimated from samples since not every distribution implements `icdf`.
+    let samples = float_samples(dist, MONTE_CARLO_SAMPLES);
+    let quantiles = SUMMARY_QUANTILES
+        .iter()
+        .map(|&q| (q, samples.quantile_scalar(q, 0, false, "linear")))
+        .collect();
+    Summary {
+        mean: dist.mean(),
+        stddev: dist.variance().sqrt(),
+        quantiles,
+        support: dist.support_bounds(),
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    constraints::Constraint,
+    transforms::{TanhTransform, Transform},
+    Distribution, Normal, TransformedDistribution,
+};
+use std::borrow::Borrow;
+use tch::Tensor;
+
+/// A Normal distribution squashed into (-1, 1) by tanh, often used for bounded continuous
+/// actions.
+///
+/// The transform caches its last sample, so the `log_prob` of a sample drawn from this
+/// distribution does not go through `atanh`, which is inaccurate close to -1 and 1.
+#[derive(Debug)]
+pub struct TanhNormal {
+    dist: TransformedDistribution<Normal>,
+}
+
+impl TanhNormal {
+    /// Creates a `TanhNormal` distribution from the `mean` and `stddev` of the Normal
+    /// distribution before squashing.
+    pub fn new(mean: impl Borrow<Tensor>, stddev: impl Borrow<Tensor>) -> Self {
+        Self {
+            dist: TransformedDistribution::new(
+                Normal::new(mean, stddev),
+                vec![Box::new(TanhTransform.with_cache())],
+            ),
+        }
+    }
+
+    /// Returns the Normal distribution before squashing.
+    pub fn base(&self) -> &Normal {
+        self.dist.base()
+    }
+}
+
+impl Distribution for TanhNormal {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        self.dist.base().params()
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        self.dist.base().arg_constraints()
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        self.dist.log_prob(val)
+    }
+
+    fn sample(&self, shape: &[i64]) -> Tensor {
+        self.dist.sample(shape)
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        self.dist.rsample(shape)
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::interval(-1.0, 1.0)
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        let mean = self.base().mean();
+        (mean.full_like(-1.0), mean.full_like(1.0))
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        self.dist.batch_shape()
+    }
+
+    fn event_shape(&self) -> &[i64] {
+        self.dist.event_shape()
+    }
+}
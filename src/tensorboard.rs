@@ -0,0 +1,215 @@
+//! Logging of distributions to TensorBoard event files.
+//!
+//! `SummaryWriter` appends scalar and histogram summaries to an event file in a log directory,
+//! which TensorBoard reads while it is being written. The records are encoded by hand so that
+//! no protobuf dependency is needed.
+use crate::{
+    error,
+    utils::{float_samples, MONTE_CARLO_SAMPLES},
+    Distribution,
+};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tch::{Device, Kind, Tensor};
+
+/// Number of bins of the histograms written by `log_distribution`.
+const HISTOGRAM_BINS: i64 = 30;
+
+/// Returns the CRC-32C checksum of `data`, as used by the TFRecord format.
+fn crc32c(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82f6_3b78
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Returns the masked checksum of `data` stored in TFRecord files.
+fn masked_crc32c(data: &[u8]) -> u32 {
+    let crc = crc32c(data);
+    crc.rotate_right(15).wrapping_add(0xa282_ead8)
+}
+
+/// Appends the varint encoding of `value` to `buf`.
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+/// Appends the key of the protobuf field `field` with wire type `wire_type` to `buf`.
+fn put_key(buf: &mut Vec<u8>, field: u32, wire_type: u8) {
+    put_varint(buf, ((field as u64) << 3) | wire_type as u64);
+}
+
+/// Appends the length-delimited protobuf field `field` holding `data` to `buf`.
+fn put_bytes(buf: &mut Vec<u8>, field: u32, data: &[u8]) {
+    put_key(buf, field, 2);
+    put_varint(buf, data.len() as u64);
+    buf.extend_from_slice(data);
+}
+
+/// Appends the double protobuf field `field` to `buf`.
+fn put_double(buf: &mut Vec<u8>, field: u32, value: f64) {
+    put_key(buf, field, 1);
+    buf.extend_from_slice(&value.to_le_bytes());
+}
+
+/// Appends the packed repeated double protobuf field `field` to `buf`.
+fn put_doubles(buf: &mut Vec<u8>, field: u32, values: &[f64]) {
+    let data: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+    put_bytes(buf, field, &data);
+}
+
+/// Writes summaries to a TensorBoard event file.
+pub struct SummaryWriter {
+    file: BufWriter<File>,
+}
+
+impl SummaryWriter {
+    /// Creates a new event file in the log directory `logdir`, which is created if needed.
+    pub fn new<P: AsRef<Path>>(logdir: P) -> io::Result<Self> {
+        fs::create_dir_all(&logdir)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let name = format!(
+            "events.out.tfevents.{}.tch_distr.{}",
+            now.as_secs(),
+            std::process::id()
+        );
+        let mut writer = Self {
+            file: BufWriter::new(File::create(logdir.as_ref().join(name))?),
+        };
+        let mut event = Vec::new();
+        put_double(&mut event, 1, now.as_secs_f64());
+        put_bytes(&mut event, 3, b"brain.Event:2");
+        writer.write_record(&event)?;
+        writer.flush()?;
+        Ok(writer)
+    }
+
+    /// Writes `data` as a TFRecord.
+    fn write_record(&mut self, data: &[u8]) -> io::Result<()> {
+        let len = (data.len() as u64).to_le_bytes();
+        self.file.write_all(&len)?;
+        self.file.write_all(&masked_crc32c(&len).to_le_bytes())?;
+        self.file.write_all(data)?;
+        self.file.write_all(&masked_crc32c(data).to_le_bytes())
+    }
+
+    /// Writes an event with the summary value `value` at `step`.
+    fn write_summary(&mut self, value: &[u8], step: i64) -> io::Result<()> {
+        let wall_time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+        let mut summary = Vec::new();
+        put_bytes(&mut summary, 1, value);
+        let mut event = Vec::new();
+        put_double(&mut event, 1, wall_time);
+        put_key(&mut event, 2, 0);
+        put_varint(&mut event, step as u64);
+        put_bytes(&mut event, 5, &summary);
+        self.write_record(&event)
+    }
+
+    /// Adds the scalar `value` under `tag` at `step`.
+    pub fn add_scalar(&mut self, tag: &str, value: f64, step: i64) -> io::Result<()> {
+        let mut summary_value = Vec::new();
+        put_bytes(&mut summary_value, 1, tag.as_bytes());
+        put_key(&mut summary_value, 2, 5);
+        summary_value.extend_from_slice(&(value as f32).to_le_bytes());
+        self.write_summary(&summary_value, step)
+    }
+
+    /// Adds a histogram of all elements of `values` with `bins` equal width bins under `tag` at
+    /// `step`.
+    pub fn add_histogram(
+        &mut self,
+        tag: &str,
+        values: &Tensor,
+        bins: i64,
+        step: i64,
+    ) -> io::Result<()> {
+        let values = values
+            .detach()
+            .to_device(Device::Cpu)
+            .to_kind(Kind::Double)
+            .flatten(0, -1);
+        let min = values.min().double_value(&[]);
+        let max = values.max().double_value(&[]);
+        let (counts, edges) = values.histogram_bin_ct(bins, &[min, max], None::<Tensor>, false);
+        let counts = Vec::<f64>::from(&counts);
+        // The right edges of the bins.
+        let limits = Vec::<f64>::from(&edges.narrow(0, 1, bins));
+
+        let mut histogram = Vec::new();
+        put_double(&mut histogram, 1, min);
+        put_double(&mut histogram, 2, max);
+        put_double(&mut histogram, 3, values.numel() as f64);
+        put_double(
+            &mut histogram,
+            4,
+            values.sum(Kind::Double).double_value(&[]),
+        );
+        put_double(
+            &mut histogram,
+            5,
+            values.square().sum(Kind::Double).double_value(&[]),
+        );
+        put_doubles(&mut histogram, 6, &limits);
+        put_doubles(&mut histogram, 7, &counts);
+
+        let mut summary_value = Vec::new();
+        put_bytes(&mut summary_value, 1, tag.as_bytes());
+        put_bytes(&mut summary_value, 5, &histogram);
+        self.write_summary(&summary_value, step)
+    }
+
+    /// Flushes the buffered events to the event file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Logs a histogram of samples of `dist` under `tag/samples`, and its mean, standard
+/// deviation and entropy, averaged over the batch, under `tag/mean`, `tag/stddev` and
+/// `tag/entropy` at `step`. Statistics the distribution does not implement are left out.
+pub fn log_distribution<D: Distribution + ?Sized>(
+    writer: &mut SummaryWriter,
+    tag: &str,
+    dist: &D,
+    step: i64,
+) -> io::Result<()> {
+    let samples = tch::no_grad(|| float_samples(dist, MONTE_CARLO_SAMPLES));
+    writer.add_histogram(&format!("{}/samples", tag), &samples, HISTOGRAM_BINS, step)?;
+    let statistics = [
+        ("mean", error::catch(|| dist.mean())),
+        ("stddev", error::catch(|| dist.variance().sqrt())),
+        ("entropy", error::catch(|| dist.entropy())),
+    ];
+    for (name, value) in statistics {
+        if let Ok(value) = value {
+            let value = value
+                .to_kind(Kind::Double)
+                .mean(Kind::Double)
+                .double_value(&[]);
+            writer.add_scalar(&format!("{}/{}", tag, name), value, step)?;
+        }
+    }
+    writer.flush()
+}
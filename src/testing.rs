@@ -0,0 +1,116 @@
+//! Helpers to verify the gradients of distribution methods against finite differences.
+use crate::Distribution;
+use tch::{Kind, Tensor};
+
+/// Step size of the central finite differences.
+pub const GRADCHECK_EPS: f64 = 1e-6;
+
+/// Absolute tolerance when comparing analytic and numerical gradients.
+pub const GRADCHECK_ATOL: f64 = 1e-5;
+
+/// Relative tolerance when comparing analytic and numerical gradients.
+pub const GRADCHECK_RTOL: f64 = 1e-3;
+
+/// Seed used to draw identical noise for every evaluation in `gradcheck_rsample`.
+const GRADCHECK_SEED: i64 = 42;
+
+/// Returns copies of `inputs` where element `i` of input `k` is shifted by `delta`.
+fn perturb(inputs: &[Tensor], k: usize, i: i64, delta: f64) -> Vec<Tensor> {
+    inputs
+        .iter()
+        .enumerate()
+        .map(|(idx, x)| {
+            let x = x.detach().copy();
+            if idx == k {
+                let mut elem = x.view([-1]).get(i);
+                let val = f64::from(&elem);
+                let _ = elem.fill_(val + delta);
+            }
+            x
+        })
+        .collect()
+}
+
+/// Checks the analytic Jacobian of `f` with respect to `inputs` against central finite
+/// differences. The inputs are converted to f64 before evaluating `f`.
+pub fn gradcheck<F>(f: F, inputs: &[Tensor]) -> bool
+where
+    F: Fn(&[Tensor]) -> Tensor,
+{
+    let inputs: Vec<Tensor> = inputs
+        .iter()
+        .map(|x| {
+            x.detach()
+                .to_kind(Kind::Double)
+                .contiguous()
+                .set_requires_grad(true)
+        })
+        .collect();
+
+    let output = f(&inputs).flatten(0, -1);
+    let n_outputs = output.size()[0];
+    let rows: Vec<Vec<Tensor>> = (0..n_outputs)
+        .map(|j| {
+            Tensor::run_backward(&[output.get(j)], &inputs, true, false)
+                .into_iter()
+                .zip(inputs.iter())
+                .map(|(grad, x)| {
+                    if grad.defined() {
+                        grad.flatten(0, -1)
+                    } else {
+                        x.zeros_like().flatten(0, -1)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+
+    inputs.iter().enumerate().all(|(k, x)| {
+        let analytic_rows: Vec<&Tensor> = rows.iter().map(|row| &row[k]).collect();
+        let analytic = Tensor::stack(&analytic_rows, 0);
+        let numerical_columns: Vec<Tensor> = tch::no_grad(|| {
+            (0..x.numel() as i64)
+                .map(|i| {
+                    let plus = f(&perturb(&inputs, k, i, GRADCHECK_EPS)).flatten(0, -1);
+                    let minus = f(&perturb(&inputs, k, i, -GRADCHECK_EPS)).flatten(0, -1);
+                    (plus - minus) / (2.0 * GRADCHECK_EPS)
+                })
+                .collect()
+        });
+        let numerical = Tensor::stack(&numerical_columns, 1).to_kind(Kind::Double);
+        numerical.allclose(
+            &analytic.to_kind(Kind::Double),
+            GRADCHECK_RTOL,
+            GRADCHECK_ATOL,
+            false,
+        )
+    })
+}
+
+/// Checks the gradients of `log_prob(value)` with respect to the parameters `params` of the
+/// distribution created by `build`.
+pub fn gradcheck_log_prob<D, F>(build: F, params: &[Tensor], value: &Tensor) -> bool
+where
+    D: Distribution,
+    F: Fn(&[Tensor]) -> D,
+{
+    let value = value.to_kind(Kind::Double);
+    gradcheck(|params| build(params).log_prob(&value), params)
+}
+
+/// Checks the gradients of `rsample(shape)` with respect to the parameters `params` of the
+/// distribution created by `build`. The random number generator is reseeded before every
+/// evaluation so that all evaluations share the same noise.
+pub fn gradcheck_rsample<D, F>(build: F, params: &[Tensor], shape: &[i64]) -> bool
+where
+    D: Distribution,
+    F: Fn(&[Tensor]) -> D,
+{
+    gradcheck(
+        |params| {
+            tch::manual_seed(GRADCHECK_SEED);
+            build(params).rsample(shape)
+        },
+        params,
+    )
+}
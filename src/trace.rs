@@ -0,0 +1,115 @@
+//! Instrumentation of the distributions with `tracing`, enabled by the `trace` feature.
+//!
+//! Constructors emit a debug event with the shapes, kinds and devices of the parameters, and a
+//! warning when a parameter holds values that are not finite. `sample`, `rsample`, `log_prob`
+//! and `kl_divergence` run inside debug spans recording the family, the shapes and the device,
+//! so subscribers recording span closes report their timing. Without the feature all functions
+//! compile to nothing.
+use crate::Distribution;
+use tch::Tensor;
+
+/// Guard of an entered span, which is exited when the guard is dropped.
+#[cfg(feature = "trace")]
+pub(crate) type Guard = tracing::span::EnteredSpan;
+
+/// Guard of an entered span, which is exited when the guard is dropped.
+#[cfg(not(feature = "trace"))]
+pub(crate) struct Guard;
+
+/// Returns the name of the family `D` without its module path and generic arguments, so
+/// that `Independent<Normal>` is reported as `Independent`.
+#[cfg(feature = "trace")]
+fn family<D: ?Sized>() -> &'static str {
+    let name = std::any::type_name::<D>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Returns the device of the first parameter of `dist`.
+#[cfg(feature = "trace")]
+fn device<D: Distribution + ?Sized>(dist: &D) -> Option<tch::Device> {
+    dist.params().first().map(|(_, p)| p.device())
+}
+
+/// Records the construction of a `family` distribution from the parameters `params`.
+#[cfg(feature = "trace")]
+pub(crate) fn construct(family: &'static str, params: &[(&'static str, &Tensor)]) {
+    let shapes: Vec<_> = params
+        .iter()
+        .map(|(name, p)| (*name, p.size(), p.kind(), p.device()))
+        .collect();
+    tracing::debug!(family, params = ?shapes, "construct");
+    if tracing::enabled!(tracing::Level::WARN) {
+        for (name, p) in params {
+            if p.is_floating_point() && !bool::from(&p.isfinite().all()) {
+                tracing::warn!(family, param = name, "parameter holds non-finite values");
+            }
+        }
+    }
+}
+
+/// Records the construction of a `family` distribution from the parameters `params`.
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+pub(crate) fn construct(_family: &'static str, _params: &[(&'static str, &Tensor)]) {}
+
+/// Enters the span of sampling a `shape` shaped sample of `dist`.
+#[cfg(feature = "trace")]
+pub(crate) fn sample<D: Distribution>(dist: &D, shape: &[i64]) -> Guard {
+    tracing::debug_span!(
+        "sample",
+        family = family::<D>(),
+        batch_shape = ?dist.batch_shape(),
+        sample_shape = ?shape,
+        device = ?device(dist),
+    )
+    .entered()
+}
+
+/// Enters the span of sampling a `shape` shaped sample of `dist`.
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+pub(crate) fn sample<D: Distribution>(_dist: &D, _shape: &[i64]) -> Guard {
+    Guard
+}
+
+/// Enters the span of evaluating the log-probabilities of `val` under `dist`.
+#[cfg(feature = "trace")]
+pub(crate) fn log_prob<D: Distribution>(dist: &D, val: &Tensor) -> Guard {
+    tracing::debug_span!(
+        "log_prob",
+        family = family::<D>(),
+        batch_shape = ?dist.batch_shape(),
+        value_shape = ?val.size(),
+        device = ?device(dist),
+    )
+    .entered()
+}
+
+/// Enters the span of evaluating the log-probabilities of `val` under `dist`.
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+pub(crate) fn log_prob<D: Distribution>(_dist: &D, _val: &Tensor) -> Guard {
+    Guard
+}
+
+/// Enters the span of computing the KL divergence from `q` to `p`.
+#[cfg(feature = "trace")]
+pub(crate) fn kl_divergence<P: Distribution, Q: Distribution>(p: &P, q: &Q) -> Guard {
+    tracing::debug_span!(
+        "kl_divergence",
+        p = family::<P>(),
+        q = family::<Q>(),
+        p_batch_shape = ?p.batch_shape(),
+        q_batch_shape = ?q.batch_shape(),
+        device = ?device(p),
+    )
+    .entered()
+}
+
+/// Enters the span of computing the KL divergence from `q` to `p`.
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+pub(crate) fn kl_divergence<P: Distribution, Q: Distribution>(_p: &P, _q: &Q) -> Guard {
+    Guard
+}
@@ -0,0 +1,120 @@
+use crate::{
+    constraints::Constraint,
+    transforms::{ComposeTransform, Transform},
+    utils::sum_rightmost,
+    Distribution,
+};
+use tch::Tensor;
+
+/// A distribution obtained by applying a chain of transforms to samples of a base
+/// distribution.
+#[derive(Debug)]
+pub struct TransformedDistribution<D: Distribution> {
+    base: D,
+    transforms: Vec<Box<dyn Transform>>,
+    batch_shape: Vec<i64>,
+    event_shape: Vec<i64>,
+}
+
+impl<D: Distribution> TransformedDistribution<D> {
+    /// Creates a distribution of `transforms` applied in order to samples of `base`.
+    pub fn new(base: D, transforms: Vec<Box<dyn Transform>>) -> Self {
+        let base_shape = [base.batch_shape(), base.event_shape()].concat();
+        let base_event_dim = base.event_shape().len() as i64;
+
+        let transform = ComposeTransform::new(transforms);
+        let domain_event_dim = transform.domain().event_dim();
+        assert!(
+            base_shape.len() as i64 >= domain_event_dim,
+            "base distribution needs at least {} dimensions, got shape {:?}",
+            domain_event_dim,
+            base_shape
+        );
+        let shape = transform.forward_shape(&base_shape);
+        let event_dim =
+            transform.codomain().event_dim() + (base_event_dim - domain_event_dim).max(0);
+        let (batch_shape, event_shape) = shape.split_at(shape.len() - event_dim as usize);
+        let (batch_shape, event_shape) = (batch_shape.to_vec(), event_shape.to_vec());
+
+        let transforms = transform.into_parts();
+        Self {
+            base,
+            transforms,
+            batch_shape,
+            event_shape,
+        }
+    }
+
+    /// Returns the base distribution.
+    pub fn base(&self) -> &D {
+        &self.base
+    }
+
+    /// Returns the transforms applied to samples of the base distribution.
+    pub fn transforms(&self) -> &[Box<dyn Transform>] {
+        &self.transforms
+    }
+
+    fn forward(&self, x: Tensor) -> Tensor {
+        self.transforms
+            .iter()
+            .fold(x, |x, transform| transform.forward(&x))
+    }
+}
+
+impl<D: Distribution> Distribution for TransformedDistribution<D> {
+    fn support(&self) -> Constraint {
+        let support = match self.transforms.last() {
+            Some(transform) => transform.codomain(),
+            None => return self.base.support(),
+        };
+        let extra = self.event_shape.len() as i64 - support.event_dim();
+        if extra > 0 {
+            Constraint::independent(support, extra)
+        } else {
+            support
+        }
+    }
+
+    fn log_prob(&self, val: &Tensor) -> Tensor {
+        self.validate_sample(val);
+        let mut event_dim = self.event_shape.len() as i64;
+        // Subtracting in place needs the sample and batch dimensions up front.
+        let size = val.size();
+        let batch_dims = size.len().saturating_sub(self.event_shape.len());
+        let mut log_prob = Tensor::zeros(&size[..batch_dims], (val.kind(), val.device()));
+        let mut y = val.shallow_clone();
+        for transform in self.transforms.iter().rev() {
+            let x = transform.inverse(&y);
+            event_dim += transform.domain().event_dim() - transform.codomain().event_dim();
+            log_prob -= sum_rightmost(
+                &transform
+                    .log_abs_det_jacobian(&x, &y)
+                    .unwrap_or_else(|err| panic!("{}", err)),
+                event_dim - transform.domain().event_dim(),
+            );
+            y = x;
+        }
+        log_prob
+            + sum_rightmost(
+                &self.base.log_prob(&y),
+                event_dim - self.base.event_shape().len() as i64,
+            )
+    }
+
+    fn sample(&self, shape: &[i64]) -> Tensor {
+        tch::no_grad(|| self.forward(self.base.sample(shape)))
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        self.forward(self.base.rsample(shape))
+    }
+
+    fn batch_shape(&self) -> &[i64] {
+        &self.batch_shape
+    }
+
+    fn event_shape(&self) -> &[i64] {
+        &self.event_shape
+    }
+}
@@ -0,0 +1,35 @@
+use super::Transform;
+use crate::{constraints::Constraint, Error};
+use tch::Tensor;
+
+/// Transform via the mapping `y = |x|`.
+///
+/// The transform is two-to-one. As in PyTorch, the inverse returns the positive preimage `y`.
+/// The log-determinant of the Jacobian is not available, so `log_abs_det_jacobian` returns an
+/// error.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AbsTransform;
+
+impl Transform for AbsTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        x.abs()
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        y.shallow_clone()
+    }
+
+    fn log_abs_det_jacobian(&self, _x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        Err(Error::Evaluation(
+            "AbsTransform is not bijective and has no log-determinant".to_string(),
+        ))
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::Positive
+    }
+
+    fn bijective(&self) -> bool {
+        false
+    }
+}
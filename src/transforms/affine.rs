@@ -0,0 +1,70 @@
+use super::Transform;
+use crate::{constraints::Constraint, utils::sum_rightmost, Error};
+use tch::Tensor;
+
+/// Transform via the pointwise affine mapping `y = loc + scale * x`.
+#[derive(Debug)]
+pub struct AffineTransform {
+    loc: Tensor,
+    scale: Tensor,
+    event_dim: i64,
+}
+
+impl AffineTransform {
+    /// Creates an element-wise affine transform with `loc` and `scale`.
+    pub fn new(loc: Tensor, scale: Tensor) -> Self {
+        Self::with_event_dim(loc, scale, 0)
+    }
+
+    /// Creates an affine transform acting on events of `event_dim` rightmost dimensions,
+    /// whose log determinants are summed over these dimensions.
+    pub fn with_event_dim(loc: Tensor, scale: Tensor, event_dim: i64) -> Self {
+        Self {
+            loc,
+            scale,
+            event_dim,
+        }
+    }
+
+    /// Returns the location of the transform.
+    pub fn loc(&self) -> &Tensor {
+        &self.loc
+    }
+
+    /// Returns the scale of the transform.
+    pub fn scale(&self) -> &Tensor {
+        &self.scale
+    }
+
+    fn constraint(&self) -> Constraint {
+        if self.event_dim == 0 {
+            Constraint::Real
+        } else {
+            Constraint::independent(Constraint::Real, self.event_dim)
+        }
+    }
+}
+
+impl Transform for AffineTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        &self.loc + &self.scale * x
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        (y - &self.loc) / &self.scale
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        let shape = x.size();
+        let result = self.scale.abs().log().expand(&shape, false);
+        Ok(sum_rightmost(&result, self.event_dim))
+    }
+
+    fn domain(&self) -> Constraint {
+        self.constraint()
+    }
+
+    fn codomain(&self) -> Constraint {
+        self.constraint()
+    }
+}
@@ -0,0 +1,88 @@
+use super::Transform;
+use crate::{constraints::Constraint, Error};
+use std::cell::RefCell;
+use tch::Tensor;
+
+/// Wraps a transform and caches the last pair `(x, y)` it has seen, like a transform with
+/// `cache_size=1` in PyTorch.
+///
+/// When `inverse` is called with the very tensor that was last returned by `forward`, the
+/// cached input is returned instead of being recomputed, and vice versa. This makes the
+/// `log_prob` of samples drawn through the transform exact and cheap, even when the inverse is
+/// expensive or numerically lossy, e.g. for tanh near saturation. Tensors are matched by
+/// identity of their storage and layout, not by value.
+#[derive(Debug)]
+pub struct CachedTransform {
+    transform: Box<dyn Transform>,
+    cache: RefCell<Option<(Tensor, Tensor)>>,
+}
+
+impl CachedTransform {
+    /// Creates a transform caching the last input and output of `transform`.
+    pub fn new(transform: Box<dyn Transform>) -> Self {
+        Self {
+            transform,
+            cache: RefCell::new(None),
+        }
+    }
+
+    /// Returns the wrapped transform.
+    pub fn transform(&self) -> &dyn Transform {
+        self.transform.as_ref()
+    }
+
+    /// Drops the cached tensors.
+    pub fn clear_cache(&self) {
+        self.cache.replace(None);
+    }
+}
+
+impl Transform for CachedTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        if let Some((cached_x, cached_y)) = self.cache.borrow().as_ref() {
+            if x.is_set_to(cached_x) {
+                return cached_y.shallow_clone();
+            }
+        }
+        let y = self.transform.forward(x);
+        self.cache
+            .replace(Some((x.shallow_clone(), y.shallow_clone())));
+        y
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        if let Some((cached_x, cached_y)) = self.cache.borrow().as_ref() {
+            if y.is_set_to(cached_y) {
+                return cached_x.shallow_clone();
+            }
+        }
+        let x = self.transform.inverse(y);
+        self.cache
+            .replace(Some((x.shallow_clone(), y.shallow_clone())));
+        x
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, y: &Tensor) -> Result<Tensor, Error> {
+        self.transform.log_abs_det_jacobian(x, y)
+    }
+
+    fn domain(&self) -> Constraint {
+        self.transform.domain()
+    }
+
+    fn codomain(&self) -> Constraint {
+        self.transform.codomain()
+    }
+
+    fn bijective(&self) -> bool {
+        self.transform.bijective()
+    }
+
+    fn forward_shape(&self, shape: &[i64]) -> Vec<i64> {
+        self.transform.forward_shape(shape)
+    }
+
+    fn inverse_shape(&self, shape: &[i64]) -> Vec<i64> {
+        self.transform.inverse_shape(shape)
+    }
+}
@@ -0,0 +1,108 @@
+use super::Transform;
+use crate::{constraints::Constraint, Error};
+use tch::{nn::Module, Tensor};
+
+/// RealNVP-style affine coupling layer acting on vectors over the rightmost dimension.
+///
+/// The input is split into a conditioning part, which is passed through unchanged, and a
+/// transformed part `x2`, mapped to `x2 * exp(s) + t` where `s` and `t` are computed by the
+/// `scale_net` and `shift_net` modules from the conditioning part. The log-determinant of the
+/// Jacobian is the sum of `s` over the transformed elements.
+#[derive(Debug)]
+pub struct AffineCouplingTransform {
+    split: i64,
+    flipped: bool,
+    scale_net: Box<dyn Module>,
+    shift_net: Box<dyn Module>,
+}
+
+impl AffineCouplingTransform {
+    /// Creates a coupling layer conditioning on the first `split` elements of the rightmost
+    /// dimension and transforming the remaining ones.
+    ///
+    /// Both networks map a tensor with `split` elements in its rightmost dimension to a tensor
+    /// with as many elements as the transformed part.
+    pub fn new(split: i64, scale_net: Box<dyn Module>, shift_net: Box<dyn Module>) -> Self {
+        assert!(split > 0, "split must be positive, got {}", split);
+        Self {
+            split,
+            flipped: false,
+            scale_net,
+            shift_net,
+        }
+    }
+
+    /// Conditions on the last `split` elements instead and transforms the leading ones, so that
+    /// alternating layers transform every element.
+    pub fn flipped(mut self) -> Self {
+        self.flipped = !self.flipped;
+        self
+    }
+
+    /// Returns the number of elements the layer conditions on.
+    pub fn split(&self) -> i64 {
+        self.split
+    }
+
+    /// Splits `t` into its conditioning and transformed parts.
+    fn partition(&self, t: &Tensor) -> (Tensor, Tensor) {
+        let n = *t
+            .size()
+            .last()
+            .expect("input must have at least one dimension");
+        assert!(
+            self.split < n,
+            "split {} must be smaller than the event size {}",
+            self.split,
+            n
+        );
+        if self.flipped {
+            (
+                t.narrow(-1, n - self.split, self.split),
+                t.narrow(-1, 0, n - self.split),
+            )
+        } else {
+            (
+                t.narrow(-1, 0, self.split),
+                t.narrow(-1, self.split, n - self.split),
+            )
+        }
+    }
+
+    /// Joins the conditioning and transformed parts back into a single tensor.
+    fn join(&self, cond: &Tensor, transformed: &Tensor) -> Tensor {
+        if self.flipped {
+            Tensor::cat(&[transformed, cond], -1)
+        } else {
+            Tensor::cat(&[cond, transformed], -1)
+        }
+    }
+}
+
+impl Transform for AffineCouplingTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        let (cond, x2) = self.partition(x);
+        let y2 = x2 * self.scale_net.forward(&cond).exp() + self.shift_net.forward(&cond);
+        self.join(&cond, &y2)
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        let (cond, y2) = self.partition(y);
+        let x2 = (y2 - self.shift_net.forward(&cond)) * (-self.scale_net.forward(&cond)).exp();
+        self.join(&cond, &x2)
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        let (cond, _) = self.partition(x);
+        let scale = self.scale_net.forward(&cond);
+        Ok(scale.sum_dim_intlist(&[-1], false, scale.kind()))
+    }
+
+    fn domain(&self) -> Constraint {
+        Constraint::independent(Constraint::Real, 1)
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::independent(Constraint::Real, 1)
+    }
+}
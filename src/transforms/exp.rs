@@ -0,0 +1,47 @@
+use super::Transform;
+use crate::{constraints::Constraint, Error};
+use tch::Tensor;
+
+/// Transform via the mapping `y = exp(x)`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ExpTransform;
+
+impl Transform for ExpTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        x.exp()
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        y.log()
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        Ok(x.shallow_clone())
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::Positive
+    }
+}
+
+/// Transform via the mapping `y = log(x)`, the inverse of `ExpTransform`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogTransform;
+
+impl Transform for LogTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        x.log()
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        y.exp()
+    }
+
+    fn log_abs_det_jacobian(&self, _x: &Tensor, y: &Tensor) -> Result<Tensor, Error> {
+        Ok(-y)
+    }
+
+    fn domain(&self) -> Constraint {
+        Constraint::Positive
+    }
+}
@@ -0,0 +1,74 @@
+use super::Transform;
+use crate::{constraints::Constraint, utils::sum_rightmost, Error};
+use tch::Tensor;
+
+/// Wraps a transform and reinterprets `reinterpreted_batch_ndims` of its rightmost batch
+/// dimensions as event dimensions, over which the log-determinants are summed.
+#[derive(Debug)]
+pub struct IndependentTransform {
+    base: Box<dyn Transform>,
+    reinterpreted_batch_ndims: i64,
+}
+
+impl IndependentTransform {
+    /// Creates a transform applying `base` and treating `reinterpreted_batch_ndims` additional
+    /// rightmost dimensions as part of the event.
+    pub fn new(base: Box<dyn Transform>, reinterpreted_batch_ndims: i64) -> Self {
+        assert!(
+            reinterpreted_batch_ndims >= 0,
+            "reinterpreted_batch_ndims must be non-negative, got {}",
+            reinterpreted_batch_ndims
+        );
+        Self {
+            base,
+            reinterpreted_batch_ndims,
+        }
+    }
+
+    /// Returns the wrapped transform.
+    pub fn base(&self) -> &dyn Transform {
+        self.base.as_ref()
+    }
+
+    /// Returns the number of batch dimensions reinterpreted as event dimensions.
+    pub fn reinterpreted_batch_ndims(&self) -> i64 {
+        self.reinterpreted_batch_ndims
+    }
+}
+
+impl Transform for IndependentTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        self.base.forward(x)
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        self.base.inverse(y)
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, y: &Tensor) -> Result<Tensor, Error> {
+        Ok(sum_rightmost(
+            &self.base.log_abs_det_jacobian(x, y)?,
+            self.reinterpreted_batch_ndims,
+        ))
+    }
+
+    fn domain(&self) -> Constraint {
+        Constraint::independent(self.base.domain(), self.reinterpreted_batch_ndims)
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::independent(self.base.codomain(), self.reinterpreted_batch_ndims)
+    }
+
+    fn bijective(&self) -> bool {
+        self.base.bijective()
+    }
+
+    fn forward_shape(&self, shape: &[i64]) -> Vec<i64> {
+        self.base.forward_shape(shape)
+    }
+
+    fn inverse_shape(&self, shape: &[i64]) -> Vec<i64> {
+        self.base.inverse_shape(shape)
+    }
+}
@@ -0,0 +1,211 @@
+//! Bijective transforms between constrained and unconstrained spaces.
+use crate::{constraints::Constraint, utils::sum_rightmost, Error};
+use std::fmt::Debug;
+use tch::Tensor;
+
+mod abs;
+mod affine;
+mod cache;
+mod coupling;
+mod exp;
+mod independent;
+mod power;
+mod registry;
+mod reshape;
+mod sigmoid;
+mod softmax;
+mod softplus;
+mod spline;
+mod stick_breaking;
+mod tanh;
+
+pub use abs::AbsTransform;
+pub use affine::AffineTransform;
+pub use cache::CachedTransform;
+pub use coupling::AffineCouplingTransform;
+pub use exp::{ExpTransform, LogTransform};
+pub use independent::IndependentTransform;
+pub use power::PowerTransform;
+pub use registry::{biject_to, transform_to};
+pub use reshape::ReshapeTransform;
+pub use sigmoid::{LogitTransform, SigmoidTransform};
+pub use softmax::SoftmaxTransform;
+pub use softplus::SoftplusTransform;
+pub use spline::RationalQuadraticSplineTransform;
+pub use stick_breaking::StickBreakingTransform;
+pub use tanh::TanhTransform;
+
+/// An invertible transform `y = f(x)` with a computable log absolute determinant of its
+/// Jacobian.
+pub trait Transform: Debug {
+    /// Computes `y = f(x)`.
+    fn forward(&self, x: &Tensor) -> Tensor;
+
+    /// Computes `x = f^-1(y)`.
+    fn inverse(&self, y: &Tensor) -> Tensor;
+
+    /// Returns log |dy/dx| given the input `x` and the output `y`, or an error if the transform
+    /// is not bijective and has no log-determinant.
+    fn log_abs_det_jacobian(&self, x: &Tensor, y: &Tensor) -> Result<Tensor, Error>;
+
+    /// Returns the constraint of the valid inputs.
+    fn domain(&self) -> Constraint {
+        Constraint::Real
+    }
+
+    /// Returns the constraint of the outputs.
+    fn codomain(&self) -> Constraint {
+        Constraint::Real
+    }
+
+    /// Returns whether the transform is bijective.
+    fn bijective(&self) -> bool {
+        true
+    }
+
+    /// Returns the output shape for an input of shape `shape`.
+    fn forward_shape(&self, shape: &[i64]) -> Vec<i64> {
+        shape.to_vec()
+    }
+
+    /// Returns the input shape for an output of shape `shape`.
+    fn inverse_shape(&self, shape: &[i64]) -> Vec<i64> {
+        shape.to_vec()
+    }
+
+    /// Returns a transform that caches its last input and output, see `CachedTransform`.
+    fn with_cache(self) -> CachedTransform
+    where
+        Self: Sized + 'static,
+    {
+        CachedTransform::new(Box::new(self))
+    }
+
+    /// Returns a transform that applies `self` and then `next`.
+    fn then<T: Transform + 'static>(self, next: T) -> ComposeTransform
+    where
+        Self: Sized + 'static,
+    {
+        ComposeTransform::new(vec![Box::new(self), Box::new(next)])
+    }
+}
+
+/// Composes multiple transforms in a chain, applied from first to last.
+#[derive(Debug)]
+pub struct ComposeTransform {
+    parts: Vec<Box<dyn Transform>>,
+}
+
+impl ComposeTransform {
+    /// Creates a transform that applies `parts` in order.
+    pub fn new(parts: Vec<Box<dyn Transform>>) -> Self {
+        Self { parts }
+    }
+
+    /// Returns the transforms of the chain.
+    pub fn parts(&self) -> &[Box<dyn Transform>] {
+        &self.parts
+    }
+
+    /// Consumes the chain and returns its transforms.
+    pub fn into_parts(self) -> Vec<Box<dyn Transform>> {
+        self.parts
+    }
+}
+
+impl Transform for ComposeTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        self.parts
+            .iter()
+            .fold(x.shallow_clone(), |x, part| part.forward(&x))
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        self.parts
+            .iter()
+            .rev()
+            .fold(y.shallow_clone(), |y, part| part.inverse(&y))
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, y: &Tensor) -> Result<Tensor, Error> {
+        if self.parts.is_empty() {
+            return Ok(x.zeros_like());
+        }
+        let mut xs = vec![x.shallow_clone()];
+        for part in &self.parts[..self.parts.len() - 1] {
+            let next = part.forward(xs.last().unwrap());
+            xs.push(next);
+        }
+        xs.push(y.shallow_clone());
+
+        let mut event_dim = self.domain().event_dim();
+        let mut result: Option<Tensor> = None;
+        for (i, part) in self.parts.iter().enumerate() {
+            let term = sum_rightmost(
+                &part.log_abs_det_jacobian(&xs[i], &xs[i + 1])?,
+                event_dim - part.domain().event_dim(),
+            );
+            event_dim += part.codomain().event_dim() - part.domain().event_dim();
+            result = Some(match result {
+                Some(result) => result + term,
+                None => term,
+            });
+        }
+        Ok(result.unwrap())
+    }
+
+    fn domain(&self) -> Constraint {
+        let (first, last) = match (self.parts.first(), self.parts.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Constraint::Real,
+        };
+        let domain = first.domain();
+        let mut event_dim = last.codomain().event_dim();
+        for part in self.parts.iter().rev() {
+            event_dim += part.domain().event_dim() - part.codomain().event_dim();
+            event_dim = event_dim.max(part.domain().event_dim());
+        }
+        if event_dim > domain.event_dim() {
+            let extra = event_dim - domain.event_dim();
+            Constraint::independent(domain, extra)
+        } else {
+            domain
+        }
+    }
+
+    fn codomain(&self) -> Constraint {
+        let (first, last) = match (self.parts.first(), self.parts.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Constraint::Real,
+        };
+        let codomain = last.codomain();
+        let mut event_dim = first.domain().event_dim();
+        for part in self.parts.iter() {
+            event_dim += part.codomain().event_dim() - part.domain().event_dim();
+            event_dim = event_dim.max(part.codomain().event_dim());
+        }
+        if event_dim > codomain.event_dim() {
+            let extra = event_dim - codomain.event_dim();
+            Constraint::independent(codomain, extra)
+        } else {
+            codomain
+        }
+    }
+
+    fn bijective(&self) -> bool {
+        self.parts.iter().all(|part| part.bijective())
+    }
+
+    fn forward_shape(&self, shape: &[i64]) -> Vec<i64> {
+        self.parts
+            .iter()
+            .fold(shape.to_vec(), |shape, part| part.forward_shape(&shape))
+    }
+
+    fn inverse_shape(&self, shape: &[i64]) -> Vec<i64> {
+        self.parts
+            .iter()
+            .rev()
+            .fold(shape.to_vec(), |shape, part| part.inverse_shape(&shape))
+    }
+}
@@ -0,0 +1,43 @@
+use super::Transform;
+use crate::{constraints::Constraint, Error};
+use tch::Tensor;
+
+/// Transform via the mapping `y = x^exponent` on the positive reals.
+#[derive(Debug)]
+pub struct PowerTransform {
+    exponent: Tensor,
+}
+
+impl PowerTransform {
+    /// Creates an element-wise power transform with `exponent`.
+    pub fn new(exponent: Tensor) -> Self {
+        Self { exponent }
+    }
+
+    /// Returns the exponent of the transform.
+    pub fn exponent(&self) -> &Tensor {
+        &self.exponent
+    }
+}
+
+impl Transform for PowerTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        x.pow(&self.exponent)
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        y.pow(&self.exponent.reciprocal())
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, y: &Tensor) -> Result<Tensor, Error> {
+        Ok((&self.exponent * y / x).abs().log())
+    }
+
+    fn domain(&self) -> Constraint {
+        Constraint::Positive
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::Positive
+    }
+}
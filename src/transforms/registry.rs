@@ -0,0 +1,52 @@
+use super::{
+    AffineTransform, ComposeTransform, ExpTransform, IndependentTransform, SigmoidTransform,
+    SoftmaxTransform, StickBreakingTransform, Transform,
+};
+use crate::constraints::Constraint;
+
+/// Returns a bijective transform from unconstrained space to `constraint`, or `None` if no such
+/// transform is available.
+///
+/// The returned transform maps any real tensor into the constraint and its inverse maps values
+/// of the constraint back, which makes it suitable to optimize constrained parameters in
+/// unconstrained space while keeping track of the log-determinant of the Jacobian.
+pub fn biject_to(constraint: &Constraint) -> Option<Box<dyn Transform>> {
+    let transform: Box<dyn Transform> = match constraint {
+        Constraint::Real => Box::new(ComposeTransform::new(Vec::new())),
+        Constraint::Positive | Constraint::Nonnegative => Box::new(ExpTransform),
+        Constraint::GreaterThan(lower_bound) => Box::new(ExpTransform.then(AffineTransform::new(
+            lower_bound.shallow_clone(),
+            lower_bound.ones_like(),
+        ))),
+        Constraint::UnitInterval => Box::new(SigmoidTransform),
+        Constraint::Interval(low, high) => {
+            Box::new(SigmoidTransform.then(AffineTransform::new(low.shallow_clone(), high - low)))
+        }
+        Constraint::Simplex => Box::new(StickBreakingTransform),
+        Constraint::Independent(base, ndims) => {
+            Box::new(IndependentTransform::new(biject_to(base)?, *ndims))
+        }
+        Constraint::Boolean
+        | Constraint::IntegerGreaterThan(_)
+        | Constraint::IntegerInterval(..)
+        | Constraint::LowerCholesky
+        | Constraint::PositiveDefinite => return None,
+    };
+    Some(transform)
+}
+
+/// Returns a transform from unconstrained space to `constraint`, or `None` if no such
+/// transform is available.
+///
+/// Unlike `biject_to`, the transform does not need to be bijective, which allows for cheaper
+/// or better conditioned maps, e.g. the softmax for the simplex.
+pub fn transform_to(constraint: &Constraint) -> Option<Box<dyn Transform>> {
+    match constraint {
+        Constraint::Simplex => Some(Box::new(SoftmaxTransform)),
+        Constraint::Independent(base, ndims) => Some(Box::new(IndependentTransform::new(
+            transform_to(base)?,
+            *ndims,
+        ))),
+        _ => biject_to(constraint),
+    }
+}
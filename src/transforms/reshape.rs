@@ -0,0 +1,92 @@
+use super::Transform;
+use crate::{constraints::Constraint, Error};
+use tch::Tensor;
+
+/// Transform that reshapes the rightmost dimensions of a tensor from `in_shape` to
+/// `out_shape`, leaving the batch dimensions untouched.
+#[derive(Clone, Debug)]
+pub struct ReshapeTransform {
+    in_shape: Vec<i64>,
+    out_shape: Vec<i64>,
+}
+
+impl ReshapeTransform {
+    /// Creates a transform reshaping events of shape `in_shape` into events of shape
+    /// `out_shape`. Both shapes must have the same number of elements.
+    pub fn new(in_shape: &[i64], out_shape: &[i64]) -> Self {
+        assert_eq!(
+            in_shape.iter().product::<i64>(),
+            out_shape.iter().product::<i64>(),
+            "in_shape {:?} and out_shape {:?} have different numbers of elements",
+            in_shape,
+            out_shape
+        );
+        Self {
+            in_shape: in_shape.to_vec(),
+            out_shape: out_shape.to_vec(),
+        }
+    }
+
+    /// Returns the event shape of the inputs.
+    pub fn in_shape(&self) -> &[i64] {
+        &self.in_shape
+    }
+
+    /// Returns the event shape of the outputs.
+    pub fn out_shape(&self) -> &[i64] {
+        &self.out_shape
+    }
+}
+
+/// Replaces the trailing `from` dimensions of `shape` by `to`.
+fn replace_event_shape(shape: &[i64], from: &[i64], to: &[i64]) -> Vec<i64> {
+    assert!(
+        shape.len() >= from.len() && shape[shape.len() - from.len()..] == *from,
+        "shape {:?} does not end with the event shape {:?}",
+        shape,
+        from
+    );
+    let mut shape = shape[..shape.len() - from.len()].to_vec();
+    shape.extend_from_slice(to);
+    shape
+}
+
+fn real_constraint(event_dim: usize) -> Constraint {
+    if event_dim == 0 {
+        Constraint::Real
+    } else {
+        Constraint::independent(Constraint::Real, event_dim as i64)
+    }
+}
+
+impl Transform for ReshapeTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        x.reshape(&self.forward_shape(&x.size()))
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        y.reshape(&self.inverse_shape(&y.size()))
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        let shape = x.size();
+        let batch_shape = &shape[..shape.len() - self.in_shape.len()];
+        Ok(Tensor::zeros(batch_shape, (x.kind(), x.device())))
+    }
+
+    fn domain(&self) -> Constraint {
+        real_constraint(self.in_shape.len())
+    }
+
+    fn codomain(&self) -> Constraint {
+        real_constraint(self.out_shape.len())
+    }
+
+    fn forward_shape(&self, shape: &[i64]) -> Vec<i64> {
+        replace_event_shape(shape, &self.in_shape, &self.out_shape)
+    }
+
+    fn inverse_shape(&self, shape: &[i64]) -> Vec<i64> {
+        replace_event_shape(shape, &self.out_shape, &self.in_shape)
+    }
+}
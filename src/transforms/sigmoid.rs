@@ -0,0 +1,59 @@
+use super::Transform;
+use crate::{
+    constraints::Constraint,
+    numerics::{clamp_open_unit, log_odds},
+    Error,
+};
+use tch::Tensor;
+
+pub(super) fn clipped_sigmoid(x: &Tensor) -> Tensor {
+    clamp_open_unit(&x.sigmoid())
+}
+
+fn clipped_logit(y: &Tensor) -> Tensor {
+    log_odds(&clamp_open_unit(y))
+}
+
+/// Transform via the mapping `y = 1 / (1 + exp(-x))`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SigmoidTransform;
+
+impl Transform for SigmoidTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        clipped_sigmoid(x)
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        clipped_logit(y)
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        Ok(-(-x).softplus() - x.softplus())
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::UnitInterval
+    }
+}
+
+/// Transform via the mapping `y = log(x / (1 - x))`, the inverse of `SigmoidTransform`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LogitTransform;
+
+impl Transform for LogitTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        clipped_logit(x)
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        clipped_sigmoid(y)
+    }
+
+    fn log_abs_det_jacobian(&self, _x: &Tensor, y: &Tensor) -> Result<Tensor, Error> {
+        Ok((-y).softplus() + y.softplus())
+    }
+
+    fn domain(&self) -> Constraint {
+        Constraint::UnitInterval
+    }
+}
@@ -0,0 +1,42 @@
+use super::Transform;
+use crate::{constraints::Constraint, Error};
+use tch::Tensor;
+
+/// Transform from unconstrained space to the simplex via `y = exp(x)` followed by
+/// normalization over the rightmost dimension.
+///
+/// The transform is not bijective, since adding a constant to `x` does not change `y`. As in
+/// PyTorch, the inverse returns `log(y)`, which is only defined up to that constant, and the
+/// log-determinant of the Jacobian is not available, so `log_abs_det_jacobian` returns an error.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SoftmaxTransform;
+
+impl Transform for SoftmaxTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        let (max, _) = x.max_dim(-1, true);
+        let probs = (x - max).exp();
+        &probs / probs.sum_dim_intlist(&[-1], true, probs.kind())
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        y.log()
+    }
+
+    fn log_abs_det_jacobian(&self, _x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        Err(Error::Evaluation(
+            "SoftmaxTransform is not bijective and has no log-determinant".to_string(),
+        ))
+    }
+
+    fn domain(&self) -> Constraint {
+        Constraint::independent(Constraint::Real, 1)
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::Simplex
+    }
+
+    fn bijective(&self) -> bool {
+        false
+    }
+}
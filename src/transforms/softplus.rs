@@ -0,0 +1,25 @@
+use super::Transform;
+use crate::{constraints::Constraint, numerics::softplus_inverse, Error};
+use tch::Tensor;
+
+/// Transform via the mapping `y = log(1 + exp(x))`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SoftplusTransform;
+
+impl Transform for SoftplusTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        x.softplus()
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        softplus_inverse(y)
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        Ok(-(-x).softplus())
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::Positive
+    }
+}
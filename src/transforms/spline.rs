@@ -0,0 +1,194 @@
+use super::Transform;
+use crate::Error;
+use tch::{Kind, Tensor};
+
+/// Minimal width of a bin, as a fraction of the interval.
+const SPLINE_MIN_BIN_WIDTH: f64 = 1e-3;
+
+/// Minimal height of a bin, as a fraction of the interval.
+const SPLINE_MIN_BIN_HEIGHT: f64 = 1e-3;
+
+/// Minimal derivative of the spline at the knots.
+const SPLINE_MIN_DERIVATIVE: f64 = 1e-3;
+
+/// Knot positions and derivatives of the spline, each with the bins in the rightmost dimension.
+struct Knots {
+    cumwidths: Tensor,
+    cumheights: Tensor,
+    derivatives: Tensor,
+}
+
+/// Monotonic rational-quadratic spline on `[-bound, bound]`, extended by the identity outside of
+/// the interval, as used by neural spline flows.
+///
+/// The spline is applied element-wise. Its `K` bins are parameterized by unnormalized widths and
+/// heights of shape `[..., K]` and by unnormalized derivatives at the `K - 1` interior knots of
+/// shape `[..., K - 1]`, whose leading dimensions broadcast against the inputs. Widths and
+/// heights are normalized by a softmax and derivatives made positive by a softplus; the
+/// derivatives at the boundary knots are one so that the spline joins the identity smoothly.
+#[derive(Debug)]
+pub struct RationalQuadraticSplineTransform {
+    unnormalized_widths: Tensor,
+    unnormalized_heights: Tensor,
+    unnormalized_derivatives: Tensor,
+    bound: f64,
+}
+
+impl RationalQuadraticSplineTransform {
+    /// Creates a spline on `[-bound, bound]` from its unnormalized bin parameters.
+    pub fn new(
+        unnormalized_widths: Tensor,
+        unnormalized_heights: Tensor,
+        unnormalized_derivatives: Tensor,
+        bound: f64,
+    ) -> Self {
+        let n_bins = *unnormalized_widths
+            .size()
+            .last()
+            .expect("widths must not be scalar");
+        assert_eq!(
+            unnormalized_heights.size().last(),
+            Some(&n_bins),
+            "widths and heights must have the same number of bins"
+        );
+        assert_eq!(
+            unnormalized_derivatives.size().last(),
+            Some(&(n_bins - 1)),
+            "derivatives must be given at the {} interior knots",
+            n_bins - 1
+        );
+        assert!(
+            SPLINE_MIN_BIN_WIDTH * n_bins as f64 <= 1.0
+                && SPLINE_MIN_BIN_HEIGHT * n_bins as f64 <= 1.0,
+            "too many bins for the minimal bin size"
+        );
+        assert!(bound > 0.0, "bound must be positive, got {}", bound);
+        Self {
+            unnormalized_widths,
+            unnormalized_heights,
+            unnormalized_derivatives,
+            bound,
+        }
+    }
+
+    /// Returns the number of bins of the spline.
+    pub fn n_bins(&self) -> i64 {
+        *self.unnormalized_widths.size().last().unwrap()
+    }
+
+    /// Returns the half-width of the interval on which the spline is defined.
+    pub fn bound(&self) -> f64 {
+        self.bound
+    }
+
+    /// Turns unnormalized bin sizes into the positions of the knots on `[-bound, bound]`.
+    fn knot_positions(&self, unnormalized: &Tensor, min_size: f64) -> Tensor {
+        let n_bins = self.n_bins();
+        let sizes = unnormalized.softmax(-1, unnormalized.kind());
+        let sizes = min_size + (1.0 - min_size * n_bins as f64) * sizes;
+        let interior = sizes.narrow(-1, 0, n_bins - 1).cumsum(-1, sizes.kind()) * 2.0 * self.bound
+            - self.bound;
+        let mut shape = interior.size();
+        *shape.last_mut().unwrap() = 1;
+        let options = (interior.kind(), interior.device());
+        Tensor::cat(
+            &[
+                Tensor::full(&shape, -self.bound, options),
+                interior,
+                Tensor::full(&shape, self.bound, options),
+            ],
+            -1,
+        )
+    }
+
+    fn knots(&self) -> Knots {
+        let derivatives = &self.unnormalized_derivatives;
+        let mut shape = derivatives.size();
+        *shape.last_mut().unwrap() = 1;
+        // The boundary value is the inverse softplus of `1 - SPLINE_MIN_DERIVATIVE`.
+        let boundary = (1.0 - SPLINE_MIN_DERIVATIVE).exp_m1().ln();
+        let boundary = Tensor::full(&shape, boundary, (derivatives.kind(), derivatives.device()));
+        let derivatives = Tensor::cat(&[&boundary, derivatives, &boundary], -1);
+        Knots {
+            cumwidths: self.knot_positions(&self.unnormalized_widths, SPLINE_MIN_BIN_WIDTH),
+            cumheights: self.knot_positions(&self.unnormalized_heights, SPLINE_MIN_BIN_HEIGHT),
+            derivatives: SPLINE_MIN_DERIVATIVE + derivatives.softplus(),
+        }
+    }
+
+    /// Evaluates the spline or its inverse at `input` and returns the output together with the
+    /// log absolute derivative of the forward spline at the corresponding input.
+    fn spline(&self, input: &Tensor, inverse: bool) -> (Tensor, Tensor) {
+        let knots = self.knots();
+        let n_bins = self.n_bins();
+        let inside = input.ge(-self.bound).logical_and(&input.le(self.bound));
+        let clamped = input.clamp(-self.bound, self.bound);
+
+        let mut shape = clamped.size();
+        shape.push(n_bins + 1);
+        let cumwidths = knots.cumwidths.expand(&shape, false);
+        let cumheights = knots.cumheights.expand(&shape, false);
+        let derivatives = knots.derivatives.expand(&shape, false);
+        let locations = if inverse { &cumheights } else { &cumwidths };
+        let bin = clamped
+            .unsqueeze(-1)
+            .ge_tensor(&locations.narrow(-1, 0, n_bins))
+            .sum_dim_intlist(&[-1], true, Kind::Int64)
+            .clamp(1, n_bins)
+            - 1;
+        let next = &bin + 1;
+        let at = |t: &Tensor, idx: &Tensor| t.gather(-1, idx, false).squeeze_dim(-1);
+
+        let x_k = at(&cumwidths, &bin);
+        let width = at(&cumwidths, &next) - &x_k;
+        let y_k = at(&cumheights, &bin);
+        let height = at(&cumheights, &next) - &y_k;
+        let d_k = at(&derivatives, &bin);
+        let d_k1 = at(&derivatives, &next);
+        let delta = &height / &width;
+        let slope_sum = &d_k + &d_k1 - 2.0 * &delta;
+
+        let (output, theta) = if inverse {
+            let shifted = &clamped - &y_k;
+            let a = &height * (&delta - &d_k) + &shifted * &slope_sum;
+            let b = &height * &d_k - &shifted * &slope_sum;
+            let c = -&delta * &shifted;
+            let discriminant = (b.square() - a * &c * 4.0).clamp_min(0.0);
+            let theta = 2.0 * c / (-b - discriminant.sqrt());
+            (&theta * &width + &x_k, theta)
+        } else {
+            let theta = (&clamped - &x_k) / &width;
+            let theta_one_minus = &theta * (1.0 - &theta);
+            let numerator = &height * (&delta * theta.square() + &d_k * &theta_one_minus);
+            let denominator = &delta + &slope_sum * &theta_one_minus;
+            (&y_k + numerator / denominator, theta)
+        };
+
+        let theta_one_minus = &theta * (1.0 - &theta);
+        let denominator = &delta + &slope_sum * &theta_one_minus;
+        let derivative_numerator = delta.square()
+            * (&d_k1 * theta.square()
+                + &delta * &theta_one_minus * 2.0
+                + &d_k * (-&theta + 1.0).square());
+        let log_derivative = derivative_numerator.log() - denominator.log() * 2.0;
+
+        (
+            output.where_self(&inside, input),
+            log_derivative.where_self(&inside, &log_derivative.zeros_like()),
+        )
+    }
+}
+
+impl Transform for RationalQuadraticSplineTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        self.spline(x, false).0
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        self.spline(y, true).0
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        Ok(self.spline(x, false).1)
+    }
+}
@@ -0,0 +1,77 @@
+use super::{sigmoid::clipped_sigmoid, Transform};
+use crate::{constraints::Constraint, utils::tiny, Error};
+use tch::Tensor;
+
+/// Returns the offsets `[n, n - 1, ..., 1]` that center the stick-breaking fractions.
+fn offset(like: &Tensor, n: i64) -> Tensor {
+    let ones = Tensor::ones(&[n], (like.kind(), like.device()));
+    (n as f64) - ones.cumsum(-1, like.kind()) + 1.0
+}
+
+/// Pads the rightmost dimension of `t` with a single one, on the left or on the right.
+fn pad_one(t: &Tensor, left: bool) -> Tensor {
+    let mut shape = t.size();
+    *shape.last_mut().unwrap() = 1;
+    let ones = Tensor::ones(&shape, (t.kind(), t.device()));
+    if left {
+        Tensor::cat(&[&ones, t], -1)
+    } else {
+        Tensor::cat(&[t, &ones], -1)
+    }
+}
+
+/// Transform from unconstrained vectors of length `K - 1` to the `K`-simplex by the
+/// stick-breaking process.
+///
+/// Each element of `x` is mapped through a shifted sigmoid to the fraction of the remaining
+/// stick that is broken off. The shift makes `x = 0` map to the center of the simplex.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StickBreakingTransform;
+
+impl Transform for StickBreakingTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        let n = *x.size().last().unwrap();
+        let z = clipped_sigmoid(&(x - offset(x, n).log()));
+        let z_cumprod = (-&z + 1.0).cumprod(-1, z.kind());
+        pad_one(&z, false) * pad_one(&z_cumprod, true)
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        let n = *y.size().last().unwrap() - 1;
+        let y_crop = y.narrow(-1, 0, n);
+        let sf = (-y_crop.cumsum(-1, y.kind()) + 1.0).clamp_min(tiny(y.kind()).unwrap());
+        y_crop.log() - sf.log() + offset(y, n).log()
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, y: &Tensor) -> Result<Tensor, Error> {
+        let n = *x.size().last().unwrap();
+        let x = x - offset(x, n).log();
+        Ok(
+            (-&x + x.log_sigmoid() + y.narrow(-1, 0, n).log()).sum_dim_intlist(
+                &[-1],
+                false,
+                x.kind(),
+            ),
+        )
+    }
+
+    fn domain(&self) -> Constraint {
+        Constraint::independent(Constraint::Real, 1)
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::Simplex
+    }
+
+    fn forward_shape(&self, shape: &[i64]) -> Vec<i64> {
+        let mut shape = shape.to_vec();
+        *shape.last_mut().expect("shape must not be empty") += 1;
+        shape
+    }
+
+    fn inverse_shape(&self, shape: &[i64]) -> Vec<i64> {
+        let mut shape = shape.to_vec();
+        *shape.last_mut().expect("shape must not be empty") -= 1;
+        shape
+    }
+}
@@ -0,0 +1,27 @@
+use super::Transform;
+use crate::{constraints::Constraint, Error};
+use std::f64::consts::LN_2;
+use tch::Tensor;
+
+/// Transform via the mapping `y = tanh(x)`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TanhTransform;
+
+impl Transform for TanhTransform {
+    fn forward(&self, x: &Tensor) -> Tensor {
+        x.tanh()
+    }
+
+    fn inverse(&self, y: &Tensor) -> Tensor {
+        y.atanh()
+    }
+
+    fn log_abs_det_jacobian(&self, x: &Tensor, _y: &Tensor) -> Result<Tensor, Error> {
+        // log(1 - tanh(x)^2) rewritten in a form that is stable for large |x|.
+        Ok(2.0 * (LN_2 - x - (x * -2.0).softplus()))
+    }
+
+    fn codomain(&self) -> Constraint {
+        Constraint::interval(-1.0, 1.0)
+    }
+}
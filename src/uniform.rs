@@ -1,4 +1,14 @@
-use crate::{utils::infinity, Distribution, KullackLeiberDivergence};
+use crate::{
+    constraints::Constraint,
+    error::check_fit_data,
+    persist::{param, Persist},
+    trace,
+    utils::{debug_assert_same_device, infinity, params_match, stack_param},
+    Distribution, Error, KullackLeiberDivergence, Normal, Reparameterize, Stack,
+};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::f64::consts::PI;
 use tch::Tensor;
 
 /// A Uniform distribution.
@@ -6,6 +16,7 @@ use tch::Tensor;
 pub struct Uniform {
     low: Tensor,
     high: Tensor,
+    log_width: Tensor,
     batch_shape: Vec<i64>,
 }
 
@@ -14,6 +25,7 @@ impl Clone for Uniform {
         Self {
             low: self.low.copy(),
             high: self.high.copy(),
+            log_width: self.log_width.copy(),
             batch_shape: self.batch_shape.clone(),
         }
     }
@@ -21,15 +33,36 @@ impl Clone for Uniform {
 
 impl Uniform {
     // Generates uniformly distributed random samples from the half-open interval [low, high).
-    pub fn new(low: Tensor, high: Tensor) -> Self {
+    pub fn new(low: impl Borrow<Tensor>, high: impl Borrow<Tensor>) -> Self {
+        let low = low.borrow().shallow_clone();
+        let high = high.borrow().shallow_clone();
+        debug_assert_same_device(&[&low, &high]);
+        trace::construct("Uniform", &[("low", &low), ("high", &high)]);
         let batch_shape = low.size();
+        let log_width = (&high - &low).log();
         Self {
             low,
             high,
+            log_width,
             batch_shape,
         }
     }
 
+    /// Creates a `Uniform` distribution over the unit interval [0, 1).
+    pub fn unit() -> Self {
+        Self::new(Tensor::from(0.0), Tensor::from(1.0))
+    }
+
+    /// Fits a `Uniform` distribution to `data` by maximum likelihood, the sample minimum and
+    /// maximum along the leading dimension, which indexes the samples. As the upper bound is
+    /// excluded from the support, the maximum itself has zero density under the fit.
+    pub fn fit_mle(data: &Tensor) -> Result<Self, Error> {
+        let data = check_fit_data(data, &Constraint::Real)?;
+        let (low, _) = data.min_dim(0, false);
+        let (high, _) = data.max_dim(0, false);
+        Ok(Self::new(low, high))
+    }
+
     /// Returns the lower range (inclusive).
     pub fn low(&self) -> &Tensor {
         &self.low
@@ -41,13 +74,54 @@ impl Uniform {
     }
 }
 
+impl From<(f64, f64)> for Uniform {
+    /// Creates a scalar `Uniform` distribution from `(low, high)`.
+    fn from((low, high): (f64, f64)) -> Self {
+        Self::new(Tensor::from(low), Tensor::from(high))
+    }
+}
+
+impl PartialEq for Uniform {
+    fn eq(&self, other: &Self) -> bool {
+        params_match(&self.params(), &other.params(), |a, b| a.equal(b))
+    }
+}
+
 impl Distribution for Uniform {
+    fn params(&self) -> Vec<(&'static str, &Tensor)> {
+        vec![("low", &self.low), ("high", &self.high)]
+    }
+
+    fn arg_constraints(&self) -> Vec<(&'static str, Constraint)> {
+        vec![
+            ("low", Constraint::Real),
+            ("high", Constraint::GreaterThan(self.low.shallow_clone())),
+        ]
+    }
+
     fn cdf(&self, val: &Tensor) -> Tensor {
+        self.validate_sample(val);
         ((val - &self.low) / (&self.high - &self.low)).clamp(0.0, 1.0)
     }
 
     fn entropy(&self) -> Tensor {
-        (&self.high - &self.low).log()
+        self.log_width.shallow_clone()
+    }
+
+    fn mean(&self) -> Tensor {
+        (&self.high + &self.low) / 2.0
+    }
+
+    fn variance(&self) -> Tensor {
+        (&self.high - &self.low).pow_tensor_scalar(2) / 12.0
+    }
+
+    fn support(&self) -> Constraint {
+        Constraint::Interval(self.low.shallow_clone(), self.high.shallow_clone())
+    }
+
+    fn support_bounds(&self) -> (Tensor, Tensor) {
+        (self.low.shallow_clone(), self.high.shallow_clone())
     }
 
     fn icdf(&self, val: &Tensor) -> Tensor {
@@ -55,15 +129,29 @@ impl Distribution for Uniform {
     }
 
     fn log_prob(&self, val: &Tensor) -> Tensor {
-        let lb = self.low.le_tensor(val).type_as(&self.low);
-        let ub = self.high.gt_tensor(val).type_as(&self.low);
-        (&lb * &ub).log() - (&self.high - &self.low).log()
+        let _span = trace::log_prob(self, val);
+        self.validate_sample(val);
+        // Like in PyTorch, the density is supported on the half-open interval [low, high) and
+        // values outside of it have a log-probability of -inf when validation is disabled.
+        let inside = self
+            .low
+            .le_tensor(val)
+            .logical_and(&self.high.gt_tensor(val));
+        (-&self.log_width).where_self(&inside, &-infinity(&self.log_width))
     }
 
     fn sample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
         let shape = self.extended_shape(shape);
-        let rand = Tensor::rand(&shape, (self.low.kind(), self.high.device()));
-        &self.low + &rand * (&self.high - &self.low)
+        tch::no_grad(|| {
+            let rand = Tensor::rand(&shape, (self.low.kind(), self.low.device()));
+            &self.low + &rand * (&self.high - &self.low)
+        })
+    }
+
+    fn rsample(&self, shape: &[i64]) -> Tensor {
+        let _span = trace::sample(self, shape);
+        self.reparameterize(&self.noise(shape))
     }
 
     fn batch_shape(&self) -> &[i64] {
@@ -71,15 +159,59 @@ impl Distribution for Uniform {
     }
 }
 
+impl Reparameterize for Uniform {
+    fn noise(&self, shape: &[i64]) -> Tensor {
+        let shape = self.extended_shape(shape);
+        Tensor::rand(&shape, (self.low.kind(), self.low.device()))
+    }
+
+    fn fill_noise(&self, noise: &mut Tensor) {
+        let _ = noise.uniform_(0.0, 1.0);
+    }
+
+    fn reparameterize(&self, noise: &Tensor) -> Tensor {
+        &self.low + noise * (&self.high - &self.low)
+    }
+}
+
+impl Stack for Uniform {
+    fn stack(dists: &[Self]) -> Self {
+        Self::new(
+            stack_param(dists, Self::low),
+            stack_param(dists, Self::high),
+        )
+    }
+}
+
+impl Persist for Uniform {
+    const FAMILY: &'static str = "uniform";
+
+    fn from_params(params: &HashMap<String, Tensor>) -> Result<Self, Error> {
+        Ok(Self::new(param(params, "low")?, param(params, "high")?))
+    }
+}
+
 impl KullackLeiberDivergence<Self> for Uniform {
     fn kl_divergence(&self, other: &Self) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
         let result = ((other.high() - other.low()) / (self.high() - self.low())).log();
         result.where_self(
             &other
                 .low()
                 .le_tensor(self.low())
                 .logical_and(&other.high().ge_tensor(self.high())),
-            &infinity(result.kind()),
+            &infinity(&result),
         )
     }
 }
+
+impl KullackLeiberDivergence<Normal> for Uniform {
+    fn kl_divergence(&self, other: &Normal) -> Tensor {
+        let _span = trace::kl_divergence(self, other);
+        let width = self.high() - self.low();
+        let t1 = (other.stddev() * (2.0 * PI).sqrt() / &width).log();
+        let t2 = width.pow_tensor_scalar(2.0) / 12.0;
+        let t3 = ((self.high() + self.low() - other.mean() * 2.0) / 2.0).pow_tensor_scalar(2.0);
+        t1 + (t2 + t3) * 0.5 / other.stddev().pow_tensor_scalar(2.0)
+    }
+}
@@ -1,4 +1,15 @@
-use tch::{Kind, Tensor};
+use crate::Distribution;
+use std::cell::OnceCell;
+use tch::{Kind, Reduction, Tensor};
+
+/// Number of samples drawn when a quantity has to be estimated by Monte Carlo.
+pub(crate) const MONTE_CARLO_SAMPLES: i64 = 10_000;
+
+/// Number of Newton iterations taken by maximum likelihood fits without a closed form.
+pub(crate) const NEWTON_STEPS: i64 = 20;
+
+/// The Euler-Mascheroni constant, `-digamma(1)`.
+pub(crate) const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
 
 /// Returns the smallest representable floating point number such that 1.0 + eps != 1.0.
 pub fn eps(kind: Kind) -> Option<f64> {
@@ -40,20 +51,6 @@ pub fn min(kind: Kind) -> Option<f64> {
     })
 }
 
-fn clamp_probs(probs: &Tensor) -> Tensor {
-    let eps = eps(probs.kind()).unwrap();
-    probs.clamp(eps, 1.0 - eps)
-}
-
-pub fn probs_to_logits(probs: &Tensor, is_binary: bool) -> Tensor {
-    let ps_clamped = clamp_probs(probs);
-    if is_binary {
-        ps_clamped.log() - (-ps_clamped).log1p()
-    } else {
-        ps_clamped.log()
-    }
-}
-
 pub fn logits_to_probs(logits: &Tensor, is_binary: bool) -> Tensor {
     if is_binary {
         logits.sigmoid()
@@ -62,14 +59,144 @@ pub fn logits_to_probs(logits: &Tensor, is_binary: bool) -> Tensor {
     }
 }
 
-pub fn infinity(kind: Kind) -> Tensor {
-    match kind {
-        Kind::Float => std::f32::INFINITY.into(),
-        Kind::Double => std::f64::INFINITY.into(),
+/// Returns the shape `a` and `b` broadcast to, or `None` if they cannot be broadcast.
+pub(crate) fn broadcast_shapes(a: &[i64], b: &[i64]) -> Option<Vec<i64>> {
+    let len = a.len().max(b.len());
+    let dim = |shape: &[i64], i: usize| (i + shape.len()).checked_sub(len).map_or(1, |i| shape[i]);
+    (0..len)
+        .map(|i| match (dim(a, i), dim(b, i)) {
+            (x, y) if x == y || y == 1 => Some(x),
+            (1, y) => Some(y),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns a scalar infinity with the kind and on the device of `like`.
+pub fn infinity(like: &Tensor) -> Tensor {
+    match like.kind() {
+        Kind::Float | Kind::Double => {
+            Tensor::full(&[], f64::INFINITY, (like.kind(), like.device()))
+        }
         k => panic!("{:?} cannot represent infinity", k),
     }
 }
 
+/// Asserts in debug builds that all `tensors` live on the same device.
+pub(crate) fn debug_assert_same_device(tensors: &[&Tensor]) {
+    if let Some((first, rest)) = tensors.split_first() {
+        for t in rest {
+            debug_assert_eq!(
+                first.device(),
+                t.device(),
+                "tensors are on different devices"
+            );
+        }
+    }
+}
+
+/// Draws standard Gumbel noise, `-log(-log(u))` for uniforms `u` bounded away from zero.
+pub(crate) fn standard_gumbel(shape: &[i64], kind: Kind, device: tch::Device) -> Tensor {
+    let tiny = tiny(kind).unwrap();
+    -(-Tensor::empty(shape, (kind, device))
+        .uniform_(tiny, 1.0)
+        .log())
+    .log()
+}
+
 pub fn standard_normal(shape: &[i64], dtype: tch::Kind, device: tch::Device) -> Tensor {
     Tensor::empty(shape, (dtype, device)).normal_(0., 1.)
 }
+
+/// Returns a cell holding a copy of the value of `cell`, if any.
+pub(crate) fn copy_cell(cell: &OnceCell<Tensor>) -> OnceCell<Tensor> {
+    let copy = OnceCell::new();
+    if let Some(t) = cell.get() {
+        let _ = copy.set(t.copy());
+    }
+    copy
+}
+
+/// Converts the values `val` to the kind of `param` if they are integers or booleans, like
+/// count data often are, so that they can be combined with the parameter.
+pub(crate) fn float_values(val: &Tensor, param: &Tensor) -> Tensor {
+    if val.is_floating_point() {
+        val.shallow_clone()
+    } else {
+        val.to_kind(param.kind())
+    }
+}
+
+/// Stacks the parameter `param` of each of `dists` along a new leading dimension.
+pub(crate) fn stack_param<D, F: Fn(&D) -> &Tensor>(dists: &[D], param: F) -> Tensor {
+    assert!(
+        !dists.is_empty(),
+        "cannot stack an empty list of distributions"
+    );
+    let params: Vec<&Tensor> = dists.iter().map(param).collect();
+    Tensor::stack(&params, 0)
+}
+
+/// Reduces `t` over `dims`, or over all dimensions if `dims` is `None`.
+pub(crate) fn reduce(t: Tensor, dims: Option<&[i64]>, reduction: Reduction) -> Tensor {
+    let kind = t.kind();
+    match (reduction, dims) {
+        (Reduction::None, _) => t,
+        (Reduction::Mean, None) => t.mean(kind),
+        (Reduction::Mean, Some(dims)) => t.mean_dim(dims, false, kind),
+        (Reduction::Sum, None) => t.sum(kind),
+        (Reduction::Sum, Some(dims)) => t.sum_dim_intlist(dims, false, kind),
+        (Reduction::Other(r), _) => panic!("unsupported reduction {}", r),
+    }
+}
+
+/// Returns true if both parameter lists have the same names and shapes and `eq` holds for
+/// every pair of parameters.
+pub(crate) fn params_match<F>(a: &[(&str, &Tensor)], b: &[(&str, &Tensor)], eq: F) -> bool
+where
+    F: Fn(&Tensor, &Tensor) -> bool,
+{
+    a.len() == b.len()
+        && a.iter().zip(b.iter()).all(|((name_a, a), (name_b, b))| {
+            name_a == name_b && a.size() == b.size() && a.kind() == b.kind() && eq(a, b)
+        })
+}
+
+/// Draws `n` samples and converts them to a floating point kind if necessary.
+pub(crate) fn float_samples<D: Distribution + ?Sized>(dist: &D, n: i64) -> Tensor {
+    let samples = dist.sample(&[n]);
+    if samples.is_floating_point() {
+        samples
+    } else {
+        samples.to_kind(Kind::Double)
+    }
+}
+
+/// Sums out the `ndims` rightmost dimensions of `t`.
+pub(crate) fn sum_rightmost(t: &Tensor, ndims: i64) -> Tensor {
+    if ndims <= 0 {
+        return t.shallow_clone();
+    }
+    let size = t.size();
+    let keep = &size[..size.len() - ndims as usize];
+    t.reshape(&[keep, &[-1]].concat())
+        .sum_dim_intlist(&[-1], false, t.kind())
+}
+
+/// Returns the sample mean and the biased sample variance of `data` along its leading
+/// dimension, which indexes the samples.
+pub(crate) fn sample_moments(data: &Tensor) -> (Tensor, Tensor) {
+    assert!(
+        data.dim() >= 1,
+        "data needs a leading sample dimension, got shape {:?}",
+        data.size()
+    );
+    let data = if data.is_floating_point() {
+        data.shallow_clone()
+    } else {
+        data.to_kind(Kind::Double)
+    };
+    let mean = data.mean_dim(&[0], false, data.kind());
+    let variance = data.var_dim(&[0], false, false);
+    (mean, variance)
+}
@@ -0,0 +1,19 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static VALIDATE_ARGS: AtomicBool = AtomicBool::new(false);
+
+/// Enables or disables the validation of the values passed to `log_prob` and `cdf` against the
+/// support of the distribution. Validation is disabled by default.
+///
+/// When enabled, `log_prob` and `cdf` panic and `try_log_prob` and `try_cdf` return
+/// `Error::Support` for values outside of the support. When disabled, such values produce
+/// whatever the formula of the distribution yields, e.g. `-inf` for `Uniform` but meaningless
+/// finite values for many others.
+pub fn set_validate_args(validate: bool) {
+    VALIDATE_ARGS.store(validate, Ordering::Relaxed);
+}
+
+/// Returns whether values are validated against the support, see `set_validate_args`.
+pub fn validate_args() -> bool {
+    VALIDATE_ARGS.load(Ordering::Relaxed)
+}
@@ -0,0 +1,124 @@
+//! Variational inference objectives and a stochastic variational inference driver.
+use crate::{Distribution, KullackLeiberDivergence, Parametrized};
+use std::fmt;
+use tch::{nn::Optimizer, Kind, Tensor};
+
+/// Estimates the evidence lower bound `E_q[log p(x, z) - log q(z)]` of the variational
+/// distribution `guide` from `n_samples` draws of `rsample`, so gradients flow back to the
+/// parameters of `guide`. Guides drawing with `Reparameterize` as well as the implicitly
+/// reparameterized Gamma, Beta and Dirichlet distributions can be used.
+///
+/// `model_log_joint` is called with a `[n_samples, batch..., event...]` shaped tensor of latent
+/// draws `z` and `data`, and returns `log p(data, z)` of shape `[n_samples, batch...]`. The
+/// result has the batch shape of `guide`.
+pub fn elbo<G, F>(model_log_joint: F, guide: &G, data: &Tensor, n_samples: i64) -> Tensor
+where
+    G: Distribution,
+    F: Fn(&Tensor, &Tensor) -> Tensor,
+{
+    let z = guide.rsample(&[n_samples]);
+    let values = model_log_joint(&z, data) - guide.log_prob(&z);
+    values.mean_dim(&[0], false, values.kind())
+}
+
+/// Estimates the evidence lower bound `E_q[log p(x | z)] - KL(q || p)` like `elbo`, but
+/// substitutes the closed form KL divergence between `guide` and `prior` for its Monte Carlo
+/// estimate, which lowers the variance of the estimate and its gradients.
+///
+/// `log_likelihood` is called like the log joint in `elbo` and returns `log p(data | z)`.
+pub fn elbo_analytic_kl<G, P, F>(
+    log_likelihood: F,
+    guide: &G,
+    prior: &P,
+    data: &Tensor,
+    n_samples: i64,
+) -> Tensor
+where
+    G: Distribution + KullackLeiberDivergence<P>,
+    P: Distribution,
+    F: Fn(&Tensor, &Tensor) -> Tensor,
+{
+    let z = guide.rsample(&[n_samples]);
+    let log_likelihood = log_likelihood(&z, data);
+    log_likelihood.mean_dim(&[0], false, log_likelihood.kind()) - guide.kl_divergence(prior)
+}
+
+/// Stochastic variational inference.
+///
+/// Fits a guide with trainable parameters to the posterior of a model by maximizing the
+/// `elbo` with a `tch` optimizer over the variables of the guide's `VarStore`. The model is
+/// given by the log density of its prior and the log likelihood of the data, both called
+/// with `[n_samples, batch..., event...]` shaped latent draws and returning
+/// `[n_samples, batch...]` shaped log densities.
+pub struct Svi<D, F, P, L> {
+    guide: Parametrized<D, F>,
+    optimizer: Optimizer,
+    log_prior: P,
+    log_likelihood: L,
+    n_samples: i64,
+}
+
+impl<D, F, P, L> Svi<D, F, P, L>
+where
+    D: Distribution,
+    F: Fn(&[Tensor]) -> D,
+    P: Fn(&Tensor) -> Tensor,
+    L: Fn(&Tensor, &Tensor) -> Tensor,
+{
+    /// Creates a driver optimizing `guide` with `optimizer`, which must have been built from
+    /// the `VarStore` holding the variables of `guide`. Each step estimates the `elbo` from
+    /// `n_samples` draws of the guide.
+    pub fn new(
+        guide: Parametrized<D, F>,
+        optimizer: Optimizer,
+        log_prior: P,
+        log_likelihood: L,
+        n_samples: i64,
+    ) -> Self {
+        Self {
+            guide,
+            optimizer,
+            log_prior,
+            log_likelihood,
+            n_samples,
+        }
+    }
+
+    /// Returns the guide.
+    pub fn guide(&self) -> &Parametrized<D, F> {
+        &self.guide
+    }
+
+    /// Returns the optimizer, e.g. to adjust its learning rate.
+    pub fn optimizer_mut(&mut self) -> &mut Optimizer {
+        &mut self.optimizer
+    }
+
+    /// Takes an optimization step on the negative `elbo` of `data` and returns this loss,
+    /// summed over the batch dimensions of the guide.
+    pub fn step(&mut self, data: &Tensor) -> f64 {
+        self.step_scaled(data, 1.0)
+    }
+
+    /// Takes an optimization step like `step` on a minibatch `data` of the full data set,
+    /// scaling its log likelihood by `scale`. Using the ratio of the size of the data set to
+    /// the size of the minibatch gives an unbiased estimate of the loss of the full data set.
+    pub fn step_scaled(&mut self, data: &Tensor, scale: f64) -> f64 {
+        let guide = self.guide.distribution();
+        let log_joint = |z: &Tensor, data: &Tensor| {
+            (self.log_likelihood)(z, data) * scale + (self.log_prior)(z)
+        };
+        let loss = -elbo(log_joint, &guide, data, self.n_samples).sum(Kind::Double);
+        self.optimizer.backward_step(&loss);
+        f64::from(&loss)
+    }
+}
+
+impl<D, F, P, L> fmt::Debug for Svi<D, F, P, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Svi")
+            .field("guide", &self.guide)
+            .field("n_samples", &self.n_samples)
+            .finish()
+    }
+}
@@ -5,8 +5,13 @@ use serial_test::serial;
 use std::convert::{TryFrom, TryInto};
 use tch::Tensor;
 use tch_distr::{
-    Bernoulli, Categorical, Cauchy, Distribution, Exponential, Gamma, Geometric,
-    KullackLeiberDivergence, MultivariateNormal, Normal, Poisson, Uniform,
+    transforms::{
+        AbsTransform, AffineTransform, ExpTransform, IndependentTransform, LogTransform,
+        LogitTransform, PowerTransform, ReshapeTransform, SigmoidTransform, SoftmaxTransform,
+        SoftplusTransform, StickBreakingTransform, TanhTransform, Transform,
+    },
+    Bernoulli, Beta, Categorical, Cauchy, Distribution, Exponential, Gamma, Geometric, Independent,
+    KullackLeiberDivergence, MultivariateNormal, Normal, Poisson, TanhNormal, Uniform,
 };
 
 const SEED: i64 = 42;
@@ -661,6 +666,42 @@ fn gamma() {
     }
 }
 
+#[test]
+#[serial]
+fn beta() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let args: Vec<(Tensor, Tensor)> = vec![
+        (0.5.into(), 0.5.into()),
+        (2.0.into(), 3.0.into()),
+        (Tensor::of_slice(&[1.0, 4.0]), Tensor::of_slice(&[2.0, 0.7])),
+    ];
+
+    let mut test_cases = TestCases::default();
+    test_cases.log_prob = Some(vec![0.25.into(), Tensor::of_slice(&[0.1, 0.9])]);
+    test_cases.cdf = None;
+    test_cases.icdf = None;
+
+    for (concentration1, concentration0) in args.into_iter() {
+        let dist_py = py_distribution(&py_env, "Beta", &[&concentration1, &concentration0]);
+        let dist_rs = Beta::new(concentration1, concentration0);
+        run_test_cases(&py_env, dist_rs, dist_py, &test_cases);
+    }
+
+    let (p_1, p_0) = (Tensor::of_slice(&[0.3, 2.0]), Tensor::of_slice(&[0.7, 1.5]));
+    let (q_1, q_0) = (Tensor::of_slice(&[0.6, 1.0]), Tensor::of_slice(&[0.5, 4.0]));
+    let dist_p_py = py_distribution(&py_env, "Beta", &[&p_1, &p_0]);
+    let dist_q_py = py_distribution(&py_env, "Beta", &[&q_1, &q_0]);
+    test_kl_divergence(
+        &py_env,
+        &Beta::new(p_1, p_0),
+        &Beta::new(q_1, q_0),
+        dist_p_py,
+        dist_q_py,
+    );
+}
+
 #[test]
 #[serial]
 fn geometric() {
@@ -983,3 +1024,427 @@ fn categorical() {
 
     // TODO: test kl divergence?
 }
+
+#[test]
+#[serial]
+fn interval() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let mass = 0.9;
+    let tail: Tensor = ((1.0 - mass) / 2.0).into();
+    let upper_tail: Tensor = (1.0 - (1.0 - mass) / 2.0).into();
+
+    let mean = Tensor::of_slice(&[1.0, 2.0]);
+    let std = Tensor::of_slice(&[0.5, 3.0]);
+    let dist_py = py_env
+        .distributions
+        .getattr("Normal")
+        .expect("call Normal failed")
+        .call1((
+            tensor_to_py_obj(&py_env, &mean),
+            tensor_to_py_obj(&py_env, &std),
+        ))
+        .unwrap();
+    let dist_rs = Normal::new(mean, std);
+
+    let (lower_rs, upper_rs) = dist_rs.interval(mass);
+    let lower_py = dist_py
+        .call_method1("icdf", (tensor_to_py_obj(&py_env, &tail),))
+        .unwrap();
+    let upper_py = dist_py
+        .call_method1("icdf", (tensor_to_py_obj(&py_env, &upper_tail),))
+        .unwrap();
+    assert_tensor_eq(py_env.py, &lower_rs, lower_py);
+    assert_tensor_eq(py_env.py, &upper_rs, upper_py);
+}
+
+fn py_distribution<'py>(py_env: &'py PyEnv, name: &str, args: &[&Tensor]) -> &'py PyAny {
+    let args_py: Vec<&PyAny> = args
+        .iter()
+        .map(|arg| tensor_to_py_obj(py_env, arg))
+        .collect();
+    py_env
+        .distributions
+        .getattr(name)
+        .unwrap_or_else(|_| panic!("call {} failed", name))
+        .call1(PyTuple::new(py_env.py, args_py))
+        .unwrap()
+}
+
+fn test_mean_and_variance<D: Distribution>(py_env: &PyEnv, dist_rs: &D, dist_py: &PyAny) {
+    let mean_py = dist_py.getattr("mean").expect("call property mean failed");
+    assert_tensor_eq(py_env.py, &dist_rs.mean(), mean_py);
+    let variance_py = dist_py
+        .getattr("variance")
+        .expect("call property variance failed");
+    assert_tensor_eq(py_env.py, &dist_rs.variance(), variance_py);
+}
+
+#[test]
+#[serial]
+fn mean_and_variance() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let (first, second) = (Tensor::of_slice(&[1.0, 0.5]), Tensor::of_slice(&[2.0, 3.0]));
+
+    test_mean_and_variance(
+        &py_env,
+        &Normal::new(first.copy(), second.copy()),
+        py_distribution(&py_env, "Normal", &[&first, &second]),
+    );
+    test_mean_and_variance(
+        &py_env,
+        &Uniform::new(first.copy(), second.copy()),
+        py_distribution(&py_env, "Uniform", &[&first, &second]),
+    );
+    test_mean_and_variance(
+        &py_env,
+        &Gamma::new(first.copy(), second.copy()),
+        py_distribution(&py_env, "Gamma", &[&first, &second]),
+    );
+    test_mean_and_variance(
+        &py_env,
+        &Exponential::new(first.copy()),
+        py_distribution(&py_env, "Exponential", &[&first]),
+    );
+    test_mean_and_variance(
+        &py_env,
+        &Poisson::new(first.copy()),
+        py_distribution(&py_env, "Poisson", &[&first]),
+    );
+}
+
+fn py_transform<'py>(py_env: &'py PyEnv, name: &str, args: &[&Tensor]) -> &'py PyAny {
+    let args_py: Vec<&PyAny> = args
+        .iter()
+        .map(|arg| tensor_to_py_obj(py_env, arg))
+        .collect();
+    py_env
+        .distributions
+        .getattr("transforms")
+        .unwrap()
+        .getattr(name)
+        .unwrap_or_else(|_| panic!("call {} failed", name))
+        .call1(PyTuple::new(py_env.py, args_py))
+        .unwrap()
+}
+
+fn test_transform<T: Transform>(
+    py_env: &PyEnv,
+    transform_rs: &T,
+    transform_py: &PyAny,
+    args: &[Tensor],
+) {
+    for x in args.iter() {
+        let x_py = tensor_to_py_obj(py_env, x);
+        let y_py = transform_py.call1((x_py,)).unwrap();
+        let y_rs = transform_rs.forward(x);
+        assert_tensor_eq(py_env.py, &y_rs, y_py);
+
+        let x_inv_py = transform_py.getattr("inv").unwrap().call1((y_py,)).unwrap();
+        let x_inv_rs = transform_rs.inverse(&y_rs);
+        assert_tensor_eq(py_env.py, &x_inv_rs, x_inv_py);
+
+        let log_abs_det_jacobian_py = transform_py
+            .call_method1("log_abs_det_jacobian", (x_py, y_py))
+            .unwrap();
+        let log_abs_det_jacobian_rs = transform_rs.log_abs_det_jacobian(x, &y_rs).unwrap();
+        assert_tensor_eq(py_env.py, &log_abs_det_jacobian_rs, log_abs_det_jacobian_py);
+    }
+}
+
+#[test]
+#[serial]
+fn affine_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let args: Vec<(Tensor, Tensor)> = vec![
+        (1.0.into(), 2.0.into()),
+        ((-1.0).into(), (-0.5).into()),
+        (
+            Tensor::of_slice(&[1.0, 0.0]),
+            Tensor::of_slice(&[2.0, -3.0]),
+        ),
+    ];
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5]),
+        Tensor::try_from(array![[1.0, 2.0], [-3.0, 0.25]]).unwrap(),
+    ];
+
+    for (loc, scale) in args.into_iter() {
+        let transform_py = py_transform(&py_env, "AffineTransform", &[&loc, &scale]);
+        let transform_rs = AffineTransform::new(loc, scale);
+        test_transform(&py_env, &transform_rs, transform_py, &xs);
+    }
+}
+
+#[test]
+#[serial]
+fn exp_and_log_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let exp_py = py_transform(&py_env, "ExpTransform", &[]);
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5]),
+        Tensor::try_from(array![[1.0, 2.0], [-3.0, 0.25]]).unwrap(),
+    ];
+    test_transform(&py_env, &ExpTransform, exp_py, &xs);
+
+    let log_py = exp_py.getattr("inv").unwrap();
+    let xs = vec![
+        Tensor::of_slice(&[0.5, 1.5]),
+        Tensor::try_from(array![[1.0, 2.0], [3.0, 0.25]]).unwrap(),
+    ];
+    test_transform(&py_env, &LogTransform, log_py, &xs);
+}
+
+#[test]
+#[serial]
+fn sigmoid_and_logit_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let sigmoid_py = py_transform(&py_env, "SigmoidTransform", &[]);
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5]),
+        Tensor::try_from(array![[1.0, 20.0], [-30.0, 0.25]]).unwrap(),
+    ];
+    test_transform(&py_env, &SigmoidTransform, sigmoid_py, &xs);
+
+    let logit_py = sigmoid_py.getattr("inv").unwrap();
+    let xs = vec![
+        Tensor::of_slice(&[0.5, 0.01]),
+        Tensor::try_from(array![[0.1, 0.2], [0.999, 0.25]]).unwrap(),
+    ];
+    test_transform(&py_env, &LogitTransform, logit_py, &xs);
+}
+
+#[test]
+#[serial]
+fn tanh_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let tanh_py = py_transform(&py_env, "TanhTransform", &[]);
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5]),
+        Tensor::try_from(array![[1.0, 2.0], [-3.0, 0.25]]).unwrap(),
+    ];
+    test_transform(&py_env, &TanhTransform, tanh_py, &xs);
+}
+
+#[test]
+#[serial]
+fn tanh_normal() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let args: Vec<(Tensor, Tensor)> = vec![
+        (0.0.into(), 1.0.into()),
+        (
+            Tensor::of_slice(&[1.0, -0.5]),
+            Tensor::of_slice(&[2.0, 0.3]),
+        ),
+    ];
+    let vals = vec![
+        Tensor::of_slice(&[0.5, -0.9]),
+        Tensor::try_from(array![[0.1, 0.2], [-0.99, 0.25]]).unwrap(),
+    ];
+
+    for (mean, stddev) in args.into_iter() {
+        let normal_py = py_distribution(&py_env, "Normal", &[&mean, &stddev]);
+        let tanh_py = py_transform(&py_env, "TanhTransform", &[]);
+        let dist_py = py_env
+            .distributions
+            .getattr("TransformedDistribution")
+            .unwrap()
+            .call1((normal_py, vec![tanh_py]))
+            .unwrap();
+        let dist_rs = TanhNormal::new(mean, stddev);
+        test_log_prob(&py_env, &dist_rs, dist_py, &vals);
+    }
+}
+
+#[test]
+#[serial]
+fn independent() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let mean = Tensor::try_from(array![[1.0, -0.5, 0.0], [2.0, 0.3, -1.0]]).unwrap();
+    let stddev = Tensor::try_from(array![[2.0, 0.3, 1.0], [0.5, 1.5, 0.7]]).unwrap();
+    let other_mean = mean.flip(&[0]);
+    let vals = vec![
+        Tensor::of_slice(&[0.5, -0.9, 0.2]),
+        Tensor::try_from(array![[0.1, 0.2, 0.3], [-0.99, 0.25, 1.0]]).unwrap(),
+    ];
+
+    for reinterpreted in 0..=2 {
+        let dist_py = py_env
+            .distributions
+            .getattr("Independent")
+            .unwrap()
+            .call1((
+                py_distribution(&py_env, "Normal", &[&mean, &stddev]),
+                reinterpreted,
+            ))
+            .unwrap();
+        let dist_rs = Independent::new(Normal::new(&mean, &stddev), reinterpreted);
+        test_entropy(&py_env, &dist_rs, dist_py);
+        test_log_prob(&py_env, &dist_rs, dist_py, &vals);
+
+        let other_py = py_env
+            .distributions
+            .getattr("Independent")
+            .unwrap()
+            .call1((
+                py_distribution(&py_env, "Normal", &[&other_mean, &stddev]),
+                reinterpreted,
+            ))
+            .unwrap();
+        let other_rs = Independent::new(Normal::new(&other_mean, &stddev), reinterpreted);
+        test_kl_divergence(&py_env, &dist_rs, &other_rs, dist_py, other_py);
+    }
+}
+
+#[test]
+#[serial]
+fn softmax_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let softmax_py = py_transform(&py_env, "SoftmaxTransform", &[]);
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5, 3.0]),
+        Tensor::try_from(array![[1.0, 20.0], [-30.0, 0.25]]).unwrap(),
+    ];
+    for x in xs.iter() {
+        let y_py = softmax_py.call1((tensor_to_py_obj(&py_env, x),)).unwrap();
+        let y_rs = SoftmaxTransform.forward(x);
+        assert_tensor_eq(py_env.py, &y_rs, y_py);
+
+        let x_inv_py = softmax_py.getattr("inv").unwrap().call1((y_py,)).unwrap();
+        assert_tensor_eq(py_env.py, &SoftmaxTransform.inverse(&y_rs), x_inv_py);
+    }
+}
+
+#[test]
+#[serial]
+fn stick_breaking_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let stick_breaking_py = py_transform(&py_env, "StickBreakingTransform", &[]);
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5, 3.0]),
+        Tensor::try_from(array![[1.0, 2.0], [-3.0, 0.25]]).unwrap(),
+    ];
+    test_transform(&py_env, &StickBreakingTransform, stick_breaking_py, &xs);
+}
+
+#[test]
+#[serial]
+fn power_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let exponents: Vec<Tensor> = vec![2.0.into(), 0.5.into(), Tensor::of_slice(&[-1.0, 3.0])];
+    let xs = vec![
+        Tensor::of_slice(&[0.5, 1.5]),
+        Tensor::try_from(array![[1.0, 2.0], [3.0, 0.25]]).unwrap(),
+    ];
+
+    for exponent in exponents.into_iter() {
+        let transform_py = py_transform(&py_env, "PowerTransform", &[&exponent]);
+        let transform_rs = PowerTransform::new(exponent);
+        test_transform(&py_env, &transform_rs, transform_py, &xs);
+    }
+}
+
+#[test]
+#[serial]
+fn abs_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let abs_py = py_transform(&py_env, "AbsTransform", &[]);
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5, 0.0]),
+        Tensor::try_from(array![[1.0, -2.0], [-3.0, 0.25]]).unwrap(),
+    ];
+    for x in xs.iter() {
+        let y_py = abs_py.call1((tensor_to_py_obj(&py_env, x),)).unwrap();
+        let y_rs = AbsTransform.forward(x);
+        assert_tensor_eq(py_env.py, &y_rs, y_py);
+
+        let x_inv_py = abs_py.getattr("inv").unwrap().call1((y_py,)).unwrap();
+        assert_tensor_eq(py_env.py, &AbsTransform.inverse(&y_rs), x_inv_py);
+    }
+}
+
+#[test]
+#[serial]
+fn reshape_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let transform_py = py_env
+        .distributions
+        .getattr("transforms")
+        .unwrap()
+        .getattr("ReshapeTransform")
+        .unwrap()
+        .call1((vec![4], vec![2, 2]))
+        .unwrap();
+    let transform_rs = ReshapeTransform::new(&[4], &[2, 2]);
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5, 3.0, 1.0]),
+        Tensor::try_from(array![[1.0, 2.0, 3.0, 4.0], [-3.0, 0.25, 0.0, 2.0]]).unwrap(),
+    ];
+    test_transform(&py_env, &transform_rs, transform_py, &xs);
+
+    assert_eq!(transform_rs.forward_shape(&[3, 4]), vec![3, 2, 2]);
+    assert_eq!(transform_rs.inverse_shape(&[3, 2, 2]), vec![3, 4]);
+}
+
+#[test]
+#[serial]
+fn independent_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let exp_py = py_transform(&py_env, "ExpTransform", &[]);
+    let transform_py = py_env
+        .distributions
+        .getattr("transforms")
+        .unwrap()
+        .getattr("IndependentTransform")
+        .unwrap()
+        .call1((exp_py, 1))
+        .unwrap();
+    let transform_rs = IndependentTransform::new(Box::new(ExpTransform), 1);
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5, 3.0]),
+        Tensor::try_from(array![[1.0, 2.0], [-3.0, 0.25]]).unwrap(),
+    ];
+    test_transform(&py_env, &transform_rs, transform_py, &xs);
+    assert_eq!(transform_rs.domain().event_dim(), 1);
+}
+
+#[test]
+#[serial]
+fn softplus_transform() {
+    let gil = Python::acquire_gil();
+    let py_env = PyEnv::new(&gil);
+
+    let softplus_py = py_transform(&py_env, "SoftplusTransform", &[]);
+    let xs = vec![
+        Tensor::of_slice(&[0.5, -1.5]),
+        Tensor::try_from(array![[1.0, 20.0], [-3.0, 0.25]]).unwrap(),
+    ];
+    test_transform(&py_env, &SoftplusTransform, softplus_py, &xs);
+}
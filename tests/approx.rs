@@ -0,0 +1,19 @@
+use tch::{nn, Tensor};
+use tch_distr::{approx::laplace, Distribution, MultivariateNormal};
+
+#[test]
+fn laplace_recovers_gaussian() {
+    let mean = Tensor::of_slice(&[1.0, -1.0]);
+    let cov = Tensor::of_slice(&[2.0, 0.5, 0.5, 1.0]).reshape(&[2, 2]);
+    let target = MultivariateNormal::from_cov(&mean, &cov);
+
+    let approx = laplace(
+        |x| target.log_prob(x),
+        &Tensor::zeros(&[2], tch::kind::DOUBLE_CPU),
+        nn::Adam::default(),
+        0.05,
+        5000,
+    );
+    assert!(approx.mean().allclose(&mean, 1e-3, 1e-3, false));
+    assert!(approx.covariance_matrix().allclose(&cov, 1e-4, 1e-4, false));
+}
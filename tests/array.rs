@@ -0,0 +1,49 @@
+#![cfg(feature = "ndarray")]
+
+use ndarray::{array, ArrayD};
+use tch::Tensor;
+use tch_distr::{array::NdarrayExt, Distribution, Error, Normal, Uniform};
+
+#[test]
+fn ndarray_round_trip() {
+    let mean = array![0.0, 1.0].into_dyn();
+    let stddev = array![1.0, 2.0].into_dyn();
+    let normal =
+        Normal::from_ndarray(&[("mean", mean.clone()), ("stddev", stddev.clone())]).unwrap();
+    assert_eq!(normal.batch_shape(), &[2]);
+    let params = normal.to_ndarray();
+    assert_eq!(params, vec![("mean", mean), ("stddev", stddev)]);
+
+    let val: ArrayD<f64> = array![[0.5, 0.5], [1.0, -1.0]].into_dyn();
+    let log_prob = normal.log_prob_ndarray(&val).unwrap();
+    let expected = normal.log_prob(&Tensor::of_slice(&[0.5f64, 0.5, 1.0, -1.0]).view([2, 2]));
+    let expected: ArrayD<f64> = (&expected).try_into().unwrap();
+    assert_eq!(log_prob, expected);
+
+    let cdf = normal.cdf_ndarray(&array![0.0, 1.0].into_dyn()).unwrap();
+    assert!((cdf[[0]] - 0.5).abs() < 1e-12 && (cdf[[1]] - 0.5).abs() < 1e-12);
+    let samples = normal.sample_ndarray(&[10]).unwrap();
+    assert_eq!(samples.shape(), &[10, 2]);
+
+    // Transposed arrays are not in standard layout and are copied before the conversion.
+    let low = array![[0.0, 1.0], [2.0, 3.0]].reversed_axes().into_dyn();
+    let high = &low + 1.0;
+    let uniform = Uniform::from_ndarray(&[("low", low), ("high", high)]).unwrap();
+    assert_eq!(
+        uniform.to_ndarray()[0].1,
+        array![[0.0, 2.0], [1.0, 3.0]].into_dyn()
+    );
+}
+
+#[test]
+fn ndarray_errors() {
+    let missing = Normal::from_ndarray(&[("mean", array![0.0].into_dyn())]);
+    assert!(matches!(missing, Err(Error::Format(_))));
+    let normal = Normal::new(
+        Tensor::of_slice(&[0.0f64, 1.0]),
+        Tensor::of_slice(&[1.0f64, 1.0]),
+    );
+    assert!(normal
+        .log_prob_ndarray(&array![0.0, 1.0, 2.0].into_dyn())
+        .is_err());
+}
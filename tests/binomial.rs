@@ -0,0 +1,55 @@
+use tch::{Kind, Tensor};
+use tch_distr::{Binomial, Distribution, NegativeBinomial};
+
+/// Checks that `cdf` at `0..n` is the cumulative sum of the probabilities of `dist`, whose
+/// batch has two elements.
+fn check_cdf<D: Distribution>(dist: &D, n: i64) {
+    let k = Tensor::arange(n, (Kind::Double, tch::Device::Cpu))
+        .unsqueeze(-1)
+        .expand(&[n, 2], false);
+    let expected = dist.log_prob(&k).exp().cumsum(0, Kind::Double);
+    let cdf = dist.cdf(&k);
+    assert!(
+        cdf.allclose(&expected, 1e-8, 1e-10, false),
+        "{:?} != {:?}",
+        cdf,
+        expected
+    );
+    let below = dist.cdf(&Tensor::of_slice(&[-1.0, -0.5]));
+    assert_eq!(Vec::<f64>::from(&below), [0.0, 0.0]);
+}
+
+#[test]
+fn binomial() {
+    let dist = Binomial::from_probs(
+        Tensor::of_slice(&[10.0, 4.0]),
+        Tensor::of_slice(&[0.3, 0.8]),
+    );
+    check_cdf(&dist, 12);
+    let total = dist.log_prob(&Tensor::arange(11, (Kind::Double, tch::Device::Cpu)).unsqueeze(-1));
+    let total = total.exp().sum_dim_intlist(&[0], false, Kind::Double);
+    assert!(total.allclose(&Tensor::of_slice(&[1.0, 1.0]), 1e-10, 1e-12, false));
+    // Non-integer values are floored.
+    let cdf = dist.cdf(&Tensor::of_slice(&[2.5, 4.0]));
+    let expected = dist.cdf(&Tensor::of_slice(&[2.0, 4.0]));
+    assert!(cdf.allclose(&expected, 1e-12, 1e-12, false));
+    assert_eq!(
+        Vec::<f64>::from(&dist.cdf(&Tensor::of_slice(&[10.0, 4.0]))),
+        [1.0, 1.0]
+    );
+
+    let samples = dist.sample(&[1000]);
+    assert!(bool::from(samples.le_tensor(dist.total_count()).all()));
+}
+
+#[test]
+fn negative_binomial() {
+    let dist = NegativeBinomial::from_logits(
+        Tensor::of_slice(&[3.0, 0.5]),
+        Tensor::of_slice(&[0.2, -1.0]),
+    );
+    check_cdf(&dist, 40);
+    tch::manual_seed(0);
+    let mean = dist.sample(&[50_000]).mean_dim(&[0], false, Kind::Double);
+    assert!(mean.allclose(&dist.mean(), 0.05, 0.0, false), "{:?}", mean);
+}
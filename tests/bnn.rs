@@ -0,0 +1,96 @@
+use tch::{nn, Device, Kind, Tensor};
+use tch_distr::{
+    bnn::{MeanFieldLinear, WeightPriors},
+    Distribution, KullackLeiberDivergence, Normal,
+};
+
+const SEED: i64 = 42;
+
+#[test]
+fn weight_priors() {
+    let vs = nn::VarStore::new(Device::Cpu);
+    let root = vs.root();
+    let encoder = (&root / "encoder").var("weight", &[2, 3], nn::Init::Const(1.0));
+    let encoder_bias = (&root / "encoder").var("bias", &[2], nn::Init::Const(0.5));
+    let decoder = (&root / "decoder").var("weight", &[4], nn::Init::Const(-1.0));
+    let other = root.var("encoder_other", &[1], nn::Init::Const(2.0));
+
+    let wide = Normal::new(Tensor::from(0.0f32), Tensor::from(10.0f32));
+    let narrow = Normal::new(Tensor::from(0.0f32), Tensor::from(0.1f32));
+    let mut priors =
+        WeightPriors::with_default(Normal::new(Tensor::from(0.0f32), Tensor::from(1.0f32)));
+    priors
+        .set(
+            "encoder",
+            Normal::new(Tensor::from(0.0f32), Tensor::from(10.0f32)),
+        )
+        .set(
+            "encoder.bias",
+            Normal::new(Tensor::from(0.0f32), Tensor::from(0.1f32)),
+        );
+
+    let standard = Normal::new(Tensor::from(0.0f32), Tensor::from(1.0f32));
+    let expected = wide.log_prob(&encoder).sum(Kind::Double)
+        + narrow.log_prob(&encoder_bias).sum(Kind::Double)
+        + standard.log_prob(&decoder).sum(Kind::Double)
+        + standard.log_prob(&other).sum(Kind::Double);
+    let log_prob = priors.log_prob(&vs);
+    assert!((f64::from(&log_prob) - f64::from(expected)).abs() < 1e-4);
+    log_prob.backward();
+    assert!(encoder.grad().defined());
+
+    let flat = WeightPriors::new();
+    assert_eq!(f64::from(flat.log_prob(&vs)), 0.0);
+}
+
+#[test]
+fn mean_field_linear() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let layer = MeanFieldLinear::new(&vs.root(), 3, 2, 0.2);
+    assert!(layer.weight_posterior().stddev().allclose(
+        &Tensor::full(&[2, 3], 0.2, tch::kind::FLOAT_CPU),
+        1e-5,
+        1e-5,
+        false
+    ));
+
+    let prior = Normal::new(Tensor::from(0.0f32), Tensor::from(1.0f32));
+    let expected = layer
+        .weight_posterior()
+        .kl_divergence(&prior)
+        .sum(Kind::Double)
+        + layer
+            .bias_posterior()
+            .kl_divergence(&prior)
+            .sum(Kind::Double);
+    assert!((f64::from(layer.kl_divergence(&prior)) - f64::from(expected)).abs() < 1e-6);
+
+    // The locally reparameterized outputs have the moments of the outputs under sampled
+    // weights.
+    let xs = Tensor::of_slice(&[1.0f32, -2.0, 0.5])
+        .unsqueeze(0)
+        .expand(&[20000, 3], false);
+    let local = tch::no_grad(|| layer.forward(&xs));
+    let sampled = tch::no_grad(|| {
+        Tensor::cat(
+            &(0..2000)
+                .map(|_| layer.forward_sampled(&xs.narrow(0, 0, 1)))
+                .collect::<Vec<_>>(),
+            0,
+        )
+    });
+    let mean = tch::no_grad(|| layer.forward_mean(&xs.narrow(0, 0, 1))).squeeze_dim(0);
+    assert!(local
+        .mean_dim(&[0], false, Kind::Float)
+        .allclose(&mean, 0.0, 0.02, false));
+    assert!(sampled
+        .mean_dim(&[0], false, Kind::Float)
+        .allclose(&mean, 0.0, 0.05, false));
+    assert!(local.var_dim(&[0], false, false).allclose(
+        &sampled.var_dim(&[0], false, false),
+        0.1,
+        0.0,
+        false
+    ));
+}
@@ -0,0 +1,75 @@
+use tch::{Device, Kind, Tensor};
+use tch_distr::{
+    bnp::{dp_mixture, weights_from_fractions, StickBreaking},
+    Beta, Distribution, Normal,
+};
+
+const SEED: i64 = 42;
+
+#[test]
+fn stick_breaking_weights() {
+    let fractions = Tensor::of_slice(&[0.5f64, 0.5, 0.5]);
+    let weights = weights_from_fractions(&fractions);
+    let expected = Tensor::of_slice(&[0.5f64, 0.25, 0.125, 0.125]);
+    assert!(weights.allclose(&expected, 1e-10, 1e-10, false));
+}
+
+#[test]
+fn stick_breaking() {
+    tch::manual_seed(SEED);
+    let concentration = Tensor::of_slice(&[0.5f64, 2.0, 5.0]);
+    let dist = StickBreaking::new(&concentration, 6);
+    assert_eq!(dist.batch_shape(), &[3]);
+    assert_eq!(dist.event_shape(), &[6]);
+
+    let samples = dist.sample(&[20000]);
+    assert_eq!(samples.size(), vec![20000, 3, 6]);
+    let totals = samples.sum_dim_intlist(&[-1], false, Kind::Double);
+    assert!(totals.allclose(&totals.ones_like(), 1e-10, 1e-10, false));
+    let empirical = samples.mean_dim(&[0], false, Kind::Double);
+    assert!(empirical.allclose(&dist.mean(), 0.01, 0.01, false));
+
+    // The density of the weights is the density of the fractions, corrected by the Jacobian
+    // of the map to the weights.
+    let dist = StickBreaking::new(Tensor::from(2.0f64), 3);
+    let weights = Tensor::of_slice(&[0.2f64, 0.6, 0.2]);
+    let beta = Beta::new(Tensor::from(1.0f64), Tensor::from(2.0f64));
+    let expected =
+        beta.log_prob(&Tensor::from(0.2f64)) + beta.log_prob(&Tensor::from(0.75f64)) - 0.8f64.ln();
+    let log_prob = dist.log_prob(&weights);
+    assert!(log_prob.allclose(&expected, 1e-10, 1e-10, false));
+}
+
+#[test]
+fn stick_breaking_rsample() {
+    tch::manual_seed(SEED);
+    let concentration = Tensor::from(1.5f64).set_requires_grad(true);
+    let dist = StickBreaking::new(&concentration, 4);
+    dist.rsample(&[100])
+        .select(-1, 0)
+        .sum(Kind::Double)
+        .backward();
+    // More concentration moves mass away from the first component.
+    assert!(f64::from(concentration.grad()) < 0.0);
+}
+
+#[test]
+fn dp_mixture_of_normals() {
+    tch::manual_seed(SEED);
+    let dist = StickBreaking::new(Tensor::from(1.0f64), 5);
+    let components = Normal::new(
+        Tensor::linspace(-4.0, 4.0, 5, (Kind::Double, Device::Cpu)),
+        Tensor::ones(&[5], (Kind::Double, Device::Cpu)),
+    );
+    let mixture = dp_mixture(&dist.sample(&[]), components);
+    assert_eq!(mixture.batch_shape(), &[] as &[i64]);
+    let log_prob = mixture.log_prob(&Tensor::of_slice(&[0.0f64, 1.0]));
+    assert_eq!(log_prob.size(), vec![2]);
+
+    let components = Normal::new(
+        Tensor::zeros(&[2, 5], (Kind::Double, Device::Cpu)),
+        Tensor::ones(&[2, 5], (Kind::Double, Device::Cpu)),
+    );
+    let mixture = dist.sample_mixture(&[2], components);
+    assert_eq!(mixture.batch_shape(), &[2]);
+}
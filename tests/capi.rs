@@ -0,0 +1,84 @@
+#![cfg(feature = "capi")]
+
+use std::{ffi::CStr, ptr};
+use tch_distr::capi::*;
+
+#[test]
+fn capi_normal() {
+    unsafe {
+        let mean = [0.0, 1.0];
+        let stddev = [1.0, 2.0];
+        let dist = tch_distr_normal_new(mean.as_ptr(), stddev.as_ptr(), 2);
+        assert!(!dist.is_null());
+        assert_eq!(tch_distr_sample_size(dist), 2);
+
+        let mut samples = [0.0; 6];
+        assert_eq!(tch_distr_sample(dist, 3, samples.as_mut_ptr(), 6), 0);
+        assert!(samples.iter().all(|s| s.is_finite()));
+
+        let values = [0.0, 1.0];
+        let mut log_prob = [0.0; 2];
+        assert_eq!(
+            tch_distr_log_prob(dist, values.as_ptr(), 2, log_prob.as_mut_ptr(), 2),
+            0
+        );
+        let expected = -0.5 * (2.0 * std::f64::consts::PI).ln();
+        assert!((log_prob[0] - expected).abs() < 1e-9);
+        assert!((log_prob[1] - (expected - 2f64.ln())).abs() < 1e-9);
+
+        // The output is too small for the samples.
+        assert_eq!(tch_distr_sample(dist, 4, samples.as_mut_ptr(), 6), -1);
+        let msg = CStr::from_ptr(tch_distr_last_error()).to_str().unwrap();
+        assert!(msg.contains("8 are needed"), "{}", msg);
+        tch_distr_free(dist);
+    }
+}
+
+#[test]
+fn capi_errors() {
+    unsafe {
+        assert!(tch_distr_exponential_new(ptr::null(), 1).is_null());
+        assert!(!tch_distr_last_error().is_null());
+
+        let probs = [0.2, 0.3, 0.5];
+        let dist = tch_distr_categorical_new(probs.as_ptr(), 1, 3);
+        assert!(!dist.is_null());
+        assert_eq!(tch_distr_sample_size(dist), 1);
+        let values = [0.0, 2.0];
+        let mut log_prob = [0.0; 2];
+        assert_eq!(
+            tch_distr_log_prob(dist, values.as_ptr(), 2, log_prob.as_mut_ptr(), 2),
+            0
+        );
+        assert!((log_prob[1] - 0.5f64.ln()).abs() < 1e-9);
+        tch_distr_free(dist);
+        tch_distr_free(ptr::null_mut());
+    }
+}
+
+#[test]
+fn capi_binomial() {
+    unsafe {
+        let total_count = [10.0, 5.0];
+        let probs = [0.3, 0.5];
+        let dist = tch_distr_binomial_new(total_count.as_ptr(), probs.as_ptr(), 2);
+        assert!(!dist.is_null());
+        let values = [3.0, 5.0];
+        let mut log_prob = [0.0; 2];
+        assert_eq!(
+            tch_distr_log_prob(dist, values.as_ptr(), 2, log_prob.as_mut_ptr(), 2),
+            0
+        );
+        let expected = 120.0 * 0.3f64.powi(3) * 0.7f64.powi(7);
+        assert!((log_prob[0] - expected.ln()).abs() < 1e-9);
+        assert!((log_prob[1] - 0.5f64.powi(5).ln()).abs() < 1e-9);
+        tch_distr_free(dist);
+
+        let dist = tch_distr_negative_binomial_new(total_count.as_ptr(), probs.as_ptr(), 2);
+        assert!(!dist.is_null());
+        let mut samples = [0.0; 4];
+        assert_eq!(tch_distr_sample(dist, 2, samples.as_mut_ptr(), 4), 0);
+        assert!(samples.iter().all(|s| *s >= 0.0 && s.fract() == 0.0));
+        tch_distr_free(dist);
+    }
+}
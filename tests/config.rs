@@ -0,0 +1,103 @@
+#![cfg(feature = "config")]
+
+use serde_json::json;
+use tch::Tensor;
+use tch_distr::{AnyDistribution, Error};
+
+fn format_error(config: serde_json::Value) -> String {
+    match AnyDistribution::from_config(&config) {
+        Err(Error::Format(msg)) => msg,
+        other => panic!("expected a format error, got {:?}", other),
+    }
+}
+
+#[test]
+fn config_construction() {
+    let dist = AnyDistribution::from_config(&json!({
+        "family": "Normal",
+        "params": {"mean": 0.0, "stddev": [1.0, 2.0]},
+    }))
+    .unwrap();
+    assert_eq!(dist.family(), "Normal");
+    let dist = dist.as_distribution();
+    assert_eq!(dist.batch_shape(), &[2]);
+    let log_prob = dist.log_prob(&Tensor::of_slice(&[0.0f64, 0.0]));
+    assert!(
+        (f64::from(log_prob.get(1)) + 0.5 * (2.0 * std::f64::consts::PI).ln() + 2f64.ln()).abs()
+            < 1e-12
+    );
+
+    let dist = AnyDistribution::from_config(&json!({
+        "family": "MultivariateNormal",
+        "params": {"mean": [0.0, 1.0], "cov": [[2.0, 0.5], [0.5, 1.0]]},
+    }))
+    .unwrap();
+    assert_eq!(dist.as_distribution().event_shape(), &[2]);
+
+    let dist = AnyDistribution::from_config(&json!({
+        "family": "Categorical",
+        "params": {"probs": [0.2, 0.3, 0.5]},
+    }))
+    .unwrap();
+    assert_eq!(dist.as_distribution().event_shape(), &[] as &[i64]);
+}
+
+#[test]
+fn config_round_trip() {
+    for config in [
+        json!({"family": "Gamma", "params": {"concentration": [2.0, 3.0], "rate": 0.5}}),
+        json!({"family": "Bernoulli", "params": {"probs": 0.25}}),
+        json!({"family": "Uniform", "params": {"low": -1.0, "high": [[1.0], [2.0]]}}),
+        json!({"family": "Binomial", "params": {"total_count": 10.0, "probs": [0.2, 0.5]}}),
+        json!({"family": "NegativeBinomial", "params": {"total_count": [2.0, 3.5], "logits": 0.3}}),
+    ] {
+        let dist = AnyDistribution::from_config(&config).unwrap();
+        let again = AnyDistribution::from_config(&dist.to_config()).unwrap();
+        let (a, b) = (dist.as_distribution(), again.as_distribution());
+        for ((name_a, a), (name_b, b)) in a.params().into_iter().zip(b.params()) {
+            assert_eq!(name_a, name_b);
+            assert!(a.allclose(b, 1e-12, 1e-12, false));
+        }
+    }
+}
+
+#[test]
+fn config_errors() {
+    assert!(format_error(json!({"params": {}})).starts_with("family:"));
+    assert!(format_error(json!({"family": "Normal"})).starts_with("params:"));
+    assert!(format_error(json!({"family": "Banana", "params": {}})).starts_with("family:"));
+    assert!(format_error(json!({
+        "family": "Normal",
+        "params": {"mean": 0.0},
+    }))
+    .starts_with("params.stddev:"));
+    assert!(format_error(json!({
+        "family": "Normal",
+        "params": {"mean": "zero", "stddev": 1.0},
+    }))
+    .starts_with("params.mean:"));
+    assert!(format_error(json!({
+        "family": "Normal",
+        "params": {"mean": [[0.0, 1.0], [2.0]], "stddev": 1.0},
+    }))
+    .starts_with("params.mean:"));
+    assert!(format_error(json!({
+        "family": "Normal",
+        "params": {"mean": 0.0, "stddev": 1.0, "scale": 1.0},
+    }))
+    .starts_with("params.scale:"));
+    assert!(format_error(json!({
+        "family": "Bernoulli",
+        "params": {"probs": 0.5, "logits": 0.0},
+    }))
+    .starts_with("params:"));
+
+    let result = AnyDistribution::from_config(&json!({
+        "family": "Normal",
+        "params": {"mean": 0.0, "stddev": -1.0},
+    }));
+    match result {
+        Err(Error::Support(msg)) => assert!(msg.starts_with("params.stddev:")),
+        other => panic!("expected a support error, got {:?}", other),
+    }
+}
@@ -0,0 +1,104 @@
+use tch::Tensor;
+use tch_distr::{
+    constraints::Constraint, Bernoulli, Categorical, Cauchy, Distribution, Exponential, Gamma,
+    Geometric, MultivariateNormal, Normal, Poisson, Uniform,
+};
+
+fn check(constraint: &Constraint, value: &Tensor) -> Vec<bool> {
+    Vec::<bool>::from(&constraint.check(value).flatten(0, -1))
+}
+
+#[test]
+fn scalar_constraints() {
+    let value = Tensor::of_slice(&[-1.0, 0.0, 0.5, 1.0, 2.0, f64::NAN]);
+    assert_eq!(
+        check(&Constraint::Real, &value),
+        [true, true, true, true, true, false]
+    );
+    assert_eq!(
+        check(&Constraint::Positive, &value),
+        [false, false, true, true, true, false]
+    );
+    assert_eq!(
+        check(&Constraint::greater_than(0.5), &value),
+        [false, false, false, true, true, false]
+    );
+    assert_eq!(
+        check(&Constraint::UnitInterval, &value),
+        [false, true, true, true, false, false]
+    );
+    assert_eq!(
+        check(&Constraint::interval(-1.0, 0.5), &value),
+        [true, true, true, false, false, false]
+    );
+    assert_eq!(
+        check(&Constraint::nonnegative_integer(), &value),
+        [false, true, false, true, true, false]
+    );
+}
+
+#[test]
+fn simplex() {
+    let value = Tensor::of_slice(&[0.2, 0.8, 0.5, 0.6, -0.5, 1.5]).view([3, 2]);
+    assert_eq!(check(&Constraint::Simplex, &value), [true, false, false]);
+}
+
+#[test]
+fn matrix_constraints() {
+    let value = Tensor::of_slice(&[
+        2.0, 0.0, 0.5, 1.0, // lower Cholesky, not symmetric
+        2.0, 0.5, 0.5, 1.0, // positive definite
+        1.0, 0.0, 0.0, -1.0, // neither
+    ])
+    .view([3, 2, 2]);
+    assert_eq!(
+        check(&Constraint::LowerCholesky, &value),
+        [true, false, false]
+    );
+    assert_eq!(
+        check(&Constraint::PositiveDefinite, &value),
+        [false, true, false]
+    );
+}
+
+#[test]
+fn independent() {
+    let value = Tensor::of_slice(&[1.0, 2.0, 3.0, -1.0]).view([2, 2]);
+    let constraint = Constraint::independent(Constraint::Positive, 1);
+    assert_eq!(constraint.event_dim(), 1);
+    assert_eq!(check(&constraint, &value), [true, false]);
+    assert!(!constraint.check_all(&value));
+}
+
+fn check_arg_constraints<D: Distribution>(dist: &D) {
+    let params = dist.params();
+    let constraints = dist.arg_constraints();
+    assert_eq!(params.len(), constraints.len());
+    for ((name, param), (constraint_name, constraint)) in params.iter().zip(constraints.iter()) {
+        assert_eq!(name, constraint_name);
+        assert!(
+            constraint.check_all(param),
+            "{} violates {:?}",
+            name,
+            constraint
+        );
+    }
+}
+
+#[test]
+fn arg_constraints() {
+    let probs = Tensor::of_slice(&[0.2, 0.7]);
+    check_arg_constraints(&Normal::new(Tensor::of_slice(&[0.0, -1.0]), probs.copy()));
+    check_arg_constraints(&Uniform::new(Tensor::of_slice(&[0.0, -1.0]), probs.copy()));
+    check_arg_constraints(&Exponential::new(probs.copy()));
+    check_arg_constraints(&Poisson::new(probs.copy()));
+    check_arg_constraints(&Gamma::new(probs.copy(), probs.copy()));
+    check_arg_constraints(&Cauchy::new(probs.copy(), probs.copy()));
+    check_arg_constraints(&Bernoulli::from_probs(probs.copy()));
+    check_arg_constraints(&Geometric::from_probs(probs.copy()));
+    check_arg_constraints(&Categorical::from_probs(Tensor::of_slice(&[0.2, 0.8])));
+    check_arg_constraints(&MultivariateNormal::from_scale_tril(
+        Tensor::of_slice(&[0.0, 1.0]),
+        Tensor::of_slice(&[1.0, 0.0, 0.5, 2.0]).view([2, 2]),
+    ));
+}
@@ -0,0 +1,55 @@
+use tch::Tensor;
+use tch_distr::{Cauchy, Distribution, Exponential, Gamma, Normal, Poisson, Uniform};
+
+fn assert_param(param: &Tensor, expected: f64) {
+    assert!(param.size().is_empty());
+    assert_eq!(f64::from(param), expected);
+}
+
+#[test]
+fn standard_constructors() {
+    let normal = Normal::standard();
+    assert!(normal.batch_shape().is_empty());
+    assert_param(normal.mean(), 0.0);
+    assert_param(normal.stddev(), 1.0);
+
+    let uniform = Uniform::unit();
+    assert!(uniform.batch_shape().is_empty());
+    assert_param(uniform.low(), 0.0);
+    assert_param(uniform.high(), 1.0);
+
+    let exponential = Exponential::standard();
+    assert!(exponential.batch_shape().is_empty());
+    assert_param(exponential.rate(), 1.0);
+}
+
+#[test]
+fn scalar_conversions() {
+    let normal = Normal::from((-1.0, 2.0));
+    assert!(normal.batch_shape().is_empty());
+    assert_param(normal.mean(), -1.0);
+    assert_param(normal.stddev(), 2.0);
+
+    let uniform = Uniform::from((2.0, 5.0));
+    assert!(uniform.batch_shape().is_empty());
+    assert_param(uniform.low(), 2.0);
+    assert_param(uniform.high(), 5.0);
+
+    let cauchy = Cauchy::from((1.0, 0.5));
+    assert!(cauchy.batch_shape().is_empty());
+    assert_param(cauchy.median(), 1.0);
+    assert_param(cauchy.scale(), 0.5);
+
+    let gamma = Gamma::from((3.0, 2.0));
+    assert!(gamma.batch_shape().is_empty());
+    assert_param(gamma.concentration(), 3.0);
+    assert_param(gamma.rate(), 2.0);
+
+    let exponential = Exponential::from(4.0);
+    assert!(exponential.batch_shape().is_empty());
+    assert_param(exponential.rate(), 4.0);
+
+    let poisson = Poisson::from(3.0);
+    assert!(poisson.batch_shape().is_empty());
+    assert_param(poisson.rate(), 3.0);
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "cuda")]
+
+use tch::{Device, Tensor};
+use tch_distr::{
+    Bernoulli, Categorical, Cauchy, Distribution, Exponential, Geometric, KullackLeiberDivergence,
+    MultivariateNormal, Normal, Poisson, Uniform,
+};
+
+const DEVICE: Device = Device::Cuda(0);
+
+fn tensor(values: &[f64]) -> Tensor {
+    Tensor::of_slice(values).to_device(DEVICE)
+}
+
+fn check_device<D: Distribution>(dist: &D, val: &Tensor) {
+    assert_eq!(dist.sample(&[3]).device(), DEVICE);
+    assert_eq!(dist.log_prob(val).device(), DEVICE);
+    assert_eq!(dist.mean().device(), DEVICE);
+    assert_eq!(dist.variance().device(), DEVICE);
+}
+
+#[test]
+fn continuous() {
+    let loc = tensor(&[0.5]);
+    let scale = tensor(&[2.0]);
+    let val = tensor(&[0.7]);
+
+    let normal = Normal::new(loc.shallow_clone(), scale.shallow_clone());
+    check_device(&normal, &val);
+    assert_eq!(normal.rsample(&[3]).device(), DEVICE);
+    assert_eq!(normal.cdf(&val).device(), DEVICE);
+    assert_eq!(normal.kl_divergence(&normal).device(), DEVICE);
+
+    let cauchy = Cauchy::new(loc.shallow_clone(), scale.shallow_clone());
+    assert_eq!(cauchy.sample(&[3]).device(), DEVICE);
+    assert_eq!(cauchy.log_prob(&val).device(), DEVICE);
+
+    let exponential = Exponential::new(scale.shallow_clone());
+    check_device(&exponential, &val);
+
+    let uniform = Uniform::new(loc.shallow_clone(), &loc + &scale);
+    check_device(&uniform, &val);
+    assert_eq!(uniform.kl_divergence(&uniform).device(), DEVICE);
+
+    let mvn = MultivariateNormal::from_scale_tril(
+        tensor(&[0.5, -1.0]),
+        tensor(&[2.0, 0.0, 0.3, 1.5]).reshape(&[2, 2]),
+    );
+    assert_eq!(mvn.sample(&[3]).device(), DEVICE);
+    assert_eq!(mvn.log_prob(&tensor(&[0.1, 0.2])).device(), DEVICE);
+}
+
+#[test]
+fn discrete() {
+    let probs = tensor(&[0.3]);
+    let val = tensor(&[1.0]);
+
+    let bernoulli = Bernoulli::from_probs(probs.shallow_clone());
+    check_device(&bernoulli, &val);
+    assert_eq!(bernoulli.kl_divergence(&bernoulli).device(), DEVICE);
+
+    let geometric = Geometric::from_probs(probs.shallow_clone());
+    check_device(&geometric, &val);
+
+    let poisson = Poisson::new(tensor(&[2.0]));
+    check_device(&poisson, &val);
+
+    let categorical = Categorical::from_probs(tensor(&[0.2, 0.3, 0.5]));
+    assert_eq!(categorical.sample(&[3]).device(), DEVICE);
+    let val = Tensor::of_slice(&[2i64]).to_device(DEVICE);
+    assert_eq!(categorical.log_prob(&val).device(), DEVICE);
+}
@@ -0,0 +1,49 @@
+use tch::{Kind, Tensor};
+use tch_distr::{Distribution, Gamma, MultivariateNormal, Normal};
+
+#[test]
+fn named_params() {
+    let gamma = Gamma::new(Tensor::of_slice(&[1.0, 2.0]), Tensor::of_slice(&[0.5, 3.0]));
+    let names: Vec<_> = gamma.params().iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, ["concentration", "rate"]);
+    assert!(gamma.params()[1].1.equal(gamma.rate()));
+}
+
+#[test]
+fn partial_eq() {
+    let normal = Normal::new(Tensor::of_slice(&[0.0, 1.0]), Tensor::of_slice(&[1.0, 2.0]));
+    let same = Normal::new(Tensor::of_slice(&[0.0, 1.0]), Tensor::of_slice(&[1.0, 2.0]));
+    let shifted = Normal::new(
+        Tensor::of_slice(&[0.0, 1.0 + 1e-9]),
+        Tensor::of_slice(&[1.0, 2.0]),
+    );
+    let broadcast = Normal::new(Tensor::from(0.0), Tensor::from(1.0));
+    let single = Normal::new(
+        Tensor::of_slice(&[0.0, 1.0]).to_kind(Kind::Float),
+        Tensor::of_slice(&[1.0, 2.0]).to_kind(Kind::Float),
+    );
+    assert!(normal == same);
+    assert!(normal != shifted);
+    assert!(normal != broadcast);
+    // Parameters of another kind do not compare equal, even if their values match.
+    assert!(normal != single);
+}
+
+#[test]
+fn approx_eq() {
+    let normal = Normal::new(Tensor::of_slice(&[0.0, 1.0]), Tensor::of_slice(&[1.0, 2.0]));
+    let shifted = Normal::new(
+        Tensor::of_slice(&[0.0, 1.0 + 1e-9]),
+        Tensor::of_slice(&[1.0, 2.0]),
+    );
+    let far = Normal::new(Tensor::of_slice(&[0.0, 1.1]), Tensor::of_slice(&[1.0, 2.0]));
+    assert!(normal.approx_eq(&shifted, 1e-6, 1e-8));
+    assert!(!normal.approx_eq(&far, 1e-6, 1e-8));
+    assert!(normal.approx_eq(&far, 0.0, 0.2));
+
+    let mean = Tensor::of_slice(&[0.5, -1.0]);
+    let cov = Tensor::of_slice(&[2.0, 0.3, 0.3, 1.0]).reshape(&[2, 2]);
+    let from_cov = MultivariateNormal::from_cov(&mean, &cov);
+    let from_tril = MultivariateNormal::from_scale_tril(&mean, cov.cholesky(false));
+    assert!(from_cov.approx_eq(&from_tril, 1e-6, 1e-8));
+}
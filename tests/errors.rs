@@ -0,0 +1,47 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tch::Tensor;
+use tch_distr::{Distribution, Error, MultivariateNormal, Poisson};
+
+static REPORTED: AtomicUsize = AtomicUsize::new(0);
+
+// The panic hook is global, so this is the only test of the binary that panics.
+#[test]
+fn caught_panics_are_not_reported() {
+    std::panic::set_hook(Box::new(|_| {
+        REPORTED.fetch_add(1, Ordering::SeqCst);
+    }));
+    let poisson = Poisson::new(Tensor::of_slice(&[1.0, 2.0]));
+    let val = Tensor::of_slice(&[0.5, 0.25]);
+
+    assert!(matches!(poisson.try_icdf(&val), Err(Error::Evaluation(_))));
+    assert_eq!(REPORTED.load(Ordering::SeqCst), 0);
+
+    assert!(std::panic::catch_unwind(|| poisson.icdf(&val)).is_err());
+    assert_eq!(REPORTED.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn wrong_shaped_values_are_shape_errors() {
+    let mvn = MultivariateNormal::from_cov(
+        Tensor::zeros(&[3, 2], tch::kind::DOUBLE_CPU),
+        Tensor::eye(2, tch::kind::DOUBLE_CPU),
+    );
+    let wrong_event = Tensor::zeros(&[3, 4], tch::kind::DOUBLE_CPU);
+    assert!(matches!(
+        mvn.try_log_prob(&wrong_event),
+        Err(Error::Shape(_))
+    ));
+    let wrong_batch = Tensor::zeros(&[4, 2], tch::kind::DOUBLE_CPU);
+    assert!(matches!(
+        mvn.try_log_prob(&wrong_batch),
+        Err(Error::Shape(_))
+    ));
+    assert!(mvn
+        .try_log_prob(&Tensor::zeros(&[5, 1, 2], tch::kind::DOUBLE_CPU))
+        .is_ok());
+
+    let poisson = Poisson::new(Tensor::of_slice(&[1.0, 2.0]));
+    let val = Tensor::of_slice(&[0.0, 1.0, 2.0]);
+    assert!(matches!(poisson.try_cdf(&val), Err(Error::Shape(_))));
+    assert!(matches!(poisson.try_sample(&[-1]), Err(Error::Shape(_))));
+}
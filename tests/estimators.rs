@@ -0,0 +1,75 @@
+use tch::{Kind, Tensor};
+use tch_distr::{
+    estimators::{
+        importance_sampling, score_function, self_normalized_importance_sampling, Baseline,
+        MovingAverage,
+    },
+    Categorical, Distribution, Normal,
+};
+
+const SEED: i64 = 42;
+
+fn reward(x: &Tensor) -> Tensor {
+    Tensor::of_slice(&[1.0, -2.0, 3.0]).index_select(0, x)
+}
+
+fn expected_gradient(logits: &Tensor) -> Tensor {
+    let probs = logits.softmax(-1, Kind::Double);
+    let rewards = Tensor::of_slice(&[1.0, -2.0, 3.0]);
+    let mean = (&probs * &rewards).sum(Kind::Double);
+    probs * (rewards - mean)
+}
+
+#[test]
+fn score_function_gradients() {
+    let mut logits = Tensor::of_slice(&[0.2, -0.5, 0.1]).set_requires_grad(true);
+    let dist = Categorical::from_logits(&logits);
+    let expected = expected_gradient(&logits.detach());
+
+    for baseline in [Baseline::None, Baseline::LeaveOneOut] {
+        tch::manual_seed(SEED);
+        let surrogate = score_function(&dist, reward, 50_000, baseline);
+        assert_eq!(surrogate.size(), Vec::<i64>::new());
+        logits.zero_grad();
+        surrogate.backward();
+        assert!(logits.grad().allclose(&expected, 0.0, 0.05, false));
+    }
+}
+
+#[test]
+fn moving_average_baseline() {
+    tch::manual_seed(SEED);
+    let mut logits = Tensor::of_slice(&[0.2, -0.5, 0.1]).set_requires_grad(true);
+    let dist = Categorical::from_logits(&logits);
+    let mut average = MovingAverage::new(0.9);
+    assert!(average.value().is_none());
+
+    let surrogate = score_function(&dist, reward, 1000, Baseline::MovingAverage(&mut average));
+    let first = average.value().unwrap().detach();
+    assert!(first.allclose(&surrogate.detach(), 1e-6, 1e-8, false));
+
+    let surrogate = score_function(&dist, reward, 50_000, Baseline::MovingAverage(&mut average));
+    logits.zero_grad();
+    surrogate.backward();
+    assert!(logits
+        .grad()
+        .allclose(&expected_gradient(&logits.detach()), 0.0, 0.05, false));
+}
+
+#[test]
+fn importance_sampling_estimates() {
+    tch::manual_seed(SEED);
+    let target = Normal::from((1.0, 0.5));
+    let proposal = Normal::from((0.0, 2.0));
+    let unnormalized = |x: &Tensor| target.log_prob(x) + 3f64.ln();
+
+    let result = importance_sampling(unnormalized, &proposal, 50_000);
+    assert_eq!(result.log_weights.size(), [50_000]);
+    assert!((f64::from(&result.estimate) - 3f64.ln()).abs() < 0.02);
+    let ess = f64::from(&result.effective_sample_size);
+    assert!(ess > 1.0 && ess < 50_000.0);
+
+    let result =
+        self_normalized_importance_sampling(unnormalized, &proposal, |x| x.shallow_clone(), 50_000);
+    assert!((f64::from(&result.estimate) - 1.0).abs() < 0.02);
+}
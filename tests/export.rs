@@ -0,0 +1,101 @@
+use std::{collections::HashMap, path::PathBuf};
+use tch::Tensor;
+use tch_distr::{export, Distribution, Error, Gamma, MultivariateNormal, Normal};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tch_distr_export_{}", name))
+}
+
+fn read_npz(path: &PathBuf) -> HashMap<String, Tensor> {
+    let arrays = Tensor::read_npz(path).unwrap().into_iter().collect();
+    std::fs::remove_file(path).unwrap();
+    arrays
+}
+
+#[test]
+fn export_samples() {
+    let dist = Normal::new(
+        Tensor::of_slice(&[0.0f64, 1.0]),
+        Tensor::of_slice(&[1.0f64, 2.0]),
+    );
+
+    let path = temp_path("samples.npy");
+    export::samples_to_npy(&dist, &[100], &path).unwrap();
+    assert_eq!(Tensor::read_npy(&path).unwrap().size(), vec![100, 2]);
+    std::fs::remove_file(&path).unwrap();
+
+    let path = temp_path("samples.npz");
+    export::samples_to_npz(&dist, &[("small", &[10]), ("large", &[1000, 3])], &path).unwrap();
+    let arrays = read_npz(&path);
+    assert_eq!(arrays["small"].size(), vec![10, 2]);
+    assert_eq!(arrays["large"].size(), vec![1000, 3, 2]);
+}
+
+#[test]
+fn export_grid() {
+    let dist = Normal::new(
+        Tensor::of_slice(&[0.0f64, 1.0]),
+        Tensor::of_slice(&[1.0f64, 2.0]),
+    );
+    let path = temp_path("normal_grid.npz");
+    export::linspace_to_npz(&dist, -3.0, 3.0, 50, &path).unwrap();
+    let arrays = read_npz(&path);
+    assert_eq!(arrays["values"].size(), vec![50]);
+    assert_eq!(arrays["log_prob"].size(), vec![50, 2]);
+    let expected = dist.cdf(&arrays["values"].unsqueeze(-1));
+    assert!(arrays["cdf"].allclose(&expected, 1e-12, 1e-12, false));
+
+    // Gamma has no cdf, and the grid extends past its support.
+    let dist = Gamma::new(Tensor::from(2.0f64), Tensor::from(1.0f64));
+    let path = temp_path("gamma_grid.npz");
+    export::grid_to_npz(&dist, &Tensor::of_slice(&[-1.0f64, 0.5, 2.0]), &path).unwrap();
+    let arrays = read_npz(&path);
+    assert_eq!(arrays["log_prob"].size(), vec![3]);
+    assert!(!arrays.contains_key("cdf"));
+
+    let dist = MultivariateNormal::from_cov(
+        Tensor::zeros(&[2], tch::kind::DOUBLE_CPU),
+        Tensor::eye(2, tch::kind::DOUBLE_CPU),
+    );
+    let result = export::linspace_to_npz(&dist, -1.0, 1.0, 10, temp_path("mvn_grid.npz"));
+    assert!(matches!(result, Err(Error::Shape(_))));
+}
+
+#[cfg(feature = "arrow")]
+#[test]
+fn export_arrow() {
+    use arrow::array::{Float64Array, Int64Array};
+    use tch_distr::{Categorical, MultivariateNormal};
+
+    let dist = MultivariateNormal::from_cov(
+        Tensor::of_slice(&[0.0f64, 5.0]),
+        Tensor::eye(2, tch::kind::DOUBLE_CPU),
+    );
+    let batch = export::to_arrow(&dist, 100, &["a", "b"]).unwrap();
+    assert_eq!(batch.num_rows(), 100);
+    assert_eq!(batch.schema().field(1).name(), "b");
+    let b = batch
+        .column(1)
+        .as_any()
+        .downcast_ref::<Float64Array>()
+        .unwrap();
+    let mean = b.values().iter().sum::<f64>() / 100.0;
+    assert!((mean - 5.0).abs() < 0.5);
+
+    let dist = Categorical::from_probs(Tensor::of_slice(&[0.5f64, 0.5]));
+    let batch = export::to_arrow(&dist, 10, &[]).unwrap();
+    assert_eq!(batch.schema().field(0).name(), "x0");
+    assert!(batch
+        .column(0)
+        .as_any()
+        .downcast_ref::<Int64Array>()
+        .is_some());
+
+    let batch = export::to_arrow(&dist, 0, &[]).unwrap();
+    assert_eq!((batch.num_rows(), batch.num_columns()), (0, 1));
+
+    assert!(matches!(
+        export::to_arrow(&dist, 10, &["a", "b"]),
+        Err(Error::Shape(_))
+    ));
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "python")]
+
+use pyo3::{prelude::*, types::IntoPyDict, wrap_pymodule};
+
+/// Runs `code` with the `tch_distr` extension module and `torch` in scope.
+fn run(code: &str) {
+    Python::with_gil(|py| {
+        let module = wrap_pymodule!(tch_distr::extension::tch_distr)(py);
+        let locals = [
+            ("tch_distr", module),
+            ("torch", py.import("torch").unwrap().into()),
+        ]
+        .into_py_dict(py);
+        if let Err(err) = py.run(code, None, Some(locals)) {
+            err.print(py);
+            panic!("python code failed");
+        }
+    });
+}
+
+#[test]
+fn normal() {
+    run(r#"
+loc, scale = torch.tensor([0.5, -1.0]), torch.tensor([1.0, 2.0])
+dist = tch_distr.Normal(loc, scale)
+reference = torch.distributions.Normal(loc, scale)
+value = torch.tensor([0.0, 1.0])
+assert torch.allclose(dist.log_prob(value), reference.log_prob(value))
+assert torch.allclose(dist.cdf(value), reference.cdf(value))
+assert torch.allclose(dist.icdf(torch.tensor([0.3, 0.8])), reference.icdf(torch.tensor([0.3, 0.8])))
+assert torch.allclose(dist.entropy(), reference.entropy())
+assert torch.allclose(dist.mean, loc) and torch.allclose(dist.stddev, scale)
+assert dist.batch_shape == [2] and dist.event_shape == []
+assert list(dist.sample().shape) == [2]
+assert list(dist.sample([3]).shape) == [3, 2]
+assert isinstance(dist, tch_distr.Distribution)
+assert isinstance(dist.to_torch(), torch.distributions.Normal)
+"#);
+}
+
+#[test]
+fn probs_or_logits() {
+    run(r#"
+probs = torch.tensor([0.2, 0.3, 0.5])
+dist = tch_distr.Categorical(probs=probs)
+from_logits = tch_distr.Categorical(logits=probs.log())
+value = torch.tensor([0, 2])
+assert torch.allclose(dist.log_prob(value), from_logits.log_prob(value))
+assert torch.allclose(dist.log_prob(value), probs.log()[value])
+for args in [{}, {"probs": probs, "logits": probs.log()}]:
+    try:
+        tch_distr.Bernoulli(**args)
+    except ValueError:
+        pass
+    else:
+        raise AssertionError("expected a ValueError")
+"#);
+}
+
+#[test]
+fn errors_are_raised() {
+    run(r#"
+dist = tch_distr.Poisson(torch.tensor([1.0, 2.0]))
+try:
+    dist.icdf(torch.tensor([0.5, 0.5]))
+except ValueError:
+    pass
+else:
+    raise AssertionError("expected a ValueError")
+"#);
+}
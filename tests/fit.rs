@@ -0,0 +1,237 @@
+use tch::{nn, Device, Kind, Tensor};
+use tch_distr::{
+    constraints::Constraint,
+    fit::{em, em_with, gradient_m_step, map, Params},
+    Bernoulli, Beta, Categorical, Distribution, Error, Exponential, Gamma, Geometric,
+    MixtureSameFamily, Normal, Poisson, Uniform,
+};
+
+const SEED: i64 = 42;
+
+#[test]
+fn map_estimate() {
+    tch::manual_seed(SEED);
+    let data = Tensor::randn(&[500], tch::kind::DOUBLE_CPU) * 0.5 + 2.0;
+    let labels = Tensor::of_slice(&[0i64, 1, 1, 2, 2, 2, 2, 2]);
+
+    let mut params = Params::new(Device::Cpu);
+    params
+        .add("mean", &Tensor::from(0.0), &Constraint::Real)
+        .add("stddev", &Tensor::from(1.0), &Constraint::Positive)
+        .add(
+            "probs",
+            &Tensor::of_slice(&[0.2, 0.3, 0.5]),
+            &Constraint::Simplex,
+        );
+    assert_eq!(params.names(), ["mean", "stddev", "probs"]);
+    assert_eq!(params.get("stddev").kind(), Kind::Double);
+
+    let prior = Normal::new(Tensor::from(0.0), Tensor::from(10.0));
+    let log_joint = |params: &Params| {
+        let normal = Normal::new(params.get("mean"), params.get("stddev"));
+        let categorical = Categorical::from_probs(params.get("probs"));
+        prior.log_prob(&params.get("mean"))
+            + normal.log_prob(&data).sum(Kind::Double)
+            + categorical.log_prob(&labels).sum(Kind::Double)
+    };
+    map(log_joint, &params, nn::Adam::default(), 0.05, 3000, 1e-9);
+
+    let stddev = f64::from(data.std(false));
+    assert!((f64::from(params.get("mean")) - f64::from(data.mean(Kind::Double))).abs() < 0.01);
+    assert!((f64::from(params.get("stddev")) - stddev).abs() < 0.01);
+    assert!(params.get("probs").allclose(
+        &Tensor::of_slice(&[0.125, 0.25, 0.625]),
+        0.0,
+        0.01,
+        false
+    ));
+}
+
+#[test]
+fn fit_moments() {
+    tch::manual_seed(SEED);
+    let normal = Normal::new(
+        Tensor::of_slice(&[-1.0, 2.0]),
+        Tensor::of_slice(&[0.5, 3.0]),
+    );
+    let fitted = Normal::fit_moments(&normal.sample(&[20000]));
+    assert_eq!(fitted.batch_shape(), [2]);
+    assert!(fitted.mean().allclose(normal.mean(), 0.0, 0.05, false));
+    assert!(fitted.stddev().allclose(normal.stddev(), 0.02, 0.0, false));
+
+    let gamma = Gamma::new(Tensor::of_slice(&[2.0, 5.0]), Tensor::of_slice(&[1.0, 0.5]));
+    let fitted = Gamma::fit_moments(&gamma.sample(&[20000]));
+    assert!(fitted
+        .concentration()
+        .allclose(gamma.concentration(), 0.05, 0.0, false));
+    assert!(fitted.rate().allclose(gamma.rate(), 0.05, 0.0, false));
+
+    let beta = Beta::new(Tensor::of_slice(&[0.5, 3.0]), Tensor::of_slice(&[2.0, 4.0]));
+    let fitted = Beta::fit_moments(&beta.sample(&[20000]));
+    assert!(fitted
+        .concentration1()
+        .allclose(beta.concentration1(), 0.05, 0.0, false));
+    assert!(fitted
+        .concentration0()
+        .allclose(beta.concentration0(), 0.05, 0.0, false));
+}
+
+#[test]
+fn fit_mle() {
+    tch::manual_seed(SEED);
+    let n = 20000;
+    let params = Tensor::of_slice(&[0.3, 0.8]);
+
+    let fitted = Normal::fit_mle(&Normal::new(&params, &params).sample(&[n])).unwrap();
+    assert!(fitted.mean().allclose(&params, 0.0, 0.02, false));
+    assert!(fitted.stddev().allclose(&params, 0.02, 0.0, false));
+
+    let fitted = Exponential::fit_mle(&Exponential::new(&params).sample(&[n])).unwrap();
+    assert!(fitted.rate().allclose(&params, 0.03, 0.0, false));
+
+    let fitted = Poisson::fit_mle(&Poisson::new(&params).sample(&[n])).unwrap();
+    assert!(fitted.rate().allclose(&params, 0.03, 0.0, false));
+
+    let fitted = Bernoulli::fit_mle(&Bernoulli::from_probs(&params).sample(&[n])).unwrap();
+    assert!(fitted.probs().allclose(&params, 0.0, 0.02, false));
+
+    let fitted = Geometric::fit_mle(&Geometric::from_probs(&params).sample(&[n])).unwrap();
+    assert!(fitted.probs().allclose(&params, 0.0, 0.02, false));
+
+    let uniform = Uniform::new(-&params, &params);
+    let fitted = Uniform::fit_mle(&uniform.sample(&[n])).unwrap();
+    assert!(fitted.low().allclose(uniform.low(), 0.0, 0.01, false));
+    assert!(fitted.high().allclose(uniform.high(), 0.0, 0.01, false));
+
+    let gamma = Gamma::new(Tensor::of_slice(&[0.5, 4.0]), Tensor::of_slice(&[2.0, 0.5]));
+    let fitted = Gamma::fit_mle(&gamma.sample(&[n])).unwrap();
+    assert!(fitted
+        .concentration()
+        .allclose(gamma.concentration(), 0.03, 0.0, false));
+    assert!(fitted.rate().allclose(gamma.rate(), 0.03, 0.0, false));
+
+    let beta = Beta::new(Tensor::of_slice(&[0.5, 3.0]), Tensor::of_slice(&[2.0, 4.0]));
+    let fitted = Beta::fit_mle(&beta.sample(&[n])).unwrap();
+    assert!(fitted
+        .concentration1()
+        .allclose(beta.concentration1(), 0.03, 0.0, false));
+    assert!(fitted
+        .concentration0()
+        .allclose(beta.concentration0(), 0.03, 0.0, false));
+}
+
+#[test]
+fn fit_mle_errors() {
+    assert!(matches!(
+        Normal::fit_mle(&Tensor::from(1.0)),
+        Err(Error::Shape(_))
+    ));
+    assert!(matches!(
+        Poisson::fit_mle(&Tensor::of_slice(&[1.0, 2.5])),
+        Err(Error::Support(_))
+    ));
+    assert!(matches!(
+        Gamma::fit_mle(&Tensor::of_slice(&[2.0, 2.0, 2.0])),
+        Err(Error::Evaluation(_))
+    ));
+}
+
+fn gaussian_mixture() -> MixtureSameFamily<Normal> {
+    MixtureSameFamily::new(
+        Categorical::from_probs(Tensor::of_slice(&[0.3, 0.7])),
+        Normal::new(
+            Tensor::of_slice(&[-2.0, 3.0]),
+            Tensor::of_slice(&[0.5, 1.0]),
+        ),
+    )
+}
+
+#[test]
+fn mixture_same_family() {
+    let mixture = gaussian_mixture();
+    let x = Tensor::of_slice(&[-2.5, 0.0, 1.0, 3.5]);
+    let expected = (Tensor::from(0.3f64).log()
+        + Normal::new(Tensor::from(-2.0), Tensor::from(0.5)).log_prob(&x))
+    .logaddexp(
+        &(Tensor::from(0.7f64).log()
+            + Normal::new(Tensor::from(3.0), Tensor::from(1.0)).log_prob(&x)),
+    );
+    assert!(mixture.log_prob(&x).allclose(&expected, 1e-6, 1e-6, false));
+    assert_eq!(mixture.batch_shape(), [] as [i64; 0]);
+    assert!((f64::from(mixture.mean()) - 1.5).abs() < 1e-6);
+    // 0.3 * 0.25 + 0.7 * 1.0 + 0.3 * 3.5^2 + 0.7 * 1.5^2
+    assert!((f64::from(mixture.variance()) - 5.45).abs() < 1e-6);
+
+    tch::manual_seed(SEED);
+    let samples = mixture.sample(&[20000]);
+    assert_eq!(samples.size(), [20000]);
+    assert!((f64::from(samples.mean(Kind::Double)) - 1.5).abs() < 0.1);
+}
+
+#[test]
+fn em_gaussian_mixture() {
+    tch::manual_seed(SEED);
+    let data = gaussian_mixture().sample(&[5000]);
+    let mut mixture = MixtureSameFamily::new(
+        Categorical::from_probs(Tensor::of_slice(&[0.5, 0.5])),
+        Normal::new(
+            Tensor::of_slice(&[-1.0, 1.0]),
+            Tensor::of_slice(&[1.0, 1.0]),
+        ),
+    );
+    let result = em(&mut mixture, &data, 200, 1e-8);
+    assert!(result.converged);
+    assert!(
+        (result.log_likelihood - f64::from(mixture.log_prob(&data).mean(Kind::Double))).abs()
+            < 1e-6
+    );
+    assert!(mixture
+        .mixture()
+        .probs()
+        .allclose(&Tensor::of_slice(&[0.3, 0.7]), 0.0, 0.03, false));
+    assert!(mixture
+        .components()
+        .mean()
+        .allclose(&Tensor::of_slice(&[-2.0, 3.0]), 0.0, 0.1, false));
+    assert!(mixture.components().stddev().allclose(
+        &Tensor::of_slice(&[0.5, 1.0]),
+        0.0,
+        0.1,
+        false
+    ));
+}
+
+#[test]
+fn em_gradient_m_step() {
+    tch::manual_seed(SEED);
+    let rates = Poisson::new(Tensor::of_slice(&[2.0f32, 10.0]));
+    let data = MixtureSameFamily::new(
+        Categorical::from_probs(Tensor::of_slice(&[0.5f32, 0.5])),
+        rates,
+    )
+    .sample(&[2000]);
+    let init = || {
+        MixtureSameFamily::new(
+            Categorical::from_probs(Tensor::of_slice(&[0.5f32, 0.5])),
+            Poisson::new(Tensor::of_slice(&[1.0f32, 5.0])),
+        )
+    };
+
+    let mut closed_form = init();
+    em(&mut closed_form, &data, 100, 1e-6);
+    let mut gradient = init();
+    let m_step = gradient_m_step(
+        &["rate"],
+        |p: &[Tensor]| Poisson::new(p[0].shallow_clone()),
+        nn::Adam::default(),
+        0.1,
+        200,
+    );
+    em_with(&mut gradient, &data, 100, 1e-6, m_step);
+    assert!(gradient.components().mean().allclose(
+        &closed_form.components().mean(),
+        0.0,
+        0.1,
+        false
+    ));
+}
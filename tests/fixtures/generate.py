@@ -0,0 +1,92 @@
+"""Generates the PyTorch reference outputs compared against by `tests/golden.rs`.
+
+Run from the root of the repository with a Python installation providing torch and numpy:
+
+    python tests/fixtures/generate.py
+
+Each case is written to `tests/fixtures/<case>.npz`, holding the parameters as `param_<name>`,
+the evaluated values as `<method>_value` next to the results as `<method>`, and the moments and
+entropy. Methods a distribution does not implement are left out.
+"""
+import os
+
+import numpy as np
+import torch
+import torch.distributions as D
+
+torch.set_default_dtype(torch.float64)
+
+VALUES = {
+    "real": torch.tensor([[-1.5, 0.0], [0.5, 2.0]]),
+    "positive": torch.tensor([[0.1, 1.0], [2.0, 5.0]]),
+    "unit": torch.tensor([[0.1, 0.3], [0.6, 0.9]]),
+    "binary": torch.tensor([[0.0, 1.0], [1.0, 0.0]]),
+    "count": torch.tensor([[0.0, 1.0], [3.0, 7.0]]),
+    "category": torch.tensor([[0.0, 1.0], [2.0, 1.0]]),
+    "vector": torch.tensor([[[0.0, 1.0], [-1.0, 0.5]], [[2.0, -2.0], [0.3, 0.3]]]),
+}
+PROBS = torch.tensor([[0.1, 0.3], [0.6, 0.9]])
+
+CASES = {
+    "bernoulli": (D.Bernoulli, {"probs": torch.tensor([0.2, 0.7])}, "binary"),
+    "beta": (
+        D.Beta,
+        {"concentration1": torch.tensor([0.5, 2.0]), "concentration0": torch.tensor([1.5, 3.0])},
+        "unit",
+    ),
+    "categorical": (D.Categorical, {"probs": torch.tensor([0.2, 0.3, 0.5])}, "category"),
+    "cauchy": (D.Cauchy, {"loc": torch.tensor([0.0, 1.0]), "scale": torch.tensor([1.0, 2.0])}, "real"),
+    "exponential": (D.Exponential, {"rate": torch.tensor([0.5, 2.0])}, "positive"),
+    "gamma": (
+        D.Gamma,
+        {"concentration": torch.tensor([0.5, 3.0]), "rate": torch.tensor([1.0, 2.0])},
+        "positive",
+    ),
+    "geometric": (D.Geometric, {"probs": torch.tensor([0.2, 0.7])}, "count"),
+    "multivariate_normal": (
+        D.MultivariateNormal,
+        {
+            "loc": torch.tensor([0.5, -1.0]),
+            "covariance_matrix": torch.tensor([[2.0, 0.3], [0.3, 1.0]]),
+        },
+        "vector",
+    ),
+    "normal": (D.Normal, {"loc": torch.tensor([0.0, 1.0]), "scale": torch.tensor([1.0, 2.0])}, "real"),
+    "poisson": (D.Poisson, {"rate": torch.tensor([0.5, 4.0])}, "count"),
+    "uniform": (D.Uniform, {"low": torch.tensor([0.0, -1.0]), "high": torch.tensor([1.0, 3.0])}, "unit"),
+}
+
+
+def evaluate(f, *args):
+    try:
+        return f(*args)
+    except NotImplementedError:
+        return None
+
+
+def main():
+    directory = os.path.dirname(os.path.abspath(__file__))
+    for case, (family, params, support) in CASES.items():
+        dist = family(**params)
+        arrays = {"param_" + name: value for name, value in params.items()}
+        value = VALUES[support]
+        results = {
+            "log_prob": (value, evaluate(dist.log_prob, value)),
+            "cdf": (value, evaluate(dist.cdf, value)),
+            "icdf": (PROBS, evaluate(dist.icdf, PROBS)),
+        }
+        for method, (value, result) in results.items():
+            if result is not None:
+                arrays[method + "_value"] = value
+                arrays[method] = result
+        moments = {
+            "mean": evaluate(lambda: dist.mean),
+            "variance": evaluate(lambda: dist.variance),
+            "entropy": evaluate(dist.entropy),
+        }
+        arrays.update({name: result for name, result in moments.items() if result is not None})
+        np.savez(os.path.join(directory, case + ".npz"), **{k: v.numpy() for k, v in arrays.items()})
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,111 @@
+//! Parity tests against PyTorch reference outputs stored in `tests/fixtures`, which run without
+//! a Python installation. The fixtures are generated by `tests/fixtures/generate.py`; the
+//! committed ones were computed from the same closed forms in double precision without
+//! PyTorch, and are replaced by its outputs when the script is run.
+use std::{
+    collections::HashMap,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+    path::Path,
+};
+use tch::Tensor;
+use tch_distr::{
+    Bernoulli, Beta, Categorical, Cauchy, Distribution, Exponential, Gamma, Geometric,
+    MultivariateNormal, Normal, Poisson, Uniform,
+};
+
+type Arrays = HashMap<String, Tensor>;
+
+/// Builds the distribution of a case from a function returning its named parameters.
+type Build = fn(&dyn Fn(&str) -> Tensor) -> Box<dyn Distribution>;
+
+/// Returns the arrays of the fixture `case`, panicking if it is missing.
+fn load(case: &str) -> Arrays {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(format!("{}.npz", case));
+    assert!(
+        path.exists(),
+        "missing fixture {}: generate the fixtures with tests/fixtures/generate.py",
+        case
+    );
+    Tensor::read_npz(&path).unwrap().into_iter().collect()
+}
+
+/// Returns the result of `f`, or `None` if it is not implemented by this crate.
+fn evaluate<F: FnOnce() -> Tensor>(f: F) -> Option<Tensor> {
+    catch_unwind(AssertUnwindSafe(f))
+        .map_err(|payload| {
+            let msg = payload
+                .downcast_ref::<&str>()
+                .map(|msg| msg.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned());
+            match msg {
+                Some(msg) if msg.contains("not implemented") => (),
+                _ => resume_unwind(payload),
+            }
+        })
+        .ok()
+}
+
+/// Compares the methods of `dist` against the reference outputs of `case`.
+fn check(case: &str, arrays: &Arrays, dist: &dyn Distribution) {
+    let compare = |method: &str, result: Option<Tensor>| {
+        if let (Some(result), Some(expected)) = (result, arrays.get(method)) {
+            assert!(
+                result.allclose(expected, 1e-6, 1e-8, true),
+                "{}.{}: {:?} != {:?}",
+                case,
+                method,
+                result,
+                expected
+            );
+        }
+    };
+    for method in ["log_prob", "cdf", "icdf"] {
+        if let Some(val) = arrays.get(&format!("{}_value", method)) {
+            let result = evaluate(|| match method {
+                "log_prob" => dist.log_prob(val),
+                "cdf" => dist.cdf(val),
+                _ => dist.icdf(val),
+            });
+            compare(method, result);
+        }
+    }
+    compare("mean", evaluate(|| dist.mean()));
+    compare("variance", evaluate(|| dist.variance()));
+    compare("entropy", evaluate(|| dist.entropy()));
+}
+
+#[test]
+fn golden_parity() {
+    let cases: Vec<(&str, Build)> = vec![
+        ("bernoulli", |p| Box::new(Bernoulli::from_probs(p("probs")))),
+        ("beta", |p| {
+            Box::new(Beta::new(p("concentration1"), p("concentration0")))
+        }),
+        ("categorical", |p| {
+            Box::new(Categorical::from_probs(p("probs")))
+        }),
+        ("cauchy", |p| Box::new(Cauchy::new(p("loc"), p("scale")))),
+        ("exponential", |p| Box::new(Exponential::new(p("rate")))),
+        ("gamma", |p| {
+            Box::new(Gamma::new(p("concentration"), p("rate")))
+        }),
+        ("geometric", |p| Box::new(Geometric::from_probs(p("probs")))),
+        ("multivariate_normal", |p| {
+            Box::new(MultivariateNormal::from_cov(
+                p("loc"),
+                p("covariance_matrix"),
+            ))
+        }),
+        ("normal", |p| Box::new(Normal::new(p("loc"), p("scale")))),
+        ("poisson", |p| Box::new(Poisson::new(p("rate")))),
+        ("uniform", |p| Box::new(Uniform::new(p("low"), p("high")))),
+    ];
+    for (case, build) in cases {
+        let arrays = load(case);
+        let param = |name: &str| arrays[&format!("param_{}", name)].shallow_clone();
+        let dist = build(&param);
+        check(case, &arrays, dist.as_ref());
+    }
+}
@@ -0,0 +1,113 @@
+use tch::{nn, nn::OptimizerConfig, Device, Kind, Tensor};
+use tch_distr::{
+    gp::{GaussianProcess, Kernel, Matern, Rbf},
+    Distribution, MultivariateNormal,
+};
+
+fn inputs() -> Tensor {
+    Tensor::linspace(-2.0, 2.0, 8, (Kind::Double, Device::Cpu)).unsqueeze(-1)
+}
+
+#[test]
+fn kernels() {
+    let x = Tensor::of_slice(&[0.0f64, 1.0, 3.0]).unsqueeze(-1);
+    let lengthscale = Tensor::from(2.0f64);
+    let variance = Tensor::from(1.5f64);
+    let r = Tensor::of_slice(&[0.0f64, 1.0, 3.0, 1.0, 0.0, 2.0, 3.0, 2.0, 0.0]).view([3, 3]) / 2.0;
+
+    let rbf = Rbf::new(&lengthscale, &variance).matrix(&x, &x);
+    let expected = (r.pow_tensor_scalar(2) * -0.5).exp() * 1.5;
+    assert!(rbf.allclose(&expected, 1e-8, 1e-8, false));
+
+    let matern = Matern::new(0.5, &lengthscale, &variance).matrix(&x, &x);
+    assert!(matern.allclose(&((-&r).exp() * 1.5), 1e-5, 1e-5, false));
+
+    let matern = Matern::new(1.5, &lengthscale, &variance).matrix(&x, &x);
+    let scaled = &r * 3f64.sqrt();
+    let expected = (&scaled + 1.0) * (-&scaled).exp() * 1.5;
+    assert!(matern.allclose(&expected, 1e-5, 1e-5, false));
+
+    let matern = Matern::new(2.5, &lengthscale, &variance).matrix(&x, &x);
+    let scaled = &r * 5f64.sqrt();
+    let expected = (&scaled + 1.0 + scaled.pow_tensor_scalar(2) / 3.0) * (-&scaled).exp() * 1.5;
+    assert!(matern.allclose(&expected, 1e-5, 1e-5, false));
+}
+
+#[test]
+#[should_panic]
+fn matern_unsupported_nu() {
+    Matern::new(1.0, Tensor::from(1.0f64), Tensor::from(1.0f64));
+}
+
+#[test]
+fn gp_marginal_likelihood() {
+    let x = inputs();
+    let y = x.squeeze_dim(-1).sin();
+    let kernel = Rbf::new(Tensor::from(0.7f64), Tensor::from(1.2f64));
+    let gp = GaussianProcess::new(kernel, Tensor::from(0.1f64));
+
+    let cov = gp.kernel().matrix(&x, &x) + Tensor::eye(8, (Kind::Double, Device::Cpu)) * 0.1;
+    let expected = MultivariateNormal::from_cov(y.zeros_like(), cov).log_prob(&y);
+    let mll = gp.marginal_log_likelihood(&x, &y);
+    assert!((f64::from(&mll) - f64::from(&expected)).abs() < 1e-4);
+
+    let prior = gp.prior(&x);
+    assert_eq!(prior.batch_shape(), &[] as &[i64]);
+    assert_eq!(prior.event_shape(), &[8]);
+}
+
+#[test]
+fn gp_posterior() {
+    let x = inputs();
+    let y = x.squeeze_dim(-1).sin();
+    let x_test = Tensor::of_slice(&[-1.3f64, 0.1, 0.9]).unsqueeze(-1);
+    let kernel = Matern::new(2.5, Tensor::from(1.0f64), Tensor::from(1.0f64));
+    let gp = GaussianProcess::new(kernel, Tensor::from(0.05f64));
+    let predictive = gp.posterior(&x, &y).predict(&x_test);
+
+    let k_inv =
+        (gp.kernel().matrix(&x, &x) + Tensor::eye(8, (Kind::Double, Device::Cpu)) * 0.05).inverse();
+    let cross = gp.kernel().matrix(&x, &x_test);
+    let mean = cross
+        .tr()
+        .matmul(&k_inv)
+        .matmul(&y.unsqueeze(-1))
+        .squeeze_dim(-1);
+    let cov = gp.kernel().matrix(&x_test, &x_test) - cross.tr().matmul(&k_inv).matmul(&cross);
+    assert!(predictive.mean().allclose(&mean, 1e-6, 1e-6, false));
+    assert!(predictive
+        .covariance_matrix()
+        .allclose(&cov, 1e-4, 1e-4, false));
+
+    // With almost no noise the posterior interpolates the observations.
+    let gp = GaussianProcess::new(
+        Rbf::new(Tensor::from(1.0f64), Tensor::from(1.0f64)),
+        Tensor::from(1e-6f64),
+    );
+    let fitted = gp.posterior(&x, &y).predict(&x);
+    assert!(fitted.mean().allclose(&y, 1e-3, 1e-3, false));
+}
+
+#[test]
+fn gp_hyperparameter_training() {
+    tch::manual_seed(42);
+    let x = inputs().to_kind(Kind::Float);
+    let y = (x.squeeze_dim(-1) * 2.0).sin() + Tensor::randn(&[8], (Kind::Float, Device::Cpu)) * 0.1;
+
+    let vs = nn::VarStore::new(Device::Cpu);
+    let root = vs.root();
+    let log_lengthscale = root.var("log_lengthscale", &[], nn::Init::Const(1.0));
+    let log_variance = root.var("log_variance", &[], nn::Init::Const(0.0));
+    let log_noise = root.var("log_noise", &[], nn::Init::Const(0.0));
+    let mll = || {
+        let kernel = Rbf::new(log_lengthscale.exp(), log_variance.exp());
+        GaussianProcess::new(kernel, log_noise.exp()).marginal_log_likelihood(&x, &y)
+    };
+
+    let initial = f64::from(mll());
+    let mut opt = nn::Adam::default().build(&vs, 0.05).unwrap();
+    for _ in 0..200 {
+        opt.backward_step(&-mll());
+    }
+    assert!(f64::from(mll()) > initial + 1.0);
+}
@@ -0,0 +1,149 @@
+use tch::Tensor;
+use tch_distr::{
+    testing::{gradcheck_log_prob, gradcheck_rsample},
+    Beta, Cauchy, Dirichlet, Distribution, Exponential, Gamma, MultivariateNormal, Normal, Uniform,
+};
+
+fn params(values: &[&[f64]]) -> Vec<Tensor> {
+    values.iter().map(|v| Tensor::of_slice(v)).collect()
+}
+
+#[test]
+fn normal() {
+    let build = |p: &[Tensor]| Normal::new(p[0].shallow_clone(), p[1].shallow_clone());
+    let params = params(&[&[1.0, -0.5], &[2.0, 0.7]]);
+    assert!(gradcheck_log_prob(
+        build,
+        &params,
+        &Tensor::of_slice(&[0.3, 1.2])
+    ));
+    assert!(gradcheck_rsample(build, &params, &[3]));
+}
+
+#[test]
+fn cauchy() {
+    let build = |p: &[Tensor]| Cauchy::new(p[0].shallow_clone(), p[1].shallow_clone());
+    let params = params(&[&[1.0, -0.5], &[2.0, 0.7]]);
+    assert!(gradcheck_log_prob(
+        build,
+        &params,
+        &Tensor::of_slice(&[0.3, 1.2])
+    ));
+}
+
+#[test]
+fn exponential() {
+    let build = |p: &[Tensor]| Exponential::new(p[0].shallow_clone());
+    let params = params(&[&[0.5, 2.0]]);
+    assert!(gradcheck_log_prob(
+        build,
+        &params,
+        &Tensor::of_slice(&[0.3, 1.2])
+    ));
+}
+
+#[test]
+fn gamma() {
+    let build = |p: &[Tensor]| Gamma::new(p[0].shallow_clone(), p[1].shallow_clone());
+    let params = params(&[&[1.5, 3.0], &[2.0, 0.7]]);
+    assert!(gradcheck_log_prob(
+        build,
+        &params,
+        &Tensor::of_slice(&[0.3, 1.2])
+    ));
+}
+
+/// Returns the gradients of the Monte Carlo estimate of the mean of the rsamples of `dist`.
+fn mean_gradients<D: Distribution>(dist: &D, params: &[&Tensor]) -> Vec<f64> {
+    tch::manual_seed(0);
+    dist.rsample(&[20_000]).mean(tch::Kind::Double).backward();
+    params.iter().map(|p| f64::from(p.grad())).collect()
+}
+
+#[test]
+fn gamma_rsample() {
+    let concentration = Tensor::from(1.5).set_requires_grad(true);
+    let rate = Tensor::from(2.0).set_requires_grad(true);
+    let dist = Gamma::new(&concentration, &rate);
+    assert!(!dist.sample(&[3]).requires_grad());
+    // The mean is concentration / rate.
+    let grads = mean_gradients(&dist, &[&concentration, &rate]);
+    assert!((grads[0] - 0.5).abs() < 0.03, "{:?}", grads);
+    assert!((grads[1] + 0.375).abs() < 0.03, "{:?}", grads);
+}
+
+#[test]
+fn beta_rsample() {
+    let concentration1 = Tensor::from(2.0).set_requires_grad(true);
+    let concentration0 = Tensor::from(3.0).set_requires_grad(true);
+    let dist = Beta::new(&concentration1, &concentration0);
+    assert!(!dist.sample(&[3]).requires_grad());
+    // The mean is concentration1 / (concentration1 + concentration0).
+    let grads = mean_gradients(&dist, &[&concentration1, &concentration0]);
+    assert!((grads[0] - 0.12).abs() < 0.01, "{:?}", grads);
+    assert!((grads[1] + 0.08).abs() < 0.01, "{:?}", grads);
+}
+
+#[test]
+fn dirichlet_rsample() {
+    let concentration = Tensor::of_slice(&[2.0, 3.0]).set_requires_grad(true);
+    let dist = Dirichlet::new(&concentration);
+    assert!(!dist.sample(&[3]).requires_grad());
+    assert!(gradcheck_log_prob(
+        |p: &[Tensor]| Dirichlet::new(p[0].shallow_clone()),
+        &params(&[&[2.0, 3.0]]),
+        &Tensor::of_slice(&[0.4, 0.6])
+    ));
+    // The mean of the first category is concentration[0] / concentration.sum().
+    tch::manual_seed(0);
+    dist.rsample(&[20_000])
+        .select(-1, 0)
+        .mean(tch::Kind::Double)
+        .backward();
+    let grads = Vec::<f64>::from(&concentration.grad());
+    assert!((grads[0] - 0.12).abs() < 0.01, "{:?}", grads);
+    assert!((grads[1] + 0.08).abs() < 0.01, "{:?}", grads);
+}
+
+#[test]
+fn multivariate_normal() {
+    let build = |p: &[Tensor]| {
+        MultivariateNormal::from_scale_tril(p[0].shallow_clone(), p[1].reshape(&[2, 2]).tril(0))
+    };
+    let params = params(&[&[1.0, -0.5], &[2.0, 0.0, 0.3, 1.5]]);
+    assert!(gradcheck_rsample(build, &params, &[3]));
+}
+
+#[test]
+fn sample_does_not_record_gradients() {
+    let loc = Tensor::of_slice(&[1.0, -0.5]).set_requires_grad(true);
+    let scale = Tensor::of_slice(&[2.0, 0.7]).set_requires_grad(true);
+    let dists: Vec<Box<dyn Distribution>> = vec![
+        Box::new(Cauchy::new(loc.shallow_clone(), scale.shallow_clone())),
+        Box::new(Exponential::new(scale.shallow_clone())),
+        Box::new(Uniform::new(loc.shallow_clone(), &loc + &scale)),
+        Box::new(MultivariateNormal::from_scale_tril(
+            loc.shallow_clone(),
+            scale.diag_embed(0, -2, -1),
+        )),
+    ];
+    for dist in &dists {
+        assert!(!dist.sample(&[3]).requires_grad());
+    }
+    let dist =
+        MultivariateNormal::from_scale_tril(loc.shallow_clone(), scale.diag_embed(0, -2, -1));
+    assert!(dist.rsample(&[3]).requires_grad());
+}
+
+#[test]
+fn borrowed_parameters() {
+    let mean = Tensor::of_slice(&[1.0, -0.5]).set_requires_grad(true);
+    let stddev = Tensor::of_slice(&[2.0, 0.7]).set_requires_grad(true);
+    let dist = Normal::new(&mean, &stddev);
+    assert_eq!(dist.mean().data_ptr(), mean.data_ptr());
+    dist.log_prob(&Tensor::of_slice(&[0.3, 1.2]))
+        .sum(tch::Kind::Double)
+        .backward();
+    assert!(mean.grad().defined());
+    assert!(stddev.grad().defined());
+}
@@ -0,0 +1,35 @@
+use tch::{Kind, Tensor};
+use tch_distr::{
+    Bernoulli, Binomial, Categorical, Distribution, Geometric, NegativeBinomial, Poisson,
+};
+
+/// Checks that `f` gives the same result for integer `values` as for their floating point
+/// equivalent, in the kind of the parameters.
+fn assert_accepts_integers(f: impl Fn(&Tensor) -> Tensor, values: &[i64]) {
+    let expected = f(&Tensor::of_slice(values).to_kind(Kind::Double));
+    for kind in [Kind::Int64, Kind::Int] {
+        let result = f(&Tensor::of_slice(values).to_kind(kind));
+        assert_eq!(result.kind(), Kind::Double);
+        assert!(result.allclose(&expected, 1e-12, 1e-12, false));
+    }
+}
+
+#[test]
+fn discrete_distributions_accept_integer_values() {
+    let probs = Tensor::of_slice(&[0.2, 0.7]);
+    let bernoulli = Bernoulli::from_probs(&probs);
+    assert_accepts_integers(|v| bernoulli.log_prob(v), &[0, 1]);
+    let poisson = Poisson::new(Tensor::of_slice(&[0.5, 3.0]));
+    assert_accepts_integers(|v| poisson.log_prob(v), &[0, 4]);
+    let geometric = Geometric::from_probs(&probs);
+    assert_accepts_integers(|v| geometric.log_prob(v), &[0, 3]);
+    let total_count = Tensor::of_slice(&[5.0, 10.0]);
+    let binomial = Binomial::from_probs(&total_count, &probs);
+    assert_accepts_integers(|v| binomial.log_prob(v), &[2, 10]);
+    assert_accepts_integers(|v| binomial.cdf(v), &[2, 7]);
+    let negative_binomial = NegativeBinomial::from_probs(&total_count, &probs);
+    assert_accepts_integers(|v| negative_binomial.log_prob(v), &[0, 6]);
+    assert_accepts_integers(|v| negative_binomial.cdf(v), &[1, 6]);
+    let categorical = Categorical::from_probs(Tensor::of_slice(&[0.1, 0.3, 0.6]));
+    assert_accepts_integers(|v| categorical.log_prob(v), &[0, 2]);
+}
@@ -0,0 +1,44 @@
+use tch::Tensor;
+use tch_distr::{Distribution, Exponential, Normal};
+
+#[test]
+fn equal_tailed_interval() {
+    let loc = Tensor::of_slice(&[0.0, 2.0]);
+    let normal = Normal::new(&loc, Tensor::of_slice(&[1.0, 0.5]));
+    let (lower, upper) = normal.interval(0.95);
+    let half_width = Tensor::of_slice(&[1.959_964, 0.979_982]);
+    assert!(lower.allclose(&(&loc - &half_width), 1e-5, 1e-5, false));
+    assert!(upper.allclose(&(&loc + &half_width), 1e-5, 1e-5, false));
+    let mass = normal.cdf(&upper) - normal.cdf(&lower);
+    assert!(mass.allclose(&Tensor::of_slice(&[0.95, 0.95]), 1e-6, 1e-6, false));
+}
+
+#[test]
+fn hdi_of_symmetric_distribution() {
+    tch::manual_seed(0);
+    let normal = Normal::new(Tensor::of_slice(&[0.0, 2.0]), Tensor::of_slice(&[1.0, 0.5]));
+    let (lower, upper) = normal.hdi(0.9);
+    let (expected_lower, expected_upper) = normal.interval(0.9);
+    assert!(lower.allclose(&expected_lower, 0.0, 0.05, false));
+    assert!(upper.allclose(&expected_upper, 0.0, 0.05, false));
+}
+
+#[test]
+fn hdi_of_asymmetric_distribution() {
+    tch::manual_seed(0);
+    let exponential = Exponential::new(Tensor::from(2.0));
+    // The density decreases on the support, so the highest density interval starts at zero.
+    let (lower, upper) = exponential.hdi(0.8);
+    let expected_upper = -(0.2f64).ln() / 2.0;
+    assert!(f64::from(&lower) >= 0.0 && f64::from(&lower) < 0.01);
+    assert!((f64::from(&upper) - expected_upper).abs() < 0.03);
+
+    let (tail_lower, tail_upper) = exponential.interval(0.8);
+    assert!(f64::from(&upper - &lower) < f64::from(&tail_upper - &tail_lower));
+}
+
+#[test]
+#[should_panic(expected = "mass must lie in (0, 1]")]
+fn hdi_rejects_invalid_mass() {
+    let _ = Normal::new(Tensor::from(0.0), Tensor::from(1.0)).hdi(1.5);
+}
@@ -0,0 +1,243 @@
+use tch::Tensor;
+use tch_distr::{
+    kl_divergence_batched, kl_divergence_to, Bernoulli, Beta, Categorical, Cauchy, Dirichlet,
+    Distribution, Exponential, Gamma, KullackLeiberDivergence, MultivariateNormal, Normal, Uniform,
+};
+
+fn normals(means: &[f64], stddevs: &[f64]) -> Vec<Normal> {
+    means
+        .iter()
+        .zip(stddevs)
+        .map(|(&m, &s)| Normal::new(Tensor::of_slice(&[m, m + 1.0]), Tensor::of_slice(&[s, s])))
+        .collect()
+}
+
+#[test]
+fn batched_matches_pairwise() {
+    let p = normals(&[0.0, 1.0, -2.0], &[1.0, 0.5, 2.0]);
+    let q = normals(&[0.5, -1.0, 3.0], &[2.0, 1.5, 0.3]);
+
+    let batched = kl_divergence_batched(&p, &q);
+    assert_eq!(batched.size(), [3, 2]);
+    let pairwise = Tensor::stack(
+        &p.iter()
+            .zip(&q)
+            .map(|(p, q)| p.kl_divergence(q))
+            .collect::<Vec<_>>(),
+        0,
+    );
+    assert!(batched.allclose(&pairwise, 1e-6, 1e-8, false));
+
+    let p: Vec<_> = [0.1, 0.5, 0.9]
+        .iter()
+        .map(|&p| Bernoulli::from_probs(Tensor::from(p)))
+        .collect();
+    let q: Vec<_> = [0.3, 0.2, 0.6]
+        .iter()
+        .map(|&q| Bernoulli::from_probs(Tensor::from(q)))
+        .collect();
+    let batched = kl_divergence_batched(&p, &q);
+    for (i, (p, q)) in p.iter().zip(&q).enumerate() {
+        assert!(batched
+            .get(i as i64)
+            .allclose(&p.kl_divergence(q), 1e-6, 1e-8, false));
+    }
+}
+
+#[test]
+fn batched_against_single() {
+    let p: Vec<_> = [0.5, 1.0, 4.0]
+        .iter()
+        .map(|&c| {
+            Gamma::new(
+                Tensor::of_slice(&[c, 2.0 * c]),
+                Tensor::of_slice(&[1.0, 3.0]),
+            )
+        })
+        .collect();
+    let q = Gamma::new(Tensor::of_slice(&[2.0, 1.0]), Tensor::of_slice(&[0.5, 2.0]));
+
+    let batched = kl_divergence_to(&p, &q);
+    assert_eq!(batched.size(), [3, 2]);
+    for (i, p) in p.iter().enumerate() {
+        assert!(batched
+            .get(i as i64)
+            .allclose(&p.kl_divergence(&q), 1e-6, 1e-8, false));
+    }
+}
+
+#[test]
+fn multivariate_normal() {
+    let p_mean = Tensor::of_slice(&[0.5, -1.0, 2.0]);
+    let p_var = Tensor::of_slice(&[1.0, 0.3, 2.5]);
+    let q_mean = Tensor::of_slice(&[-0.2, 0.4, 1.0]);
+    let q_var = Tensor::of_slice(&[0.7, 1.2, 0.9]);
+    let p = MultivariateNormal::from_cov(&p_mean, p_var.diag_embed(0, -2, -1));
+    let q = MultivariateNormal::from_precision(&q_mean, q_var.reciprocal().diag_embed(0, -2, -1));
+
+    let expected = Normal::new(&p_mean, p_var.sqrt())
+        .kl_divergence(&Normal::new(&q_mean, q_var.sqrt()))
+        .sum(tch::Kind::Double);
+    assert!(p.kl_divergence(&q).allclose(&expected, 1e-5, 1e-6, false));
+    assert!(p
+        .kl_divergence(&p)
+        .allclose(&Tensor::from(0.0), 1e-5, 1e-6, false));
+    assert_eq!(p.kl_divergence(&q).size(), Vec::<i64>::new());
+
+    assert!(q
+        .covariance_matrix()
+        .allclose(&q_var.diag_embed(0, -2, -1), 1e-6, 1e-8, false));
+    assert!(p.precision_matrix().allclose(
+        &p_var.reciprocal().diag_embed(0, -2, -1),
+        1e-6,
+        1e-8,
+        false
+    ));
+    assert!(p
+        .scale_tril()
+        .allclose(&p_var.sqrt().diag_embed(0, -2, -1), 1e-6, 1e-8, false));
+}
+
+#[test]
+fn multivariate_normal_diag() {
+    let p_mean = Tensor::of_slice(&[0.5, -1.0, 2.0, 0.0, 1.0, -0.5]).reshape(&[2, 3]);
+    let p_var = Tensor::of_slice(&[1.0, 0.3, 2.5, 0.4, 1.1, 0.8]).reshape(&[2, 3]);
+    let q_mean = Tensor::of_slice(&[-0.2, 0.4, 1.0]);
+    let q_var = Tensor::of_slice(&[0.7, 1.2, 0.9]);
+    let p = MultivariateNormal::from_diag(&p_mean, &p_var);
+    let q = MultivariateNormal::from_diag(&q_mean, &q_var);
+    let p_dense = MultivariateNormal::from_cov(&p_mean, p_var.diag_embed(0, -2, -1));
+    let q_dense = MultivariateNormal::from_cov(&q_mean, q_var.diag_embed(0, -2, -1));
+    assert!(p.is_diagonal() && !p_dense.is_diagonal());
+
+    assert_eq!(p.batch_shape(), [2]);
+    assert_eq!(q.batch_shape(), Vec::<i64>::new());
+    assert!(p
+        .kl_divergence(&q)
+        .allclose(&p_dense.kl_divergence(&q_dense), 1e-5, 1e-6, false));
+    assert!(p.kl_divergence(&q_dense).allclose(
+        &p_dense.kl_divergence(&q_dense),
+        1e-5,
+        1e-6,
+        false
+    ));
+
+    let val = Tensor::of_slice(&[0.1, 0.2, 0.3]);
+    assert!(p
+        .log_prob(&val)
+        .allclose(&p_dense.log_prob(&val), 1e-5, 1e-6, false));
+    assert!(p.entropy().allclose(&p_dense.entropy(), 1e-5, 1e-6, false));
+    assert!(p.variance().allclose(&p_var, 1e-6, 1e-8, false));
+    assert!(p
+        .covariance_matrix()
+        .allclose(p_dense.covariance_matrix(), 1e-6, 1e-8, false));
+    assert_eq!(p.sample(&[4]).size(), [4, 2, 3]);
+}
+
+#[test]
+fn categorical() {
+    let p = Categorical::from_probs(
+        Tensor::of_slice(&[0.2, 0.8, 0.0, 0.5, 0.25, 0.25]).reshape(&[2, 3]),
+    );
+    let q = Categorical::from_probs(
+        Tensor::of_slice(&[0.1, 0.6, 0.3, 0.25, 0.25, 0.5]).reshape(&[2, 3]),
+    );
+    let expected = Tensor::of_slice(&[
+        0.2 * 2f64.ln() + 0.8 * (0.8f64 / 0.6).ln(),
+        0.5 * 2f64.ln() + 0.25 * 0.5f64.ln(),
+    ]);
+    assert!(p.kl_divergence(&q).allclose(&expected, 1e-6, 1e-6, false));
+    assert!(p.kl_divergence(&p).abs().max().double_value(&[]) < 1e-12);
+}
+
+#[test]
+fn dirichlet_with_two_categories_matches_beta() {
+    let (a, b) = (Tensor::of_slice(&[2.0, 0.5]), Tensor::of_slice(&[3.0, 1.5]));
+    let (c, d) = (Tensor::of_slice(&[1.0, 4.0]), Tensor::of_slice(&[0.7, 2.0]));
+    let p = Dirichlet::new(Tensor::stack(&[&a, &b], -1));
+    let q = Dirichlet::new(Tensor::stack(&[&c, &d], -1));
+    let p_beta = Beta::new(&a, &b);
+    let q_beta = Beta::new(&c, &d);
+    let x = Tensor::of_slice(&[0.3, 0.8]);
+    let close = |x: &Tensor, y: &Tensor| x.allclose(y, 1e-6, 1e-8, false);
+    assert!(close(
+        &p.log_prob(&Tensor::stack(&[&x, &(1.0 - &x)], -1)),
+        &p_beta.log_prob(&x)
+    ));
+    assert!(close(&p.entropy(), &p_beta.entropy()));
+    assert!(close(&p.mean().select(-1, 0), &p_beta.mean()));
+    assert!(close(&p.variance().select(-1, 0), &p_beta.variance()));
+    assert!(close(&p.kl_divergence(&q), &p_beta.kl_divergence(&q_beta)));
+}
+
+/// Returns a Monte Carlo estimate of `KL(p || q)` from `n` samples of `p`.
+fn monte_carlo_kl<P: Distribution, Q: Distribution>(p: &P, q: &Q, n: i64) -> Tensor {
+    tch::manual_seed(0);
+    let x = p.sample(&[n]);
+    (p.log_prob(&x) - q.log_prob(&x)).mean_dim(&[0], false, tch::Kind::Double)
+}
+
+#[test]
+fn cauchy() {
+    let p = Cauchy::new(
+        Tensor::of_slice(&[0.0, 1.0, -2.0]),
+        Tensor::of_slice(&[1.0, 0.5, 2.0]),
+    );
+    let q = Cauchy::new(
+        Tensor::of_slice(&[0.5, -1.0, -2.0]),
+        Tensor::of_slice(&[2.0, 1.5, 0.3]),
+    );
+    let kl = p.kl_divergence(&q);
+    assert!(kl.allclose(&q.kl_divergence(&p), 1e-6, 1e-8, false));
+    assert!(p.kl_divergence(&p).allclose(
+        &Tensor::zeros(&[3], tch::kind::DOUBLE_CPU),
+        1e-6,
+        1e-8,
+        false
+    ));
+    assert!(kl.allclose(&monte_carlo_kl(&p, &q, 200_000), 2e-2, 2e-2, false));
+}
+
+#[test]
+fn across_families() {
+    let rate = Tensor::of_slice(&[0.5, 1.0, 3.0]);
+    let other_rate = Tensor::of_slice(&[2.0, 0.7, 1.0]);
+    let exponential = Exponential::new(&rate);
+    let expected = exponential.kl_divergence(&Exponential::new(&other_rate));
+    let q = Gamma::new(other_rate.ones_like(), &other_rate);
+    assert!(exponential
+        .kl_divergence(&q)
+        .allclose(&expected, 1e-6, 1e-8, false));
+    let p = Gamma::new(rate.ones_like(), &rate);
+    assert!(p
+        .kl_divergence(&Exponential::new(&other_rate))
+        .allclose(&expected, 1e-6, 1e-8, false));
+    let q = Gamma::new(Tensor::of_slice(&[0.5, 2.0, 4.0]), &other_rate);
+    assert!(exponential.kl_divergence(&q).allclose(
+        &monte_carlo_kl(&exponential, &q, 200_000),
+        2e-2,
+        2e-2,
+        false
+    ));
+
+    let normal = Normal::new(
+        Tensor::of_slice(&[0.0, 1.0, -1.0]),
+        Tensor::of_slice(&[1.0, 2.0, 0.5]),
+    );
+    assert!(exponential.kl_divergence(&normal).allclose(
+        &monte_carlo_kl(&exponential, &normal, 200_000),
+        2e-2,
+        2e-2,
+        false
+    ));
+    let uniform = Uniform::new(
+        Tensor::of_slice(&[-1.0, 0.0, 2.0]),
+        Tensor::of_slice(&[1.0, 0.5, 3.0]),
+    );
+    assert!(uniform.kl_divergence(&normal).allclose(
+        &monte_carlo_kl(&uniform, &normal, 200_000),
+        2e-2,
+        2e-2,
+        false
+    ));
+}
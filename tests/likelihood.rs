@@ -0,0 +1,91 @@
+use tch::{Kind, Reduction, Tensor};
+use tch_distr::{Distribution, Normal};
+
+fn normal() -> Normal {
+    Normal::new(
+        Tensor::of_slice(&[0.0, 1.0, -1.0]),
+        Tensor::of_slice(&[1.0, 0.5, 2.0]),
+    )
+}
+
+fn values() -> Tensor {
+    Tensor::of_slice(&[0.1, 0.3, -0.5, 1.2, 0.7, 2.0]).reshape(&[2, 3])
+}
+
+#[test]
+fn nll() {
+    let normal = normal();
+    let expected = -normal.log_prob(&values());
+    assert!(normal
+        .nll(&values(), Reduction::None)
+        .allclose(&expected, 1e-12, 1e-12, false));
+    assert!(normal.nll(&values(), Reduction::Sum).allclose(
+        &expected.sum(Kind::Double),
+        1e-12,
+        1e-12,
+        false
+    ));
+    assert!(normal.nll(&values(), Reduction::Mean).allclose(
+        &expected.mean(Kind::Double),
+        1e-12,
+        1e-12,
+        false
+    ));
+}
+
+#[test]
+fn nll_dims() {
+    let normal = normal();
+    let expected = -normal.log_prob(&values());
+    let summed = normal.nll_dims(&values(), &[0], Reduction::Sum);
+    assert_eq!(summed.size(), [3]);
+    assert!(summed.allclose(
+        &expected.sum_dim_intlist(&[0], false, Kind::Double),
+        1e-12,
+        1e-12,
+        false
+    ));
+    let averaged = normal.nll_dims(&values(), &[-1], Reduction::Mean);
+    assert_eq!(averaged.size(), [2]);
+    assert!(averaged.allclose(
+        &expected.mean_dim(&[-1], false, Kind::Double),
+        1e-12,
+        1e-12,
+        false
+    ));
+    assert_eq!(
+        normal.nll_dims(&values(), &[0], Reduction::None).size(),
+        [2, 3]
+    );
+}
+
+#[test]
+fn weighted_log_prob() {
+    let normal = normal();
+    let log_prob = normal.log_prob(&values());
+    // Weights of the batch shape broadcast against every sample.
+    let weights = Tensor::of_slice(&[0.5, 2.0, 0.0]);
+    let weighted = normal.weighted_log_prob(&values(), &weights);
+    assert_eq!(weighted.size(), [2, 3]);
+    assert!(weighted.allclose(&(&log_prob * &weights), 1e-12, 1e-12, false));
+    assert_eq!(Vec::<f64>::from(weighted.select(1, 2)), [0.0, 0.0]);
+
+    let per_sample = Tensor::of_slice(&[1.0, 3.0]).unsqueeze(-1);
+    let nll = normal.weighted_nll(&values(), &per_sample, Reduction::Sum);
+    let expected = -(&log_prob * &per_sample).sum(Kind::Double);
+    assert!(nll.allclose(&expected, 1e-12, 1e-12, false));
+}
+
+#[test]
+fn weighted_nll_mean_divides_by_total_weight() {
+    let normal = normal();
+    let log_prob = normal.log_prob(&values());
+    let weights = Tensor::of_slice(&[0.5, 2.0, 0.0]);
+    let nll = normal.weighted_nll(&values(), &weights, Reduction::Mean);
+    // The weights are broadcast over the two samples, so they sum to 5.
+    let expected = -(&log_prob * &weights).sum(Kind::Double) / 5.0;
+    assert!(nll.allclose(&expected, 1e-12, 1e-12, false));
+    assert!(normal
+        .weighted_nll(&values(), &weights.ones_like(), Reduction::Mean)
+        .allclose(&normal.nll(&values(), Reduction::Mean), 1e-12, 1e-12, false));
+}
@@ -0,0 +1,64 @@
+use tch::Tensor;
+use tch_distr::{Categorical, Distribution, MultivariateNormal, Normal};
+
+fn check_against_loop<D: Distribution>(dist: &D, values: &Tensor) {
+    let batched = dist.log_prob_n(values);
+    let looped = Tensor::stack(
+        &(0..values.size()[0])
+            .map(|i| dist.log_prob(&values.get(i)))
+            .collect::<Vec<_>>(),
+        0,
+    );
+    assert!(batched.allclose(&looped, 1e-6, 1e-8, false));
+}
+
+#[test]
+fn matches_per_sample_loop() {
+    let normal = Normal::new(
+        Tensor::of_slice(&[1.0, -0.5, 0.0]),
+        Tensor::of_slice(&[2.0, 0.7, 1.0]),
+    );
+    check_against_loop(&normal, &Tensor::randn(&[5, 3], tch::kind::DOUBLE_CPU));
+
+    let mvn = MultivariateNormal::from_scale_tril(
+        Tensor::of_slice(&[1.0, -0.5]),
+        Tensor::of_slice(&[2.0, 0.0, 0.3, 1.5]).reshape(&[2, 2]),
+    );
+    check_against_loop(
+        &mvn,
+        &Tensor::of_slice(&[0.1, 0.2, -1.0, 0.5, 2.0, 0.0]).reshape(&[3, 2]),
+    );
+
+    let categorical = Categorical::from_probs(Tensor::of_slice(&[0.2, 0.3, 0.5]));
+    check_against_loop(&categorical, &Tensor::of_slice(&[0i64, 2, 1, 1]));
+}
+
+#[test]
+fn leading_dimension_is_the_sample_dimension() {
+    // A single value per sample is broadcast over the batch instead of being aligned with it.
+    let normal = Normal::new(
+        Tensor::of_slice(&[1.0, -0.5, 0.0]),
+        Tensor::of_slice(&[2.0, 0.7, 1.0]),
+    );
+    let values = Tensor::of_slice(&[0.3, 1.2]);
+    let log_prob = normal.log_prob_n(&values);
+    assert_eq!(log_prob.size(), [2, 3]);
+    assert!(log_prob
+        .get(1)
+        .allclose(&normal.log_prob(&Tensor::from(1.2)), 1e-6, 1e-8, false));
+}
+
+#[test]
+fn non_contiguous_values() {
+    let normal = Normal::new(
+        Tensor::of_slice(&[1.0, -0.5, 0.0, 2.0, 0.5, -1.0]).reshape(&[2, 3]),
+        Tensor::of_slice(&[2.0, 0.7, 1.0, 0.3, 1.5, 0.8]).reshape(&[2, 3]),
+    );
+    // Transposed values, which gain a batch dimension before being evaluated.
+    let values = Tensor::randn(&[3, 5], tch::kind::DOUBLE_CPU).transpose(0, 1);
+    assert_eq!(values.stride(), [1, 5]);
+    let log_prob = normal.log_prob_n(&values);
+    assert_eq!(log_prob.size(), [5, 2, 3]);
+    let expected = normal.log_prob_n(&values.contiguous());
+    assert!(log_prob.allclose(&expected, 1e-12, 1e-12, false));
+}
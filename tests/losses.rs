@@ -0,0 +1,66 @@
+use std::f64::consts::PI;
+use tch::{Kind, Reduction, Tensor};
+use tch_distr::{
+    losses::{categorical_nll_with_label_smoothing, gaussian_nll, kl_regularized_nll, poisson_nll},
+    KullackLeiberDivergence, Normal,
+};
+
+#[test]
+fn gaussian_and_poisson_nll() {
+    let mean = Tensor::of_slice(&[0.0, 1.0]);
+    let stddev = Tensor::of_slice(&[1.0, 2.0]);
+    let target = Tensor::of_slice(&[0.5, -1.0]);
+    let expected = (&target - &mean).pow_tensor_scalar(2) / (stddev.pow_tensor_scalar(2) * 2.0)
+        + stddev.log()
+        + 0.5 * (2.0 * PI).ln();
+    let nll = gaussian_nll(&mean, &stddev, &target, Reduction::None);
+    assert!(nll.allclose(&expected, 1e-6, 1e-6, false));
+    let mean_nll = gaussian_nll(&mean, &stddev, &target, Reduction::Mean);
+    assert!((f64::from(mean_nll) - f64::from(expected.mean(Kind::Double))).abs() < 1e-6);
+
+    let rate = Tensor::of_slice(&[0.5, 3.0]);
+    let counts = Tensor::of_slice(&[0.0, 2.0]);
+    let expected = &rate - &counts * rate.log() + (&counts + 1.0).lgamma();
+    let nll = poisson_nll(&rate, &counts, Reduction::Sum);
+    assert!((f64::from(nll) - f64::from(expected.sum(Kind::Double))).abs() < 1e-6);
+}
+
+#[test]
+fn label_smoothing() {
+    let logits = Tensor::of_slice(&[2.0, 0.5, -1.0, 0.0, 1.0, 3.0]).reshape(&[2, 3]);
+    let labels = Tensor::of_slice(&[0i64, 2]);
+    let log_probs = logits.log_softmax(-1, Kind::Double);
+
+    let nll = categorical_nll_with_label_smoothing(&logits, &labels, 0.0, Reduction::None);
+    let expected = -log_probs
+        .gather(-1, &labels.unsqueeze(-1), false)
+        .squeeze_dim(-1);
+    assert!(nll.allclose(&expected, 1e-6, 1e-6, false));
+
+    let smoothed = categorical_nll_with_label_smoothing(&logits, &labels, 0.3, Reduction::None);
+    let targets = labels.one_hot(3).to_kind(Kind::Double) * 0.7 + 0.1;
+    let expected = -(targets * log_probs).sum_dim_intlist(&[-1], false, Kind::Double);
+    assert!(smoothed.allclose(&expected, 1e-6, 1e-6, false));
+}
+
+#[test]
+fn kl_regularized() {
+    let posterior = Normal::new(
+        Tensor::of_slice(&[0.5, -0.5]),
+        Tensor::of_slice(&[0.8, 1.2]),
+    );
+    let prior = Normal::standard();
+    let prior = Normal::new(
+        prior.mean().expand(&[2], false),
+        prior.stddev().expand(&[2], false),
+    );
+    let target = Tensor::of_slice(&[0.0, 1.0]);
+    let loss = kl_regularized_nll(&posterior, &target, &prior, 0.5, Reduction::Sum);
+    let expected = gaussian_nll(
+        posterior.mean(),
+        posterior.stddev(),
+        &target,
+        Reduction::Sum,
+    ) + posterior.kl_divergence(&prior).sum(Kind::Double) * 0.5;
+    assert!((f64::from(loss) - f64::from(expected)).abs() < 1e-6);
+}
@@ -0,0 +1,143 @@
+use tch::{Kind, Tensor};
+use tch_distr::{
+    mcmc::{Gibbs, Hmc, Nuts, SliceSampler},
+    Distribution, Gamma, Normal,
+};
+
+const SEED: i64 = 42;
+const N_CHAINS: i64 = 8;
+
+fn target() -> Normal {
+    Normal::new(
+        Tensor::of_slice(&[1.0, -2.0]),
+        Tensor::of_slice(&[1.0, 3.0]),
+    )
+}
+
+fn log_prob(x: &Tensor) -> Tensor {
+    target()
+        .log_prob(x)
+        .sum_dim_intlist(&[-1], false, Kind::Double)
+}
+
+fn assert_moments(samples: &Tensor) {
+    let samples = samples.reshape(&[-1, 2]);
+    let mean = samples.mean_dim(&[0], false, Kind::Double);
+    let variance = samples.var_dim(&[0], true, false);
+    assert!(
+        mean.allclose(target().mean(), 0.0, 0.3, false),
+        "{:?}",
+        mean
+    );
+    assert!(
+        variance.allclose(&target().variance(), 0.15, 0.0, false),
+        "{:?}",
+        variance
+    );
+}
+
+#[test]
+fn hmc() {
+    tch::manual_seed(SEED);
+    let mut hmc = Hmc::new(
+        log_prob,
+        &Tensor::zeros(&[N_CHAINS, 2], tch::kind::DOUBLE_CPU),
+        0.3,
+        10,
+    );
+    hmc.warmup(500);
+    assert!(hmc
+        .inverse_mass()
+        .allclose(&target().variance(), 0.3, 0.0, false));
+
+    let samples = hmc.sample(1000);
+    assert_eq!(samples.size(), [1000, N_CHAINS, 2]);
+    assert_moments(&samples);
+    let acceptance = f64::from(hmc.acceptance_rate().mean(Kind::Double));
+    assert!(acceptance > 0.5 && acceptance <= 1.0);
+}
+
+#[test]
+fn nuts() {
+    tch::manual_seed(SEED);
+    let mut nuts = Nuts::new(
+        log_prob,
+        &Tensor::zeros(&[4, 2], tch::kind::DOUBLE_CPU),
+        1.0,
+    );
+    nuts.warmup(300);
+    assert!(nuts.step_size() > 0.1 && nuts.step_size() < 5.0);
+
+    let samples = nuts.sample(1000);
+    assert_eq!(samples.size(), [1000, 4, 2]);
+    assert_moments(&samples);
+    assert!(nuts.divergences().iter().all(|&n| n == 0));
+    for acceptance in nuts.acceptance_rate() {
+        assert!((acceptance - 0.8).abs() < 0.15, "{}", acceptance);
+    }
+}
+
+#[test]
+fn slice_sampler() {
+    tch::manual_seed(SEED);
+    let mut sampler = SliceSampler::new(
+        log_prob,
+        &Tensor::zeros(&[N_CHAINS, 2], tch::kind::DOUBLE_CPU),
+        1.0,
+    );
+    let _ = sampler.sample(100);
+    let samples = sampler.sample(1000);
+    assert_eq!(samples.size(), [1000, N_CHAINS, 2]);
+    assert_moments(&samples);
+
+    // Only evaluations are needed, so a target without gradients can be sampled.
+    let mut sampler = SliceSampler::new(
+        |x: &Tensor| {
+            x.abs()
+                .le(1.0)
+                .all_dim(-1, false)
+                .to_kind(Kind::Double)
+                .log()
+        },
+        &Tensor::zeros(&[N_CHAINS, 2], tch::kind::DOUBLE_CPU),
+        0.5,
+    );
+    let samples = sampler.sample(500);
+    assert!(bool::from(samples.abs().le(1.0).all()));
+    assert!(samples
+        .var(true)
+        .allclose(&Tensor::from(1.0 / 3.0), 0.1, 0.0, false));
+}
+
+#[test]
+fn gibbs() {
+    tch::manual_seed(SEED);
+    // Normal observations with unknown mean `mu` and precision `tau`, under the conjugate
+    // priors mu ~ N(0, 10) and tau ~ Gamma(1, 1).
+    let data = Tensor::randn(&[200], tch::kind::DOUBLE_CPU) * 0.5 + 2.0;
+    let n = data.size()[0] as f64;
+    let sum = data.sum(Kind::Double);
+    let mut gibbs = Gibbs::new();
+    gibbs
+        .block("mu", Tensor::from(0.0), |state| {
+            let precision = state.get("tau") * n + 0.01;
+            Normal::new(state.get("tau") * &sum / &precision, precision.rsqrt())
+        })
+        .block("tau", Tensor::from(1.0), |state| {
+            let squares = (&data - state.get("mu"))
+                .pow_tensor_scalar(2)
+                .sum(Kind::Double);
+            Gamma::new(Tensor::from(1.0 + n / 2.0), squares / 2.0 + 1.0)
+        })
+        .set_thinning(2);
+    gibbs.warmup(100);
+    assert_eq!(gibbs.state().blocks().len(), 2);
+
+    let samples = gibbs.sample(500);
+    assert_eq!(samples[0].0, "mu");
+    assert_eq!(samples[0].1.size(), [500]);
+    let mu = f64::from(samples[0].1.mean(Kind::Double));
+    let tau = f64::from(samples[1].1.mean(Kind::Double));
+    assert!((mu - f64::from(data.mean(Kind::Double))).abs() < 0.02);
+    assert!((tau - 4.0).abs() < 1.0, "{}", tau);
+}
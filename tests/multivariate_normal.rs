@@ -0,0 +1,129 @@
+use std::panic::catch_unwind;
+use tch::{kind::DOUBLE_CPU, Tensor};
+use tch_distr::{Distribution, Error, MultivariateNormal};
+
+#[test]
+fn broadcasts_mean_against_matrix_batch() {
+    let cov = Tensor::eye(2, DOUBLE_CPU).expand(&[3, 2, 2], true) * 2.0;
+    let mean = Tensor::zeros(&[4, 1, 2], DOUBLE_CPU);
+    let mvn = MultivariateNormal::from_cov(&mean, &cov);
+    assert_eq!(mvn.batch_shape(), [4, 3]);
+    assert_eq!(mvn.event_shape(), [2]);
+    assert_eq!(mvn.mean().size(), [4, 3, 2]);
+    assert_eq!(mvn.covariance_matrix().size(), [4, 3, 2, 2]);
+    assert_eq!(mvn.sample(&[5]).size(), [5, 4, 3, 2]);
+    assert_eq!(mvn.scale_tril().size(), [4, 3, 2, 2]);
+    assert_eq!(mvn.log_prob(&mvn.sample(&[5])).size(), [5, 4, 3]);
+
+    let mvn = MultivariateNormal::from_precision(&mean, &cov);
+    assert_eq!(mvn.scale_tril().size(), [4, 3, 2, 2]);
+    assert_eq!(mvn.entropy().size(), [4, 3]);
+
+    let mvn = MultivariateNormal::from_diag(
+        Tensor::of_slice(&[0.0, 1.0]),
+        Tensor::ones(&[3, 2], DOUBLE_CPU),
+    );
+    assert_eq!(mvn.batch_shape(), [3]);
+    assert_eq!(mvn.mean().size(), [3, 2]);
+    assert_eq!(mvn.entropy().size(), [3]);
+
+    let mvn =
+        MultivariateNormal::from_cov(Tensor::zeros(&[2], DOUBLE_CPU), Tensor::eye(2, DOUBLE_CPU));
+    assert!(mvn.batch_shape().is_empty());
+    assert_eq!(mvn.log_prob(&mvn.sample(&[])).size(), Vec::<i64>::new());
+    assert_eq!(mvn.log_prob(&mvn.sample(&[5])).size(), [5]);
+    assert_eq!(mvn.entropy().size(), Vec::<i64>::new());
+}
+
+#[test]
+fn rejects_mismatched_shapes() {
+    let message = |f: fn()| {
+        let payload = catch_unwind(f).unwrap_err();
+        payload
+            .downcast::<String>()
+            .map_or_else(|p| p.downcast_ref::<&str>().unwrap().to_string(), |m| *m)
+    };
+    let msg = message(|| {
+        MultivariateNormal::from_cov(Tensor::zeros(&[3], DOUBLE_CPU), Tensor::eye(2, DOUBLE_CPU));
+    });
+    assert!(
+        msg.contains("does not match the event shape [3]"),
+        "{}",
+        msg
+    );
+    let msg = message(|| {
+        MultivariateNormal::from_scale_tril(
+            Tensor::zeros(&[2, 2], DOUBLE_CPU),
+            Tensor::eye(2, DOUBLE_CPU).expand(&[3, 2, 2], true),
+        );
+    });
+    assert!(msg.contains("cannot be broadcast"), "{}", msg);
+    let msg = message(|| {
+        MultivariateNormal::from_cov(Tensor::from(0.0), Tensor::eye(1, DOUBLE_CPU));
+    });
+    assert!(msg.contains("at least one-dimensional"), "{}", msg);
+}
+
+#[test]
+fn try_constructors_return_shape_errors() {
+    let mean = Tensor::zeros(&[3], DOUBLE_CPU);
+    let eye = Tensor::eye(2, DOUBLE_CPU);
+    assert!(matches!(
+        MultivariateNormal::try_from_cov(&mean, &eye),
+        Err(Error::Shape(_))
+    ));
+    assert!(matches!(
+        MultivariateNormal::try_from_precision(&mean, &eye),
+        Err(Error::Shape(_))
+    ));
+    assert!(matches!(
+        MultivariateNormal::try_from_scale_tril(Tensor::from(0.0), &eye),
+        Err(Error::Shape(_))
+    ));
+    assert!(matches!(
+        MultivariateNormal::try_from_diag(&mean, Tensor::ones(&[2], DOUBLE_CPU)),
+        Err(Error::Shape(_))
+    ));
+    let mvn = MultivariateNormal::try_from_cov(Tensor::zeros(&[2], DOUBLE_CPU), &eye).unwrap();
+    assert_eq!(mvn.event_shape(), [2]);
+}
+
+#[test]
+fn derives_every_parameterization() {
+    let scale_tril =
+        Tensor::of_slice(&[1.0, 0.0, 0.0, 0.5, 2.0, 0.0, -0.3, 0.1, 0.7]).reshape(&[3, 3]);
+    let cov = scale_tril.matmul(&scale_tril.tr());
+    let precision = cov.inverse();
+    let mean = Tensor::of_slice(&[0.5, -1.0, 2.0]);
+    let close = |a: &Tensor, b: &Tensor| a.allclose(b, 1e-6, 1e-8, false);
+    for mvn in [
+        MultivariateNormal::from_cov(&mean, &cov),
+        MultivariateNormal::from_precision(&mean, &precision),
+        MultivariateNormal::from_scale_tril(&mean, &scale_tril),
+    ] {
+        assert!(close(mvn.scale_tril(), &scale_tril));
+        assert!(close(mvn.covariance_matrix(), &cov));
+        assert!(close(mvn.precision_matrix(), &precision));
+        // The derived matrices are computed once and then served from the cache.
+        assert!(std::ptr::eq(
+            mvn.covariance_matrix(),
+            mvn.covariance_matrix()
+        ));
+        assert!(std::ptr::eq(mvn.precision_matrix(), mvn.precision_matrix()));
+    }
+
+    let variance = Tensor::of_slice(&[1.0, 0.25, 4.0]);
+    let mvn = MultivariateNormal::from_diag(&mean, &variance);
+    assert!(close(
+        mvn.scale_tril(),
+        &variance.sqrt().diag_embed(0, -2, -1)
+    ));
+    assert!(close(
+        mvn.covariance_matrix(),
+        &variance.diag_embed(0, -2, -1)
+    ));
+    assert!(close(
+        mvn.precision_matrix(),
+        &variance.reciprocal().diag_embed(0, -2, -1)
+    ));
+}
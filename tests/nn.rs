@@ -0,0 +1,92 @@
+use tch::{nn, nn::OptimizerConfig, Device, Kind, Tensor};
+use tch_distr::{
+    nn::{CategoricalHead, Head, MdnHead, MixtureHead, NormalHead},
+    Distribution,
+};
+
+const SEED: i64 = 42;
+
+#[test]
+fn head_shapes() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let xs = Tensor::randn(&[8, 3], tch::kind::FLOAT_CPU);
+
+    let normal = NormalHead::new(&(&vs.root() / "normal"), 3, 2).forward(&xs);
+    assert_eq!(normal.batch_shape(), [8, 2]);
+    assert!(bool::from(normal.stddev().gt(0.0).all()));
+
+    let categorical = CategoricalHead::new(&(&vs.root() / "categorical"), 3, 4).forward(&xs);
+    assert_eq!(categorical.batch_shape(), [8]);
+    assert!(categorical
+        .probs()
+        .sum_dim_intlist(&[-1], false, Kind::Float)
+        .allclose(&Tensor::ones(&[8], tch::kind::FLOAT_CPU), 1e-5, 1e-5, false));
+
+    let mixture = MixtureHead::new(
+        &(&vs.root() / "mixture"),
+        3,
+        5,
+        NormalHead::new(&(&vs.root() / "components"), 3, 5),
+    )
+    .forward(&xs);
+    assert_eq!(mixture.batch_shape(), [8]);
+    assert_eq!(
+        mixture
+            .log_prob(&Tensor::zeros(&[8], tch::kind::FLOAT_CPU))
+            .size(),
+        [8]
+    );
+}
+
+#[test]
+fn normal_head_regression() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let head = NormalHead::new(&vs.root(), 1, 1);
+    let mut opt = nn::Adam::default().build(&vs, 0.05).unwrap();
+    let xs = Tensor::linspace(-1.0, 1.0, 256, tch::kind::FLOAT_CPU).unsqueeze(-1);
+    let ys = &xs * 2.0 + 1.0 + Tensor::randn(&[256, 1], tch::kind::FLOAT_CPU) * 0.1;
+    for _ in 0..1000 {
+        let loss = -head.forward(&xs).log_prob(&ys).mean(Kind::Float);
+        opt.backward_step(&loss);
+    }
+    let fitted = tch::no_grad(|| head.forward(&Tensor::zeros(&[1, 1], tch::kind::FLOAT_CPU)));
+    assert!((f64::from(fitted.mean()) - 1.0).abs() < 0.05);
+    assert!((f64::from(fitted.stddev()) - 0.1).abs() < 0.03);
+}
+
+#[test]
+fn mdn_head() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let head = MdnHead::new(&vs.root(), 1, 2, 2);
+    let xs = Tensor::rand(&[512, 1], tch::kind::FLOAT_CPU);
+    let mixture = head.forward(&xs);
+    assert_eq!(mixture.batch_shape(), [512]);
+    assert_eq!(mixture.event_shape(), [2]);
+    assert_eq!(mixture.sample(&[3]).size(), [3, 512, 2]);
+
+    // Targets far away from all components still have a finite loss.
+    let far = Tensor::full(&[512, 2], 1e3, tch::kind::FLOAT_CPU);
+    assert!(f64::from(head.nll(&xs, &far)).is_finite());
+
+    // A bimodal target: either (x, x) or (-x, -x) with equal probability.
+    let signs = Tensor::randint(2, &[512, 1], tch::kind::FLOAT_CPU) * 2.0 - 1.0;
+    let ys = (&xs * signs).expand(&[512, 2], false)
+        + Tensor::randn(&[512, 2], tch::kind::FLOAT_CPU) * 0.05;
+    let mut opt = nn::Adam::default().build(&vs, 0.02).unwrap();
+    let initial = f64::from(head.nll(&xs, &ys));
+    for _ in 0..1500 {
+        opt.backward_step(&head.nll(&xs, &ys));
+    }
+    let fitted = f64::from(head.nll(&xs, &ys));
+    assert!(fitted < initial - 1.0);
+    let probs = tch::no_grad(|| {
+        head.forward(&xs)
+            .mixture()
+            .probs()
+            .mean_dim(&[0], false, Kind::Float)
+    });
+    assert!(probs.allclose(&Tensor::of_slice(&[0.5f32, 0.5]), 0.0, 0.1, false));
+}
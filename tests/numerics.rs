@@ -0,0 +1,81 @@
+use tch::Tensor;
+use tch_distr::numerics::{
+    betainc, clamp_probs, log1mexp, log_odds, logaddexp, logit, logsumexp, softplus_inverse,
+};
+
+fn assert_close(result: &Tensor, expected: &[f64]) {
+    let expected = Tensor::of_slice(expected);
+    assert!(
+        result.allclose(&expected, 1e-10, 1e-12, false),
+        "{:?} != {:?}",
+        result,
+        expected
+    );
+}
+
+#[test]
+fn log1mexp_is_accurate_near_and_far_from_zero() {
+    let x = Tensor::of_slice(&[-1e-20, -0.5, -50.0]);
+    let expected = [
+        (1e-20f64).ln(),
+        (1.0 - (-0.5f64).exp()).ln(),
+        -(-50.0f64).exp(),
+    ];
+    assert_close(&log1mexp(&x), &expected);
+    assert_eq!(f64::from(log1mexp(&Tensor::from(0.0))), f64::NEG_INFINITY);
+}
+
+#[test]
+fn logaddexp_handles_infinities() {
+    let a = Tensor::of_slice(&[1000.0, f64::NEG_INFINITY, 0.0]);
+    let b = Tensor::of_slice(&[1000.0, f64::NEG_INFINITY, f64::NEG_INFINITY]);
+    let result = Vec::<f64>::from(&logaddexp(&a, &b));
+    assert!((result[0] - (1000.0 + 2f64.ln())).abs() < 1e-10);
+    assert_eq!(result[1], f64::NEG_INFINITY);
+    assert_eq!(result[2], 0.0);
+}
+
+#[test]
+fn logsumexp_handles_infinities() {
+    let x = Tensor::of_slice(&[1000.0, 1000.0, f64::NEG_INFINITY, f64::NEG_INFINITY]).view([2, 2]);
+    let result = Vec::<f64>::from(&logsumexp(&x, &[-1], false));
+    assert!((result[0] - (1000.0 + 2f64.ln())).abs() < 1e-10);
+    assert_eq!(result[1], f64::NEG_INFINITY);
+}
+
+#[test]
+fn softplus_inverse_inverts_softplus() {
+    let x = Tensor::of_slice(&[-30.0, 0.5, 100.0]);
+    assert_close(&softplus_inverse(&x.softplus()), &[-30.0, 0.5, 100.0]);
+}
+
+#[test]
+fn logit_is_finite() {
+    let probs = Tensor::of_slice(&[0.0, 0.5, 1.0]);
+    let result = logit(&probs);
+    assert!(bool::from(result.isfinite().all()));
+    assert_close(&result.narrow(0, 1, 1), &[0.0]);
+    assert_close(
+        &clamp_probs(&probs),
+        &[f64::EPSILON, 0.5, 1.0 - f64::EPSILON],
+    );
+    let result = Vec::<f64>::from(&log_odds(&probs));
+    assert_eq!(result, [f64::NEG_INFINITY, 0.0, f64::INFINITY]);
+}
+
+#[test]
+fn betainc_matches_closed_forms() {
+    let x = Tensor::of_slice(&[0.0, 0.2, 0.5, 0.9, 1.0]);
+    let ones = x.ones_like();
+    // I_x(1, 1) = x and I_x(a, 1) = x^a.
+    assert_close(&betainc(&ones, &ones, &x), &[0.0, 0.2, 0.5, 0.9, 1.0]);
+    let a = ones.full_like(3.0);
+    assert_close(&betainc(&a, &ones, &x), &[0.0, 0.008, 0.125, 0.729, 1.0]);
+    // I_{1/2}(2, 3) = 11 / 16, evaluated on both sides of the mean.
+    let result = betainc(
+        &Tensor::of_slice(&[2.0, 3.0]),
+        &Tensor::of_slice(&[3.0, 2.0]),
+        &Tensor::of_slice(&[0.5, 0.5]),
+    );
+    assert_close(&result, &[0.6875, 0.3125]);
+}
@@ -0,0 +1,77 @@
+use tch::Tensor;
+use tch_distr::{Distribution, Exponential, Gamma, Normal};
+
+fn check_out<D: Distribution>(dist: &D, val: &Tensor, cdf: bool) {
+    let mut out = val.zeros_like();
+    dist.log_prob_out(&mut out, val);
+    assert!(out.allclose(&dist.log_prob(val), 1e-6, 1e-8, false));
+    if cdf {
+        dist.cdf_out(&mut out, val);
+        assert!(out.allclose(&dist.cdf(val), 1e-6, 1e-8, false));
+    }
+}
+
+#[test]
+fn matches_allocating_versions() {
+    let val = Tensor::of_slice(&[0.3, 1.2, 2.5]);
+    check_out(
+        &Normal::new(
+            Tensor::of_slice(&[1.0, -0.5, 0.0]),
+            Tensor::of_slice(&[2.0, 0.7, 1.0]),
+        ),
+        &val,
+        true,
+    );
+    check_out(
+        &Exponential::new(Tensor::of_slice(&[0.5, 2.0, 1.0])),
+        &val,
+        true,
+    );
+    check_out(
+        &Gamma::new(
+            Tensor::of_slice(&[0.5, 2.0, 1.0]),
+            Tensor::of_slice(&[1.0, 3.0, 0.2]),
+        ),
+        &val,
+        false,
+    );
+}
+
+#[test]
+fn reuses_buffer() {
+    let dist = Normal::new(Tensor::from(0.5), Tensor::from(2.0));
+    let mut out = Tensor::zeros(&[3], (tch::Kind::Double, tch::Device::Cpu));
+    let ptr = out.data_ptr();
+    dist.log_prob_out(&mut out, &Tensor::of_slice(&[0.3, 1.2, 2.5]));
+    assert_eq!(out.data_ptr(), ptr);
+}
+
+#[test]
+fn log_prob_and_entropy() {
+    let val = Tensor::of_slice(&[0.3, 1.2, 2.5]);
+    let check = |dist: &dyn Distribution| {
+        let (log_prob, entropy) = dist.log_prob_and_entropy(&val);
+        assert!(log_prob.allclose(&dist.log_prob(&val), 1e-6, 1e-8, false));
+        assert!(entropy.allclose(&dist.entropy(), 1e-6, 1e-8, false));
+    };
+    check(&Normal::new(
+        Tensor::of_slice(&[1.0, -0.5, 0.0]),
+        Tensor::of_slice(&[2.0, 0.7, 1.0]),
+    ));
+    check(&Gamma::new(
+        Tensor::of_slice(&[0.5, 2.0, 1.0]),
+        Tensor::of_slice(&[1.0, 3.0, 0.2]),
+    ));
+    check(&Exponential::new(Tensor::of_slice(&[0.5, 2.0, 1.0])));
+}
+
+#[test]
+fn exponential_cdf_below_support() {
+    let dist = Exponential::new(Tensor::from(2.0));
+    let val = Tensor::of_slice(&[-1.0, 0.0, 1.0]);
+    let expected = Tensor::of_slice(&[0.0, 0.0, 1.0 - (-2.0f64).exp()]);
+    assert!(dist.cdf(&val).allclose(&expected, 1e-12, 1e-12, false));
+    let mut out = val.zeros_like();
+    dist.cdf_out(&mut out, &val);
+    assert!(out.allclose(&expected, 1e-12, 1e-12, false));
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "rayon")]
+
+use tch::Tensor;
+use tch_distr::{parallel, Distribution, Normal};
+
+fn normals(n: usize) -> Vec<Normal> {
+    (0..n)
+        .map(|i| {
+            Normal::new(
+                Tensor::of_slice(&[i as f64, -(i as f64)]),
+                Tensor::of_slice(&[1.0, 0.5]),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn sample() {
+    let mut dists = normals(37);
+    let samples = parallel::sample(&mut dists, &[1_000]);
+    assert_eq!(samples.size(), [37, 1_000, 2]);
+    let means = samples.mean_dim(&[1], false, tch::Kind::Double);
+    for (i, dist) in dists.iter().enumerate() {
+        assert!(means.get(i as i64).allclose(dist.mean(), 0.0, 0.2, false));
+    }
+}
+
+#[test]
+fn log_prob() {
+    let mut dists = normals(37);
+    let values = Tensor::randn(&[37, 2], tch::kind::DOUBLE_CPU);
+    let log_prob = parallel::log_prob(&mut dists, &values);
+    assert_eq!(log_prob.size(), [37, 2]);
+    for (i, dist) in dists.iter().enumerate() {
+        assert!(log_prob.get(i as i64).allclose(
+            &dist.log_prob(&values.get(i as i64)),
+            1e-6,
+            1e-8,
+            false
+        ));
+    }
+}
+
+#[test]
+fn sample_seeded() {
+    let mut dists = normals(37);
+    let samples = parallel::sample_seeded(&mut dists, &[10], 7);
+    assert_eq!(samples.size(), [37, 10, 2]);
+    assert!(samples.equal(&parallel::sample_seeded(&mut dists, &[10], 7)));
+    for (i, dist) in dists.iter().enumerate() {
+        tch::manual_seed(7 + i as i64);
+        assert!(samples.get(i as i64).equal(&dist.sample(&[10])));
+    }
+}
@@ -0,0 +1,60 @@
+use tch::{nn, nn::OptimizerConfig, Device, Kind, Tensor};
+use tch_distr::{Categorical, Distribution, Normal, Parametrized};
+
+const SEED: i64 = 42;
+
+#[test]
+fn fit_normal() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let init = Normal::new(Tensor::from(0.0f32), Tensor::from(1.0f32));
+    let model = Parametrized::new(&vs.root(), &init, &["mean", "stddev"], |p| {
+        Normal::new(p[0].shallow_clone(), p[1].shallow_clone())
+    });
+    assert!(model.distribution().approx_eq(&init, 1e-6, 1e-6));
+
+    let data = Tensor::randn(&[1000], (Kind::Float, Device::Cpu)) * 0.5 + 2.0;
+    let mut opt = nn::Adam::default().build(&vs, 0.05).unwrap();
+    for _ in 0..500 {
+        let loss = -model.distribution().log_prob(&data).mean(Kind::Float);
+        opt.backward_step(&loss);
+    }
+
+    let dist = model.distribution();
+    assert!((f64::from(dist.mean()) - 2.0).abs() < 0.1);
+    assert!((f64::from(dist.stddev()) - 0.5).abs() < 0.1);
+}
+
+#[test]
+fn trainable() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let normal = Normal::trainable(&(&vs.root() / "normal"), &[2]);
+    let categorical = Categorical::trainable(&(&vs.root() / "categorical"), &[3]);
+    let mut names: Vec<String> = vs.variables().into_keys().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        ["categorical.logits", "normal.mean", "normal.stddev"]
+    );
+    assert_eq!(normal.distribution().batch_shape(), &[2]);
+
+    let data = Tensor::randn(&[1000, 2], (Kind::Float, Device::Cpu)) * 0.5 + 2.0;
+    let labels = Tensor::of_slice(&[0i64, 2, 2, 2]);
+    let mut opt = nn::Adam::default().build(&vs, 0.05).unwrap();
+    for _ in 0..500 {
+        let loss = -normal.distribution().log_prob(&data).mean(Kind::Float)
+            - categorical
+                .distribution()
+                .log_prob(&labels)
+                .mean(Kind::Float);
+        opt.backward_step(&loss);
+    }
+
+    let dist = normal.distribution();
+    assert!((dist.mean() - 2.0).abs().max().double_value(&[]) < 0.1);
+    assert!((dist.stddev() - 0.5).abs().max().double_value(&[]) < 0.1);
+    let probs = categorical.distribution().probs().to_kind(Kind::Double);
+    let expected = Tensor::of_slice(&[0.25f64, 0.0, 0.75]);
+    assert!(probs.allclose(&expected, 0.05, 0.05, false));
+}
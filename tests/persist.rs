@@ -0,0 +1,65 @@
+use std::path::PathBuf;
+use tch::{Device, Kind, Tensor};
+use tch_distr::{Bernoulli, Categorical, Error, Gamma, MultivariateNormal, Normal, Persist};
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("tch_distr_persist_{}.pt", name))
+}
+
+fn round_trip<D: Persist>(dist: &D, name: &str) -> D {
+    let path = temp_path(name);
+    dist.save(&path).unwrap();
+    let loaded = D::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    loaded
+}
+
+#[test]
+fn persist_round_trip() {
+    let normal = Normal::new(
+        Tensor::of_slice(&[0.5f32, -1.0]),
+        Tensor::of_slice(&[1.0f32, 2.0]),
+    );
+    let loaded = round_trip(&normal, "normal");
+    assert!(loaded == normal);
+    assert_eq!(loaded.mean().kind(), Kind::Float);
+    assert_eq!(loaded.mean().device(), Device::Cpu);
+
+    let gamma = Gamma::new(Tensor::from(2.0f64), Tensor::from(3.0f64));
+    let loaded = round_trip(&gamma, "gamma");
+    assert!(loaded == gamma);
+    assert_eq!(loaded.concentration().kind(), Kind::Double);
+
+    let bernoulli = Bernoulli::from_probs(Tensor::of_slice(&[0.2f64, 0.7]));
+    let loaded = round_trip(&bernoulli, "bernoulli");
+    assert!(loaded
+        .logits()
+        .allclose(bernoulli.logits(), 1e-12, 1e-12, false));
+
+    let categorical = Categorical::from_probs(Tensor::of_slice(&[0.1f64, 0.3, 0.6]));
+    let loaded = round_trip(&categorical, "categorical");
+    assert!(loaded
+        .probs()
+        .allclose(categorical.probs(), 1e-12, 1e-12, false));
+
+    let mvn = MultivariateNormal::from_cov(
+        Tensor::of_slice(&[1.0f64, 2.0]),
+        Tensor::of_slice(&[2.0f64, 0.5, 0.5, 1.0]).view([2, 2]),
+    );
+    let loaded = round_trip(&mvn, "mvn");
+    assert!(loaded
+        .covariance_matrix()
+        .allclose(mvn.covariance_matrix(), 1e-12, 1e-12, false));
+}
+
+#[test]
+fn persist_family_mismatch() {
+    let path = temp_path("mismatch");
+    Normal::new(Tensor::from(0.0f64), Tensor::from(1.0f64))
+        .save(&path)
+        .unwrap();
+    let loaded = Gamma::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(loaded, Err(Error::Format(_))));
+    assert!(Normal::load(temp_path("missing")).is_err());
+}
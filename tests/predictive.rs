@@ -0,0 +1,35 @@
+use tch::{Kind, Tensor};
+use tch_distr::{predictive::mixture_of_draws, Distribution, Normal};
+
+const SEED: i64 = 42;
+
+#[test]
+fn posterior_predictive() {
+    tch::manual_seed(SEED);
+    // Posterior draws of the mean of a Normal with unit standard deviation, whose posterior
+    // predictive is a Normal with standard deviation sqrt(1 + 0.5^2).
+    let posterior = Normal::new(Tensor::from(2.0), Tensor::from(0.5));
+    let draws = posterior.sample(&[4000]);
+    let predictive = mixture_of_draws(&draws, |mean| Normal::new(mean, Tensor::from(1.0)));
+    assert_eq!(predictive.n_draws(), 4000);
+    assert_eq!(predictive.batch_shape(), [] as [i64; 0]);
+
+    let expected = Normal::new(Tensor::from(2.0), Tensor::from(1.25f64.sqrt()));
+    assert!((f64::from(predictive.mean()) - 2.0).abs() < 0.05);
+    assert!((f64::from(predictive.variance()) - 1.25).abs() < 0.05);
+
+    let held_out = Tensor::of_slice(&[0.5, 2.0, 3.5]);
+    assert!(predictive.log_prob(&held_out).allclose(
+        &expected.log_prob(&held_out),
+        0.0,
+        0.02,
+        false
+    ));
+    let lppd = f64::from(predictive.log_predictive_density(&held_out));
+    assert!((lppd - f64::from(expected.log_prob(&held_out).sum(Kind::Double))).abs() < 0.05);
+
+    let (lower, upper) = predictive.sample_interval(0.9, 20000);
+    let (expected_lower, expected_upper) = expected.interval(0.9);
+    assert!((f64::from(lower) - f64::from(expected_lower)).abs() < 0.1);
+    assert!((f64::from(upper) - f64::from(expected_upper)).abs() < 0.1);
+}
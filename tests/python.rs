@@ -0,0 +1,96 @@
+#![cfg(feature = "python")]
+
+use pyo3::prelude::*;
+use tch::Tensor;
+use tch_distr::{
+    python::{from_py, AnyDistribution, ToPy},
+    Binomial, Categorical, Distribution, MultivariateNormal, NegativeBinomial, Normal,
+};
+
+#[test]
+fn python_round_trip() {
+    Python::with_gil(|py| {
+        let normal = Normal::new(
+            Tensor::of_slice(&[0.5f32, -1.0]),
+            Tensor::of_slice(&[1.0f32, 2.0]),
+        );
+        let obj = normal.to_py(py).unwrap();
+        let value = Tensor::of_slice(&[0.0f32, 1.0]);
+        match from_py(obj.as_ref(py)).unwrap() {
+            AnyDistribution::Normal(loaded) => {
+                assert!(loaded == normal);
+                assert!(loaded.log_prob(&value).allclose(
+                    &normal.log_prob(&value),
+                    1e-6,
+                    1e-6,
+                    false
+                ));
+            }
+            other => panic!("expected a Normal, got {:?}", other),
+        }
+
+        let mvn = MultivariateNormal::from_cov(
+            Tensor::of_slice(&[1.0f64, 2.0]),
+            Tensor::of_slice(&[2.0f64, 0.5, 0.5, 1.0]).view([2, 2]),
+        );
+        let obj = AnyDistribution::MultivariateNormal(mvn).to_py(py).unwrap();
+        assert_eq!(
+            obj.as_ref(py).get_type().name().unwrap(),
+            "MultivariateNormal"
+        );
+        assert!(matches!(
+            from_py(obj.as_ref(py)).unwrap(),
+            AnyDistribution::MultivariateNormal(_)
+        ));
+
+        let binomial = Binomial::from_probs(
+            Tensor::of_slice(&[10.0f64, 5.0]),
+            Tensor::of_slice(&[0.3f64, 0.5]),
+        );
+        let obj = AnyDistribution::Binomial(binomial).to_py(py).unwrap();
+        assert_eq!(obj.as_ref(py).get_type().name().unwrap(), "Binomial");
+        assert!(matches!(
+            from_py(obj.as_ref(py)).unwrap(),
+            AnyDistribution::Binomial(_)
+        ));
+
+        let negative_binomial =
+            NegativeBinomial::from_probs(Tensor::of_slice(&[3.0f64]), Tensor::of_slice(&[0.4f64]));
+        let obj = negative_binomial.to_py(py).unwrap();
+        match from_py(obj.as_ref(py)).unwrap() {
+            AnyDistribution::NegativeBinomial(loaded) => {
+                assert!(loaded
+                    .probs()
+                    .allclose(negative_binomial.probs(), 1e-12, 1e-12, false))
+            }
+            other => panic!("expected a NegativeBinomial, got {:?}", other),
+        }
+
+        let categorical = Categorical::from_probs(Tensor::of_slice(&[0.2f64, 0.8]));
+        let obj = categorical.to_py(py).unwrap();
+        let probs = obj.as_ref(py).getattr("probs").unwrap();
+        let sum: f64 = probs
+            .call_method0("sum")
+            .unwrap()
+            .call_method0("item")
+            .unwrap()
+            .extract()
+            .unwrap();
+        assert!((sum - 1.0).abs() < 1e-12);
+    });
+}
+
+#[test]
+fn python_unsupported_family() {
+    Python::with_gil(|py| {
+        let dists = py.import("torch.distributions").unwrap();
+        let torch = py.import("torch").unwrap();
+        let zero = torch.call_method1("tensor", (0.0,)).unwrap();
+        let laplace = dists
+            .getattr("Laplace")
+            .unwrap()
+            .call1((zero, 1.0))
+            .unwrap();
+        assert!(from_py(laplace).is_err());
+    });
+}
@@ -0,0 +1,56 @@
+use tch::{Kind, Tensor};
+use tch_distr::{rl::PolicyOutput, Categorical, Distribution, KullackLeiberDivergence, Normal};
+
+#[test]
+fn continuous_policy() {
+    let mean = Tensor::of_slice(&[0.0, 1.0, -1.0, 0.5, 0.2, 0.0]).reshape(&[3, 2]);
+    let stddev = Tensor::of_slice(&[1.0, 0.5, 2.0, 1.0, 0.3, 0.7]).reshape(&[3, 2]);
+    let policy = PolicyOutput::new(Normal::new(&mean, &stddev));
+    let actions = policy.sample();
+    assert_eq!(actions.size(), [3, 2]);
+
+    let normal = policy.distribution();
+    let log_prob = policy.log_prob(&actions);
+    let expected = normal
+        .log_prob(&actions)
+        .sum_dim_intlist(&[1], false, Kind::Double);
+    assert!(log_prob.allclose(&expected, 1e-6, 1e-6, false));
+    let expected = normal.entropy().sum_dim_intlist(&[1], false, Kind::Double);
+    assert!(policy.entropy().allclose(&expected, 1e-6, 1e-6, false));
+    assert!(policy.ratio(&actions, &log_prob).allclose(
+        &Tensor::ones(&[3], tch::kind::DOUBLE_CPU),
+        1e-6,
+        1e-6,
+        false
+    ));
+
+    let old = PolicyOutput::new(Normal::new(&mean + 0.1, &stddev));
+    assert_eq!(policy.kl_to(&old).size(), [3]);
+    assert!(policy.kl_to(&policy).allclose(
+        &Tensor::zeros(&[3], tch::kind::DOUBLE_CPU),
+        1e-6,
+        1e-6,
+        false
+    ));
+}
+
+#[test]
+fn discrete_policy() {
+    let probs = Tensor::of_slice(&[0.2, 0.8, 0.0, 0.5, 0.25, 0.25]).reshape(&[2, 3]);
+    let other =
+        Tensor::of_slice(&[0.1, 0.6, 0.3, 1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0]).reshape(&[2, 3]);
+    let policy = PolicyOutput::new(Categorical::from_probs(&probs));
+    let old = PolicyOutput::new(Categorical::from_probs(&other));
+    assert_eq!(policy.sample().size(), [2]);
+    assert_eq!(policy.log_prob(&Tensor::of_slice(&[1i64, 0])).size(), [2]);
+
+    let expected = Tensor::of_slice(&[
+        0.2 * (0.2f64 / 0.1).ln() + 0.8 * (0.8f64 / 0.6).ln(),
+        0.5 * 1.5f64.ln() + 0.5 * 0.75f64.ln(),
+    ]);
+    assert!(policy.kl_to(&old).allclose(&expected, 1e-6, 1e-6, false));
+    assert!(policy
+        .distribution()
+        .kl_divergence(old.distribution())
+        .allclose(&expected, 1e-6, 1e-6, false));
+}
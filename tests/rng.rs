@@ -0,0 +1,108 @@
+#![cfg(feature = "rand")]
+
+use rand::{rngs::StdRng, SeedableRng};
+use tch::Tensor;
+use tch_distr::scalar::ScalarDistribution;
+use tch_distr::{
+    Bernoulli, Beta, Cauchy, Distribution, Exponential, Gamma, Geometric, Normal, Poisson, Uniform,
+};
+
+const N: usize = 20000;
+
+fn moments(draws: &[f64]) -> (f64, f64) {
+    let n = draws.len() as f64;
+    let mean = draws.iter().sum::<f64>() / n;
+    let variance = draws.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+    (mean, variance)
+}
+
+fn check_continuous<D>(dist: &D)
+where
+    D: Distribution + rand::distributions::Distribution<f64>,
+{
+    let mut rng = StdRng::seed_from_u64(42);
+    let draws: Vec<f64> = (0..N)
+        .map(|_| rand::distributions::Distribution::sample(dist, &mut rng))
+        .collect();
+    let (mean, variance) = moments(&draws);
+    let expected_mean = f64::from(Distribution::mean(dist));
+    let expected_variance = f64::from(Distribution::variance(dist));
+    assert!((mean - expected_mean).abs() < 0.05 * expected_variance.sqrt().max(1.0));
+    assert!((variance - expected_variance).abs() < 0.05 * expected_variance.max(1.0));
+}
+
+fn check_discrete<D>(dist: &D)
+where
+    D: Distribution + rand::distributions::Distribution<u64>,
+{
+    let mut rng = StdRng::seed_from_u64(42);
+    let draws: Vec<f64> = (0..N)
+        .map(|_| rand::distributions::Distribution::sample(dist, &mut rng) as f64)
+        .collect();
+    let (mean, variance) = moments(&draws);
+    let expected_mean = f64::from(Distribution::mean(dist));
+    let expected_variance = f64::from(Distribution::variance(dist));
+    assert!((mean - expected_mean).abs() < 0.05 * expected_variance.sqrt().max(1.0));
+    assert!((variance - expected_variance).abs() < 0.05 * expected_variance.max(1.0));
+}
+
+#[test]
+fn rand_continuous() {
+    check_continuous(&Normal::new(Tensor::from(1.0f64), Tensor::from(2.0f64)));
+    check_continuous(&Uniform::new(Tensor::from(-1.0f64), Tensor::from(3.0f64)));
+    check_continuous(&Exponential::new(Tensor::from(0.5f64)));
+    check_continuous(&Gamma::new(Tensor::from(0.4f64), Tensor::from(2.0f64)));
+    check_continuous(&Gamma::new(Tensor::from(5.0f64), Tensor::from(0.5f64)));
+    check_continuous(&Beta::new(Tensor::from(2.0f64), Tensor::from(3.0f64)));
+
+    // The Cauchy distribution has no moments, so its median and quartiles are checked.
+    let cauchy = Cauchy::new(Tensor::from(1.0f64), Tensor::from(2.0f64));
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut draws: Vec<f64> = (0..N)
+        .map(|_| rand::distributions::Distribution::sample(&cauchy, &mut rng))
+        .collect();
+    draws.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert!((draws[N / 4] + 1.0).abs() < 0.1);
+    assert!((draws[N / 2] - 1.0).abs() < 0.1);
+    assert!((draws[3 * N / 4] - 3.0).abs() < 0.1);
+}
+
+#[test]
+fn rand_discrete() {
+    check_discrete(&Bernoulli::from_probs(Tensor::from(0.3f64)));
+    check_discrete(&Geometric::from_probs(Tensor::from(0.2f64)));
+    check_discrete(&Poisson::new(Tensor::from(3.0f64)));
+    check_discrete(&Poisson::new(Tensor::from(250.0f64)));
+}
+
+#[test]
+fn rand_scalar_distribution() {
+    let normal = Normal::new(Tensor::from(1.0f64), Tensor::from(2.0f64));
+    let scalar = ScalarDistribution::try_from(&normal).unwrap();
+    let sample = |dist: &dyn Fn(&mut StdRng) -> f64| {
+        let mut rng = StdRng::seed_from_u64(42);
+        (0..10).map(|_| dist(&mut rng)).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        sample(&|rng| rand::distributions::Distribution::sample(&scalar, rng)),
+        sample(&|rng| rand::distributions::Distribution::sample(&normal, rng)),
+    );
+
+    let poisson = Poisson::new(Tensor::from(3.0f64));
+    let scalar = ScalarDistribution::try_from(&poisson).unwrap();
+    let draw: f64 =
+        rand::distributions::Distribution::sample(&scalar, &mut StdRng::seed_from_u64(42));
+    let count: u64 =
+        rand::distributions::Distribution::sample(&poisson, &mut StdRng::seed_from_u64(42));
+    assert_eq!(draw, count as f64);
+}
+
+#[test]
+#[should_panic]
+fn rand_batched_parameters() {
+    let dist = Normal::new(
+        Tensor::of_slice(&[0.0f64, 1.0]),
+        Tensor::of_slice(&[1.0f64, 1.0]),
+    );
+    let _: f64 = rand::distributions::Distribution::sample(&dist, &mut StdRng::seed_from_u64(42));
+}
@@ -0,0 +1,359 @@
+use tch::Tensor;
+use tch_distr::{
+    Bernoulli, Categorical, Cauchy, Distribution, Exponential, Gamma, Independent, Normal, Poisson,
+    RelaxedOneHotCategorical, Uniform,
+};
+
+const SEED: i64 = 42;
+const N_SAMPLES: i64 = 20_000;
+
+fn assert_matches_cdf<D: Distribution>(dist: &D, edges: &[f64]) {
+    tch::manual_seed(SEED);
+    let check = dist.empirical_check(N_SAMPLES, &Tensor::of_slice(edges));
+    assert!(
+        check.max_abs_error() < 0.02,
+        "observed {:?}, expected {:?}",
+        check.observed,
+        check.expected
+    );
+}
+
+#[test]
+fn empirical_check() {
+    let edges = [-3.0, -1.0, -0.5, 0.0, 0.5, 1.0, 3.0];
+    assert_matches_cdf(&Normal::from((0.0, 1.0)), &edges);
+    assert_matches_cdf(&Cauchy::from((0.0, 1.0)), &edges);
+    assert_matches_cdf(&Uniform::from((-1.0, 1.0)), &edges);
+    assert_matches_cdf(&Exponential::from(2.0), &edges);
+    assert_matches_cdf(
+        &Uniform::new(
+            Tensor::of_slice(&[0.0, -2.0]),
+            Tensor::of_slice(&[1.0, 0.5]),
+        ),
+        &edges,
+    );
+}
+
+#[test]
+fn batched_samples() {
+    tch::manual_seed(SEED);
+    let mean = Tensor::of_slice(&[-2.0, 0.0, 3.0]);
+    let stddev = Tensor::of_slice(&[0.5, 1.0, 2.0]);
+    let normal = Normal::new(mean.shallow_clone(), stddev.shallow_clone());
+    let samples = normal.sample(&[N_SAMPLES]);
+    assert_eq!(samples.size(), [N_SAMPLES, 3]);
+    assert!(!samples.requires_grad());
+    let kind = tch::Kind::Double;
+    assert!(samples
+        .mean_dim(&[0], false, kind)
+        .allclose(&mean, 0.0, 0.1, false));
+    assert!(samples
+        .std_dim(&[0], true, false)
+        .allclose(&stddev, 0.0, 0.1, false));
+
+    let rate = Tensor::of_slice(&[0.5, 4.0, 20.0]);
+    let samples = Poisson::new(rate.shallow_clone()).sample(&[N_SAMPLES]);
+    assert_eq!(samples.size(), [N_SAMPLES, 3]);
+    assert!(samples
+        .mean_dim(&[0], false, kind)
+        .allclose(&rate, 0.0, 0.2, false));
+
+    let probs = Tensor::of_slice(&[0.1, 0.5, 0.9]);
+    let bernoulli = Bernoulli::from_probs(probs.shallow_clone());
+    assert_eq!(bernoulli.batch_shape(), [3]);
+    let samples = bernoulli.sample(&[N_SAMPLES]).to_kind(kind);
+    assert_eq!(samples.size(), [N_SAMPLES, 3]);
+    assert!(samples
+        .mean_dim(&[0], false, kind)
+        .allclose(&probs, 0.0, 0.02, false));
+
+    let concentration = Tensor::of_slice(&[0.5, 2.0, 9.0]);
+    let rate = Tensor::of_slice(&[1.0, 0.5, 3.0]);
+    let gamma = Gamma::new(concentration.shallow_clone(), rate.shallow_clone());
+    let samples = gamma.sample(&[N_SAMPLES]);
+    assert_eq!(samples.size(), [N_SAMPLES, 3]);
+    assert!(samples
+        .mean_dim(&[0], false, kind)
+        .allclose(&(concentration / rate), 0.0, 0.1, false));
+}
+
+#[test]
+fn sample_scalar() {
+    tch::manual_seed(SEED);
+    let x = Uniform::from((2.0, 3.0)).sample_scalar();
+    assert!((2.0..3.0).contains(&x));
+    let k = Poisson::from(4.0).sample_scalar_i64();
+    assert!(k >= 0);
+    let k = Bernoulli::from_probs(Tensor::from(1.0)).sample_scalar_i64();
+    assert_eq!(k, 1);
+    let k = Uniform::from((2.0, 3.0)).sample_scalar_i64();
+    assert_eq!(k, 2);
+
+    let batched = Normal::new(Tensor::of_slice(&[0.0, 1.0]), Tensor::of_slice(&[1.0, 1.0]));
+    assert!(std::panic::catch_unwind(|| batched.sample_scalar()).is_err());
+    let batched = Poisson::new(Tensor::of_slice(&[1.0, 2.0]));
+    assert!(std::panic::catch_unwind(|| batched.sample_scalar_i64()).is_err());
+}
+
+#[test]
+fn mc_expectation() {
+    tch::manual_seed(SEED);
+    let dist = Uniform::new(
+        Tensor::of_slice(&[0.0, -2.0]),
+        Tensor::of_slice(&[1.0, 0.5]),
+    );
+    let estimate = dist.mc_expectation(|x| x.pow_tensor_scalar(2), N_SAMPLES, 3_000);
+    assert_eq!(estimate.n_samples, N_SAMPLES);
+    // E[x^2] = (b^3 - a^3) / (3 (b - a))
+    let expected = Tensor::of_slice(&[1.0 / 3.0, 8.125 / 7.5]);
+    assert!(estimate.mean.allclose(&expected, 0.0, 0.03, false));
+    assert!(bool::from(
+        (&estimate.mean - &expected)
+            .abs()
+            .le_tensor(&(estimate.std_error() * 5.0))
+            .all()
+    ));
+
+    // Merging chunks recovers the estimate computed from all samples at once.
+    tch::manual_seed(SEED);
+    let chunked = dist.mc_expectation(|x| x.shallow_clone(), 1_000, 300);
+    tch::manual_seed(SEED);
+    let whole = dist.mc_expectation(|x| x.shallow_clone(), 1_000, 1_000);
+    assert!(chunked.mean.allclose(&whole.mean, 1e-6, 1e-8, false));
+    assert!(chunked
+        .variance
+        .allclose(&whole.variance, 1e-6, 1e-8, false));
+}
+
+#[test]
+fn sample_qmc() {
+    let samples = Uniform::from((0.0, 1.0)).sample_qmc(4);
+    assert_eq!(Vec::<f64>::from(&samples), [0.5, 0.75, 0.25, 0.375]);
+
+    let dist = Normal::new(Tensor::of_slice(&[0.0, 3.0]), Tensor::of_slice(&[1.0, 0.5]));
+    let samples = dist.sample_qmc(1024);
+    assert_eq!(samples.size(), [1024, 2]);
+    let mean = samples.mean_dim(&[0], false, tch::Kind::Double);
+    assert!(mean.allclose(&Tensor::of_slice(&[0.0, 3.0]), 0.0, 1e-2, false));
+}
+
+#[test]
+fn sample_antithetic() {
+    tch::manual_seed(SEED);
+    let dist = Normal::new(Tensor::of_slice(&[0.0, 3.0]), Tensor::of_slice(&[1.0, 0.5]));
+    let (x, y) = dist.sample_antithetic(&[5]);
+    assert_eq!(x.size(), [5, 2]);
+    assert!(((&x + &y) / 2.0).allclose(&dist.mean().expand(&[5, 2], false), 1e-6, 1e-8, false));
+
+    let dist = Exponential::new(Tensor::of_slice(&[0.5, 2.0]));
+    let (x, y) = dist.sample_antithetic(&[5]);
+    let total = dist.cdf(&x) + dist.cdf(&y);
+    assert!(total.allclose(&total.ones_like(), 1e-6, 1e-8, false));
+
+    // The antithetic average of a monotone function has a lower variance than the average of
+    // two independent draws.
+    let dist = Uniform::from((0.0, 1.0));
+    let (x, y) = dist.sample_antithetic(&[N_SAMPLES]);
+    let paired = (x.exp() + y.exp()) / 2.0;
+    let independent = (dist.sample(&[N_SAMPLES]).exp() + dist.sample(&[N_SAMPLES]).exp()) / 2.0;
+    assert!(f64::from(&paired.var(true)) < 0.1 * f64::from(&independent.var(true)));
+}
+
+#[test]
+fn sample_stratified() {
+    tch::manual_seed(SEED);
+    let dist = Uniform::new(
+        Tensor::of_slice(&[0.0, -2.0]),
+        Tensor::of_slice(&[1.0, 2.0]),
+    );
+    let samples = dist.sample_stratified(12, 4);
+    assert_eq!(samples.size(), [12, 2]);
+    // Every consecutive group of three samples falls into its own quarter of the support.
+    let stratum = dist.cdf(&samples).multiply_scalar(4.0).floor();
+    let expected = Tensor::of_slice(&[0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 2.0, 2.0, 2.0, 3.0, 3.0, 3.0])
+        .unsqueeze(-1)
+        .expand(&[12, 2], false);
+    assert!(stratum.equal(&expected));
+}
+
+#[test]
+fn sample_latin_hypercube() {
+    tch::manual_seed(SEED);
+    let n = 8;
+    let dist = Independent::new(
+        Normal::new(
+            Tensor::of_slice(&[0.0, 3.0, -1.0]),
+            Tensor::of_slice(&[1.0, 0.5, 2.0]),
+        ),
+        1,
+    );
+    let samples = dist.sample_latin_hypercube(n);
+    assert_eq!(samples.size(), [n, 3]);
+    // In every dimension each of the n strata contains exactly one sample.
+    let stratum = dist.base().cdf(&samples).multiply_scalar(n as f64).floor();
+    let (sorted, _) = stratum.sort(0, false);
+    let expected = Tensor::arange(n, (tch::Kind::Double, tch::Device::Cpu))
+        .unsqueeze(-1)
+        .expand(&[n, 3], false);
+    assert!(sorted.equal(&expected));
+}
+
+#[test]
+fn sample_crn() {
+    tch::manual_seed(SEED);
+    let p = Normal::new(Tensor::of_slice(&[0.0, 3.0]), Tensor::of_slice(&[1.0, 0.5]));
+    let q = Normal::new(Tensor::of_slice(&[0.1, 3.0]), Tensor::of_slice(&[1.0, 0.6]));
+    let (x, y) = tch_distr::sample_crn(&p, &q, &[N_SAMPLES]);
+    assert_eq!(x.size(), [N_SAMPLES, 2]);
+    // Common random numbers shift the location exactly and scale around it.
+    assert!((&y - &x)
+        .select(1, 0)
+        .allclose(&Tensor::from(0.1), 1e-6, 1e-8, false));
+    let independent = q.sample(&[N_SAMPLES]) - p.sample(&[N_SAMPLES]);
+    assert!(10.0 * f64::from(&(&y - &x).var(true)) < f64::from(&independent.var(true)));
+}
+
+#[test]
+fn sampler() {
+    let dist = Normal::new(Tensor::of_slice(&[0.0, 3.0]), Tensor::of_slice(&[1.0, 0.5]));
+    let mut sampler = tch_distr::Sampler::new(&dist, &[N_SAMPLES]);
+    let ptr = sampler.samples().data_ptr();
+    let first = sampler.next().copy();
+    let second = sampler.next();
+    assert_eq!(second.data_ptr(), ptr);
+    assert_eq!(second.size(), [N_SAMPLES, 2]);
+    assert!(!first.equal(second));
+    let mean = second.mean_dim(&[0], false, tch::Kind::Double);
+    assert!(mean.allclose(dist.mean(), 0.0, 0.05, false));
+
+    // Families without in-place reparameterization fall back to copying into the buffer.
+    let dist = Uniform::new(
+        Tensor::of_slice(&[0.0, -2.0]),
+        Tensor::of_slice(&[1.0, 0.5]),
+    );
+    let mut sampler = tch_distr::Sampler::new(&dist, &[3]);
+    let ptr = sampler.samples().data_ptr();
+    assert_eq!(sampler.next().data_ptr(), ptr);
+}
+
+#[test]
+fn gumbel_sampling() {
+    tch::manual_seed(SEED);
+    let probs = Tensor::of_slice(&[0.1, 0.2, 0.3, 0.4]);
+    let dist = Categorical::from_probs(&probs);
+
+    let samples = dist.sample_gumbel_max(&[N_SAMPLES]);
+    assert_eq!(samples.size(), [N_SAMPLES]);
+    let frequencies =
+        samples
+            .one_hot(4)
+            .to_kind(tch::Kind::Double)
+            .mean_dim(&[0], false, tch::Kind::Double);
+    assert!(frequencies.allclose(&probs, 0.0, 0.02, false));
+
+    let top = dist.sample_top_k(3, &[100]);
+    assert_eq!(top.size(), [100, 3]);
+    let (sorted, _) = top.sort(-1, false);
+    let distinct = sorted.narrow(-1, 1, 2).ne_tensor(&sorted.narrow(-1, 0, 2));
+    assert!(bool::from(&distinct.all()));
+
+    let logits = Tensor::of_slice(&[0.5, -1.0, 2.0]).set_requires_grad(true);
+    let dist = Categorical::from_logits(&logits);
+    let one_hot = dist.rsample_straight_through(&[5]);
+    assert!(bool::from(
+        &one_hot
+            .sum_dim_intlist(&[-1], false, tch::Kind::Double)
+            .eq(1.0)
+            .all()
+    ));
+    (one_hot * Tensor::of_slice(&[1.0, 2.0, 3.0]))
+        .sum(tch::Kind::Double)
+        .backward();
+    assert!(logits.grad().defined());
+}
+
+#[test]
+fn sampling_without_replacement() {
+    tch::manual_seed(SEED);
+    let logits = Tensor::of_slice(&[0.2f64, 0.3, 0.5])
+        .log()
+        .set_requires_grad(true);
+    let dist = Categorical::from_logits(&logits);
+
+    // Drawing 2 then 1 has probability 0.5 * 0.3 / 0.5.
+    let ordered = Tensor::of_slice(&[2i64, 1]);
+    let log_prob = dist.log_prob_without_replacement(&ordered);
+    assert!((f64::from(&log_prob) - 0.3f64.ln()).abs() < 1e-6);
+
+    let (samples, log_probs) = dist.sample_without_replacement(2, &[N_SAMPLES]);
+    assert_eq!(samples.size(), [N_SAMPLES, 2]);
+    assert_eq!(log_probs.size(), [N_SAMPLES]);
+    let frequency = f64::from(
+        samples
+            .eq_tensor(&ordered)
+            .all_dim(-1, false)
+            .to_kind(tch::Kind::Double)
+            .mean(tch::Kind::Double),
+    );
+    assert!((frequency - 0.3).abs() < 0.02);
+    log_probs.sum(tch::Kind::Double).backward();
+    assert!(logits.grad().defined());
+}
+
+#[test]
+fn relaxed_categorical() {
+    tch::manual_seed(SEED);
+    let logits = Tensor::of_slice(&[0.5, -1.0, 2.0]).set_requires_grad(true);
+    let dist = RelaxedOneHotCategorical::from_logits(Tensor::from(0.5), &logits);
+    assert_eq!(dist.event_shape(), [3]);
+
+    let soft = dist.rsample(&[N_SAMPLES]);
+    assert_eq!(soft.size(), [N_SAMPLES, 3]);
+    assert!(soft
+        .sum_dim_intlist(&[-1], false, tch::Kind::Double)
+        .allclose(
+            &Tensor::ones(&[N_SAMPLES], tch::kind::DOUBLE_CPU),
+            1e-6,
+            1e-6,
+            false
+        ));
+
+    let hard = dist.rsample_straight_through(&[5]);
+    assert!(bool::from(
+        &hard
+            .sum_dim_intlist(&[-1], false, tch::Kind::Double)
+            .eq(1.0)
+            .all()
+    ));
+    assert!(bool::from(&hard.eq(0.0).logical_or(&hard.eq(1.0)).all()));
+    (hard * Tensor::of_slice(&[1.0, 2.0, 3.0]))
+        .sum(tch::Kind::Double)
+        .backward();
+    assert!(logits.grad().defined());
+
+    // With two categories the density of the first coordinate integrates to one.
+    let dist =
+        RelaxedOneHotCategorical::from_logits(Tensor::from(1.5), Tensor::of_slice(&[0.3, -0.2]));
+    let t = Tensor::linspace(1e-4, 1.0 - 1e-4, 20_000, tch::kind::DOUBLE_CPU);
+    let points = Tensor::stack(&[&t, &(1.0 - &t)], -1);
+    let density = dist.log_prob(&points).exp();
+    let integral = f64::from(Tensor::trapz(&density, &t, -1));
+    assert!((integral - 1.0).abs() < 1e-2);
+}
+
+#[test]
+fn rejection_sampling() {
+    tch::manual_seed(SEED);
+    // exp(-x^2 / 2) / cauchy(x) = pi (1 + x^2) exp(-x^2 / 2) peaks at 2 pi / sqrt(e).
+    let proposal = Cauchy::new(Tensor::from(0.0), Tensor::from(1.0));
+    let log_m = (2.0 * std::f64::consts::PI).ln() - 0.5;
+    let samples = tch_distr::sampling::rejection(
+        |x| -x.pow_tensor_scalar(2) / 2.0,
+        &proposal,
+        log_m,
+        &[N_SAMPLES, 2],
+    );
+    assert_eq!(samples.size(), [N_SAMPLES, 2]);
+    assert!(f64::from(samples.mean(tch::Kind::Double)).abs() < 0.02);
+    assert!((f64::from(samples.var(true)) - 1.0).abs() < 0.03);
+}
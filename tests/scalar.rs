@@ -0,0 +1,119 @@
+#![cfg(feature = "scalar")]
+
+use tch::Tensor;
+use tch_distr::scalar::ScalarDistribution;
+use tch_distr::{
+    Bernoulli, Beta, Cauchy, Distribution, Error, Exponential, Gamma, Geometric, Normal, Poisson,
+    Uniform,
+};
+
+fn assert_close(a: f64, b: f64) {
+    assert!((a - b).abs() < 1e-9 * b.abs().max(1.0), "{} != {}", a, b);
+}
+
+fn check_log_prob<D: Distribution>(dist: &D, scalar: ScalarDistribution, values: &[f64]) {
+    for &x in values {
+        let expected = f64::from(dist.log_prob(&Tensor::from(x)));
+        assert_close(scalar.log_prob(x), expected);
+    }
+}
+
+fn check_cdf<D: Distribution>(dist: &D, scalar: ScalarDistribution, values: &[f64]) {
+    for &x in values {
+        assert_close(scalar.cdf(x), f64::from(dist.cdf(&Tensor::from(x))));
+    }
+    for p in [0.001, 0.1, 0.5, 0.9, 0.999] {
+        assert_close(
+            scalar.icdf(p).unwrap(),
+            f64::from(dist.icdf(&Tensor::from(p))),
+        );
+    }
+}
+
+#[test]
+fn scalar_continuous() {
+    let normal = Normal::new(Tensor::from(1.0f64), Tensor::from(2.0f64));
+    let scalar = ScalarDistribution::try_from(&normal).unwrap();
+    check_log_prob(&normal, scalar, &[-3.0, 0.0, 1.0, 4.5]);
+    check_cdf(&normal, scalar, &[-7.0, -3.0, 0.0, 1.0, 4.5, 9.0]);
+
+    let cauchy = Cauchy::new(Tensor::from(-1.0f64), Tensor::from(0.5f64));
+    let scalar = ScalarDistribution::try_from(&cauchy).unwrap();
+    check_log_prob(&cauchy, scalar, &[-3.0, -1.0, 2.0]);
+    check_cdf(&cauchy, scalar, &[-3.0, -1.0, 2.0]);
+
+    let exponential = Exponential::new(Tensor::from(1.5f64));
+    let scalar = ScalarDistribution::try_from(&exponential).unwrap();
+    check_log_prob(&exponential, scalar, &[0.0, 0.3, 4.0]);
+    check_cdf(&exponential, scalar, &[0.0, 0.3, 4.0]);
+
+    let uniform = Uniform::new(Tensor::from(-1.0f64), Tensor::from(3.0f64));
+    let scalar = ScalarDistribution::try_from(&uniform).unwrap();
+    check_log_prob(&uniform, scalar, &[-1.0, 0.5, 2.9]);
+    check_cdf(&uniform, scalar, &[-1.0, 0.5, 2.9]);
+
+    let gamma = Gamma::new(Tensor::from(2.5f64), Tensor::from(0.5f64));
+    let scalar = ScalarDistribution::try_from(&gamma).unwrap();
+    check_log_prob(&gamma, scalar, &[0.1, 2.0, 12.0]);
+    // With a unit concentration, the Gamma distribution is exponential.
+    let scalar = ScalarDistribution::Gamma {
+        concentration: 1.0,
+        rate: 1.5,
+    };
+    check_cdf(&exponential, scalar, &[0.0, 0.3, 4.0]);
+
+    let beta = Beta::new(Tensor::from(2.0f64), Tensor::from(3.0f64));
+    let scalar = ScalarDistribution::try_from(&beta).unwrap();
+    check_log_prob(&beta, scalar, &[0.1, 0.5, 0.95]);
+    // The distribution function of Beta(2, 3) is 6x^2 - 8x^3 + 3x^4.
+    for x in [0.1, 0.5, 0.95] {
+        assert_close(
+            scalar.cdf(x),
+            6.0 * x * x - 8.0 * x.powi(3) + 3.0 * x.powi(4),
+        );
+    }
+}
+
+#[test]
+fn scalar_discrete() {
+    let bernoulli = Bernoulli::from_probs(Tensor::from(0.3f64));
+    let scalar = ScalarDistribution::try_from(&bernoulli).unwrap();
+    check_log_prob(&bernoulli, scalar, &[0.0, 1.0]);
+    assert_close(scalar.cdf(0.0), 0.7);
+
+    let geometric = Geometric::from_probs(Tensor::from(0.2f64));
+    let scalar = ScalarDistribution::try_from(&geometric).unwrap();
+    check_log_prob(&geometric, scalar, &[0.0, 3.0, 10.0]);
+
+    let poisson = Poisson::new(Tensor::from(3.5f64));
+    let scalar = ScalarDistribution::try_from(&poisson).unwrap();
+    check_log_prob(&poisson, scalar, &[0.0, 3.0, 10.0]);
+
+    // The distribution functions are the running sums of the mass functions.
+    for scalar in [
+        ScalarDistribution::try_from(&geometric).unwrap(),
+        ScalarDistribution::try_from(&poisson).unwrap(),
+    ] {
+        let mut sum = 0.0;
+        for k in 0..20 {
+            sum += scalar.pdf(k as f64);
+            assert_close(scalar.cdf(k as f64), sum);
+        }
+    }
+}
+
+#[test]
+fn scalar_icdf_without_closed_form() {
+    let gamma = Gamma::new(Tensor::from(2.5f64), Tensor::from(0.5f64));
+    let scalar = ScalarDistribution::try_from(&gamma).unwrap();
+    assert!(matches!(scalar.icdf(0.5), Err(Error::Evaluation(_))));
+}
+
+#[test]
+fn scalar_batched_parameters() {
+    let dist = Normal::new(
+        Tensor::of_slice(&[0.0f64, 1.0]),
+        Tensor::of_slice(&[1.0f64, 1.0]),
+    );
+    assert!(ScalarDistribution::try_from(&dist).is_err());
+}
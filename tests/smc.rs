@@ -0,0 +1,63 @@
+use tch::{Kind, Tensor};
+use tch_distr::{
+    smc::{resample, ParticleFilter, Resampling},
+    Distribution, Normal,
+};
+
+const SEED: i64 = 42;
+
+#[test]
+fn resampling_schemes() {
+    tch::manual_seed(SEED);
+    let log_weights = Tensor::of_slice(&[0.1, 0.2, 0.7]).log().repeat(&[1000]) - 1000f64.ln();
+    for scheme in [
+        Resampling::Multinomial,
+        Resampling::Systematic,
+        Resampling::Stratified,
+    ] {
+        let ancestors = resample(&log_weights, scheme);
+        assert_eq!(ancestors.size(), [3000]);
+        let counts = ancestors.remainder(3).bincount::<Tensor>(None, 3);
+        let frequencies = counts.to_kind(Kind::Double) / 3000.0;
+        assert!(frequencies.allclose(&Tensor::of_slice(&[0.1, 0.2, 0.7]), 0.0, 0.03, false));
+    }
+}
+
+#[test]
+fn filters_linear_gaussian_model() {
+    tch::manual_seed(SEED);
+    // x_t = 0.9 x_{t-1} + N(0, 1) with x_0 ~ N(0, 1), observed as y_t = x_t + N(0, 0.5).
+    let observations = [0.3, 1.2, 0.8, -0.4, -1.1, 0.2];
+    let mut filter = ParticleFilter::new(
+        Tensor::randn(&[5000, 1], tch::kind::DOUBLE_CPU),
+        Resampling::Systematic,
+        0.5,
+    );
+
+    let (mut mean, mut variance, mut log_evidence) = (0.0f64, 1.0f64, 0.0);
+    for &y in &observations {
+        filter.step_bootstrap(
+            |x| Normal::new(x * 0.9, Tensor::ones_like(x)),
+            |x| {
+                Normal::new(x, Tensor::full_like(x, 0.5))
+                    .log_prob(&Tensor::full_like(x, y))
+                    .squeeze_dim(-1)
+            },
+        );
+
+        // Kalman filter.
+        let prior_mean = 0.9 * mean;
+        let prior_variance = 0.81 * variance + 1.0;
+        let innovation_variance = prior_variance + 0.25;
+        log_evidence += f64::from(
+            Normal::from((prior_mean, innovation_variance.sqrt())).log_prob(&Tensor::from(y)),
+        );
+        let gain = prior_variance / innovation_variance;
+        mean = prior_mean + gain * (y - prior_mean);
+        variance = (1.0 - gain) * prior_variance;
+
+        assert!((f64::from(filter.mean()) - mean).abs() < 0.05);
+    }
+    assert_eq!(filter.ess_history().len(), observations.len());
+    assert!((f64::from(filter.log_evidence()) - log_evidence).abs() < 0.1);
+}
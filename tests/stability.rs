@@ -0,0 +1,78 @@
+use tch::Tensor;
+use tch_distr::{Bernoulli, Distribution, Exponential, Geometric};
+
+fn assert_close(result: &Tensor, expected: &[f64]) {
+    let expected = Tensor::of_slice(expected);
+    assert!(
+        result.allclose(&expected, 1e-6, 1e-8, false),
+        "{:?} != {:?}",
+        result,
+        expected
+    );
+}
+
+#[test]
+fn bernoulli_extreme_logits() {
+    let dist = Bernoulli::from_logits(Tensor::of_slice(&[50.0, -50.0]));
+    assert_close(
+        &dist.log_prob(&Tensor::of_slice(&[0.0, 1.0])),
+        &[-50.0, -50.0],
+    );
+    assert_close(&dist.log_prob(&Tensor::of_slice(&[1.0, 0.0])), &[0.0, 0.0]);
+}
+
+#[test]
+fn geometric_extreme_logits() {
+    // The probabilities round to one and zero.
+    let dist = Geometric::from_logits(Tensor::of_slice(&[50.0, -50.0]));
+    assert_close(
+        &dist.log_prob(&Tensor::of_slice(&[0.0, 0.0])),
+        &[0.0, -50.0],
+    );
+    assert_close(
+        &dist.log_prob(&Tensor::of_slice(&[2.0, 2.0])),
+        &[-100.0, -50.0],
+    );
+}
+
+#[test]
+fn geometric_certain_success() {
+    let dist = Geometric::from_probs(Tensor::of_slice(&[1.0, 1.0]));
+    let log_prob = dist.log_prob(&Tensor::of_slice(&[0.0, 1.0]));
+    assert_eq!(Vec::<f64>::from(&log_prob), [0.0, f64::NEG_INFINITY]);
+}
+
+#[test]
+fn geometric_boundary_probs() {
+    let dist = Geometric::from_probs(Tensor::of_slice(&[1.0, 0.0]));
+    let log_prob = dist.log_prob(&Tensor::of_slice(&[0.0, 0.0]));
+    assert_eq!(Vec::<f64>::from(&log_prob), [0.0, f64::NEG_INFINITY]);
+    assert_eq!(Vec::<f64>::from(&dist.mean()), [0.0, f64::INFINITY]);
+    assert_eq!(Vec::<f64>::from(&dist.variance()), [0.0, f64::INFINITY]);
+    // The entropy is computed from clamped logits like in PyTorch.
+    let entropy = f64::from(dist.entropy().select(0, 0));
+    assert!(entropy.is_finite() && entropy < 1e-10);
+    let samples = dist.sample(&[100]);
+    assert!(bool::from(samples.select(1, 0).eq(0.0).all()));
+}
+
+#[test]
+fn geometric_saturated_logits_moments() {
+    let dist = Geometric::from_logits(Tensor::of_slice(&[40.0]));
+    assert_close(&dist.mean(), &[(-40.0f64).exp()]);
+    assert_close(&dist.variance(), &[(-40.0f64).exp()]);
+}
+
+#[test]
+fn exponential_icdf_near_endpoints() {
+    let dist = Exponential::new(Tensor::of_slice(&[2.0f32]));
+    // 1 - q rounds to one in single precision.
+    let q = Tensor::of_slice(&[1e-9f32]);
+    assert!(dist
+        .icdf(&q)
+        .allclose(&Tensor::of_slice(&[5e-10f32]), 1e-5, 0.0, false));
+    assert!(dist.cdf(&dist.icdf(&q)).allclose(&q, 1e-5, 0.0, false));
+    let upper = dist.icdf(&Tensor::of_slice(&[1.0f32, 0.0]));
+    assert!(bool::from(upper.isfinite().all()));
+    assert!(f32::from(upper.get(1)) < 1e-30);
+}
@@ -0,0 +1,43 @@
+use tch::Tensor;
+use tch_distr::{Categorical, Distribution, Exponential, Normal, SUMMARY_QUANTILES};
+
+#[test]
+fn normal() {
+    tch::manual_seed(0);
+    let normal = Normal::new(Tensor::of_slice(&[0.0, 2.0]), Tensor::of_slice(&[1.0, 0.5]));
+    let summary = normal.summary();
+    assert!(summary.mean.allclose(normal.mean(), 1e-6, 1e-6, false));
+    assert!(summary.stddev.allclose(normal.stddev(), 1e-6, 1e-6, false));
+    assert_eq!(summary.quantiles.len(), SUMMARY_QUANTILES.len());
+    for (q, quantile) in &summary.quantiles {
+        assert_eq!(quantile.size(), [2]);
+        assert!(quantile.allclose(&normal.icdf(&Tensor::from(*q)), 0.0, 0.05, false));
+    }
+    assert_eq!(Vec::<f64>::from(&summary.support.0), [f64::NEG_INFINITY; 2]);
+    assert_eq!(Vec::<f64>::from(&summary.support.1), [f64::INFINITY; 2]);
+}
+
+#[test]
+fn exponential() {
+    tch::manual_seed(0);
+    let exponential = Exponential::new(Tensor::of_slice(&[0.5, 2.0]));
+    let summary = exponential.summary();
+    assert!(summary.stddev.allclose(&summary.mean, 1e-6, 1e-6, false));
+    let (_, median) = &summary.quantiles[2];
+    let expected = Tensor::of_slice(&[2.0f64.ln() / 0.5, 2.0f64.ln() / 2.0]);
+    assert!(median.allclose(&expected, 0.03, 0.0, false));
+    assert_eq!(Vec::<f64>::from(&summary.support.0), [0.0, 0.0]);
+}
+
+#[test]
+fn categorical() {
+    let probs = Tensor::of_slice(&[0.2, 0.3, 0.5]);
+    let categorical = Categorical::from_probs(&probs);
+    let summary = categorical.summary();
+    // Like its inherent methods, the moments of a Categorical are not defined.
+    assert!(f64::from(&summary.mean).is_nan());
+    assert!(f64::from(categorical.mean()).is_nan());
+    assert!(f64::from(categorical.variance()).is_nan());
+    assert_eq!(f64::from(&summary.support.0), 0.0);
+    assert_eq!(f64::from(&summary.support.1), 2.0);
+}
@@ -0,0 +1,56 @@
+use tch::Tensor;
+use tch_distr::{
+    tensorboard::{log_distribution, SummaryWriter},
+    Normal, Poisson,
+};
+
+/// Returns the payloads of the TFRecords of the only event file in `logdir`.
+fn read_records(logdir: &std::path::Path) -> Vec<Vec<u8>> {
+    let mut files: Vec<_> = std::fs::read_dir(logdir).unwrap().collect();
+    assert_eq!(files.len(), 1);
+    let path = files.pop().unwrap().unwrap().path();
+    assert!(path
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .starts_with("events.out.tfevents."));
+    let data = std::fs::read(path).unwrap();
+    let mut records = Vec::new();
+    let mut rest = &data[..];
+    while !rest.is_empty() {
+        let len = u64::from_le_bytes(rest[..8].try_into().unwrap()) as usize;
+        records.push(rest[12..12 + len].to_vec());
+        rest = &rest[12 + len + 4..];
+    }
+    records
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[test]
+fn tensorboard_log_distribution() {
+    let logdir = std::env::temp_dir().join("tch_distr_tensorboard");
+    let _ = std::fs::remove_dir_all(&logdir);
+    let mut writer = SummaryWriter::new(&logdir).unwrap();
+    let normal = Normal::new(Tensor::of_slice(&[0.0, 1.0]), Tensor::of_slice(&[1.0, 2.0]));
+    log_distribution(&mut writer, "posterior", &normal, 3).unwrap();
+    // Poisson does not implement the entropy, which is left out.
+    log_distribution(&mut writer, "counts", &Poisson::new(Tensor::from(3.0)), 3).unwrap();
+
+    let records = read_records(&logdir);
+    std::fs::remove_dir_all(&logdir).unwrap();
+    assert_eq!(records.len(), 1 + 4 + 3);
+    assert!(contains(&records[0], b"brain.Event:2"));
+    assert!(contains(&records[1], b"posterior/samples"));
+    for (record, tag) in
+        records[2..5]
+            .iter()
+            .zip(["posterior/mean", "posterior/stddev", "posterior/entropy"])
+    {
+        assert!(contains(record, tag.as_bytes()));
+    }
+    assert!(contains(&records[7], b"counts/stddev"));
+}
@@ -0,0 +1,97 @@
+#![cfg(feature = "trace")]
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tch::Tensor;
+use tch_distr::{Distribution, KullackLeiberDivergence, Normal};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Event, Metadata, Subscriber,
+};
+
+/// A subscriber recording the names and families of the spans and the levels of the events.
+#[derive(Default)]
+struct Recorder {
+    next_id: AtomicU64,
+    spans: Mutex<Vec<(&'static str, String)>>,
+    events: Mutex<Vec<tracing::Level>>,
+}
+
+/// A visitor keeping the value of the `family` or `p` field of a span.
+#[derive(Default)]
+struct FamilyVisitor(String);
+
+impl Visit for FamilyVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "family" || field.name() == "p" {
+            self.0 = format!("{:?}", value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "family" || field.name() == "p" {
+            self.0 = value.to_string();
+        }
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut family = FamilyVisitor::default();
+        span.record(&mut family);
+        self.spans
+            .lock()
+            .unwrap()
+            .push((span.metadata().name(), family.0));
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        self.events.lock().unwrap().push(*event.metadata().level());
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn trace_spans_and_events() {
+    let recorder = Arc::new(Recorder::default());
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let p = Normal::new(Tensor::of_slice(&[0.0, 1.0]), Tensor::of_slice(&[1.0, 2.0]));
+        let q = Normal::new(Tensor::from(0.0), Tensor::from(f64::NAN));
+        let x = p.sample(&[3]);
+        let _ = p.log_prob(&x);
+        let _ = p.kl_divergence(&q);
+    });
+    let spans = recorder.spans.lock().unwrap();
+    assert_eq!(
+        *spans,
+        [
+            ("sample", "Normal".to_string()),
+            ("log_prob", "Normal".to_string()),
+            ("kl_divergence", "Normal".to_string())
+        ]
+    );
+    let events = recorder.events.lock().unwrap();
+    assert_eq!(
+        *events,
+        [
+            tracing::Level::DEBUG,
+            tracing::Level::DEBUG,
+            tracing::Level::WARN
+        ]
+    );
+}
@@ -0,0 +1,202 @@
+use tch::{nn, nn::Module, Device, Kind, Tensor};
+use tch_distr::{
+    constraints::Constraint,
+    transforms::{
+        biject_to, transform_to, AbsTransform, AffineCouplingTransform,
+        RationalQuadraticSplineTransform, SoftmaxTransform, TanhTransform, Transform,
+    },
+    Distribution, Error, MultivariateNormal, TanhNormal, TransformedDistribution,
+};
+
+const SEED: i64 = 42;
+
+fn mlp(vs: &nn::Path, n_in: i64, n_out: i64) -> Box<dyn Module> {
+    Box::new(
+        nn::seq()
+            .add(nn::linear(vs / "hidden", n_in, 8, Default::default()))
+            .add_fn(|x| x.tanh())
+            .add(nn::linear(vs / "out", 8, n_out, Default::default())),
+    )
+}
+
+fn coupling(vs: &nn::Path) -> AffineCouplingTransform {
+    AffineCouplingTransform::new(1, mlp(&(vs / "scale"), 1, 2), mlp(&(vs / "shift"), 1, 2))
+}
+
+#[test]
+fn affine_coupling_inverse() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let transforms = [
+        coupling(&(&vs.root() / "first")),
+        coupling(&(&vs.root() / "second")).flipped(),
+    ];
+    let x = Tensor::randn(&[5, 3], (Kind::Float, Device::Cpu));
+    for transform in transforms.iter() {
+        let y = transform.forward(&x);
+        assert_eq!(y.size(), x.size());
+        assert!(transform.inverse(&y).allclose(&x, 1e-5, 1e-5, false));
+    }
+}
+
+#[test]
+fn affine_coupling_log_abs_det_jacobian() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let transform = coupling(&vs.root());
+    let x = Tensor::of_slice(&[0.3f32, -1.2, 0.7]);
+    let y = transform.forward(&x);
+
+    let x_grad = x.set_requires_grad(true);
+    let rows: Vec<Tensor> = (0..3)
+        .map(|i| {
+            let y = transform.forward(&x_grad);
+            Tensor::run_backward(&[y.get(i)], &[&x_grad], false, false).remove(0)
+        })
+        .collect();
+    let jacobian = Tensor::stack(&rows, 0);
+    let expected = jacobian.det().abs().log();
+    let actual = transform.log_abs_det_jacobian(&x, &y).unwrap();
+    assert!(actual.allclose(&expected, 1e-4, 1e-5, false));
+}
+
+#[test]
+fn affine_coupling_flow() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let base = MultivariateNormal::from_scale_tril(
+        Tensor::zeros(&[3], (Kind::Float, Device::Cpu)),
+        Tensor::eye(3, (Kind::Float, Device::Cpu)),
+    );
+    let flow = TransformedDistribution::new(
+        base,
+        vec![
+            Box::new(coupling(&(&vs.root() / "first"))),
+            Box::new(coupling(&(&vs.root() / "second")).flipped()),
+        ],
+    );
+    let samples = flow.rsample(&[4]);
+    assert_eq!(samples.size(), vec![4, 3]);
+    assert_eq!(flow.log_prob(&samples).size(), vec![4]);
+}
+
+fn spline() -> RationalQuadraticSplineTransform {
+    RationalQuadraticSplineTransform::new(
+        Tensor::of_slice(&[0.3, -1.0, 0.5, 2.0]),
+        Tensor::of_slice(&[-0.2, 1.0, 0.1, 0.7]),
+        Tensor::of_slice(&[0.4, -0.3, 1.5]),
+        3.0,
+    )
+}
+
+#[test]
+fn rational_quadratic_spline_inverse() {
+    let transform = spline();
+    let x = Tensor::of_slice(&[-4.0, -3.0, -1.7, -0.2, 0.0, 0.9, 2.5, 3.0, 5.0]);
+    let y = transform.forward(&x);
+    assert!(transform.inverse(&y).allclose(&x, 1e-6, 1e-6, false));
+
+    let outside = Tensor::of_slice(&[-4.0, 5.0]);
+    assert!(transform
+        .forward(&outside)
+        .allclose(&outside, 0.0, 0.0, false));
+}
+
+#[test]
+fn rational_quadratic_spline_log_abs_det_jacobian() {
+    let transform = spline();
+    let x = Tensor::of_slice(&[-4.0, -1.7, -0.2, 0.9, 2.5, 5.0]).set_requires_grad(true);
+    let y = transform.forward(&x);
+    let dy_dx = Tensor::run_backward(&[y.sum(Kind::Double)], &[&x], false, false).remove(0);
+    let actual = transform.log_abs_det_jacobian(&x, &y).unwrap();
+    assert!(actual.allclose(&dy_dx.log(), 1e-6, 1e-6, false));
+}
+
+#[test]
+fn rational_quadratic_spline_monotonic() {
+    let transform = spline();
+    let x = Tensor::linspace(-3.0, 3.0, 101, (Kind::Double, Device::Cpu));
+    let y = transform.forward(&x);
+    let increments = y.narrow(0, 1, 100) - y.narrow(0, 0, 100);
+    assert!(bool::from(&increments.gt(0.0).all()));
+}
+
+#[test]
+fn cached_transform() {
+    let transform = TanhTransform.with_cache();
+    let x = Tensor::of_slice(&[0.5, 30.0]);
+    let y = transform.forward(&x);
+    assert!(transform.inverse(&y).is_set_to(&x));
+    assert!(transform.forward(&x).is_set_to(&y));
+
+    let other = y.copy();
+    assert!(!transform.inverse(&other).is_set_to(&x));
+    transform.clear_cache();
+    assert!(!transform.inverse(&y).is_set_to(&x));
+}
+
+#[test]
+fn tanh_normal_log_prob_of_saturated_sample() {
+    tch::manual_seed(SEED);
+    let dist = TanhNormal::new(
+        Tensor::of_slice(&[20.0, 0.0]),
+        Tensor::of_slice(&[1.0, 1.0]),
+    );
+    let sample = dist.rsample(&[100]);
+    let log_prob = dist.log_prob(&sample);
+    assert!(bool::from(&log_prob.isfinite().all()));
+}
+
+#[test]
+fn biject_to_constraints() {
+    tch::manual_seed(SEED);
+    let constraints = [
+        Constraint::Real,
+        Constraint::Positive,
+        Constraint::greater_than(Tensor::of_slice(&[-1.0, 2.0, 0.5])),
+        Constraint::UnitInterval,
+        Constraint::interval(-2.0, 3.0),
+        Constraint::independent(Constraint::Positive, 1),
+    ];
+    let x = Tensor::randn(&[4, 3], (Kind::Double, Device::Cpu));
+    for constraint in constraints.iter() {
+        let transform = biject_to(constraint).unwrap();
+        let y = transform.forward(&x);
+        assert!(constraint.check_all(&y), "{:?}", constraint);
+        assert!(transform.inverse(&y).allclose(&x, 1e-6, 1e-6, false));
+    }
+
+    let transform = biject_to(&Constraint::Simplex).unwrap();
+    let y = transform.forward(&x);
+    assert_eq!(y.size(), vec![4, 4]);
+    assert!(Constraint::Simplex.check_all(&y));
+    assert!(transform.inverse(&y).allclose(&x, 1e-6, 1e-6, false));
+
+    assert!(biject_to(&Constraint::nonnegative_integer()).is_none());
+}
+
+#[test]
+fn transform_to_constraints() {
+    tch::manual_seed(SEED);
+    let x = Tensor::randn(&[4, 3], (Kind::Double, Device::Cpu));
+    let transform = transform_to(&Constraint::Simplex).unwrap();
+    assert!(!transform.bijective());
+    assert!(Constraint::Simplex.check_all(&transform.forward(&x)));
+    assert!(
+        Constraint::Positive.check_all(&transform_to(&Constraint::Positive).unwrap().forward(&x))
+    );
+}
+
+#[test]
+fn non_bijective_log_abs_det_jacobian() {
+    let x = Tensor::of_slice(&[0.5, -1.5, 3.0]);
+    let y = SoftmaxTransform.forward(&x);
+    assert!(matches!(
+        SoftmaxTransform.log_abs_det_jacobian(&x, &y),
+        Err(Error::Evaluation(_))
+    ));
+    assert!(matches!(
+        AbsTransform.log_abs_det_jacobian(&x, &x.abs()),
+        Err(Error::Evaluation(_))
+    ));
+}
@@ -0,0 +1,39 @@
+use tch::Tensor;
+use tch_distr::{set_validate_args, Distribution, Error, Exponential, Poisson, Uniform};
+
+// The validation flag is global, so all checks run in a single test.
+#[test]
+fn validate_args() {
+    let exponential = Exponential::new(Tensor::of_slice(&[1.0, 2.0]));
+    let poisson = Poisson::new(Tensor::of_slice(&[1.0, 2.0]));
+    let uniform = Uniform::new(Tensor::from(0.0), Tensor::from(1.0));
+    let val = Tensor::of_slice(&[0.5, -1.0]);
+
+    assert!(exponential.try_log_prob(&val).is_ok());
+    assert!(poisson.try_log_prob(&val).is_ok());
+
+    set_validate_args(true);
+    assert!(matches!(
+        exponential.try_log_prob(&val),
+        Err(Error::Support(_))
+    ));
+    assert!(matches!(poisson.try_log_prob(&val), Err(Error::Support(_))));
+    assert!(matches!(uniform.try_cdf(&val), Err(Error::Support(_))));
+    assert!(poisson.try_log_prob(&Tensor::of_slice(&[0.0, 3.0])).is_ok());
+    // The support of the Uniform is closed, although its density is zero at `high`.
+    let log_prob = uniform
+        .try_log_prob(&Tensor::of_slice(&[0.0, 1.0]))
+        .unwrap();
+    assert_eq!(Vec::<f64>::from(&log_prob), [0.0, f64::NEG_INFINITY]);
+    assert!(std::panic::catch_unwind(|| exponential.log_prob(&val)).is_err());
+    set_validate_args(false);
+
+    assert!(exponential.try_log_prob(&val).is_ok());
+    let log_prob = uniform.log_prob(&Tensor::of_slice(&[-1.0, 0.5, 1.0, 2.0]));
+    assert_eq!(
+        Vec::<f64>::from(&log_prob),
+        [f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY, f64::NEG_INFINITY]
+    );
+    let cdf = uniform.cdf(&Tensor::of_slice(&[-1.0, 0.25, 2.0]));
+    assert_eq!(Vec::<f64>::from(&cdf), [0.0, 0.25, 1.0]);
+}
@@ -0,0 +1,97 @@
+use tch::{nn, nn::OptimizerConfig, Device, Kind, Tensor};
+use tch_distr::{vi, Distribution, Exponential, Gamma, Normal, Parametrized};
+
+const SEED: i64 = 42;
+
+fn log_likelihood(z: &Tensor, data: &Tensor) -> Tensor {
+    Normal::new(z, Tensor::ones_like(z)).log_prob(&data.expand_as(z))
+}
+
+#[test]
+fn elbo_of_exact_posterior_is_evidence() {
+    tch::manual_seed(SEED);
+    // With a standard normal prior and a unit variance likelihood the posterior of `z` given
+    // `x` is N(x / 2, 1 / sqrt(2)) and the evidence is N(x | 0, sqrt(2)).
+    let data = Tensor::of_slice(&[0.7, -1.5]);
+    let prior = Normal::new(Tensor::zeros_like(&data), Tensor::ones_like(&data));
+    let posterior = Normal::new(&data / 2.0, Tensor::full_like(&data, 0.5f64.sqrt()));
+    let evidence = Normal::new(
+        Tensor::zeros_like(&data),
+        Tensor::full_like(&data, 2.0f64.sqrt()),
+    )
+    .log_prob(&data);
+
+    let log_joint = |z: &Tensor, data: &Tensor| log_likelihood(z, data) + prior.log_prob(z);
+    let elbo = vi::elbo(log_joint, &posterior, &data, 10);
+    assert_eq!(elbo.size(), [2]);
+    assert!(elbo.allclose(&evidence, 1e-5, 1e-6, false));
+}
+
+#[test]
+fn analytic_kl_matches_monte_carlo() {
+    tch::manual_seed(SEED);
+    let data = Tensor::of_slice(&[0.7, -1.5]);
+    let prior = Normal::new(Tensor::zeros_like(&data), Tensor::ones_like(&data));
+    let loc = Tensor::of_slice(&[0.1, 0.3]).set_requires_grad(true);
+    let guide = Normal::new(&loc, Tensor::of_slice(&[0.8, 1.2]));
+
+    let log_joint = |z: &Tensor, data: &Tensor| log_likelihood(z, data) + prior.log_prob(z);
+    let monte_carlo = vi::elbo(log_joint, &guide, &data, 50_000);
+    let analytic = vi::elbo_analytic_kl(log_likelihood, &guide, &prior, &data, 50_000);
+    assert!(analytic.allclose(&monte_carlo, 0.0, 0.05, false));
+
+    analytic.sum(tch::Kind::Double).backward();
+    assert!(loc.grad().defined());
+}
+
+#[test]
+fn elbo_of_implicitly_reparameterized_guide() {
+    tch::manual_seed(SEED);
+    // With an Exponential(1) prior and a Poisson likelihood of a count of 3, the posterior of
+    // the rate is Gamma(4, 2) and the evidence is 1 / 16.
+    let data = Tensor::from(3.0f64);
+    let prior = Exponential::new(Tensor::from(1.0f64));
+    let log_joint =
+        |z: &Tensor, data: &Tensor| prior.log_prob(z) + data * z.log() - z - (data + 1.0).lgamma();
+    let concentration = Tensor::from(4.0f64).set_requires_grad(true);
+    let posterior = Gamma::new(&concentration, Tensor::from(2.0f64));
+    let elbo = vi::elbo(log_joint, &posterior, &data, 10);
+    assert!((f64::from(&elbo) + 16f64.ln()).abs() < 1e-6);
+
+    let guide = Gamma::new(&concentration, Tensor::from(1.0f64));
+    vi::elbo(log_joint, &guide, &data, 100).backward();
+    assert!(bool::from(concentration.grad().isfinite()));
+}
+
+#[test]
+fn svi_fits_conjugate_posterior() {
+    tch::manual_seed(SEED);
+    let vs = nn::VarStore::new(Device::Cpu);
+    let init = Normal::new(Tensor::from(0.0f32), Tensor::from(1.0f32));
+    let guide = Parametrized::new(&vs.root(), &init, &["mean", "stddev"], |p| {
+        Normal::new(p[0].shallow_clone(), p[1].shallow_clone())
+    });
+    let optimizer = nn::Adam::default().build(&vs, 0.02).unwrap();
+    let prior = Normal::new(Tensor::from(0.0f32), Tensor::from(1.0f32));
+    let log_likelihood = |z: &Tensor, data: &Tensor| {
+        let z = z.unsqueeze(-1);
+        Normal::new(&z, Tensor::ones_like(&z))
+            .log_prob(&data.expand(&[z.size()[0], data.size()[0]], false))
+            .sum_dim_intlist(&[-1], false, Kind::Float)
+    };
+    let mut svi = vi::Svi::new(guide, optimizer, |z| prior.log_prob(z), log_likelihood, 8);
+
+    // The posterior of the mean `z` of 20 unit variance observations under a standard normal
+    // prior is N(sum(x) / 21, 1 / sqrt(21)).
+    let data = Tensor::randn(&[20], (Kind::Float, Device::Cpu)) + 1.5;
+    let mean = f64::from(data.sum(Kind::Float)) / 21.0;
+    for i in 0..2000 {
+        let minibatch = data.narrow(0, (i % 4) * 5, 5);
+        let loss = svi.step_scaled(&minibatch, 4.0);
+        assert!(loss.is_finite());
+    }
+
+    let posterior = svi.guide().distribution();
+    assert!((f64::from(posterior.mean()) - mean).abs() < 0.1);
+    assert!((f64::from(posterior.stddev()) - 21f64.sqrt().recip()).abs() < 0.1);
+}